@@ -0,0 +1,5 @@
+fn main() {
+    // Sets up linking of the Node addon built with the `node` feature
+    #[cfg(feature = "node")]
+    napi_build::setup();
+}
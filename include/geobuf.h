@@ -0,0 +1,62 @@
+#ifndef GEOBUF_H
+#define GEOBUF_H
+
+/* Generated with cbindgen from src/ffi.rs, do not edit by hand */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// Bytes owned by the library
+//
+// An empty buffer has a null `data` pointer and a `len` of 0.
+typedef struct GeobufBuffer {
+  uint8_t *data;
+  size_t len;
+} GeobufBuffer;
+
+// Encodes the `len` bytes of UTF-8 GeoJSON at `json` into a Geobuf stored in `out`
+//
+// Returns 0 on success, or -1 if `json` isn't valid JSON or holds GeoJSON that can't be
+// encoded, leaving `out` empty.
+//
+// # Safety
+//
+// `json` must point to `len` readable bytes, and `out` to a writable `GeobufBuffer`. The
+// buffer previously held by `out` isn't freed.
+int32_t geobuf_encode(const uint8_t *json,
+                      size_t len,
+                      uint32_t precision,
+                      uint32_t dim,
+                      struct GeobufBuffer *out);
+
+// Decodes the `len` bytes of the Geobuf at `data` into UTF-8 GeoJSON stored in `out`
+//
+// The GeoJSON isn't null terminated. Returns 0 on success, or -1 if `data` isn't a valid
+// Geobuf, leaving `out` empty.
+//
+// # Safety
+//
+// `data` must point to `len` readable bytes, and `out` to a writable `GeobufBuffer`. The
+// buffer previously held by `out` isn't freed.
+int32_t geobuf_decode(const uint8_t *data, size_t len, struct GeobufBuffer *out);
+
+// Frees the bytes of a buffer filled by `geobuf_encode` or `geobuf_decode` and empties it
+//
+// Freeing an empty buffer does nothing.
+//
+// # Safety
+//
+// `buffer` must be null or point to a buffer filled by this library and not modified since.
+void geobuf_buffer_free(struct GeobufBuffer *buffer);
+
+// Returns the reason of the last failed call on the calling thread as a null terminated
+// string, or null if no call failed
+//
+// The string is owned by the library and stays valid until the next failed call on the same
+// thread.
+const char *geobuf_last_error(void);
+
+#endif /* GEOBUF_H */
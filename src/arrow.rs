@@ -0,0 +1,287 @@
+//! Conversion of Geobufs to and from Arrow record batches with a GeoArrow geometry column
+//!
+//! Each feature becomes a row with an `id` column if any feature has an id, a column per key
+//! of the Geobuf, and a `geometry` column of WKB tagged with the `geoarrow.wkb` extension, so
+//! that decoded datasets can be handed to DataFusion, Polars or any other Arrow engine.
+//! Properties holding only booleans, integers, numbers or strings get the matching Arrow
+//! type, and other properties are stored as JSON text tagged with the `arrow.json` extension.
+//! Geobuf has no null properties, so nulls are missing properties both ways. An `id` column
+//! always gives the feature ids, so properties named `id` come back as ids.
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::runtime::MessageField;
+use arrow_array::cast::AsArray;
+use arrow_array::types::{
+    Float32Type, Float64Type, Int16Type, Int32Type, Int64Type, Int8Type, UInt16Type, UInt32Type,
+    UInt64Type, UInt8Type,
+};
+use arrow_array::{
+    Array, ArrayRef, BinaryArray, BooleanArray, Float64Array, Int64Array, RecordBatch, StringArray,
+};
+use arrow_schema::{DataType, Field, Schema};
+use serde_json::Value as JSONValue;
+
+use crate::geobuf_pb::data::{feature, Data_type, Feature, FeatureCollection};
+use crate::geobuf_pb::Data;
+use crate::props;
+use crate::validate::validate;
+use crate::wkb::{geometry_to_wkb, wkb_to_geometry};
+
+/// Field metadata key naming the extension type of a column
+pub const EXTENSION_NAME: &str = "ARROW:extension:name";
+/// Field metadata key holding the parameters of the extension type of a column
+pub const EXTENSION_METADATA: &str = "ARROW:extension:metadata";
+/// Extension type of GeoArrow geometry columns holding WKB
+pub const WKB_EXTENSION: &str = "geoarrow.wkb";
+/// Extension type of string columns holding JSON
+pub const JSON_EXTENSION: &str = "arrow.json";
+
+/// Returns a record batch with a row per feature of a Geobuf holding a FeatureCollection or
+/// Feature
+///
+/// Geometries are written as stored, so Geobufs with a transform are rejected rather than
+/// giving coordinates in transformed units. Properties named `geometry`, or `id` when
+/// features have ids, clash with the columns of the same name and are errors too.
+///
+/// # Example
+///
+/// ```
+/// use geobuf::arrow::{from_record_batches, to_record_batch};
+/// use geobuf::decode::Decoder;
+/// use geobuf::encode::Encoder;
+///
+/// let geojson = serde_json::from_str(r#"{"type": "FeatureCollection", "features": [
+///     {"type": "Feature", "properties": {"name": "a", "pop": 10}, "geometry": {"type": "Point", "coordinates": [1.0, 2.0]}},
+///     {"type": "Feature", "properties": {"name": "b"}, "geometry": {"type": "Point", "coordinates": [3.0, 4.0]}}
+/// ]}"#).unwrap();
+/// let data = Encoder::encode(&geojson, 6, 2).unwrap();
+///
+/// let batch = to_record_batch(&data).unwrap();
+/// assert_eq!(batch.num_rows(), 2);
+/// let names: Vec<_> = batch.schema().fields().iter().map(|field| field.name().clone()).collect();
+/// assert_eq!(names, vec!["name", "pop", "geometry"]);
+///
+/// let data = from_record_batches(&[batch], 6, 2).unwrap();
+/// assert_eq!(Decoder::decode(&data).unwrap(), geojson);
+/// ```
+pub fn to_record_batch(data: &Data) -> Result<RecordBatch, String> {
+    let features = match data.data_type.as_ref() {
+        Some(Data_type::FeatureCollection(fc)) => &fc.features[..],
+        Some(Data_type::Feature(feature)) => std::slice::from_ref(feature),
+        _ => return Err(String::from("Data holds no features.")),
+    };
+    if data.transform.is_some() {
+        return Err(String::from(
+            "Geobufs with a transform can't be converted to Arrow.",
+        ));
+    }
+    // Writing WKB panics on out of range lengths and indexes
+    if let Some(problem) = validate(data).first() {
+        return Err(format!("Invalid geobuf: {}", problem));
+    }
+
+    let mut ids = Vec::with_capacity(features.len());
+    let mut properties = vec![vec![JSONValue::Null; features.len()]; data.keys.len()];
+    let mut geometries = Vec::with_capacity(features.len());
+    for (n, feature) in features.iter().enumerate() {
+        ids.push(match &feature.id_type {
+            Some(feature::Id_type::Id(id)) => JSONValue::from(id.as_str()),
+            Some(feature::Id_type::IntId(id)) => JSONValue::from(*id),
+            None => JSONValue::Null,
+        });
+        for pair in feature.properties.chunks_exact(2) {
+            let value = &feature.values[pair[1] as usize];
+            properties[pair[0] as usize][n] = props::value_to_json(value, &data.strings);
+        }
+        let wkb = geometry_to_wkb(&feature.geometry, data.dimensions(), data.precision())
+            .map_err(|err| format!("Feature {}: {}", n, err))?;
+        geometries.push(wkb);
+    }
+
+    let mut fields = Vec::new();
+    let mut columns = Vec::new();
+    let has_ids = ids.iter().any(|id| !id.is_null());
+    if has_ids {
+        let (field, column) = column("id", &ids);
+        fields.push(field);
+        columns.push(column);
+    }
+    for (key, values) in data.keys.iter().zip(&properties) {
+        if key == "geometry" || (key == "id" && has_ids) {
+            return Err(format!("Property {} clashes with the {} column.", key, key));
+        }
+        let (field, column) = column(key, values);
+        fields.push(field);
+        columns.push(column);
+    }
+    let mut metadata = extension(WKB_EXTENSION);
+    metadata.insert(String::from(EXTENSION_METADATA), String::from("{}"));
+    fields.push(Field::new("geometry", DataType::Binary, false).with_metadata(metadata));
+    columns.push(Arc::new(BinaryArray::from_iter_values(&geometries)));
+
+    RecordBatch::try_new(Arc::new(Schema::new(fields)), columns).map_err(|err| err.to_string())
+}
+
+/// Returns a Geobuf FeatureCollection with `precision` and `dim` dimensions holding the rows
+/// of record batches
+///
+/// The geometry column is the one tagged with the `geoarrow.wkb` extension, or else the one
+/// named `geometry`, and holds WKB as binary or large binary. An `id` column holding strings
+/// or integers gives the feature ids, and every other column a property, boolean, integer,
+/// float and string columns being supported, as well as JSON text tagged with the
+/// `arrow.json` extension.
+pub fn from_record_batches(
+    batches: &[RecordBatch],
+    precision: u32,
+    dim: u32,
+) -> Result<Data, String> {
+    let mut data = Data::new();
+    data.set_precision(precision);
+    data.set_dimensions(dim);
+    let mut fc = FeatureCollection::new();
+
+    for batch in batches {
+        let schema = batch.schema();
+        let geometry = schema
+            .fields()
+            .iter()
+            .position(|field| is_extension(field, WKB_EXTENSION))
+            .or_else(|| schema.index_of("geometry").ok())
+            .ok_or("Record batch without a geometry column.")?;
+        let column = batch.column(geometry);
+        let geometries: Vec<Option<&[u8]>> = match column.data_type() {
+            DataType::Binary => column.as_binary::<i32>().iter().collect(),
+            DataType::LargeBinary => column.as_binary::<i64>().iter().collect(),
+            data_type => return Err(format!("Geometry column of type {}.", data_type)),
+        };
+        // Key indexes of the property columns, and None for the id column
+        let mut keys = HashMap::new();
+        for (index, field) in schema.fields().iter().enumerate() {
+            if index != geometry && field.name() != "id" {
+                keys.insert(index, props::key_index(&mut data.keys, field.name()));
+            }
+        }
+
+        for (row, wkb) in geometries.into_iter().enumerate() {
+            let row_error = |err: &str| format!("Row {}: {}", fc.features.len(), err);
+            let wkb = wkb.ok_or_else(|| row_error("Missing geometry."))?;
+            let mut feature = Feature::new();
+            feature.geometry =
+                MessageField::some(wkb_to_geometry(wkb, dim, precision).map_err(row_error)?);
+            for (index, field) in schema.fields().iter().enumerate() {
+                if index == geometry {
+                    continue;
+                }
+                let value = cell(batch.column(index), field, row).map_err(|err| row_error(&err))?;
+                match (keys.get(&index), value) {
+                    (_, JSONValue::Null) => {}
+                    (None, JSONValue::String(id)) => feature.set_id(id),
+                    (None, JSONValue::Number(id)) if id.is_i64() => {
+                        feature.set_int_id(id.as_i64().unwrap())
+                    }
+                    (None, _) => return Err(row_error("Invalid id.")),
+                    (Some(key), value) => {
+                        if let Some(value) = props::json_to_value(&value) {
+                            feature.properties.push(*key);
+                            feature.properties.push(feature.values.len() as u32);
+                            feature.values.push(value);
+                        }
+                    }
+                }
+            }
+            fc.features.push(feature);
+        }
+    }
+
+    data.set_feature_collection(fc);
+    Ok(data)
+}
+
+/// Returns the field and array of a column of JSON values, with the narrowest Arrow type
+/// holding them
+fn column(name: &str, values: &[JSONValue]) -> (Field, ArrayRef) {
+    let mut present = values.iter().filter(|value| !value.is_null());
+    let array: ArrayRef = if present.clone().all(JSONValue::is_boolean) {
+        Arc::new(
+            values
+                .iter()
+                .map(JSONValue::as_bool)
+                .collect::<BooleanArray>(),
+        )
+    } else if present.clone().all(JSONValue::is_i64) {
+        Arc::new(values.iter().map(JSONValue::as_i64).collect::<Int64Array>())
+    } else if present.clone().all(JSONValue::is_number) {
+        Arc::new(
+            values
+                .iter()
+                .map(JSONValue::as_f64)
+                .collect::<Float64Array>(),
+        )
+    } else if present.all(JSONValue::is_string) {
+        Arc::new(
+            values
+                .iter()
+                .map(JSONValue::as_str)
+                .collect::<StringArray>(),
+        )
+    } else {
+        let array = values
+            .iter()
+            .map(|value| match value {
+                JSONValue::Null => None,
+                value => Some(value.to_string()),
+            })
+            .collect::<StringArray>();
+        let field = Field::new(name, DataType::Utf8, true).with_metadata(extension(JSON_EXTENSION));
+        return (field, Arc::new(array));
+    };
+    (Field::new(name, array.data_type().clone(), true), array)
+}
+
+/// Returns the value of a cell as JSON, `null` for null cells
+fn cell(column: &ArrayRef, field: &Field, row: usize) -> Result<JSONValue, String> {
+    if column.is_null(row) {
+        return Ok(JSONValue::Null);
+    }
+    let value = match column.data_type() {
+        DataType::Boolean => JSONValue::from(column.as_boolean().value(row)),
+        DataType::Int8 => JSONValue::from(column.as_primitive::<Int8Type>().value(row)),
+        DataType::Int16 => JSONValue::from(column.as_primitive::<Int16Type>().value(row)),
+        DataType::Int32 => JSONValue::from(column.as_primitive::<Int32Type>().value(row)),
+        DataType::Int64 => JSONValue::from(column.as_primitive::<Int64Type>().value(row)),
+        DataType::UInt8 => JSONValue::from(column.as_primitive::<UInt8Type>().value(row)),
+        DataType::UInt16 => JSONValue::from(column.as_primitive::<UInt16Type>().value(row)),
+        DataType::UInt32 => JSONValue::from(column.as_primitive::<UInt32Type>().value(row)),
+        DataType::UInt64 => JSONValue::from(column.as_primitive::<UInt64Type>().value(row)),
+        DataType::Float32 => JSONValue::from(column.as_primitive::<Float32Type>().value(row)),
+        DataType::Float64 => JSONValue::from(column.as_primitive::<Float64Type>().value(row)),
+        DataType::Utf8 => text(column.as_string::<i32>().value(row), field)?,
+        DataType::LargeUtf8 => text(column.as_string::<i64>().value(row), field)?,
+        data_type => {
+            return Err(format!(
+                "Column {} of unsupported type {}.",
+                field.name(),
+                data_type
+            ))
+        }
+    };
+    Ok(value)
+}
+
+/// Returns the value of a string cell, parsed if its column holds JSON
+fn text(value: &str, field: &Field) -> Result<JSONValue, String> {
+    if is_extension(field, JSON_EXTENSION) {
+        serde_json::from_str(value).map_err(|err| format!("Column {}: {}", field.name(), err))
+    } else {
+        Ok(JSONValue::from(value))
+    }
+}
+
+fn extension(name: &str) -> HashMap<String, String> {
+    HashMap::from([(String::from(EXTENSION_NAME), String::from(name))])
+}
+
+fn is_extension(field: &Field, name: &str) -> bool {
+    field.metadata().get(EXTENSION_NAME).map(String::as_str) == Some(name)
+}
@@ -0,0 +1,203 @@
+//! Bounding boxes computed from the quantized coordinates, without decoding to GeoJSON
+use crate::geobuf_pb;
+
+/// Returns the `[min_x, min_y, max_x, max_y]` bounding box of every coordinate in `data`, or
+/// `None` if it has no coordinates
+///
+/// # Example
+///
+/// ```
+/// use geobuf::encode::Encoder;
+///
+/// let geojson = serde_json::from_str(r#"{"type": "FeatureCollection", "features": [
+///     {"type": "Feature", "properties": {}, "geometry": {"type": "LineString", "coordinates": [[1.0, 2.0], [-3.0, 4.5]]}},
+///     {"type": "Feature", "properties": {}, "geometry": {"type": "Point", "coordinates": [5.0, 0.0]}}
+/// ]}"#).unwrap();
+/// let data = Encoder::encode(&geojson, 6, 2).unwrap();
+///
+/// assert_eq!(geobuf::bbox::bbox(&data), Some([-3.0, 0.0, 5.0, 4.5]));
+/// ```
+pub fn bbox(data: &geobuf_pb::Data) -> Option<[f64; 4]> {
+    let dim = data.dimensions() as usize;
+    let mut extent = Extent::new();
+    match data.data_type.as_ref()? {
+        geobuf_pb::data::Data_type::FeatureCollection(fc) => {
+            for feature in &fc.features {
+                extend_geometry(&mut extent, &feature.geometry, dim);
+            }
+        }
+        geobuf_pb::data::Data_type::Feature(feature) => {
+            extend_geometry(&mut extent, &feature.geometry, dim)
+        }
+        geobuf_pb::data::Data_type::Geometry(geometry) => {
+            extend_geometry(&mut extent, geometry, dim)
+        }
+        geobuf_pb::data::Data_type::Topology(topology) => {
+            extend_topology(&mut extent, topology, dim);
+            if let Some(transform) = topology.transform.as_ref() {
+                return extent.to_bbox(1.0, transform);
+            }
+        }
+    }
+    extent.to_bbox(scale_factor(data), &data.transform)
+}
+
+/// Returns the bounding box of a geometry belonging to `data`, which provides the precision,
+/// dimensions and transform
+pub fn geometry_bbox(
+    data: &geobuf_pb::Data,
+    geometry: &geobuf_pb::data::Geometry,
+) -> Option<[f64; 4]> {
+    let mut extent = Extent::new();
+    extend_geometry(&mut extent, geometry, data.dimensions() as usize);
+    extent.to_bbox(scale_factor(data), &data.transform)
+}
+
+/// Returns true if two `[min_x, min_y, max_x, max_y]` bounding boxes intersect
+pub fn intersects(a: &[f64; 4], b: &[f64; 4]) -> bool {
+    a[0] <= b[2] && b[0] <= a[2] && a[1] <= b[3] && b[1] <= a[3]
+}
+
+fn scale_factor(data: &geobuf_pb::Data) -> f64 {
+    10f64.powi(data.precision() as i32)
+}
+
+/// Quantized extent of x and y
+struct Extent {
+    min: [i64; 2],
+    max: [i64; 2],
+}
+
+impl Extent {
+    fn new() -> Self {
+        Extent {
+            min: [i64::MAX; 2],
+            max: [i64::MIN; 2],
+        }
+    }
+
+    fn add(&mut self, x: i64, y: i64) {
+        self.min = [self.min[0].min(x), self.min[1].min(y)];
+        self.max = [self.max[0].max(x), self.max[1].max(y)];
+    }
+
+    /// Adds points stored as absolute coordinates
+    fn add_points(&mut self, coords: &[i64], dim: usize) {
+        for point in coords.chunks_exact(dim) {
+            self.add(point[0], point[1]);
+        }
+    }
+
+    /// Adds a line stored as deltas from the previous point
+    fn add_line(&mut self, coords: &[i64], dim: usize) {
+        let (mut x, mut y) = (0i64, 0i64);
+        for delta in coords.chunks_exact(dim) {
+            x += delta[0];
+            y += delta[1];
+            self.add(x, y);
+        }
+    }
+
+    /// Adds consecutive lines of `lengths` points each, or a single line if there are none
+    fn add_lines(&mut self, coords: &[i64], lengths: &[u32], dim: usize) {
+        if lengths.is_empty() {
+            return self.add_line(coords, dim);
+        }
+        let mut i = 0;
+        for l in lengths {
+            let end = i + *l as usize * dim;
+            match coords.get(i..end) {
+                Some(line) => self.add_line(line, dim),
+                None => return,
+            }
+            i = end;
+        }
+    }
+
+    fn to_bbox(&self, e: f64, transform: &geobuf_pb::data::Transform) -> Option<[f64; 4]> {
+        if self.min[0] > self.max[0] {
+            return None;
+        }
+        let decode = |coord: i64, scale: f64, translate: f64| coord as f64 / e * scale + translate;
+        let (x1, x2) = (
+            decode(self.min[0], transform.scale_x(), transform.translate_x()),
+            decode(self.max[0], transform.scale_x(), transform.translate_x()),
+        );
+        let (y1, y2) = (
+            decode(self.min[1], transform.scale_y(), transform.translate_y()),
+            decode(self.max[1], transform.scale_y(), transform.translate_y()),
+        );
+        Some([x1.min(x2), y1.min(y2), x1.max(x2), y1.max(y2)])
+    }
+}
+
+fn extend_geometry(extent: &mut Extent, geometry: &geobuf_pb::data::Geometry, dim: usize) {
+    if dim < 2 {
+        return;
+    }
+    let coords = &geometry.coords;
+    match geometry.type_() {
+        geobuf_pb::data::geometry::Type::POINT => extent.add_points(coords, dim),
+        geobuf_pb::data::geometry::Type::MULTIPOINT
+        | geobuf_pb::data::geometry::Type::LINESTRING => extent.add_line(coords, dim),
+        geobuf_pb::data::geometry::Type::MULTILINESTRING
+        | geobuf_pb::data::geometry::Type::POLYGON => {
+            extent.add_lines(coords, &geometry.lengths, dim)
+        }
+        geobuf_pb::data::geometry::Type::MULTIPOLYGON => {
+            extent.add_lines(coords, &ring_lengths(&geometry.lengths), dim)
+        }
+        geobuf_pb::data::geometry::Type::GEOMETRYCOLLECTION => {
+            for child in &geometry.geometries {
+                extend_geometry(extent, child, dim);
+            }
+        }
+    }
+}
+
+fn extend_topology(extent: &mut Extent, topology: &geobuf_pb::data::Topology, dim: usize) {
+    if dim < 2 {
+        return;
+    }
+    extent.add_lines(&topology.coords, &topology.lengths, dim);
+    for object in &topology.objects {
+        extend_topology_points(extent, object, dim);
+    }
+}
+
+fn extend_topology_points(extent: &mut Extent, geometry: &geobuf_pb::data::Geometry, dim: usize) {
+    match geometry.type_() {
+        geobuf_pb::data::geometry::Type::POINT => extent.add_points(&geometry.coords, dim),
+        geobuf_pb::data::geometry::Type::MULTIPOINT => extent.add_line(&geometry.coords, dim),
+        geobuf_pb::data::geometry::Type::GEOMETRYCOLLECTION => {
+            for child in &geometry.geometries {
+                extend_topology_points(extent, child, dim);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Returns the ring lengths of a MultiPolygon `lengths` array, which also holds the number of
+/// polygons and the number of rings of each polygon
+fn ring_lengths(lengths: &[u32]) -> Vec<u32> {
+    let mut rings = Vec::new();
+    let num_polygons = match lengths.first() {
+        Some(num_polygons) => *num_polygons,
+        None => return rings,
+    };
+    let mut j = 1;
+    for _ in 0..num_polygons {
+        let num_rings = match lengths.get(j) {
+            Some(num_rings) => *num_rings as usize,
+            None => break,
+        };
+        j += 1;
+        match lengths.get(j..j + num_rings) {
+            Some(ring) => rings.extend_from_slice(ring),
+            None => break,
+        }
+        j += num_rings;
+    }
+    rings
+}
@@ -1,112 +1,1710 @@
-use std::fs;
-use std::io::prelude::*;
+use std::ffi::OsString;
 use std::io::BufReader;
+use std::ops::Range;
 use std::process;
 
-use clap::{Parser, Subcommand};
-
-use protobuf::Message;
+use clap::{ArgMatches, CommandFactory, FromArgMatches, Parser, Subcommand};
+use clap_complete::Shell;
+use tracing::{debug, info, trace, warn};
 
+use geobuf::filter::Expr;
+use geobuf::framed::FramedWriter;
 use geobuf::geobuf_pb::Data;
 
+mod cli;
+
+use cli::bbox::parse_bbox;
+use cli::auto::Auto;
+use cli::compress::Compression;
+use cli::config::Config;
+use cli::error::{fail, ErrorFormat, ErrorKind};
+use cli::csv::GeometryColumns;
+use cli::extract::parse_range;
+use cli::prop::{parse_assignment, Edit, PropFilter};
+use cli::io::{open_input, parse_pbf, read_file, read_json_file, read_pbf_file, write_output, Format};
+use cli::sort::SortBy;
+
 #[derive(Subcommand)]
 enum SubCommands {
     Encode {
-        #[clap(short, long, help = "Path to the input GeoJSON file")]
+        #[clap(short, long, help = "Path to the input GeoJSON file (or .shp Shapefile), or - for stdin, or a glob pattern or directory with --output-dir")]
         input: String,
 
-        #[clap(short, long, help = "Path to the output PBF file")]
-        output: String,
+        #[clap(short, long, required_unless_present = "output-dir", help = "Path to the output PBF file, or - for stdout")]
+        output: Option<String>,
+
+        #[clap(long, conflicts_with = "output", help = "Convert every file matching the input pattern into this directory, keeping their relative paths")]
+        output_dir: Option<String>,
+
+        #[clap(short, long, help = "Number of dimensions in coordinates, or auto to use the largest in the input", default_value = "2")]
+        dim: Auto,
+
+        #[clap(short, long, help = "Maximum number of digits after the decimal point in coordinates, or auto for the fewest that keep every coordinate, up to 6", default_value = "6")]
+        precision: Auto,
+
+        #[clap(long, help = "Format of the input: geojson, geojsonseq for one GeoJSON text per line, csv for points with longitude and latitude columns, shapefile, kml, or gpx for waypoints, routes and tracks", default_value = "geojson")]
+        format: Format,
+
+        #[clap(long, help = "Name of the longitude column of CSV inputs", default_value = "lon")]
+        lon_field: String,
+
+        #[clap(long, help = "Name of the latitude column of CSV inputs", default_value = "lat")]
+        lat_field: String,
+
+        #[clap(long, help = "Wrap the output in a container with magic bytes and a checksum")]
+        container: bool,
+
+        #[clap(long, help = "Compress the output with gzip or zstd, optionally with a level (e.g. zstd:19); defaults to the output file extension")]
+        compress: Option<Compression>,
+
+        #[clap(long, help = "Number of threads used to convert files and large FeatureCollections [default: number of cores]")]
+        threads: Option<usize>,
+
+        #[clap(long, help = "Keep converting the files matching the input pattern, or in the input directory, into the output directory as they change")]
+        watch: bool,
+
+        #[clap(long, help = "Skip the features of GeoJSON inputs that can't be encoded instead of failing, listing them in a skipped.json file next to the output")]
+        skip_invalid: bool,
+
+        #[clap(long, use_value_delimiter = true, help = "Comma separated properties to keep, by name or glob pattern (e.g. name,pop_*) [default: all]")]
+        keep_props: Vec<String>,
+
+        #[clap(long, use_value_delimiter = true, help = "Comma separated properties to drop, by name or glob pattern (e.g. internal_*)")]
+        drop_props: Vec<String>,
+
+        #[clap(long, help = "Normalize GeoJSON inputs as RFC 7946 requires: rewind rings, remove repeated positions and split geometries crossing the antimeridian")]
+        rfc7946: bool,
+
+        #[clap(long, conflicts_with_all = &["output-dir", "container", "watch"], help = "Write a framed stream with one length-delimited message per feature, encoding geojsonseq inputs as they are read")]
+        framed: bool,
+    },
+
+    Decode {
+        #[clap(short, long, help = "Path to the input PBF file, or - for stdin, or a glob pattern with --output-dir")]
+        input: String,
+
+        #[clap(short, long, required_unless_present = "output-dir", help = "Path to the output GeoJSON file, or - for stdout")]
+        output: Option<String>,
+
+        #[clap(long, conflicts_with = "output", help = "Convert every file matching the input pattern into this directory, keeping their relative paths")]
+        output_dir: Option<String>,
+
+        #[clap(short, long, help = "Pretty write GeoJSON")]
+        pretty: bool,
+
+        #[clap(long, help = "Format of the output: geojson, geojsonseq for one Feature per line (RFC 8142), wkt for one WKT geometry per line, or csv for one row per feature", default_value = "geojson")]
+        format: Format,
+
+        #[clap(long, use_value_delimiter = true, help = "Comma separated columns written after each WKT geometry: id or property names")]
+        columns: Vec<String>,
+
+        #[clap(long, help = "Geometry columns of CSV outputs: wkt, or xy for x and y columns of points", default_value = "wkt")]
+        geometry: GeometryColumns,
+
+        #[clap(long, allow_hyphen_values = true, parse(try_from_str = parse_bbox), help = "Only write features intersecting min_x,min_y,max_x,max_y")]
+        bbox: Option<[f64; 4]>,
+
+        #[clap(long, help = "Compress the output with gzip or zstd, optionally with a level (e.g. zstd:19); defaults to the output file extension")]
+        compress: Option<Compression>,
+
+        #[clap(long, help = "Number of threads used to convert files and large FeatureCollections [default: number of cores]")]
+        threads: Option<usize>,
+
+        #[clap(long, help = "Number of features to leave out from the start, after filtering with --bbox", default_value = "0")]
+        skip: usize,
+
+        #[clap(long, help = "Maximum number of features to decode, after --skip")]
+        limit: Option<usize>,
+
+        #[clap(long, conflicts_with = "output-dir", help = "Read a framed stream with one length-delimited message per feature, decoding frames as they are read")]
+        framed: bool,
+    },
+
+    #[clap(about = "Print a summary of a PBF file without decoding it")]
+    #[clap(mut_arg("input", |arg| arg.help("Path to the input PBF file, or - for stdin")))]
+    Info {
+        #[clap(flatten)]
+        input: Input,
+    },
+
+    #[clap(about = "Print the number of features of a PBF file without decoding coordinates or properties")]
+    #[clap(mut_arg("input", |arg| arg.help("Path to the input PBF file, or - for stdin")))]
+    Count {
+        #[clap(flatten)]
+        input: Input,
+
+        #[clap(long, help = "Print the number of features of each geometry type")]
+        by_type: bool,
+    },
+
+    #[clap(about = "List the property keys of a PBF file with their value types, null counts and distinct value counts")]
+    #[clap(mut_arg("input", |arg| arg.help("Path to the input PBF file, or - for stdin")))]
+    Keys {
+        #[clap(flatten)]
+        input: Input,
+
+        #[clap(long, help = "Stop counting distinct values of a key at this number", default_value = "1000")]
+        max_distinct: usize,
+    },
+
+    #[clap(about = "Print the bounding box of a PBF file, computed from the quantized coordinates")]
+    #[clap(mut_arg("input", |arg| arg.help("Path to the input PBF file, or - for stdin")))]
+    Bbox {
+        #[clap(flatten)]
+        input: Input,
+
+        #[clap(long, help = "Print the bounding box of every feature instead, one JSON object per line")]
+        per_feature: bool,
+    },
+
+    #[clap(about = "Print coordinate counts, a breakdown of the file size and the largest features of a PBF file")]
+    #[clap(mut_arg("input", |arg| arg.help("Path to the input PBF file, or - for stdin")))]
+    Stats {
+        #[clap(flatten)]
+        input: Input,
+
+        #[clap(short, long, help = "Number of largest features to list", default_value = "10")]
+        top: usize,
+    },
+
+    #[clap(about = "Encode a GeoJSON file, decode it back and report coordinate errors and mismatches, exiting with 4 on failures")]
+    #[clap(mut_arg("input", |arg| arg.help("Path to the input GeoJSON file, or - for stdin")))]
+    Verify {
+        #[clap(flatten)]
+        input: Input,
 
         #[clap(short, long, help = "Number of dimensions in coordinates", default_value = "2")]
         dim: u32,
 
         #[clap(short, long, help = "Maximum number of digits after the decimal point in coordinates", default_value = "6")]
         precision: u32,
+
+        #[clap(short, long, help = "Maximum accepted coordinate error [default: 10^-precision]")]
+        tolerance: Option<f64>,
     },
 
-    Decode {
-        #[clap(short, long, help = "Path to the input PBF file")]
-        input: String,
+    #[clap(about = "Compare the features of two PBF or GeoJSON files by id, exiting with 1 if they differ")]
+    Diff {
+        #[clap(help = "Path to the old PBF or GeoJSON file, or - for stdin")]
+        old: String,
 
-        #[clap(short, long, help = "Path to the output GeoJSON file")]
+        #[clap(help = "Path to the new PBF or GeoJSON file, or - for stdin")]
+        new: String,
+
+        #[clap(short, long, help = "Precision used to encode GeoJSON inputs [default: precision of the PBF input, or 6]")]
+        precision: Option<u32>,
+
+        #[clap(long, help = "Print the differences as JSON")]
+        json: bool,
+    },
+
+    #[clap(about = "Merge PBF and GeoJSON files into a single FeatureCollection")]
+    Merge {
+        #[clap(required = true, help = "Paths to the input PBF or GeoJSON files, or - for stdin")]
+        inputs: Vec<String>,
+
+        #[clap(short, long, help = "Path to the output PBF file, or - for stdout")]
         output: String,
 
-        #[clap(short, long, help = "Pretty write GeoJSON")]
+        #[clap(short, long, help = "Precision used to encode GeoJSON inputs [default: highest precision of the PBF inputs, or 6]")]
+        precision: Option<u32>,
+
+        #[clap(long, help = "Wrap the output in a container with magic bytes and a checksum")]
+        container: bool,
+
+        #[clap(long, help = "Compress the output with gzip or zstd, optionally with a level (e.g. zstd:19); defaults to the output file extension")]
+        compress: Option<Compression>,
+    },
+
+    #[clap(about = "Append the features of PBF and GeoJSON files to a PBF file, extending its key table")]
+    Append {
+        #[clap(help = "Path to the PBF file to append to, or - for stdin")]
+        base: String,
+
+        #[clap(required = true, help = "Paths to the PBF or GeoJSON files to append, or - for stdin")]
+        inputs: Vec<String>,
+
+        #[clap(short, long, help = "Path to the output PBF file, or - for stdout; may be the base file")]
+        output: String,
+
+        #[clap(long, help = "Wrap the output in a container with magic bytes and a checksum")]
+        container: bool,
+
+        #[clap(long, help = "Compress the output with gzip or zstd, optionally with a level (e.g. zstd:19); defaults to the output file extension")]
+        compress: Option<Compression>,
+    },
+
+    #[clap(about = "Split a PBF or GeoJSON FeatureCollection into numbered standalone PBF files")]
+    Split {
+        #[clap(flatten)]
+        input: Input,
+
+        #[clap(short, long, help = "Output path template, numbered before the extension (out.pbf gives out-0.pbf, out-1.pbf, ...)")]
+        output: String,
+
+        #[clap(long, help = "Maximum number of features per file")]
+        features_per_file: Option<usize>,
+
+        #[clap(long, help = "Maximum size of each file in bytes, before compression")]
+        max_bytes: Option<usize>,
+
+        #[clap(short, long, help = "Precision used to encode GeoJSON input", default_value = "6")]
+        precision: u32,
+
+        #[clap(short, long, help = "Number of dimensions used to encode GeoJSON input", default_value = "2")]
+        dim: u32,
+
+        #[clap(long, help = "Wrap the outputs in a container with magic bytes and a checksum")]
+        container: bool,
+
+        #[clap(long, help = "Compress the outputs with gzip or zstd, optionally with a level (e.g. zstd:19); defaults to the output file extension")]
+        compress: Option<Compression>,
+    },
+
+    #[clap(about = "Keep the features of a PBF or GeoJSON file matching an expression")]
+    Filter {
+        #[clap(flatten)]
+        input: Input,
+
+        #[clap(short, long, help = "Path to the output PBF file, or - for stdout")]
+        output: String,
+
+        #[clap(long = "where", required_unless_present = "bbox", help = "Features to keep, e.g. \"population > 100000 && state == 'CA'\"")]
+        expression: Option<Expr>,
+
+        #[clap(long, allow_hyphen_values = true, parse(try_from_str = parse_bbox), help = "Only keep features intersecting min_x,min_y,max_x,max_y")]
+        bbox: Option<[f64; 4]>,
+
+        #[clap(short, long, help = "Precision used to encode GeoJSON input", default_value = "6")]
+        precision: u32,
+
+        #[clap(short, long, help = "Number of dimensions used to encode GeoJSON input", default_value = "2")]
+        dim: u32,
+
+        #[clap(long, help = "Wrap the output in a container with magic bytes and a checksum")]
+        container: bool,
+
+        #[clap(long, help = "Compress the output with gzip or zstd, optionally with a level (e.g. zstd:19); defaults to the output file extension")]
+        compress: Option<Compression>,
+    },
+
+    #[clap(about = "Drop duplicate features of a PBF or GeoJSON file, keeping the first of each")]
+    Dedupe {
+        #[clap(flatten)]
+        input: Input,
+
+        #[clap(short, long, help = "Path to the output PBF file, or - for stdout")]
+        output: String,
+
+        #[clap(long, help = "Compare features by id if it is id, or else by the property of the same name [default: by geometry and properties]")]
+        key: Option<String>,
+
+        #[clap(short, long, help = "Precision used to encode GeoJSON inputs", default_value = "6")]
+        precision: u32,
+
+        #[clap(short, long, help = "Number of dimensions in coordinates of GeoJSON inputs", default_value = "2")]
+        dim: u32,
+
+        #[clap(long, help = "Wrap the output in a container with magic bytes and a checksum")]
+        container: bool,
+
+        #[clap(long, help = "Compress the output with gzip or zstd, optionally with a level (e.g. zstd:19); defaults to the output file extension")]
+        compress: Option<Compression>,
+    },
+
+    #[clap(about = "Set, delete or rename a property of every feature of a PBF file, or of the features matching --where")]
+    Prop {
+        #[clap(subcommand)]
+        action: PropAction,
+    },
+
+    #[clap(about = "Join the columns of a CSV file onto the features of a PBF or GeoJSON file by id or by a property")]
+    Join {
+        #[clap(flatten)]
+        input: Input,
+
+        #[clap(short, long, help = "Path to the output PBF file, or - for stdout")]
+        output: String,
+
+        #[clap(long, help = "Path to the CSV file with a header row")]
+        csv: String,
+
+        #[clap(long, help = "Column matched against the feature ids if it is id, or else against the property of the same name")]
+        on: String,
+
+        #[clap(short, long, help = "Precision used to encode GeoJSON inputs", default_value = "6")]
+        precision: u32,
+
+        #[clap(short, long, help = "Number of dimensions in coordinates of GeoJSON inputs", default_value = "2")]
+        dim: u32,
+
+        #[clap(long, help = "Wrap the output in a container with magic bytes and a checksum")]
+        container: bool,
+
+        #[clap(long, help = "Compress the output with gzip or zstd, optionally with a level (e.g. zstd:19); defaults to the output file extension")]
+        compress: Option<Compression>,
+    },
+
+    #[clap(about = "Serve a PBF or GeoJSON file as GeoJSON over HTTP with a map preview page")]
+    Serve {
+        #[clap(flatten)]
+        input: Input,
+
+        #[clap(long, help = "Address to listen on", default_value = "127.0.0.1")]
+        host: String,
+
+        #[clap(long, help = "Port to listen on", default_value = "8080")]
+        port: u16,
+
+        #[clap(short, long, help = "Precision used to encode GeoJSON inputs", default_value = "6")]
+        precision: u32,
+
+        #[clap(short, long, help = "Number of dimensions in coordinates of GeoJSON inputs", default_value = "2")]
+        dim: u32,
+    },
+
+    #[clap(about = "Time encoding and decoding a PBF or GeoJSON file and report the throughput and peak memory use")]
+    Bench {
+        #[clap(flatten)]
+        input: Input,
+
+        #[clap(short = 'n', long, help = "Number of times to encode and decode", default_value = "10")]
+        iterations: usize,
+
+        #[clap(short, long, help = "Precision used to encode [default: 6 for GeoJSON inputs, precision of PBF inputs]")]
+        precision: Option<u32>,
+
+        #[clap(short, long, help = "Number of dimensions used to encode [default: 2 for GeoJSON inputs, dimensions of PBF inputs]")]
+        dim: Option<u32>,
+    },
+
+    #[clap(about = "Print a shell completion script for geobuf")]
+    Completions {
+        #[clap(help = "Shell to complete in: bash, zsh, fish, powershell or elvish")]
+        shell: Shell,
+    },
+
+    #[clap(about = "Check a PBF file for consistency and print a JSON report, exiting with 4 on problems")]
+    #[clap(mut_arg("input", |arg| arg.help("Path to the input PBF file, or - for stdin")))]
+    Validate {
+        #[clap(flatten)]
+        input: Input,
+    },
+
+    #[clap(about = "Write the features of a PBF or GeoJSON file with an id or in a range of indexes")]
+    Extract {
+        #[clap(flatten)]
+        input: Input,
+
+        #[clap(short, long, help = "Path to the output file, or - for stdout", default_value = "-")]
+        output: String,
+
+        #[clap(long, required_unless_present = "index", conflicts_with = "index", help = "Id of the features to extract")]
+        id: Option<String>,
+
+        #[clap(long, parse(try_from_str = parse_range), help = "Index of the feature to extract, or a range of indexes such as 100..200")]
+        index: Option<Range<usize>>,
+
+        #[clap(long, help = "Write a PBF file instead of GeoJSON")]
+        pbf: bool,
+
+        #[clap(long, help = "Pretty write GeoJSON")]
         pretty: bool,
+
+        #[clap(short, long, help = "Precision used to encode GeoJSON inputs", default_value = "6")]
+        precision: u32,
+
+        #[clap(short, long, help = "Number of dimensions in coordinates of GeoJSON inputs", default_value = "2")]
+        dim: u32,
+
+        #[clap(long, help = "Compress the output with gzip or zstd, optionally with a level (e.g. zstd:19); defaults to the output file extension")]
+        compress: Option<Compression>,
+    },
+
+    #[clap(about = "Write the first features of a PBF or GeoJSON file")]
+    Head {
+        #[clap(short = 'n', long, help = "Number of features to write", default_value = "10")]
+        count: usize,
+
+        #[clap(flatten)]
+        args: SubsetArgs,
+    },
+
+    #[clap(about = "Write the last features of a PBF or GeoJSON file")]
+    Tail {
+        #[clap(short = 'n', long, help = "Number of features to write", default_value = "10")]
+        count: usize,
+
+        #[clap(flatten)]
+        args: SubsetArgs,
+    },
+
+    #[clap(about = "Write a random sample of the features of a PBF or GeoJSON file")]
+    Sample {
+        #[clap(long, parse(try_from_str = cli::sample::parse_fraction), help = "Probability of writing each feature, e.g. 0.01 for about 1% of them")]
+        fraction: f64,
+
+        #[clap(long, help = "Seed giving the same sample on every run [default: a new sample on every run]")]
+        seed: Option<u64>,
+
+        #[clap(flatten)]
+        args: SubsetArgs,
+    },
+
+    #[clap(about = "Re-encode a PBF file, optionally with another precision and dimensions, and report the size change")]
+    #[clap(mut_arg("input", |arg| arg.help("Path to the input PBF file, or - for stdin")))]
+    Compact {
+        #[clap(flatten)]
+        input: Input,
+
+        #[clap(short, long, help = "Path to the output PBF file, or - for stdout")]
+        output: String,
+
+        #[clap(short, long, help = "Maximum number of digits after the decimal point in coordinates [default: precision of the input]")]
+        precision: Option<u32>,
+
+        #[clap(short, long, help = "Number of dimensions in coordinates [default: dimensions of the input]")]
+        dim: Option<u32>,
+
+        #[clap(long, help = "Also sort the features: hilbert for spatial locality, or property:NAME for the values of a property")]
+        sort: Option<SortBy>,
+
+        #[clap(long, help = "Wrap the output in a container with magic bytes and a checksum")]
+        container: bool,
+
+        #[clap(long, help = "Compress the output with gzip or zstd, optionally with a level (e.g. zstd:19); defaults to the output file extension")]
+        compress: Option<Compression>,
+    },
+
+    #[clap(about = "Simplify the lines and rings of a PBF or GeoJSON file with the Douglas-Peucker algorithm")]
+    Simplify {
+        #[clap(flatten)]
+        input: Input,
+
+        #[clap(short, long, help = "Path to the output PBF file, or - for stdout")]
+        output: String,
+
+        #[clap(short, long, help = "Maximum distance between removed positions and the simplified lines, in coordinate units")]
+        tolerance: f64,
+
+        #[clap(short, long, help = "Precision used to encode GeoJSON inputs", default_value = "6")]
+        precision: u32,
+
+        #[clap(short, long, help = "Number of dimensions in coordinates of GeoJSON inputs", default_value = "2")]
+        dim: u32,
+
+        #[clap(long, help = "Wrap the output in a container with magic bytes and a checksum")]
+        container: bool,
+
+        #[clap(long, help = "Compress the output with gzip or zstd, optionally with a level (e.g. zstd:19); defaults to the output file extension")]
+        compress: Option<Compression>,
+    },
+
+    #[clap(about = "Split the multi-geometry features of a PBF or GeoJSON file into a feature per part")]
+    Explode {
+        #[clap(flatten)]
+        input: Input,
+
+        #[clap(short, long, help = "Path to the output PBF file, or - for stdout")]
+        output: String,
+
+        #[clap(short, long, help = "Precision used to encode GeoJSON inputs", default_value = "6")]
+        precision: u32,
+
+        #[clap(short, long, help = "Number of dimensions in coordinates of GeoJSON inputs", default_value = "2")]
+        dim: u32,
+
+        #[clap(long, help = "Wrap the output in a container with magic bytes and a checksum")]
+        container: bool,
+
+        #[clap(long, help = "Compress the output with gzip or zstd, optionally with a level (e.g. zstd:19); defaults to the output file extension")]
+        compress: Option<Compression>,
+    },
+
+    #[clap(about = "Sort the features of a PBF or GeoJSON FeatureCollection spatially or by a property")]
+    Sort {
+        #[clap(flatten)]
+        input: Input,
+
+        #[clap(short, long, help = "Path to the output PBF file, or - for stdout")]
+        output: String,
+
+        #[clap(long, help = "Order of the features: hilbert for spatial locality, or property:NAME for the values of a property")]
+        by: SortBy,
+
+        #[clap(short, long, help = "Precision used to encode GeoJSON inputs", default_value = "6")]
+        precision: u32,
+
+        #[clap(short, long, help = "Number of dimensions in coordinates of GeoJSON inputs", default_value = "2")]
+        dim: u32,
+
+        #[clap(long, help = "Wrap the output in a container with magic bytes and a checksum")]
+        container: bool,
+
+        #[clap(long, help = "Compress the output with gzip or zstd, optionally with a level (e.g. zstd:19); defaults to the output file extension")]
+        compress: Option<Compression>,
+    },
+
+    #[clap(about = "Clip a PBF or GeoJSON file to an XYZ tile and write the tile as a PBF file")]
+    Tile {
+        #[clap(flatten)]
+        input: Input,
+
+        #[clap(short, long, help = "Path to the output PBF file, or - for stdout")]
+        output: String,
+
+        #[clap(short, long, visible_alias = "z", help = "Zoom level of the tile")]
+        zoom: u32,
+
+        #[clap(long, help = "Column of the tile")]
+        x: u32,
+
+        #[clap(long, help = "Row of the tile, counted from the north")]
+        y: u32,
+
+        #[clap(long, help = "Margin kept around the tile when clipping, as a fraction of the tile size", default_value = "0.015625")]
+        buffer: f64,
+
+        #[clap(long, help = "Wrap the output in a container with magic bytes and a checksum")]
+        container: bool,
+
+        #[clap(long, help = "Compress the output with gzip or zstd, optionally with a level (e.g. zstd:19); defaults to the output file extension")]
+        compress: Option<Compression>,
+    },
+
+    #[clap(about = "Write a z/x/y directory tree of clipped and simplified PBF tiles of a PBF or GeoJSON file")]
+    Tiles {
+        #[clap(flatten)]
+        input: Input,
+
+        #[clap(long, help = "Directory the tiles are written to, as z/x/y.pbf")]
+        output_dir: String,
+
+        #[clap(long, help = "Lowest zoom level", default_value = "0")]
+        min_zoom: u32,
+
+        #[clap(long, help = "Highest zoom level")]
+        max_zoom: u32,
+
+        #[clap(long, help = "Margin kept around each tile when clipping, as a fraction of the tile size", default_value = "0.015625")]
+        buffer: f64,
+
+        #[clap(long, help = "Simplification tolerance in pixels of a 256 pixel tile, or 0 to keep every position", default_value = "1")]
+        tolerance: f64,
+
+        #[clap(long, help = "Wrap the outputs in a container with magic bytes and a checksum")]
+        container: bool,
+
+        #[clap(long, help = "Compress the outputs with gzip or zstd, optionally with a level (e.g. zstd:19)")]
+        compress: Option<Compression>,
+    },
+
+    #[clap(about = "Clip a PBF or GeoJSON file to a tile and write it as a Mapbox Vector Tile")]
+    ToMvt {
+        #[clap(flatten)]
+        input: Input,
+
+        #[clap(short, long, help = "Path to the output MVT file, or - for stdout")]
+        output: String,
+
+        #[clap(short, long, help = "Zoom level of the tile")]
+        zoom: u32,
+
+        #[clap(long, help = "Column of the tile")]
+        x: u32,
+
+        #[clap(long, help = "Row of the tile, counted from the north")]
+        y: u32,
+
+        #[clap(long, help = "Name of the layer", default_value = "geobuf")]
+        layer: String,
+
+        #[clap(long, help = "Number of units per tile side", default_value = "4096")]
+        extent: u32,
+
+        #[clap(long, help = "Number of units kept around the tile when clipping", default_value = "64")]
+        buffer: u32,
+
+        #[clap(long, help = "Compress the output with gzip or zstd, optionally with a level (e.g. zstd:19); defaults to the output file extension")]
+        compress: Option<Compression>,
+    },
+
+    #[cfg(feature = "proj")]
+    #[clap(about = "Transform the coordinates of a PBF or GeoJSON file to another coordinate reference system")]
+    Reproject {
+        #[clap(flatten)]
+        input: Input,
+
+        #[clap(short, long, help = "Path to the output PBF file, or - for stdout")]
+        output: String,
+
+        #[clap(long, help = "Coordinate reference system of the input, e.g. EPSG:4326")]
+        from: String,
+
+        #[clap(long, help = "Coordinate reference system of the output, e.g. EPSG:3857")]
+        to: String,
+
+        #[clap(short, long, help = "Maximum number of digits after the decimal point in output coordinates [default: precision of the input]")]
+        precision: Option<u32>,
+
+        #[clap(long, help = "Wrap the output in a container with magic bytes and a checksum")]
+        container: bool,
+
+        #[clap(long, help = "Compress the output with gzip or zstd, optionally with a level (e.g. zstd:19); defaults to the output file extension")]
+        compress: Option<Compression>,
+    },
+
+    #[cfg(feature = "fgb")]
+    #[clap(about = "Convert a FlatGeobuf file into a PBF file")]
+    #[clap(mut_arg("input", |arg| arg.help("Path to the input FlatGeobuf file, or - for stdin")))]
+    FromFgb {
+        #[clap(flatten)]
+        input: Input,
+
+        #[clap(short, long, help = "Path to the output PBF file, or - for stdout")]
+        output: String,
+
+        #[clap(long, help = "Property holding the feature ids", default_value = "id")]
+        id_column: String,
+
+        #[clap(short, long, help = "Number of dimensions in coordinates", default_value = "2")]
+        dim: u32,
+
+        #[clap(short, long, help = "Maximum number of digits after the decimal point in coordinates", default_value = "6")]
+        precision: u32,
+
+        #[clap(long, help = "Wrap the output in a container with magic bytes and a checksum")]
+        container: bool,
+
+        #[clap(long, help = "Compress the output with gzip or zstd, optionally with a level (e.g. zstd:19); defaults to the output file extension")]
+        compress: Option<Compression>,
+    },
+
+    #[cfg(feature = "fgb")]
+    #[clap(about = "Convert a PBF file into a FlatGeobuf file with a spatial index")]
+    #[clap(mut_arg("input", |arg| arg.help("Path to the input PBF file, or - for stdin")))]
+    ToFgb {
+        #[clap(flatten)]
+        input: Input,
+
+        #[clap(short, long, help = "Path to the output FlatGeobuf file, or - for stdout")]
+        output: String,
+
+        #[clap(long, help = "Property the feature ids are written to", default_value = "id")]
+        id_column: String,
+    },
+
+    #[cfg(feature = "geo")]
+    #[clap(about = "Report self-intersections, unclosed rings, duplicate vertices and zero-area rings as JSON, exiting with 4 on problems")]
+    CheckGeometry {
+        #[clap(flatten)]
+        input: Input,
+    },
+
+    #[cfg(feature = "geo")]
+    #[clap(about = "Fix the problems found by check-geometry in a PBF or GeoJSON file: remove duplicate vertices, close rings and rebuild self-intersecting polygons")]
+    Repair {
+        #[clap(flatten)]
+        input: Input,
+
+        #[clap(short, long, help = "Path to the output PBF file, or - for stdout")]
+        output: String,
+
+        #[clap(short, long, help = "Precision used to encode GeoJSON inputs", default_value = "6")]
+        precision: u32,
+
+        #[clap(short, long, help = "Number of dimensions in coordinates of GeoJSON inputs", default_value = "2")]
+        dim: u32,
+
+        #[clap(long, help = "Wrap the output in a container with magic bytes and a checksum")]
+        container: bool,
+
+        #[clap(long, help = "Compress the output with gzip or zstd, optionally with a level (e.g. zstd:19); defaults to the output file extension")]
+        compress: Option<Compression>,
+    },
+}
+
+#[derive(Subcommand)]
+enum PropAction {
+    #[clap(about = "Set a property, parsing the value as JSON or else taking it as a string")]
+    Set {
+        #[clap(parse(try_from_str = parse_assignment), help = "Property and value, e.g. status=\"reviewed\" or count=3")]
+        assignment: (String, String),
+
+        #[clap(flatten)]
+        args: PropArgs,
+    },
+
+    #[clap(about = "Delete a property")]
+    Delete {
+        #[clap(help = "Name of the property")]
+        key: String,
+
+        #[clap(flatten)]
+        args: PropArgs,
+    },
+
+    #[clap(about = "Rename a property")]
+    Rename {
+        #[clap(parse(try_from_str = parse_assignment), help = "Old and new names, e.g. old_name=new_name")]
+        names: (String, String),
+
+        #[clap(flatten)]
+        args: PropArgs,
+    },
+}
+
+/// Path to the input given with -i/--input, or as a positional argument like before
+#[derive(clap::Args)]
+struct Input {
+    #[clap(short, long = "input", value_name = "INPUT", required_unless_present = "input-path", help = "Path to the input PBF or GeoJSON file, or - for stdin")]
+    input: Option<String>,
+
+    #[clap(name = "input-path", value_name = "INPUT", conflicts_with = "input", hide = true)]
+    path: Option<String>,
+}
+
+impl Input {
+    fn path(self) -> String {
+        self.input.or(self.path).unwrap()
     }
 }
 
+#[derive(clap::Args)]
+#[clap(mut_arg("input", |arg| arg.help("Path to the input PBF file, or - for stdin")))]
+struct PropArgs {
+    #[clap(flatten)]
+    input: Input,
+
+    #[clap(short, long, help = "Path to the output PBF file, or - for stdout; may be the input file")]
+    output: String,
+
+    #[clap(long = "where", help = "Only edit features matching the expression, e.g. \"state == 'CA'\"")]
+    expression: Option<Expr>,
+
+    #[clap(long, help = "Wrap the output in a container with magic bytes and a checksum")]
+    container: bool,
+
+    #[clap(long, help = "Compress the output with gzip or zstd, optionally with a level (e.g. zstd:19); defaults to the output file extension")]
+    compress: Option<Compression>,
+}
+
+#[derive(clap::Args)]
+struct SubsetArgs {
+    #[clap(flatten)]
+    input: Input,
+
+    #[clap(short, long, help = "Path to the output file, or - for stdout", default_value = "-")]
+    output: String,
+
+    #[clap(long, help = "Write a PBF file instead of GeoJSON")]
+    pbf: bool,
+
+    #[clap(long, help = "Pretty write GeoJSON")]
+    pretty: bool,
+
+    #[clap(short, long, help = "Precision used to encode GeoJSON inputs", default_value = "6")]
+    precision: u32,
+
+    #[clap(short, long, help = "Number of dimensions in coordinates of GeoJSON inputs", default_value = "2")]
+    dim: u32,
+
+    #[clap(long, help = "Compress the output with gzip or zstd, optionally with a level (e.g. zstd:19); defaults to the output file extension")]
+    compress: Option<Compression>,
+}
+
 #[derive(Parser, Default)]
 #[clap(arg_required_else_help = true)]
 #[clap(about = "Geobuf encoder and decoder")]
 #[clap(version)]
 struct Args {
     #[clap(subcommand)]
-    commands: Option<SubCommands>
-}
+    commands: Option<SubCommands>,
 
-pub fn read_json_file(file_path: String) -> serde_json::Value {
-    let file = match fs::File::open(&file_path) {
-        Ok(file) => file,
-        Err(_) => {
-            println!("Could not open {}", file_path);
-            process::exit(1);
-        }
-    };
-    let buff_reader = BufReader::new(file);
-    match serde_json::from_reader(buff_reader) {
-        Ok(geojson) => geojson,
-        Err(_) => {
-            println!("Could not parse geojson: {}", file_path);
-            process::exit(1);
-        }
-    }
+    #[clap(short, long, global = true, parse(from_occurrences), help = "Log stage timings and feature counts, or more details when repeated")]
+    verbose: u64,
+
+    #[clap(short, long, global = true, help = "Only print errors, without progress bars")]
+    quiet: bool,
+
+    #[clap(long, global = true, help = "Format of error messages: text or json, with code, path and message fields", default_value = "text")]
+    error_format: ErrorFormat,
+
+    #[clap(long, global = true, help = "Path to a TOML file setting the default precision, dim, compress, threads, keep-props and drop-props options [default: geobuf.toml if it exists]")]
+    config: Option<String>,
 }
 
-pub fn read_pbf_file(file_path: String) -> Data {
-    let mut file = match fs::File::open(&file_path) {
-        Ok(file) => file,
-        Err(_) => {
-            println!("Could not open {}", &file_path);
-            process::exit(1);
+/// Parses the command line `args`
+fn parse_args(args: &[OsString]) -> (Args, ArgMatches) {
+    let matches = Args::command().try_get_matches_from(args).unwrap_or_else(|err| {
+        if !err.use_stderr() {
+            err.exit();
         }
-    };
-    let mut contents = vec![];
-    file.read_to_end(&mut contents).unwrap();
-    let mut data = Data::new();
-    data.merge_from_bytes(&contents).unwrap();
-    data
+        // Usage errors exit with 1 rather than clap's 2, which is the exit code of bad input files
+        let _ = err.print();
+        process::exit(1);
+    });
+    let parsed = Args::from_arg_matches(&matches).unwrap_or_else(|err| err.exit());
+    (parsed, matches)
 }
 
 fn main() {
-    let matches = Args::parse();
+    let args: Vec<OsString> = std::env::args_os().collect();
+    let (mut matches, arg_matches) = parse_args(&args);
+    cli::progress::set_quiet(matches.quiet);
+    cli::log::init(matches.verbose, matches.quiet);
+    cli::error::set_format(matches.error_format);
+    match Config::load(matches.config.as_deref()) {
+        Ok(Some(config)) => {
+            let args = config.apply(&Args::command(), &arg_matches, args);
+            matches = parse_args(&args).0;
+        }
+        Ok(None) => {}
+        Err((kind, err)) => fail(kind, matches.config.as_deref().or(Some(cli::config::DEFAULT_PATH)), &err),
+    }
     match matches.commands {
-        Some(SubCommands::Encode { input, output, dim, precision }) => {
-            let geojson = read_json_file(input);
-            let data = geobuf::encode::Encoder::encode(
-                &geojson,
-                precision,
-                dim,
-            )
-            .unwrap();
-            let msg = data.write_to_bytes().unwrap();
-            let mut f = fs::File::create(output).unwrap();
-            f.write_all(&msg).unwrap();
-        },
-        Some(SubCommands::Decode { input, output, pretty }) => {
-            let data = read_pbf_file(input);
-            let geojson = geobuf::decode::Decoder::decode(&data).unwrap();
-            let mut f = fs::File::create(output).unwrap();
-            let geojson_str = if pretty {
-                serde_json::to_vec_pretty(&geojson).unwrap()
+        Some(SubCommands::Encode { input, output, output_dir, dim, precision, format, lon_field, lat_field, container, compress, threads, watch, skip_invalid, keep_props, drop_props, rfc7946, framed }) => {
+            if let Some(threads) = threads {
+                cli::parallel::set_threads(threads);
+            }
+            if format == Format::Wkt {
+                fail(ErrorKind::Other, None, "WKT input is not supported");
+            }
+            let format = match format {
+                Format::GeoJson if input.ends_with(".shp") => Format::Shapefile,
+                Format::GeoJson if input.ends_with(".kml") => Format::Kml,
+                Format::GeoJson if input.ends_with(".gpx") => Format::Gpx,
+                format => format,
+            };
+            // -o names the output directory when watching
+            let output_dir = if watch { output_dir.or(output.clone()) } else { output_dir };
+            if format == Format::Shapefile && output_dir.is_some() {
+                fail(ErrorKind::Other, None, "Shapefile inputs can't be converted with --output-dir or --watch");
+            }
+            let (precision_arg, dim_arg) = (precision, dim);
+            let (precision, dim) = match (precision, dim) {
+                (Auto::Value(precision), Auto::Value(dim)) => (precision, dim),
+                _ if format != Format::GeoJson => fail(ErrorKind::Other, None, "--precision auto and --dim auto need GeoJSON input"),
+                // Resolved for each GeoJSON input below
+                _ => (0, 0),
+            };
+            if skip_invalid && format != Format::GeoJson {
+                fail(ErrorKind::Other, None, "--skip-invalid needs GeoJSON input");
+            }
+            if rfc7946 && format != Format::GeoJson {
+                fail(ErrorKind::Other, None, "--rfc7946 needs GeoJSON input");
+            }
+            let props = PropFilter::new(&keep_props, &drop_props).unwrap_or_else(|err| fail(ErrorKind::Other, None, &err));
+            if let Some(output_dir) = output_dir {
+                let input = if std::path::Path::new(&input).is_dir() {
+                    let files = match format {
+                        Format::GeoJsonSeq => "*.geojsons",
+                        Format::Csv => "*.csv",
+                        Format::Kml => "*.kml",
+                        Format::Gpx => "*.gpx",
+                        _ => "*json",
+                    };
+                    format!("{}/**/{}", input.trim_end_matches('/'), files)
+                } else {
+                    input
+                };
+                let report = cli::skip::BatchReport::new(&output_dir);
+                let convert = |path: &std::path::Path, contents: &[u8]| -> Result<Vec<u8>, String> {
+                    let mut data = match format {
+                        Format::GeoJson => {
+                            let mut geojson = serde_json::from_slice(contents).map_err(|_| String::from("could not parse geojson"))?;
+                            let (precision, dim) = cli::auto::resolve(&geojson, precision_arg, dim_arg);
+                            if skip_invalid {
+                                let skipped = cli::skip::skip_invalid(&mut geojson, precision, dim);
+                                if !skipped.is_empty() {
+                                    warn!("{}: skipped {} invalid features", path.display(), skipped.len());
+                                }
+                                report.update(path, skipped)?;
+                            }
+                            if rfc7946 {
+                                let normalized = cli::rfc7946::normalize(&mut geojson)?;
+                                debug!("{}: rewound {} rings, removed {} repeated positions and split {} geometries", path.display(), normalized.rewound, normalized.duplicates, normalized.split);
+                            }
+                            cli::parallel::encode(geojson, precision, dim)?
+                        }
+                        Format::GeoJsonSeq => cli::seq::encode(contents, precision, dim)?,
+                        Format::Csv => cli::csv::encode(contents, &lon_field, &lat_field, precision, dim)?,
+                        #[cfg(feature = "kml")]
+                        Format::Kml => cli::kml::encode(contents, precision, dim)?,
+                        #[cfg(feature = "gpx")]
+                        Format::Gpx => cli::gpx::encode(contents, precision, dim)?,
+                        #[cfg(not(feature = "kml"))]
+                        Format::Kml => return Err(String::from("built without the kml feature")),
+                        #[cfg(not(feature = "gpx"))]
+                        Format::Gpx => return Err(String::from("built without the gpx feature")),
+                        Format::Wkt | Format::Shapefile => unreachable!(),
+                    };
+                    props.apply(&mut data)?;
+                    Ok(geobuf::container::to_bytes(&data, container)?)
+                };
+                if watch {
+                    #[cfg(not(target_os = "wasi"))]
+                    cli::batch::watch(&input, &output_dir, "pbf", compress, convert);
+                    #[cfg(target_os = "wasi")]
+                    fail(ErrorKind::Other, None, "--watch isn't available in WASI builds");
+                }
+                cli::batch::convert_all(&input, &output_dir, "pbf", compress, convert);
+                return;
+            }
+            if framed && format == Format::GeoJsonSeq {
+                // Records are encoded and written as they are read, so that pipes stream
+                let output = output.unwrap();
+                let mut writer = FramedWriter::new(cli::io::create_output(&output, compress));
+                let frames = cli::framed::encode_seq(BufReader::new(open_input(&input)), &mut writer, precision, dim, &props)
+                    .and_then(|frames| writer.into_inner().finish().map(|_| frames).map_err(|_| String::from("could not write output")));
+                match frames {
+                    Ok(frames) => debug!("Wrote {} frames", frames),
+                    Err(err) => {
+                        fail(ErrorKind::Parse, Some(&input), &format!("Could not encode {}: {}", input, err));
+                    }
+                }
+                return;
+            }
+            let data = match format {
+                Format::GeoJson => {
+                    let mut geojson = read_json_file(&input);
+                    let (precision, dim) = cli::auto::resolve(&geojson, precision_arg, dim_arg);
+                    if skip_invalid {
+                        let skipped = cli::skip::skip_invalid(&mut geojson, precision, dim);
+                        let report = cli::skip::report_path(output.as_deref().unwrap());
+                        if !skipped.is_empty() {
+                            warn!("Skipped {} invalid features, see {}", skipped.len(), report.display());
+                        }
+                        write_output(&report.to_string_lossy(), None, &serde_json::to_vec_pretty(&skipped).unwrap());
+                    }
+                    if rfc7946 {
+                        let normalized = cli::rfc7946::normalize(&mut geojson).unwrap_or_else(|err| fail(ErrorKind::Parse, Some(&input), &format!("Could not normalize {}: {}", input, err)));
+                        info!("Rewound {} rings, removed {} repeated positions and split {} geometries at the antimeridian", normalized.rewound, normalized.duplicates, normalized.split);
+                    }
+                    let spinner = cli::progress::spinner("Encoding");
+                    let data = cli::parallel::encode(geojson, precision, dim);
+                    drop(spinner);
+                    data.map_err(String::from)
+                }
+                Format::GeoJsonSeq => cli::seq::encode(BufReader::new(open_input(&input)), precision, dim),
+                Format::Csv => cli::csv::encode(open_input(&input), &lon_field, &lat_field, precision, dim),
+                #[cfg(feature = "shapefile")]
+                Format::Shapefile => cli::shapefile::encode(&input, precision, dim),
+                #[cfg(not(feature = "shapefile"))]
+                Format::Shapefile => Err(String::from("built without the shapefile feature")),
+                #[cfg(feature = "kml")]
+                Format::Kml => cli::kml::encode(&read_file(&input), precision, dim),
+                #[cfg(not(feature = "kml"))]
+                Format::Kml => Err(String::from("built without the kml feature")),
+                #[cfg(feature = "gpx")]
+                Format::Gpx => cli::gpx::encode(&read_file(&input), precision, dim),
+                #[cfg(not(feature = "gpx"))]
+                Format::Gpx => Err(String::from("built without the gpx feature")),
+                Format::Wkt => unreachable!(),
+            };
+            let mut data = match data {
+                Ok(data) => data,
+                Err(err) => {
+                    fail(ErrorKind::Parse, Some(&input), &format!("Could not encode {}: {}", input, err));
+                }
+            };
+            props.apply(&mut data).unwrap();
+            let output = output.unwrap();
+            if framed {
+                let mut writer = FramedWriter::new(cli::io::create_output(&output, compress));
+                let frames = cli::framed::write(&mut writer, &data)
+                    .and_then(|frames| writer.into_inner().finish().map(|_| frames).map_err(|_| "Could not write output."));
+                match frames {
+                    Ok(frames) => debug!("Wrote {} frames", frames),
+                    Err(err) => {
+                        fail(ErrorKind::Other, Some(&output), &format!("Could not write {}: {}", output, err));
+                    }
+                }
+                return;
+            }
+            let msg = geobuf::container::to_bytes(&data, container).unwrap();
+            write_output(&output, compress, &msg);
+        },
+        Some(SubCommands::Decode { input, output, output_dir, pretty, format, columns, geometry, bbox, compress, threads, skip, limit, framed }) => {
+            if let Some(threads) = threads {
+                cli::parallel::set_threads(threads);
+            }
+            if matches!(format, Format::Shapefile | Format::Kml | Format::Gpx) {
+                fail(ErrorKind::Other, None, "Shapefile, KML and GPX output are not supported");
+            }
+            if framed {
+                if format == Format::Csv {
+                    fail(ErrorKind::Other, None, "CSV output can't be written from a framed stream");
+                }
+                let output = output.unwrap();
+                let mut frames = cli::framed::Frames::new(open_input(&input), bbox, skip, limit);
+                let mut writer = cli::io::create_output(&output, compress);
+                let written = match format {
+                    Format::GeoJson => cli::framed::write_geojson(frames, pretty, &mut writer),
+                    Format::Wkt => frames.try_for_each(|data| cli::wkt::write(&data?, &columns, &mut writer)),
+                    _ => frames.try_for_each(|data| cli::seq::write(&data?, &mut writer)),
+                };
+                let written = written
+                    .and_then(|_| writer.finish().map_err(|_| String::from("could not write output")));
+                if let Err(err) = written {
+                    fail(ErrorKind::Parse, Some(&input), &format!("Could not decode {}: {}", input, err));
+                }
+                return;
+            }
+            let filter = |data: Data| {
+                let data = match bbox {
+                    Some(bbox) => geobuf::filter::filter(&data, &Expr::Intersects(bbox))?,
+                    None => data,
+                };
+                if skip == 0 && limit.is_none() {
+                    return Ok(data);
+                }
+                // Only the selected features are decoded
+                let count = data.feature_count();
+                let end = limit.map_or(count, |limit| skip.saturating_add(limit).min(count));
+                geobuf::select(&data, &(skip.min(end)..end).collect::<Vec<usize>>())
+            };
+            let to_geojson = |data: &Data| -> Result<Vec<u8>, &'static str> {
+                let geojson = cli::parallel::decode(data)?;
+                if pretty {
+                    Ok(serde_json::to_vec_pretty(&geojson).unwrap())
+                } else {
+                    Ok(serde_json::to_vec(&geojson).unwrap())
+                }
+            };
+            if let Some(output_dir) = output_dir {
+                let extension = match format {
+                    Format::GeoJson => "geojson",
+                    Format::GeoJsonSeq => "geojsons",
+                    Format::Wkt => "wkt",
+                    Format::Csv => "csv",
+                    Format::Shapefile | Format::Kml | Format::Gpx => unreachable!(),
+                };
+                cli::batch::convert_all(&input, &output_dir, extension, compress, |_, contents| {
+                    let data = filter(geobuf::container::from_bytes(contents)?)?;
+                    match format {
+                        Format::GeoJson => Ok(to_geojson(&data)?),
+                        Format::GeoJsonSeq => {
+                            let mut geojson_seq = Vec::new();
+                            cli::seq::write(&data, &mut geojson_seq)?;
+                            Ok(geojson_seq)
+                        }
+                        Format::Wkt => {
+                            let mut wkt = Vec::new();
+                            cli::wkt::write(&data, &columns, &mut wkt)?;
+                            Ok(wkt)
+                        }
+                        Format::Csv => {
+                            let mut csv = Vec::new();
+                            cli::csv::write(&data, geometry, &mut csv)?;
+                            Ok(csv)
+                        }
+                        Format::Shapefile | Format::Kml | Format::Gpx => unreachable!(),
+                    }
+                });
+                return;
+            }
+            let output = output.unwrap();
+            let data = match filter(read_pbf_file(&input)) {
+                Ok(data) => data,
+                Err(err) => {
+                    fail(ErrorKind::Other, Some(&input), &format!("Could not filter {}: {}", input, err));
+                }
+            };
+            match format {
+                Format::GeoJson => {
+                    let spinner = cli::progress::spinner("Decoding");
+                    let geojson = to_geojson(&data);
+                    drop(spinner);
+                    match geojson {
+                        Ok(geojson_str) => write_output(&output, compress, &geojson_str),
+                        Err(err) => {
+                            fail(ErrorKind::Parse, Some(&input), &format!("Could not decode {}: {}", input, err));
+                        }
+                    }
+                }
+                Format::GeoJsonSeq | Format::Wkt | Format::Csv | Format::Shapefile | Format::Kml | Format::Gpx => {
+                    let mut writer = cli::io::create_output(&output, compress);
+                    let written = match format {
+                        Format::Wkt => cli::wkt::write(&data, &columns, &mut writer),
+                        Format::Csv => cli::csv::write(&data, geometry, &mut writer),
+                        _ => cli::seq::write(&data, &mut writer),
+                    };
+                    let written = written
+                        .and_then(|_| writer.finish().map_err(|_| String::from("could not write output")));
+                    if let Err(err) = written {
+                        fail(ErrorKind::Parse, Some(&input), &format!("Could not decode {}: {}", input, err));
+                    }
+                }
+            }
+        },
+        Some(SubCommands::Info { input }) => {
+            let input = input.path();
+            let contents = read_file(&input);
+            let data = parse_pbf(&input, &contents);
+            let size = match std::fs::metadata(&input) {
+                Ok(metadata) if input != "-" => metadata.len() as usize,
+                _ => contents.len(),
+            };
+            println!("{}", cli::info::info(&data, size));
+        },
+        Some(SubCommands::Count { input, by_type }) => {
+            let input = input.path();
+            let counts = match cli::count::count(&read_file(&input), by_type) {
+                Ok(counts) => counts,
+                Err(err) => {
+                    fail(ErrorKind::Parse, Some(&input), &format!("Could not read {}: {}", input, err));
+                }
+            };
+            for (geometry_type, count) in &counts.types {
+                println!("{}: {}", geometry_type, count);
+            }
+            if by_type {
+                println!("Total: {}", counts.features);
+            } else {
+                println!("{}", counts.features);
+            }
+        },
+        Some(SubCommands::Keys { input, max_distinct }) => {
+            let input = input.path();
+            let summaries = cli::keys::keys(&read_pbf_file(&input), max_distinct);
+            println!("{}", cli::keys::format(&summaries, max_distinct));
+        },
+        Some(SubCommands::Bbox { input, per_feature }) => {
+            let input = input.path();
+            let data = read_pbf_file(&input);
+            if per_feature {
+                for line in cli::bbox::feature_bboxes(&data) {
+                    println!("{}", line);
+                }
+            } else {
+                println!("{}", serde_json::json!(geobuf::bbox::bbox(&data)));
+            }
+        },
+        Some(SubCommands::Stats { input, top }) => {
+            let input = input.path();
+            let data = read_pbf_file(&input);
+            println!("{}", cli::stats::stats(&data, top));
+        },
+        Some(SubCommands::Verify { input, dim, precision, tolerance }) => {
+            let input = input.path();
+            let geojson = read_json_file(&input);
+            let data = match geobuf::encode::Encoder::encode(&geojson, precision, dim) {
+                Ok(data) => data,
+                Err(err) => {
+                    fail(ErrorKind::Parse, Some(&input), &format!("Could not encode {}: {}", input, err));
+                }
+            };
+            let decoded = match geobuf::decode::Decoder::decode(&data) {
+                Ok(decoded) => decoded,
+                Err(err) => {
+                    fail(ErrorKind::Parse, Some(&input), &format!("Could not decode {}: {}", input, err));
+                }
+            };
+            let tolerance = tolerance.unwrap_or_else(|| 10f64.powi(-(precision as i32)));
+            let report = cli::verify::compare(&geojson, &decoded);
+
+            if report.max_error_path.is_empty() {
+                println!("Max coordinate error: 0 (tolerance {})", tolerance);
+            } else {
+                println!("Max coordinate error: {} at {} (tolerance {})", report.max_error, report.max_error_path, tolerance);
+            }
+            println!("Mismatches: {}", report.mismatches.len());
+            for mismatch in &report.mismatches {
+                println!("  {}", mismatch);
+            }
+            if report.max_error > tolerance || !report.mismatches.is_empty() {
+                process::exit(ErrorKind::Validation.exit_code());
+            }
+        },
+        Some(SubCommands::Diff { old, new, precision, json }) => {
+            let (old_contents, new_contents) = (read_file(&old), read_file(&new));
+            let pbf = [(&old, &old_contents), (&new, &new_contents)]
+                .iter()
+                .find(|(_, contents)| !cli::io::is_json(contents))
+                .map(|(path, contents)| parse_pbf(path, contents));
+            let precision = precision.or_else(|| pbf.as_ref().map(|data| data.precision())).unwrap_or(6);
+            let dim = pbf.as_ref().map(|data| data.dimensions()).unwrap_or(2);
+            let old_data = cli::io::read_data(&old, &old_contents, precision, dim);
+            let new_data = cli::io::read_data(&new, &new_contents, precision, dim);
+            let diff = match geobuf::diff(&old_data, &new_data) {
+                Ok(diff) => diff,
+                Err(err) => {
+                    fail(ErrorKind::Parse, None, &format!("Could not compare {} and {}: {}", old, new, err));
+                }
+            };
+            if json {
+                let report = cli::diff::to_json(&diff, &old_data, &new_data);
+                println!("{}", serde_json::to_string_pretty(&report).unwrap());
             } else {
-                serde_json::to_vec(&geojson).unwrap()
+                println!("{}", cli::diff::to_text(&diff, &old_data, &new_data));
+            }
+            if !diff.is_empty() {
+                process::exit(1);
+            }
+        },
+        Some(SubCommands::Merge { inputs, output, precision, container, compress }) => {
+            let contents: Vec<Vec<u8>> = inputs.iter().map(|input| read_file(input)).collect();
+            let pbfs: Vec<Data> = inputs
+                .iter()
+                .zip(&contents)
+                .filter(|(_, contents)| !cli::io::is_json(contents))
+                .map(|(input, contents)| parse_pbf(input, contents))
+                .collect();
+            let precision = precision.or_else(|| pbfs.iter().map(|data| data.precision()).max()).unwrap_or(6);
+            let dim = pbfs.first().map(|data| data.dimensions()).unwrap_or(2);
+            let data: Vec<Data> = inputs
+                .iter()
+                .zip(&contents)
+                .map(|(input, contents)| cli::io::read_data(input, contents, precision, dim))
+                .collect();
+            let merged = match geobuf::merge(&data) {
+                Ok(merged) => merged,
+                Err(err) => {
+                    fail(ErrorKind::Other, None, &format!("Could not merge: {}", err));
+                }
             };
-            f.write_all(&geojson_str).unwrap();
+            let msg = geobuf::container::to_bytes(&merged, container).unwrap();
+            write_output(&output, compress, &msg);
+        },
+        Some(SubCommands::Append { base, inputs, output, container, compress }) => {
+            let mut data = read_pbf_file(&base);
+            for input in &inputs {
+                let contents = read_file(input);
+                let appended = if cli::io::is_json(&contents) {
+                    match serde_json::from_slice(&contents) {
+                        Ok(geojson) => cli::seq::push(&mut data, geojson),
+                        Err(_) => Err("Could not parse geojson."),
+                    }
+                } else {
+                    let other = parse_pbf(input, &contents);
+                    geobuf::merge(&[std::mem::take(&mut data), other]).map(|merged| data = merged)
+                };
+                if let Err(err) = appended {
+                    fail(ErrorKind::Other, Some(input), &format!("Could not append {}: {}", input, err));
+                }
+            }
+            let msg = geobuf::container::to_bytes(&data, container).unwrap();
+            write_output(&output, compress, &msg);
+        },
+        Some(SubCommands::Split { input, output, features_per_file, max_bytes, precision, dim, container, compress }) => {
+            let input = input.path();
+            if output == "-" {
+                fail(ErrorKind::Other, None, "split writes several files and cannot write to stdout");
+            }
+            let data = cli::io::read_data(&input, &read_file(&input), precision, dim);
+            let chunks = match (features_per_file, max_bytes) {
+                (Some(0), None) => {
+                    fail(ErrorKind::Other, None, "--features-per-file must be greater than 0");
+                }
+                (Some(features_per_file), None) => geobuf::split(&data, features_per_file),
+                (None, Some(max_bytes)) => {
+                    let header_len = if container { geobuf::container::HEADER_LEN } else { 0 };
+                    geobuf::split_by_size(&data, max_bytes.saturating_sub(header_len))
+                }
+                _ => {
+                    fail(ErrorKind::Other, None, "Pass exactly one of --features-per-file and --max-bytes");
+                }
+            };
+            let chunks = match chunks {
+                Ok(chunks) => chunks,
+                Err(err) => {
+                    fail(ErrorKind::Parse, Some(&input), &format!("Could not split {}: {}", input, err));
+                }
+            };
+            for (n, chunk) in chunks.iter().enumerate() {
+                let msg = geobuf::container::to_bytes(chunk, container).unwrap();
+                write_output(&cli::split::shard_path(&output, n, chunks.len()), compress, &msg);
+            }
+        },
+        Some(SubCommands::Filter { input, output, expression, bbox, precision, dim, container, compress }) => {
+            let input = input.path();
+            let data = cli::io::read_data(&input, &read_file(&input), precision, dim);
+            let expression = match (expression, bbox) {
+                (Some(expression), Some(bbox)) => expression.and(Expr::Intersects(bbox)),
+                (Some(expression), None) => expression,
+                (None, Some(bbox)) => Expr::Intersects(bbox),
+                (None, None) => unreachable!(),
+            };
+            let filtered = match geobuf::filter::filter(&data, &expression) {
+                Ok(filtered) => filtered,
+                Err(err) => {
+                    fail(ErrorKind::Other, Some(&input), &format!("Could not filter {}: {}", input, err));
+                }
+            };
+            let msg = geobuf::container::to_bytes(&filtered, container).unwrap();
+            write_output(&output, compress, &msg);
+        },
+        Some(SubCommands::Dedupe { input, output, key, precision, dim, container, compress }) => {
+            let input = input.path();
+            let data = cli::io::read_data(&input, &read_file(&input), precision, dim);
+            let kept = cli::dedupe::dedupe(&data, key.as_deref());
+            let deduped = match geobuf::select(&data, &kept) {
+                Ok(deduped) => deduped,
+                Err(err) => {
+                    fail(ErrorKind::Other, Some(&input), &format!("Could not dedupe {}: {}", input, err));
+                }
+            };
+            let msg = geobuf::container::to_bytes(&deduped, container).unwrap();
+            write_output(&output, compress, &msg);
+            info!("Removed {} duplicate features", data.feature_count() - kept.len());
+        },
+        Some(SubCommands::Prop { action }) => {
+            let (edit, args) = match action {
+                PropAction::Set { assignment: (key, value), args } => (Edit::Set(key, cli::prop::parse_value(&value)), args),
+                PropAction::Delete { key, args } => (Edit::Delete(key), args),
+                PropAction::Rename { names: (old, new), args } => (Edit::Rename(old, new), args),
+            };
+            let input = args.input.path();
+            let mut data = read_pbf_file(&input);
+            match cli::prop::edit(&mut data, &edit, args.expression.as_ref()) {
+                Ok(changed) => info!("Changed {} features", changed),
+                Err(err) => {
+                    fail(ErrorKind::Other, Some(&input), &format!("Could not edit {}: {}", input, err));
+                }
+            }
+            let msg = geobuf::container::to_bytes(&data, args.container).unwrap();
+            write_output(&args.output, args.compress, &msg);
+        },
+        Some(SubCommands::Join { input, output, csv, on, precision, dim, container, compress }) => {
+            let input = input.path();
+            let mut data = cli::io::read_data(&input, &read_file(&input), precision, dim);
+            match cli::csv::join(&mut data, open_input(&csv), &on) {
+                Ok((joined, unmatched)) => {
+                    info!("Joined {} features", joined);
+                    if unmatched > 0 {
+                        warn!("{} rows of {} matched no feature", unmatched, csv);
+                    }
+                }
+                Err(err) => {
+                    fail(ErrorKind::Other, Some(&csv), &format!("Could not join {}: {}", csv, err));
+                }
+            }
+            let msg = geobuf::container::to_bytes(&data, container).unwrap();
+            write_output(&output, compress, &msg);
+        },
+        Some(SubCommands::Serve { input, host, port, precision, dim }) => {
+            let input = input.path();
+            let data = cli::io::read_data(&input, &read_file(&input), precision, dim);
+            if let Err(err) = cli::serve::serve(&data, &format!("{}:{}", host, port)) {
+                fail(ErrorKind::Other, None, &err);
+            }
+        },
+        Some(SubCommands::Bench { input, iterations, precision, dim }) => {
+            let input = input.path();
+            if iterations == 0 {
+                fail(ErrorKind::Other, None, "--iterations must be greater than 0");
+            }
+            let data = cli::io::read_data(&input, &read_file(&input), precision.unwrap_or(6), dim.unwrap_or(2));
+            let geojson = cli::io::decode_data(&input, &data);
+            let precision = precision.unwrap_or_else(|| data.precision());
+            let dim = dim.unwrap_or_else(|| data.dimensions());
+            match cli::bench::bench(&geojson, precision, dim, iterations) {
+                Ok(report) => println!("{}", report),
+                Err(err) => {
+                    fail(ErrorKind::Parse, Some(&input), &format!("Could not benchmark {}: {}", input, err));
+                }
+            }
+        },
+        Some(SubCommands::Completions { shell }) => {
+            clap_complete::generate(shell, &mut Args::command(), "geobuf", &mut std::io::stdout());
+        },
+        Some(SubCommands::Validate { input }) => {
+            let input = input.path();
+            let problems = match geobuf::container::from_bytes(&read_file(&input)) {
+                Ok(data) => geobuf::validate::validate(&data),
+                Err(err) => vec![geobuf::validate::Problem { path: String::new(), message: String::from(err) }],
+            };
+            let report = serde_json::json!({
+                "valid": problems.is_empty(),
+                "problems": problems.iter().map(|problem| serde_json::json!({
+                    "path": problem.path,
+                    "message": problem.message,
+                })).collect::<Vec<_>>(),
+            });
+            println!("{}", serde_json::to_string_pretty(&report).unwrap());
+            if !problems.is_empty() {
+                process::exit(ErrorKind::Validation.exit_code());
+            }
+        },
+        #[cfg(feature = "geo")]
+        Some(SubCommands::CheckGeometry { input }) => {
+            let input = input.path();
+            // GeoJSON is checked as it is, as encoding drops the last position of rings
+            let contents = read_file(&input);
+            let geojson = if cli::io::is_json(&contents) {
+                serde_json::from_slice(&contents).map_err(|_| "could not parse geojson")
+            } else {
+                cli::parallel::decode(&parse_pbf(&input, &contents))
+            };
+            let features = geojson.and_then(|geojson| cli::check::check(&geojson)).unwrap_or_else(|err| fail(ErrorKind::Parse, Some(&input), &format!("Could not check {}: {}", input, err)));
+            let report = serde_json::json!({
+                "valid": features.is_empty(),
+                "counts": cli::check::counts(&features),
+                "features": features.iter().map(|feature| serde_json::json!({
+                    "index": feature.index,
+                    "id": feature.id,
+                    "problems": feature.problems.iter().map(|problem| problem.name()).collect::<Vec<_>>(),
+                })).collect::<Vec<_>>(),
+            });
+            println!("{}", serde_json::to_string_pretty(&report).unwrap());
+            if !features.is_empty() {
+                process::exit(ErrorKind::Validation.exit_code());
+            }
+        },
+        #[cfg(feature = "geo")]
+        Some(SubCommands::Repair { input, output, precision, dim, container, compress }) => {
+            let input = input.path();
+            // GeoJSON is repaired as it is, as encoding fails on some of the problems
+            let contents = read_file(&input);
+            let (geojson, precision, dim) = if cli::io::is_json(&contents) {
+                (serde_json::from_slice(&contents).map_err(|_| "could not parse geojson"), precision, dim)
+            } else {
+                let data = parse_pbf(&input, &contents);
+                (cli::parallel::decode(&data), data.precision(), data.dimensions())
+            };
+            let mut geojson = geojson.unwrap_or_else(|err| fail(ErrorKind::Parse, Some(&input), &format!("Could not repair {}: {}", input, err)));
+            let repaired = match cli::repair::repair(&mut geojson) {
+                Ok(repaired) => repaired,
+                Err(err) => {
+                    fail(ErrorKind::Parse, Some(&input), &format!("Could not repair {}: {}", input, err));
+                }
+            };
+            let data = match cli::parallel::encode(geojson, precision, dim) {
+                Ok(data) => data,
+                Err(err) => {
+                    fail(ErrorKind::Parse, Some(&input), &format!("Could not encode {}: {}", input, err));
+                }
+            };
+            let msg = geobuf::container::to_bytes(&data, container).unwrap();
+            write_output(&output, compress, &msg);
+            info!(
+                "Removed {} duplicate vertices, closed {} rings, rebuilt {} self-intersecting polygons, dropped {} degenerate rings and removed {} features left without geometry",
+                repaired.duplicates,
+                repaired.closed,
+                repaired.rebuilt,
+                repaired.dropped,
+                repaired.removed
+            );
+        },
+        Some(SubCommands::Extract { input, output, id, index, pbf, pretty, precision, dim, compress }) => {
+            let input = input.path();
+            let data = cli::io::read_data(&input, &read_file(&input), precision, dim);
+            let indexes: Vec<usize> = match (id, index) {
+                (Some(id), _) => cli::extract::find_id(&data, &id),
+                (None, Some(range)) => range.collect(),
+                (None, None) => unreachable!(),
+            };
+            let extracted = match geobuf::select(&data, &indexes) {
+                Ok(extracted) => extracted,
+                Err(err) => {
+                    fail(ErrorKind::Other, Some(&input), &format!("Could not extract from {}: {}", input, err));
+                }
+            };
+            if pbf {
+                write_output(&output, compress, &geobuf::container::to_bytes(&extracted, false).unwrap());
+            } else {
+                let geojson = cli::io::decode_data(&input, &extracted);
+                let geojson = if pretty { serde_json::to_vec_pretty(&geojson) } else { serde_json::to_vec(&geojson) };
+                write_output(&output, compress, &geojson.unwrap());
+            }
+        },
+        Some(SubCommands::Head { count, args }) => write_subset(args, |len| (0..count.min(len)).collect()),
+        Some(SubCommands::Tail { count, args }) => write_subset(args, |len| (len.saturating_sub(count)..len).collect()),
+        Some(SubCommands::Sample { fraction, seed, args }) => write_subset(args, |len| cli::sample::sample(len, fraction, seed)),
+        Some(SubCommands::Compact { input, output, precision, dim, sort, container, compress }) => {
+            let input = input.path();
+            let contents = read_file(&input);
+            let data = parse_pbf(&input, &contents);
+            let mut compacted = match cli::compact::compact(&data, precision, dim) {
+                Ok(compacted) => compacted,
+                Err(err) => {
+                    fail(ErrorKind::Parse, Some(&input), &format!("Could not compact {}: {}", input, err));
+                }
+            };
+            if let Some(by) = sort {
+                if let Err(err) = cli::sort::sort(&mut compacted, &by) {
+                    fail(ErrorKind::Other, Some(&input), &format!("Could not sort {}: {}", input, err));
+                }
+            }
+            let msg = geobuf::container::to_bytes(&compacted, container).unwrap();
+            write_output(&output, compress, &msg);
+            info!(
+                "Compacted {} to {} bytes ({:.1}% smaller)",
+                contents.len(),
+                msg.len(),
+                (contents.len() as f64 - msg.len() as f64) * 100.0 / contents.len().max(1) as f64
+            );
+        },
+        Some(SubCommands::Simplify { input, output, tolerance, precision, dim, container, compress }) => {
+            let input = input.path();
+            let data = cli::io::read_data(&input, &read_file(&input), precision, dim);
+            let mut geojson = cli::io::decode_data(&input, &data);
+            let (before, after) = match geobuf::simplify::simplify(&mut geojson, tolerance) {
+                Ok(counts) => counts,
+                Err(err) => {
+                    fail(ErrorKind::Other, Some(&input), &format!("Could not simplify {}: {}", input, err));
+                }
+            };
+            let simplified = cli::io::encode_data(&input, geojson, data.precision(), data.dimensions());
+            let msg = geobuf::container::to_bytes(&simplified, container).unwrap();
+            write_output(&output, compress, &msg);
+            info!(
+                "Simplified {} to {} positions ({:.1}% fewer)",
+                before,
+                after,
+                (before - after) as f64 * 100.0 / before.max(1) as f64
+            );
+        },
+        Some(SubCommands::Explode { input, output, precision, dim, container, compress }) => {
+            let input = input.path();
+            let data = cli::io::read_data(&input, &read_file(&input), precision, dim);
+            let (geojson, before, after) = match cli::explode::explode(cli::io::decode_data(&input, &data)) {
+                Ok(exploded) => exploded,
+                Err(err) => {
+                    fail(ErrorKind::Other, Some(&input), &format!("Could not explode {}: {}", input, err));
+                }
+            };
+            let exploded = cli::io::encode_data(&input, geojson, data.precision(), data.dimensions());
+            let msg = geobuf::container::to_bytes(&exploded, container).unwrap();
+            write_output(&output, compress, &msg);
+            info!("Exploded {} into {} features", before, after);
+        },
+        Some(SubCommands::Sort { input, output, by, precision, dim, container, compress }) => {
+            let input = input.path();
+            let mut data = cli::io::read_data(&input, &read_file(&input), precision, dim);
+            if let Err(err) = cli::sort::sort(&mut data, &by) {
+                fail(ErrorKind::Other, Some(&input), &format!("Could not sort {}: {}", input, err));
+            }
+            let msg = geobuf::container::to_bytes(&data, container).unwrap();
+            write_output(&output, compress, &msg);
+        },
+        Some(SubCommands::Tile { input, output, zoom, x, y, buffer, container, compress }) => {
+            let input = input.path();
+            if zoom > 30 || x >= 1 << zoom || y >= 1 << zoom {
+                fail(ErrorKind::Other, None, &format!("Tile {}/{}/{} does not exist", zoom, x, y));
+            }
+            let data = cli::io::read_data(&input, &read_file(&input), 6, 2);
+            let features = match cli::tile::into_features(cli::io::decode_data(&input, &data)) {
+                Ok(features) => features,
+                Err(err) => {
+                    fail(ErrorKind::Other, Some(&input), &format!("Could not clip {}: {}", input, err));
+                }
+            };
+            let bounds = cli::tile::bounds(cli::mvt::Tile { z: zoom, x, y }, buffer);
+            let clipped = match cli::tile::clip(&features, &bounds) {
+                Ok(clipped) => serde_json::json!({"type": "FeatureCollection", "features": clipped}),
+                Err(err) => {
+                    fail(ErrorKind::Other, Some(&input), &format!("Could not clip {}: {}", input, err));
+                }
+            };
+            let tile = cli::io::encode_data(&input, clipped, data.precision(), data.dimensions());
+            let msg = geobuf::container::to_bytes(&tile, container).unwrap();
+            write_output(&output, compress, &msg);
+        },
+        Some(SubCommands::Tiles { input, output_dir, min_zoom, max_zoom, buffer, tolerance, container, compress }) => {
+            let input = input.path();
+            if min_zoom > max_zoom || max_zoom > 30 {
+                fail(ErrorKind::Other, None, "Zoom levels must satisfy --min-zoom <= --max-zoom <= 30");
+            }
+            let data = cli::io::read_data(&input, &read_file(&input), 6, 2);
+            let features = match cli::tile::into_features(cli::io::decode_data(&input, &data)) {
+                Ok(features) => features,
+                Err(err) => {
+                    fail(ErrorKind::Other, Some(&input), &format!("Could not tile {}: {}", input, err));
+                }
+            };
+            let written = cli::tile::pyramid(features, min_zoom, max_zoom, buffer, &mut |tile, features| {
+                trace!("Tile {}/{}/{} has {} features", tile.z, tile.x, tile.y, features.len());
+                let mut geojson = serde_json::json!({"type": "FeatureCollection", "features": features});
+                if tolerance > 0.0 {
+                    let degrees = tolerance * 360.0 / 2f64.powi(tile.z as i32) / 256.0;
+                    geobuf::simplify::simplify(&mut geojson, degrees)?;
+                }
+                let encoded = cli::parallel::encode(geojson, data.precision(), data.dimensions())?;
+                let dir = std::path::Path::new(&output_dir).join(tile.z.to_string()).join(tile.x.to_string());
+                std::fs::create_dir_all(&dir).map_err(|_| format!("Could not create {}", dir.display()))?;
+                let path = dir.join(format!("{}.pbf", tile.y));
+                write_output(&path.to_string_lossy(), compress, &geobuf::container::to_bytes(&encoded, container)?);
+                Ok(())
+            });
+            match written {
+                Ok(count) => info!("Wrote {} tiles", count),
+                Err(err) => {
+                    fail(ErrorKind::Other, Some(&input), &format!("Could not tile {}: {}", input, err));
+                }
+            }
+        },
+        Some(SubCommands::ToMvt { input, output, zoom, x, y, layer, extent, buffer, compress }) => {
+            let input = input.path();
+            if zoom > 30 || x >= 1 << zoom || y >= 1 << zoom {
+                fail(ErrorKind::Other, None, &format!("Tile {}/{}/{} does not exist", zoom, x, y));
+            }
+            let data = cli::io::read_data(&input, &read_file(&input), 6, 2);
+            let tile = cli::mvt::Tile { z: zoom, x, y };
+            match cli::mvt::to_mvt(&data, tile, extent, buffer, &layer) {
+                Ok(mvt) => write_output(&output, compress, &mvt),
+                Err(err) => {
+                    fail(ErrorKind::Other, Some(&input), &format!("Could not convert {}: {}", input, err));
+                }
+            }
+        },
+        #[cfg(feature = "proj")]
+        Some(SubCommands::Reproject { input, output, from, to, precision, container, compress }) => {
+            let input = input.path();
+            let data = cli::io::read_data(&input, &read_file(&input), 6, 2);
+            let mut geojson = cli::io::decode_data(&input, &data);
+            if let Err(err) = cli::reproject::reproject(&mut geojson, &from, &to) {
+                fail(ErrorKind::Other, Some(&input), &format!("Could not reproject {}: {}", input, err));
+            }
+            let precision = precision.unwrap_or_else(|| data.precision());
+            let reprojected = cli::io::encode_data(&input, geojson, precision, data.dimensions());
+            let msg = geobuf::container::to_bytes(&reprojected, container).unwrap();
+            write_output(&output, compress, &msg);
+        },
+        #[cfg(feature = "fgb")]
+        Some(SubCommands::FromFgb { input, output, id_column, dim, precision, container, compress }) => {
+            let input = input.path();
+            let data = match cli::fgb::from_fgb(&read_file(&input), &id_column, precision, dim) {
+                Ok(data) => data,
+                Err(err) => {
+                    fail(ErrorKind::Other, Some(&input), &format!("Could not convert {}: {}", input, err));
+                }
+            };
+            let msg = geobuf::container::to_bytes(&data, container).unwrap();
+            write_output(&output, compress, &msg);
+        },
+        #[cfg(feature = "fgb")]
+        Some(SubCommands::ToFgb { input, output, id_column }) => {
+            let input = input.path();
+            match cli::fgb::to_fgb(&read_pbf_file(&input), &id_column) {
+                Ok(fgb) => write_output(&output, None, &fgb),
+                Err(err) => {
+                    fail(ErrorKind::Other, Some(&input), &format!("Could not convert {}: {}", input, err));
+                }
+            }
         },
         None => {
             process::exit(1);
         }
     };
 }
+
+/// Writes the features of the input picked by `indexes` from the number of features
+fn write_subset<F: FnOnce(usize) -> Vec<usize>>(args: SubsetArgs, indexes: F) {
+    let input = args.input.path();
+    let data = cli::io::read_data(&input, &read_file(&input), args.precision, args.dim);
+    let subset = match geobuf::select(&data, &indexes(data.feature_count())) {
+        Ok(subset) => subset,
+        Err(err) => {
+            fail(ErrorKind::Parse, Some(&input), &format!("Could not read features of {}: {}", input, err));
+        }
+    };
+    if args.pbf {
+        write_output(&args.output, args.compress, &geobuf::container::to_bytes(&subset, false).unwrap());
+    } else {
+        let geojson = cli::io::decode_data(&input, &subset);
+        let geojson = if args.pretty { serde_json::to_vec_pretty(&geojson) } else { serde_json::to_vec(&geojson) };
+        write_output(&args.output, args.compress, &geojson.unwrap());
+    }
+}
@@ -0,0 +1,46 @@
+use std::str::FromStr;
+
+use serde_json::Value as JSONValue;
+use tracing::info;
+
+use geobuf::encode::Encoder;
+
+/// A number given on the command line, or `auto` to derive it from the input
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Auto {
+    Auto,
+    Value(u32),
+}
+
+impl FromStr for Auto {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(Auto::Auto),
+            _ => s
+                .parse()
+                .map(Auto::Value)
+                .map_err(|_| format!("expected a number or auto, got {:?}", s)),
+        }
+    }
+}
+
+/// Returns the precision and dimensions to encode `geojson` with, running
+/// `Encoder::analyze` for the ones set to `auto`
+pub fn resolve(geojson: &JSONValue, precision: Auto, dim: Auto) -> (u32, u32) {
+    if let (Auto::Value(precision), Auto::Value(dim)) = (precision, dim) {
+        return (precision, dim);
+    }
+    let (auto_precision, auto_dim) = Encoder::analyze(geojson);
+    let resolved = match (precision, dim) {
+        (Auto::Value(precision), _) => (precision, auto_dim),
+        (_, Auto::Value(dim)) => (auto_precision, dim),
+        _ => (auto_precision, auto_dim),
+    };
+    info!(
+        "Encoding with precision {} and {} dimensions",
+        resolved.0, resolved.1
+    );
+    resolved
+}
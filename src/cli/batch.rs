@@ -0,0 +1,270 @@
+#[cfg(not(target_os = "wasi"))]
+use std::collections::HashMap;
+use std::fs;
+use std::io::{BufWriter, Read, Write};
+use std::path::{Component, Path, PathBuf};
+use std::process;
+#[cfg(not(target_os = "wasi"))]
+use std::thread;
+#[cfg(not(target_os = "wasi"))]
+use std::time::{Duration, SystemTime};
+
+use rayon::prelude::*;
+use tracing::error;
+
+use super::compress::{self, Compression, Compressor};
+use super::error::{fail, ErrorKind};
+use super::progress;
+
+/// A file to convert and the path of its output
+struct Job {
+    input: PathBuf,
+    output: PathBuf,
+}
+
+/// Returns the files matching the glob `pattern`, with outputs in `output_dir` at the same
+/// path relative to the part of `pattern` before the first wildcard, and `extension` (and the
+/// extension of `compression`) in place of the input extensions
+fn jobs(
+    pattern: &str,
+    output_dir: &str,
+    extension: &str,
+    compression: Option<Compression>,
+) -> Result<Vec<Job>, String> {
+    let (jobs, errors) = matches(pattern, output_dir, extension, compression)?;
+    match errors.into_iter().next() {
+        Some(err) => Err(err),
+        None => Ok(jobs),
+    }
+}
+
+/// Returns the jobs of `jobs` for the paths that could be read, and an error for each path
+/// that couldn't, failing only for invalid patterns
+fn matches(
+    pattern: &str,
+    output_dir: &str,
+    extension: &str,
+    compression: Option<Compression>,
+) -> Result<(Vec<Job>, Vec<String>), String> {
+    let base = glob_base(pattern);
+    let paths =
+        glob::glob(pattern).map_err(|err| format!("Invalid pattern {}: {}", pattern, err))?;
+    let mut jobs = Vec::new();
+    let mut errors = Vec::new();
+    for path in paths {
+        let input = match path {
+            Ok(input) => input,
+            Err(err) => {
+                errors.push(format!("Could not read {}", err.path().display()));
+                continue;
+            }
+        };
+        if !input.is_file() {
+            continue;
+        }
+        let relative = input.strip_prefix(&base).unwrap_or(&input);
+        let mut output = Path::new(output_dir).join(relative);
+        let name = output.file_name().unwrap_or_default().to_string_lossy();
+        let name = name
+            .strip_suffix(".gz")
+            .or_else(|| name.strip_suffix(".zst"))
+            .unwrap_or(&name);
+        let stem = name.rsplit_once('.').map_or(name, |(stem, _)| stem);
+        let mut name = format!("{}.{}", stem, extension);
+        match compression {
+            Some(Compression::Gzip(_)) => name.push_str(".gz"),
+            Some(Compression::Zstd(_)) => name.push_str(".zst"),
+            None => {}
+        }
+        output.set_file_name(name);
+        jobs.push(Job { input, output });
+    }
+    Ok((jobs, errors))
+}
+
+/// Returns the leading directories of `pattern` without wildcards
+fn glob_base(pattern: &str) -> PathBuf {
+    let mut base = PathBuf::new();
+    let path = Path::new(pattern);
+    let components: Vec<Component> = path.components().collect();
+    for (n, component) in components.iter().enumerate() {
+        let literal = !component
+            .as_os_str()
+            .to_string_lossy()
+            .contains(['*', '?', '[']);
+        // The last component is the file name even without wildcards
+        if !literal || n == components.len() - 1 {
+            break;
+        }
+        base.push(component);
+    }
+    base
+}
+
+/// Converts every file matching `pattern` into `output_dir` with `convert`, which is given
+/// the path and contents of each file, see `jobs`, exiting with 1 if there are none or any
+/// conversion failed
+pub fn convert_all<F>(
+    pattern: &str,
+    output_dir: &str,
+    extension: &str,
+    compression: Option<Compression>,
+    convert: F,
+) where
+    F: Fn(&Path, &[u8]) -> Result<Vec<u8>, String> + Sync,
+{
+    let jobs = match jobs(pattern, output_dir, extension, compression) {
+        Ok(jobs) => jobs,
+        Err(err) => {
+            fail(ErrorKind::Other, None, &err);
+        }
+    };
+    if jobs.is_empty() {
+        fail(
+            ErrorKind::Input,
+            Some(pattern),
+            &format!("No files match {}", pattern),
+        );
+    }
+    if run(&jobs, compression, convert) > 0 {
+        process::exit(1);
+    }
+}
+
+/// Time between two checks for changed files in `watch`
+#[cfg(not(target_os = "wasi"))]
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Converts the files matching `pattern` like `convert_all`, then checks them for changes
+/// every `POLL_INTERVAL` and converts the ones that were added or modified again, until the
+/// process is stopped
+///
+/// Paths that can't be read are logged and checked again at the next poll, and only an
+/// invalid pattern stops watching.
+///
+/// Not available on WASI, where runtimes run a conversion and exit.
+#[cfg(not(target_os = "wasi"))]
+pub fn watch<F>(
+    pattern: &str,
+    output_dir: &str,
+    extension: &str,
+    compression: Option<Compression>,
+    convert: F,
+) -> !
+where
+    F: Fn(&Path, &[u8]) -> Result<Vec<u8>, String> + Sync,
+{
+    let mut modified: HashMap<PathBuf, SystemTime> = HashMap::new();
+    tracing::info!("Watching {}", pattern);
+    // Errors of the previous poll, so that lasting ones are only logged once
+    let mut errors = Vec::new();
+    loop {
+        let (jobs, new_errors) = match matches(pattern, output_dir, extension, compression) {
+            Ok(matches) => matches,
+            Err(err) => fail(ErrorKind::Other, None, &err),
+        };
+        for err in new_errors.iter().filter(|err| !errors.contains(*err)) {
+            error!("{}", err);
+        }
+        errors = new_errors;
+        let changed: Vec<Job> = jobs
+            .into_iter()
+            .filter(|job| {
+                let time = fs::metadata(&job.input)
+                    .and_then(|metadata| metadata.modified())
+                    .ok();
+                match time {
+                    Some(time) => modified.insert(job.input.clone(), time) != Some(time),
+                    None => false,
+                }
+            })
+            .collect();
+        if !changed.is_empty() {
+            run(&changed, compression, &convert);
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+/// Converts the input of every job with `convert` on the rayon thread pool, printing a line
+/// per file as it completes and a summary, and returns the number of failed conversions
+fn run<F>(jobs: &[Job], compression: Option<Compression>, convert: F) -> usize
+where
+    F: Fn(&Path, &[u8]) -> Result<Vec<u8>, String> + Sync,
+{
+    let bar = progress::files(jobs.len(), "Converting");
+    let results: Vec<Option<(usize, usize)>> = jobs
+        .par_iter()
+        .map(|job| {
+            let result = match convert_file(job, compression, &convert) {
+                Ok((input_size, output_size)) => {
+                    bar.suspend(|| {
+                        println!(
+                            "{} -> {} ({} -> {} bytes)",
+                            job.input.display(),
+                            job.output.display(),
+                            input_size,
+                            output_size
+                        )
+                    });
+                    Some((input_size, output_size))
+                }
+                Err(err) => {
+                    bar.suspend(|| error!("{}: {}", job.input.display(), err));
+                    None
+                }
+            };
+            bar.inc(1);
+            result
+        })
+        .collect();
+    drop(bar);
+    let failed = results.iter().filter(|result| result.is_none()).count();
+    let (read, written) = results
+        .iter()
+        .flatten()
+        .fold((0, 0), |(read, written), (input_size, output_size)| {
+            (read + input_size, written + output_size)
+        });
+    println!(
+        "Converted {} of {} files ({} -> {} bytes), {} failed",
+        jobs.len() - failed,
+        jobs.len(),
+        read,
+        written,
+        failed
+    );
+    failed
+}
+
+/// Returns the size of the input file and of the written output
+fn convert_file<F>(
+    job: &Job,
+    compression: Option<Compression>,
+    convert: &F,
+) -> Result<(usize, usize), String>
+where
+    F: Fn(&Path, &[u8]) -> Result<Vec<u8>, String>,
+{
+    let file = fs::File::open(&job.input).map_err(|_| String::from("could not open"))?;
+    let input_size = file.metadata().map(|m| m.len() as usize).unwrap_or(0);
+    let mut contents = Vec::new();
+    compress::decompress(file)
+        .and_then(|mut reader| reader.read_to_end(&mut contents))
+        .map_err(|_| String::from("could not read"))?;
+
+    let converted = convert(&job.input, &contents)?;
+
+    if let Some(parent) = job.output.parent() {
+        fs::create_dir_all(parent).map_err(|_| format!("could not create {}", parent.display()))?;
+    }
+    let error = || format!("could not write {}", job.output.display());
+    let file = fs::File::create(&job.output).map_err(|_| error())?;
+    let mut output = Compressor::new(BufWriter::new(&file), compression).map_err(|_| error())?;
+    output
+        .write_all(&converted)
+        .and_then(|_| output.finish())
+        .map_err(|_| error())?;
+    let output_size = file.metadata().map(|m| m.len() as usize).unwrap_or(0);
+    Ok((input_size, output_size))
+}
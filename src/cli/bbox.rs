@@ -0,0 +1,52 @@
+use serde_json::Value as JSONValue;
+
+use geobuf::geobuf_pb::{self, Data};
+
+/// Parses a `min_x,min_y,max_x,max_y` bounding box
+pub fn parse_bbox(s: &str) -> Result<[f64; 4], String> {
+    let values = s
+        .split(',')
+        .map(|value| value.trim().parse::<f64>())
+        .collect::<Result<Vec<f64>, _>>()
+        .map_err(|_| String::from("bounding box values must be numbers"))?;
+    match values[..] {
+        [min_x, min_y, max_x, max_y] if min_x <= max_x && min_y <= max_y => {
+            Ok([min_x, min_y, max_x, max_y])
+        }
+        [_, _, _, _] => Err(String::from(
+            "bounding box minimums must not exceed maximums",
+        )),
+        _ => Err(String::from("expected min_x,min_y,max_x,max_y")),
+    }
+}
+
+/// Returns a JSON object per feature of `data` with its index, its id if it has one, and its
+/// bounding box, `null` for features without coordinates
+pub fn feature_bboxes(data: &Data) -> Vec<JSONValue> {
+    let features: &[geobuf_pb::data::Feature] = match data.data_type.as_ref() {
+        Some(geobuf_pb::data::Data_type::FeatureCollection(fc)) => &fc.features,
+        Some(geobuf_pb::data::Data_type::Feature(feature)) => std::slice::from_ref(feature),
+        _ => &[],
+    };
+    features
+        .iter()
+        .enumerate()
+        .map(|(n, feature)| {
+            let mut line = serde_json::json!({ "index": n });
+            match feature.id_type.as_ref() {
+                Some(geobuf_pb::data::feature::Id_type::Id(id)) => {
+                    line["id"] = JSONValue::from(id.as_str())
+                }
+                Some(geobuf_pb::data::feature::Id_type::IntId(id)) => {
+                    line["id"] = JSONValue::from(*id)
+                }
+                _ => {}
+            }
+            line["bbox"] = match feature.geometry.as_ref() {
+                Some(geometry) => serde_json::json!(geobuf::bbox::geometry_bbox(data, geometry)),
+                None => JSONValue::Null,
+            };
+            line
+        })
+        .collect()
+}
@@ -0,0 +1,79 @@
+use std::time::{Duration, Instant};
+
+use serde_json::Value as JSONValue;
+
+use geobuf::decode::Decoder;
+use geobuf::encode::Encoder;
+
+/// Encodes and decodes `geojson` `iterations` times and returns a report of the time per
+/// iteration and the throughput, in PBF megabytes and features per second, and of the peak
+/// memory use of the process
+pub fn bench(
+    geojson: &JSONValue,
+    precision: u32,
+    dim: u32,
+    iterations: usize,
+) -> Result<String, &'static str> {
+    let features = match geojson["type"].as_str() {
+        Some("FeatureCollection") => geojson["features"].as_array().map_or(0, Vec::len),
+        Some("Feature") => 1,
+        _ => 0,
+    };
+
+    let mut bytes = Vec::new();
+    let encode = time(iterations, || {
+        let data = Encoder::encode(geojson, precision, dim)?;
+        bytes = geobuf::container::to_bytes(&data, false)?;
+        Ok(())
+    })?;
+    let decode = time(iterations, || {
+        Decoder::decode(&geobuf::container::from_bytes(&bytes)?).map(|_| ())
+    })?;
+
+    let line = |name: &str, elapsed: Duration| {
+        let seconds = elapsed.as_secs_f64() / iterations as f64;
+        format!(
+            "{}: {:.2} ms per iteration, {:.1} MB/s, {:.0} features/s",
+            name,
+            seconds * 1000.0,
+            bytes.len() as f64 / 1e6 / seconds,
+            features as f64 / seconds
+        )
+    };
+    let peak = match peak_rss() {
+        Some(peak) => format!("{:.1} MB", peak as f64 / 1e6),
+        None => String::from("unknown"),
+    };
+    Ok([
+        format!(
+            "Iterations: {}, features: {}, PBF size: {} bytes",
+            iterations,
+            features,
+            bytes.len()
+        ),
+        line("Encode", encode),
+        line("Decode", decode),
+        format!("Peak RSS: {}", peak),
+    ]
+    .join("\n"))
+}
+
+/// Returns the time taken to run `f` `iterations` times
+fn time<F>(iterations: usize, mut f: F) -> Result<Duration, &'static str>
+where
+    F: FnMut() -> Result<(), &'static str>,
+{
+    let start = Instant::now();
+    for _ in 0..iterations {
+        f()?;
+    }
+    Ok(start.elapsed())
+}
+
+/// Returns the peak resident set size of the process in bytes, on Linux only
+fn peak_rss() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|line| line.starts_with("VmHWM:"))?;
+    let kilobytes = line.split_whitespace().nth(1)?.parse::<u64>().ok()?;
+    Some(kilobytes * 1024)
+}
@@ -0,0 +1,173 @@
+use std::collections::BTreeMap;
+
+use geo::algorithm::validation::InvalidPolygon;
+use geo::{LineString, Polygon, Validation};
+use serde_json::Value as JSONValue;
+
+/// Problem found in the geometry of a feature
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Problem {
+    /// Rings crossing themselves or each other
+    SelfIntersection,
+    /// A ring whose last position isn't its first one
+    UnclosedRing,
+    /// A position repeating the previous one in a line or ring
+    DuplicateVertex,
+    /// A ring enclosing no area
+    ZeroAreaRing,
+}
+
+impl Problem {
+    pub fn name(self) -> &'static str {
+        match self {
+            Problem::SelfIntersection => "self-intersection",
+            Problem::UnclosedRing => "unclosed ring",
+            Problem::DuplicateVertex => "duplicate vertex",
+            Problem::ZeroAreaRing => "zero-area ring",
+        }
+    }
+}
+
+/// Problems of a feature
+#[derive(Debug, PartialEq)]
+pub struct FeatureProblems {
+    pub index: usize,
+    pub id: JSONValue,
+    pub problems: Vec<Problem>,
+}
+
+/// Returns the features of a GeoJSON Feature or FeatureCollection with geometry problems
+pub fn check(geojson: &JSONValue) -> Result<Vec<FeatureProblems>, &'static str> {
+    let features = match geojson["type"].as_str() {
+        Some("FeatureCollection") => geojson["features"]
+            .as_array()
+            .ok_or("FeatureCollection without features.")?
+            .as_slice(),
+        Some("Feature") => std::slice::from_ref(geojson),
+        _ => return Err("GeoJSON is not a Feature or a FeatureCollection."),
+    };
+    let mut checked = Vec::new();
+    for (index, feature) in features.iter().enumerate() {
+        let mut problems = Vec::new();
+        if !feature["geometry"].is_null() {
+            check_geometry(&feature["geometry"], &mut problems)?;
+        }
+        problems.sort();
+        problems.dedup();
+        if !problems.is_empty() {
+            checked.push(FeatureProblems {
+                index,
+                id: feature["id"].clone(),
+                problems,
+            });
+        }
+    }
+    Ok(checked)
+}
+
+/// Returns the number of features with each problem
+pub fn counts(features: &[FeatureProblems]) -> BTreeMap<&'static str, usize> {
+    let mut counts = BTreeMap::new();
+    for feature in features {
+        for problem in &feature.problems {
+            *counts.entry(problem.name()).or_insert(0) += 1;
+        }
+    }
+    counts
+}
+
+fn check_geometry(geometry: &JSONValue, problems: &mut Vec<Problem>) -> Result<(), &'static str> {
+    let coordinates = &geometry["coordinates"];
+    match geometry["type"].as_str() {
+        Some("Point") | Some("MultiPoint") => {}
+        Some("LineString") => check_line(&points(coordinates)?, problems),
+        Some("MultiLineString") => {
+            for line in nested(coordinates)? {
+                check_line(&points(line)?, problems);
+            }
+        }
+        Some("Polygon") => check_polygon(coordinates, problems)?,
+        Some("MultiPolygon") => {
+            for polygon in nested(coordinates)? {
+                check_polygon(polygon, problems)?;
+            }
+        }
+        Some("GeometryCollection") => {
+            for geometry in nested(&geometry["geometries"])? {
+                check_geometry(geometry, problems)?;
+            }
+        }
+        _ => return Err("Unknown geometry type."),
+    }
+    Ok(())
+}
+
+fn check_line(line: &[(f64, f64)], problems: &mut Vec<Problem>) {
+    if line.windows(2).any(|pair| pair[0] == pair[1]) {
+        problems.push(Problem::DuplicateVertex);
+    }
+}
+
+fn check_polygon(rings: &JSONValue, problems: &mut Vec<Problem>) -> Result<(), &'static str> {
+    let rings = nested(rings)?
+        .iter()
+        .map(points)
+        .collect::<Result<Vec<_>, &'static str>>()?;
+    let mut zero_area = false;
+    for ring in &rings {
+        match (ring.first(), ring.last()) {
+            (Some(first), Some(last)) if first != last => problems.push(Problem::UnclosedRing),
+            _ => {}
+        }
+        check_line(ring, problems);
+        zero_area |= area(ring) == 0.0;
+    }
+    let mut rings = rings.into_iter().map(LineString::from);
+    if let Some(exterior) = rings.next() {
+        if self_intersects(&Polygon::new(exterior, rings.collect())) {
+            problems.push(Problem::SelfIntersection);
+        } else if zero_area {
+            // Self-intersecting rings can also have lobes of opposite sign cancelling out
+            problems.push(Problem::ZeroAreaRing);
+        }
+    }
+    Ok(())
+}
+
+/// Returns true if the rings of `polygon` cross themselves or each other
+pub fn self_intersects(polygon: &Polygon) -> bool {
+    polygon.validation_errors().iter().any(|error| {
+        matches!(
+            error,
+            InvalidPolygon::SelfIntersection(_)
+                | InvalidPolygon::IntersectingRingsOnALine(..)
+                | InvalidPolygon::IntersectingRingsOnAnArea(..)
+        )
+    })
+}
+
+/// Returns the area enclosed by a ring with the shoelace formula, closing it if needed
+pub fn area(ring: &[(f64, f64)]) -> f64 {
+    let mut sum = 0.0;
+    for (n, a) in ring.iter().enumerate() {
+        let b = ring[(n + 1) % ring.len()];
+        sum += a.0 * b.1 - b.0 * a.1;
+    }
+    sum.abs() / 2.0
+}
+
+fn nested(coordinates: &JSONValue) -> Result<&Vec<JSONValue>, &'static str> {
+    coordinates.as_array().ok_or("Invalid coordinates.")
+}
+
+fn points(positions: &JSONValue) -> Result<Vec<(f64, f64)>, &'static str> {
+    nested(positions)?
+        .iter()
+        .map(
+            |position| match (position[0].as_f64(), position[1].as_f64()) {
+                (Some(x), Some(y)) => Ok((x, y)),
+                _ => Err("Invalid position."),
+            },
+        )
+        .collect()
+}
@@ -0,0 +1,220 @@
+use serde_json::Value as JSONValue;
+
+/// Position with x and y coordinates
+pub type Point = [f64; 2];
+
+/// Clips a GeoJSON geometry to a `[min_x, min_y, max_x, max_y]` box, returning `None` if
+/// nothing of it is left
+///
+/// Geometries entirely inside the box are returned as they are. Clipped geometries keep x and
+/// y only, and lines cut into several parts become MultiLineStrings.
+pub fn clip_geometry(
+    geometry: &JSONValue,
+    bbox: &[f64; 4],
+) -> Result<Option<JSONValue>, &'static str> {
+    if geometry["type"] == "GeometryCollection" {
+        let mut geometries = Vec::new();
+        for member in geometry["geometries"].as_array().into_iter().flatten() {
+            geometries.extend(clip_geometry(member, bbox)?);
+        }
+        if geometries.is_empty() {
+            return Ok(None);
+        }
+        return Ok(Some(serde_json::json!({
+            "type": "GeometryCollection",
+            "geometries": geometries,
+        })));
+    }
+    let coordinates = &geometry["coordinates"];
+    let mut extent = [f64::INFINITY, f64::INFINITY, -f64::INFINITY, -f64::INFINITY];
+    extend(&mut extent, coordinates)?;
+    if extent[0] >= bbox[0] && extent[1] >= bbox[1] && extent[2] <= bbox[2] && extent[3] <= bbox[3]
+    {
+        return Ok(Some(geometry.clone()));
+    }
+    if extent[0] > bbox[2] || extent[1] > bbox[3] || extent[2] < bbox[0] || extent[3] < bbox[1] {
+        return Ok(None);
+    }
+    let polygon = |rings: &JSONValue| -> Result<Option<Vec<Vec<Point>>>, &'static str> {
+        let mut clipped = Vec::new();
+        for (n, ring) in nested(rings)?.iter().enumerate() {
+            let ring = close(clip_ring(&points(ring)?, bbox));
+            if ring.len() >= 4 {
+                clipped.push(ring);
+            } else if n == 0 {
+                // A polygon whose outer ring is clipped away is dropped with its holes
+                return Ok(None);
+            }
+        }
+        Ok(Some(clipped))
+    };
+    let clipped = match geometry["type"].as_str() {
+        Some("Point") => return Ok(None),
+        Some("MultiPoint") => {
+            let points: Vec<Point> = points(coordinates)?
+                .into_iter()
+                .filter(|point| contains(bbox, point))
+                .collect();
+            (!points.is_empty()).then(|| ("MultiPoint", serde_json::json!(points)))
+        }
+        Some("LineString") => {
+            let mut lines = clip_line(&points(coordinates)?, bbox);
+            match lines.len() {
+                0 => None,
+                1 => Some(("LineString", serde_json::json!(lines.pop()))),
+                _ => Some(("MultiLineString", serde_json::json!(lines))),
+            }
+        }
+        Some("MultiLineString") => {
+            let mut lines = Vec::new();
+            for line in nested(coordinates)? {
+                lines.extend(clip_line(&points(line)?, bbox));
+            }
+            (!lines.is_empty()).then(|| ("MultiLineString", serde_json::json!(lines)))
+        }
+        Some("Polygon") => polygon(coordinates)?.map(|rings| ("Polygon", serde_json::json!(rings))),
+        Some("MultiPolygon") => {
+            let mut polygons = Vec::new();
+            for rings in nested(coordinates)? {
+                polygons.extend(polygon(rings)?);
+            }
+            (!polygons.is_empty()).then(|| ("MultiPolygon", serde_json::json!(polygons)))
+        }
+        _ => return Err("Unknown geometry type."),
+    };
+    Ok(clipped.map(|(geometry_type, coordinates)| {
+        serde_json::json!({"type": geometry_type, "coordinates": coordinates})
+    }))
+}
+
+/// Grows `extent` to hold every position of `coordinates`
+fn extend(extent: &mut [f64; 4], coordinates: &JSONValue) -> Result<(), &'static str> {
+    match coordinates.as_array() {
+        Some(position) if matches!(position.first(), Some(JSONValue::Number(_))) => {
+            let [x, y] = point(coordinates)?;
+            *extent = [
+                extent[0].min(x),
+                extent[1].min(y),
+                extent[2].max(x),
+                extent[3].max(y),
+            ];
+            Ok(())
+        }
+        Some(items) => items.iter().try_for_each(|item| extend(extent, item)),
+        None => Err("Invalid coordinates."),
+    }
+}
+
+fn contains(bbox: &[f64; 4], point: &Point) -> bool {
+    point[0] >= bbox[0] && point[0] <= bbox[2] && point[1] >= bbox[1] && point[1] <= bbox[3]
+}
+
+fn nested(coordinates: &JSONValue) -> Result<&Vec<JSONValue>, &'static str> {
+    coordinates.as_array().ok_or("Invalid coordinates.")
+}
+
+fn point(position: &JSONValue) -> Result<Point, &'static str> {
+    match (position[0].as_f64(), position[1].as_f64()) {
+        (Some(x), Some(y)) => Ok([x, y]),
+        _ => Err("Invalid position."),
+    }
+}
+
+fn points(positions: &JSONValue) -> Result<Vec<Point>, &'static str> {
+    nested(positions)?.iter().map(point).collect()
+}
+
+/// Drops repeated points of a clipped ring and closes it
+fn close(mut ring: Vec<Point>) -> Vec<Point> {
+    ring.dedup();
+    if let (Some(first), Some(last)) = (ring.first(), ring.last()) {
+        if first != last {
+            ring.push(*first);
+        }
+    }
+    ring
+}
+
+/// Returns the parts of a line inside a `[min_x, min_y, max_x, max_y]` box, see Liang–Barsky
+pub fn clip_line(line: &[Point], bbox: &[f64; 4]) -> Vec<Vec<Point>> {
+    let mut parts: Vec<Vec<Point>> = Vec::new();
+    let mut current: Vec<Point> = Vec::new();
+    for segment in line.windows(2) {
+        let (a, b) = (segment[0], segment[1]);
+        let delta = [b[0] - a[0], b[1] - a[1]];
+        let (mut t0, mut t1) = (0.0f64, 1.0f64);
+        let mut visible = true;
+        for axis in 0..2 {
+            for (p, q) in [
+                (-delta[axis], a[axis] - bbox[axis]),
+                (delta[axis], bbox[axis + 2] - a[axis]),
+            ] {
+                if p == 0.0 {
+                    if q < 0.0 {
+                        visible = false;
+                    }
+                } else if p < 0.0 {
+                    t0 = t0.max(q / p);
+                } else {
+                    t1 = t1.min(q / p);
+                }
+            }
+        }
+        if !visible || t0 > t1 {
+            if current.len() > 1 {
+                parts.push(std::mem::take(&mut current));
+            }
+            current = Vec::new();
+            continue;
+        }
+        let start = [a[0] + t0 * delta[0], a[1] + t0 * delta[1]];
+        let end = [a[0] + t1 * delta[0], a[1] + t1 * delta[1]];
+        if current.last() != Some(&start) {
+            if current.len() > 1 {
+                parts.push(std::mem::take(&mut current));
+            }
+            current = vec![start];
+        }
+        current.push(end);
+        // The line leaves the box, so the next visible segment starts a new part
+        if t1 < 1.0 {
+            parts.push(std::mem::take(&mut current));
+        }
+    }
+    if current.len() > 1 {
+        parts.push(current);
+    }
+    parts
+}
+
+/// Returns a ring clipped to a `[min_x, min_y, max_x, max_y]` box, see Sutherland–Hodgman
+pub fn clip_ring(ring: &[Point], bbox: &[f64; 4]) -> Vec<Point> {
+    let mut output = ring.to_vec();
+    for axis in 0..2 {
+        for (bound, keep_above) in [(bbox[axis], true), (bbox[axis + 2], false)] {
+            let inside = |point: &Point| {
+                if keep_above {
+                    point[axis] >= bound
+                } else {
+                    point[axis] <= bound
+                }
+            };
+            let input = std::mem::take(&mut output);
+            for (n, point) in input.iter().enumerate() {
+                let previous = &input[(n + input.len() - 1) % input.len()];
+                if inside(point) != inside(previous) {
+                    let t = (bound - previous[axis]) / (point[axis] - previous[axis]);
+                    let other = 1 - axis;
+                    let mut intersection = [0.0; 2];
+                    intersection[axis] = bound;
+                    intersection[other] = previous[other] + t * (point[other] - previous[other]);
+                    output.push(intersection);
+                }
+                if inside(point) {
+                    output.push(*point);
+                }
+            }
+        }
+    }
+    output
+}
@@ -0,0 +1,19 @@
+use geobuf::geobuf_pb::Data;
+
+use super::parallel;
+
+/// Re-encodes `data` with `precision` and `dim`, or else the precision and dimensions of
+/// `data`
+///
+/// Coordinates are rounded to the new precision, and only the keys and values the features
+/// use are kept.
+pub fn compact(
+    data: &Data,
+    precision: Option<u32>,
+    dim: Option<u32>,
+) -> Result<Data, &'static str> {
+    let geojson = parallel::decode(data)?;
+    let precision = precision.unwrap_or_else(|| data.precision());
+    let dim = dim.unwrap_or_else(|| data.dimensions());
+    parallel::encode(geojson, precision, dim)
+}
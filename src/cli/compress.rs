@@ -0,0 +1,153 @@
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::str::FromStr;
+
+use flate2::bufread::MultiGzDecoder;
+use flate2::write::GzEncoder;
+
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+const ZSTD_MAGIC: &[u8] = &[0x28, 0xb5, 0x2f, 0xfd];
+
+/// Level of zstd compression when none is given
+#[cfg(not(target_os = "wasi"))]
+const ZSTD_DEFAULT_LEVEL: i32 = zstd::DEFAULT_COMPRESSION_LEVEL;
+/// Only used to fail on `.zst` outputs, since WASI builds have no zstd
+#[cfg(target_os = "wasi")]
+const ZSTD_DEFAULT_LEVEL: i32 = 0;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Compression {
+    Gzip(u32),
+    Zstd(i32),
+}
+
+impl Compression {
+    /// Returns the compression implied by the extension of `file_path`
+    pub fn from_extension(file_path: &str) -> Option<Self> {
+        if file_path.ends_with(".gz") {
+            Some(Compression::Gzip(6))
+        } else if file_path.ends_with(".zst") {
+            Some(Compression::Zstd(ZSTD_DEFAULT_LEVEL))
+        } else {
+            None
+        }
+    }
+}
+
+impl FromStr for Compression {
+    type Err = String;
+
+    /// Parses `gzip`, `zstd`, `gzip:<level>` or `zstd:<level>`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, level) = match s.split_once(':') {
+            Some((name, level)) => (name, Some(level)),
+            None => (s, None),
+        };
+        match name {
+            "gzip" | "gz" => match level.map(str::parse::<u32>) {
+                None => Ok(Compression::Gzip(6)),
+                Some(Ok(level)) if level <= 9 => Ok(Compression::Gzip(level)),
+                Some(_) => Err(String::from("gzip level must be between 0 and 9")),
+            },
+            #[cfg(target_os = "wasi")]
+            "zstd" | "zst" => Err(String::from("zstd isn't available in WASI builds")),
+            #[cfg(not(target_os = "wasi"))]
+            "zstd" | "zst" => match level.map(str::parse::<i32>) {
+                None => Ok(Compression::Zstd(ZSTD_DEFAULT_LEVEL)),
+                Some(Ok(level)) if zstd::compression_level_range().contains(&level) => {
+                    Ok(Compression::Zstd(level))
+                }
+                Some(_) => Err(format!(
+                    "zstd level must be between {} and {}",
+                    zstd::compression_level_range().start(),
+                    zstd::compression_level_range().end()
+                )),
+            },
+            _ => Err(format!(
+                "unknown compression {:?}, expected gzip or zstd",
+                name
+            )),
+        }
+    }
+}
+
+/// Wraps `reader` in a decompressor if its content starts with gzip or zstd magic bytes
+pub fn decompress<R: Read + 'static>(reader: R) -> io::Result<Box<dyn Read>> {
+    let mut reader = BufReader::new(reader);
+    let head = reader.fill_buf()?;
+    if head.starts_with(GZIP_MAGIC) {
+        Ok(Box::new(MultiGzDecoder::new(reader)))
+    } else if head.starts_with(ZSTD_MAGIC) {
+        #[cfg(not(target_os = "wasi"))]
+        return Ok(Box::new(zstd::stream::read::Decoder::with_buffer(reader)?));
+        #[cfg(target_os = "wasi")]
+        return Err(zstd_unsupported());
+    } else {
+        Ok(Box::new(reader))
+    }
+}
+
+/// Writer compressing its output, see `finish`
+pub enum Compressor<W: Write> {
+    Plain(W),
+    Gzip(GzEncoder<W>),
+    #[cfg(not(target_os = "wasi"))]
+    Zstd(zstd::stream::write::Encoder<'static, W>),
+}
+
+impl<W: Write> Compressor<W> {
+    pub fn new(writer: W, compression: Option<Compression>) -> io::Result<Self> {
+        match compression {
+            None => Ok(Compressor::Plain(writer)),
+            Some(Compression::Gzip(level)) => Ok(Compressor::Gzip(GzEncoder::new(
+                writer,
+                flate2::Compression::new(level),
+            ))),
+            #[cfg(not(target_os = "wasi"))]
+            Some(Compression::Zstd(level)) => Ok(Compressor::Zstd(
+                zstd::stream::write::Encoder::new(writer, level)?,
+            )),
+            #[cfg(target_os = "wasi")]
+            Some(Compression::Zstd(_)) => Err(zstd_unsupported()),
+        }
+    }
+
+    /// Writes the compression trailer and flushes the underlying writer
+    pub fn finish(self) -> io::Result<()> {
+        let mut writer = match self {
+            Compressor::Plain(writer) => writer,
+            Compressor::Gzip(encoder) => encoder.finish()?,
+            #[cfg(not(target_os = "wasi"))]
+            Compressor::Zstd(encoder) => encoder.finish()?,
+        };
+        writer.flush()
+    }
+}
+
+impl<W: Write> Write for Compressor<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Compressor::Plain(writer) => writer.write(buf),
+            Compressor::Gzip(encoder) => encoder.write(buf),
+            #[cfg(not(target_os = "wasi"))]
+            Compressor::Zstd(encoder) => encoder.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Compressor::Plain(writer) => writer.flush(),
+            Compressor::Gzip(encoder) => encoder.flush(),
+            #[cfg(not(target_os = "wasi"))]
+            Compressor::Zstd(encoder) => encoder.flush(),
+        }
+    }
+}
+
+/// zstd is left out of WASI builds, see `Cargo.toml`
+#[cfg(target_os = "wasi")]
+fn zstd_unsupported() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::Unsupported,
+        "zstd isn't available in WASI builds",
+    )
+}
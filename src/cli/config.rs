@@ -0,0 +1,112 @@
+use std::ffi::OsString;
+use std::fs;
+use std::path::Path;
+
+use clap::{ArgMatches, Command, ValueSource};
+
+use super::error::ErrorKind;
+
+/// Configuration file read when `--config` isn't given, if it exists
+pub const DEFAULT_PATH: &str = "geobuf.toml";
+
+/// Options a configuration file can set, by argument id with underscores for dashes
+const KEYS: [&str; 6] = [
+    "precision",
+    "dim",
+    "compress",
+    "threads",
+    "keep_props",
+    "drop_props",
+];
+
+/// Option values from a configuration file, used by the subcommands taking these options
+/// when they aren't given on the command line
+///
+/// ```toml
+/// precision = 7
+/// dim = 2
+/// compress = "zstd:19"
+/// threads = 4
+/// drop_props = ["internal_*"]
+/// ```
+#[derive(Debug, PartialEq)]
+pub struct Config {
+    values: Vec<(&'static str, String)>,
+}
+
+impl Config {
+    /// Reads the configuration file at `path`, or at `DEFAULT_PATH` without `path`
+    pub fn load(path: Option<&str>) -> Result<Option<Config>, (ErrorKind, String)> {
+        let path = match path {
+            Some(path) => path,
+            None if Path::new(DEFAULT_PATH).is_file() => DEFAULT_PATH,
+            None => return Ok(None),
+        };
+        let contents = fs::read_to_string(path)
+            .map_err(|_| (ErrorKind::Input, format!("Could not read {}", path)))?;
+        Config::parse(&contents).map(Some).map_err(|err| {
+            (
+                ErrorKind::Parse,
+                format!("Invalid configuration {}: {}", path, err),
+            )
+        })
+    }
+
+    pub fn parse(contents: &str) -> Result<Config, String> {
+        let table: toml::value::Table = toml::from_str(contents).map_err(|err| err.to_string())?;
+        let mut values = Vec::new();
+        for (key, value) in table {
+            let key = *KEYS
+                .iter()
+                .find(|known| **known == key)
+                .ok_or_else(|| format!("unknown key {}", key))?;
+            let value = match (key, value) {
+                ("compress" | "keep_props" | "drop_props", toml::Value::String(value)) => value,
+                ("keep_props" | "drop_props", toml::Value::Array(patterns)) => patterns
+                    .iter()
+                    .map(|pattern| pattern.as_str())
+                    .collect::<Option<Vec<&str>>>()
+                    .ok_or_else(|| format!("invalid value for {}", key))?
+                    .join(","),
+                ("precision" | "dim", toml::Value::String(value)) if value == "auto" => value,
+                ("precision" | "dim" | "threads", toml::Value::Integer(value)) if value >= 0 => {
+                    value.to_string()
+                }
+                _ => return Err(format!("invalid value for {}", key)),
+            };
+            values.push((key, value));
+        }
+        Ok(Config { values })
+    }
+
+    /// Returns the command line `args` of `command`, parsed into `matches`, with the options
+    /// of the configuration that the subcommand takes and that `args` don't give
+    pub fn apply(
+        &self,
+        command: &Command,
+        matches: &ArgMatches,
+        mut args: Vec<OsString>,
+    ) -> Vec<OsString> {
+        let (name, subcommand_matches) = match matches.subcommand() {
+            Some(subcommand) => subcommand,
+            None => return args,
+        };
+        let subcommand = match command.find_subcommand(name) {
+            Some(subcommand) => subcommand,
+            None => return args,
+        };
+        for (key, value) in &self.values {
+            let id = key.replace('_', "-");
+            let long = subcommand
+                .get_arguments()
+                .find(|arg| arg.get_id() == id)
+                .and_then(|arg| arg.get_long());
+            if let Some(long) = long {
+                if subcommand_matches.value_source(&id) != Some(ValueSource::CommandLine) {
+                    args.push(OsString::from(format!("--{}={}", long, value)));
+                }
+            }
+        }
+        args
+    }
+}
@@ -0,0 +1,158 @@
+use std::collections::BTreeMap;
+
+use geobuf::container;
+
+/// Wire type of varint fields
+const VARINT: u8 = 0;
+/// Wire type of length-delimited fields: messages, strings and packed arrays
+const LEN: u8 = 2;
+
+/// Number of features of a Geobuf message
+#[derive(Debug, Default, PartialEq)]
+pub struct Counts {
+    pub features: usize,
+    /// Number of features per geometry type, with `null` for features without geometry
+    pub types: BTreeMap<&'static str, usize>,
+}
+
+/// Counts the features of a raw or container Geobuf message by scanning its fields, skipping
+/// coordinates and properties without decoding them
+///
+/// Geometry types are only read if `by_type` is true.
+pub fn count(bytes: &[u8], by_type: bool) -> Result<Counts, &'static str> {
+    let payload = if container::is_container(bytes) {
+        let (flags, payload) = container::read_container(bytes)?;
+        if flags & (container::FLAG_LAYERS | container::FLAG_PATCH) != 0 {
+            return Err("Container does not hold a single dataset.");
+        }
+        payload
+    } else {
+        bytes
+    };
+
+    let mut counts = Counts::default();
+    let mut add = |feature: &[u8]| -> Result<(), &'static str> {
+        counts.features += 1;
+        if by_type {
+            *counts.types.entry(geometry_type(feature)?).or_insert(0) += 1;
+        }
+        Ok(())
+    };
+    let mut root = Fields::new(payload);
+    while let Some((field, wire_type, value)) = root.next_field()? {
+        match (field, wire_type) {
+            // FeatureCollection
+            (4, LEN) => {
+                let mut collection = Fields::new(value);
+                while let Some((field, wire_type, feature)) = collection.next_field()? {
+                    if (field, wire_type) == (1, LEN) {
+                        add(feature)?;
+                    }
+                }
+            }
+            // Feature
+            (5, LEN) => add(value)?,
+            _ => {}
+        }
+    }
+    Ok(counts)
+}
+
+/// Returns the geometry type of an encoded feature
+fn geometry_type(feature: &[u8]) -> Result<&'static str, &'static str> {
+    let mut geometry = None;
+    let mut fields = Fields::new(feature);
+    while let Some((field, wire_type, value)) = fields.next_field()? {
+        if (field, wire_type) == (1, LEN) {
+            geometry = Some(value);
+        }
+    }
+    let geometry = match geometry {
+        Some(geometry) => geometry,
+        None => return Ok("null"),
+    };
+    // Type defaults to POINT when left out
+    let mut geometry_type = 0;
+    let mut fields = Fields::new(geometry);
+    while let Some((field, wire_type, value)) = fields.next_field()? {
+        if (field, wire_type) == (1, VARINT) {
+            geometry_type = read_varint(value, &mut 0)?;
+        }
+    }
+    Ok(match geometry_type {
+        0 => "Point",
+        1 => "MultiPoint",
+        2 => "LineString",
+        3 => "MultiLineString",
+        4 => "Polygon",
+        5 => "MultiPolygon",
+        6 => "GeometryCollection",
+        _ => "unknown",
+    })
+}
+
+/// Field number, wire type and bytes of the value of a field
+type Field<'a> = (u64, u8, &'a [u8]);
+
+/// Reader of the fields of an encoded message
+struct Fields<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Fields<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Fields { bytes, pos: 0 }
+    }
+
+    fn next_field(&mut self) -> Result<Option<Field<'a>>, &'static str> {
+        if self.pos >= self.bytes.len() {
+            return Ok(None);
+        }
+        let tag = read_varint(self.bytes, &mut self.pos)?;
+        let start = self.pos;
+        let len = match (tag & 7) as u8 {
+            VARINT => {
+                read_varint(self.bytes, &mut self.pos)?;
+                self.pos - start
+            }
+            1 => 8,
+            LEN => {
+                let len = read_varint(self.bytes, &mut self.pos)? as usize;
+                return match self.pos.checked_add(len) {
+                    Some(end) if end <= self.bytes.len() => {
+                        let value = &self.bytes[self.pos..end];
+                        self.pos = end;
+                        Ok(Some((tag >> 3, LEN, value)))
+                    }
+                    _ => Err("Truncated field."),
+                };
+            }
+            5 => 4,
+            _ => return Err("Unsupported wire type."),
+        };
+        let value = match self.bytes.get(start..start + len) {
+            Some(value) => value,
+            None => return Err("Truncated field."),
+        };
+        self.pos = start + len;
+        Ok(Some((tag >> 3, (tag & 7) as u8, value)))
+    }
+}
+
+/// Reads a varint at `pos` in `bytes`, moving `pos` past it
+fn read_varint(bytes: &[u8], pos: &mut usize) -> Result<u64, &'static str> {
+    let mut value: u64 = 0;
+    for i in 0..10 {
+        let byte = match bytes.get(*pos) {
+            Some(byte) => *byte,
+            None => return Err("Truncated varint."),
+        };
+        *pos += 1;
+        value |= ((byte & 0x7f) as u64) << (7 * i);
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err("Invalid varint.")
+}
@@ -0,0 +1,273 @@
+use std::collections::{HashMap, HashSet};
+use std::io::{Read, Write};
+use std::str::FromStr;
+
+use ::csv::StringRecord;
+use serde_json::{Map, Value as JSONValue};
+
+use geobuf::geobuf_pb::{self, Data};
+
+use super::dedupe::feature_key;
+use super::wkt::to_wkt;
+
+/// Geometry columns of CSV outputs
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GeometryColumns {
+    /// A `wkt` column
+    Wkt,
+    /// `x` and `y` columns, and `z` for 3D data, for points only
+    Xy,
+}
+
+impl FromStr for GeometryColumns {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "wkt" => Ok(GeometryColumns::Wkt),
+            "xy" => Ok(GeometryColumns::Xy),
+            _ => Err(format!(
+                "unknown geometry columns {:?}, expected wkt or xy",
+                s
+            )),
+        }
+    }
+}
+
+/// Type shared by every non-empty value of a column
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum ColumnType {
+    Bool,
+    Integer,
+    Number,
+    String,
+}
+
+impl ColumnType {
+    fn of(value: &str) -> Self {
+        // Zero-padded codes such as ZIP codes would lose their padding as numbers
+        let padded = value.len() > 1 && value.starts_with('0') && !value.starts_with("0.");
+        if value == "true" || value == "false" {
+            ColumnType::Bool
+        } else if padded {
+            ColumnType::String
+        } else if value.parse::<i64>().is_ok() {
+            ColumnType::Integer
+        } else if value.parse::<f64>().is_ok() {
+            ColumnType::Number
+        } else {
+            ColumnType::String
+        }
+    }
+
+    /// Returns the narrowest type holding values of both types
+    fn widen(self, other: Self) -> Self {
+        match (self, other) {
+            (ColumnType::Integer, ColumnType::Number)
+            | (ColumnType::Number, ColumnType::Integer) => ColumnType::Number,
+            (a, b) if a == b => a,
+            _ => ColumnType::String,
+        }
+    }
+
+    fn parse(self, value: &str) -> JSONValue {
+        match self {
+            ColumnType::Bool => JSONValue::Bool(value == "true"),
+            ColumnType::Integer => JSONValue::from(value.parse::<i64>().unwrap()),
+            ColumnType::Number => JSONValue::from(value.parse::<f64>().unwrap()),
+            ColumnType::String => JSONValue::String(String::from(value)),
+        }
+    }
+}
+
+/// Encodes a CSV with a header row into a FeatureCollection of points, taking the position
+/// from the `lon_field` and `lat_field` columns and the properties from the other columns
+///
+/// Each property column gets the narrowest type of its non-empty values: booleans
+/// (`true`/`false`), integers, numbers, or strings. Empty cells are left out of the
+/// properties.
+pub fn encode<R: Read>(
+    reader: R,
+    lon_field: &str,
+    lat_field: &str,
+    precision: u32,
+    dim: u32,
+) -> Result<Data, String> {
+    let (headers, records, types) = read_records(reader)?;
+    let column = |name: &str| {
+        headers
+            .iter()
+            .position(|header| header == name)
+            .ok_or_else(|| format!("missing column {}", name))
+    };
+    let lon = column(lon_field)?;
+    let lat = column(lat_field)?;
+
+    let mut data = Data::new();
+    data.set_precision(precision);
+    data.set_dimensions(dim);
+    data.set_feature_collection(geobuf_pb::data::FeatureCollection::new());
+    for record in &records {
+        let line = record.position().map_or(0, |position| position.line());
+        let coordinate = |column: usize, name: &str| {
+            record[column]
+                .trim()
+                .parse::<f64>()
+                .map_err(|_| format!("line {}: invalid {} {:?}", line, name, &record[column]))
+        };
+        let position = [coordinate(lon, lon_field)?, coordinate(lat, lat_field)?];
+        let mut properties = Map::new();
+        for (column, value) in record.iter().enumerate() {
+            if column == lon || column == lat || value.is_empty() {
+                continue;
+            }
+            if let Some(column_type) = types[column] {
+                properties.insert(String::from(&headers[column]), column_type.parse(value));
+            }
+        }
+        let feature = serde_json::json!({
+            "type": "Feature",
+            "properties": properties,
+            "geometry": {"type": "Point", "coordinates": position},
+        });
+        data.push_feature_json(&feature)
+            .map_err(|err| format!("line {}: {}", line, err))?;
+    }
+    Ok(data)
+}
+
+/// Joins the columns of a CSV with a header row onto the features whose key matches the `on`
+/// column, and returns the number of features joined and of rows matching no feature
+///
+/// Features are matched by `feature_key`. Values get the column types of `encode`, and empty
+/// cells leave properties unchanged. When several rows share a key, the first one is joined.
+pub fn join<R: Read>(data: &mut Data, reader: R, on: &str) -> Result<(usize, usize), String> {
+    let (headers, records, types) = read_records(reader)?;
+    let on_column = headers
+        .iter()
+        .position(|header| header == on)
+        .ok_or_else(|| format!("missing column {}", on))?;
+    let mut rows = HashMap::new();
+    for record in &records {
+        rows.entry(&record[on_column]).or_insert(record);
+    }
+
+    let keys: Vec<Option<String>> = (0..data.feature_count())
+        .map(|n| feature_key(data, n, on))
+        .collect();
+
+    let mut joined = 0;
+    let mut matched = HashSet::new();
+    for (n, key) in keys.iter().enumerate() {
+        let record = match key.as_deref().and_then(|key| rows.get(key)) {
+            Some(record) => record,
+            None => continue,
+        };
+        for (column, value) in record.iter().enumerate() {
+            if column == on_column || value.is_empty() {
+                continue;
+            }
+            if let Some(column_type) = types[column] {
+                data.set_property(n, &headers[column], &column_type.parse(value))?;
+            }
+        }
+        matched.insert(&record[on_column]);
+        joined += 1;
+    }
+    Ok((joined, rows.len() - matched.len()))
+}
+
+/// Header row, records, and type of every column with a non-empty value of a CSV
+type Records = (StringRecord, Vec<StringRecord>, Vec<Option<ColumnType>>);
+
+/// Reads the header row and records of a CSV, and infers the types of its columns
+fn read_records<R: Read>(reader: R) -> Result<Records, String> {
+    let mut reader = ::csv::Reader::from_reader(reader);
+    let headers = reader
+        .headers()
+        .map_err(|_| String::from("could not read the header row"))?
+        .clone();
+    let mut records = Vec::new();
+    let mut types: Vec<Option<ColumnType>> = vec![None; headers.len()];
+    for record in reader.records() {
+        let record = record.map_err(|err| err.to_string())?;
+        for (value, column_type) in record.iter().zip(types.iter_mut()) {
+            if value.is_empty() {
+                continue;
+            }
+            let value_type = ColumnType::of(value);
+            *column_type = Some(match column_type {
+                Some(column_type) => column_type.widen(value_type),
+                None => value_type,
+            });
+        }
+        records.push(record);
+    }
+    Ok((headers, records, types))
+}
+
+/// Writes the features of `data` as CSV with a header row: an `id` column if any feature has
+/// an id, a column per property key, and the geometry columns
+///
+/// Strings are written as they are, other values as JSON and missing values as empty cells.
+pub fn write<W: Write>(
+    data: &Data,
+    geometry: GeometryColumns,
+    writer: &mut W,
+) -> Result<(), String> {
+    let features = geobuf::decode::Decoder::features(data)?;
+    let has_ids = match data.data_type.as_ref() {
+        Some(geobuf_pb::data::Data_type::FeatureCollection(fc)) => {
+            fc.features.iter().any(|feature| feature.id_type.is_some())
+        }
+        Some(geobuf_pb::data::Data_type::Feature(feature)) => feature.id_type.is_some(),
+        _ => false,
+    };
+    let dim = if data.dimensions() > 2 { 3 } else { 2 };
+
+    let mut header = Vec::new();
+    if has_ids {
+        header.push("id");
+    }
+    header.extend(data.keys.iter().map(String::as_str));
+    match geometry {
+        GeometryColumns::Wkt => header.push("wkt"),
+        GeometryColumns::Xy => header.extend(&["x", "y", "z"][..dim]),
+    }
+    let mut writer = ::csv::Writer::from_writer(writer);
+    let error = |_| String::from("could not write output");
+    writer.write_record(&header).map_err(error)?;
+
+    for (n, feature) in features.enumerate() {
+        let mut record = Vec::with_capacity(header.len());
+        if has_ids {
+            record.push(cell(&feature["id"]));
+        }
+        for key in &data.keys {
+            record.push(cell(&feature["properties"][key]));
+        }
+        match geometry {
+            GeometryColumns::Wkt => record.push(to_wkt(&feature["geometry"])?),
+            GeometryColumns::Xy => {
+                if feature["geometry"]["type"] != "Point" {
+                    return Err(format!("feature {} is not a Point", n));
+                }
+                let position = &feature["geometry"]["coordinates"];
+                record.extend((0..dim).map(|axis| cell(&position[axis])));
+            }
+        }
+        writer.write_record(&record).map_err(error)?;
+    }
+    writer
+        .flush()
+        .map_err(|_| String::from("could not write output"))
+}
+
+/// Returns the text of a CSV cell holding `value`
+fn cell(value: &JSONValue) -> String {
+    match value {
+        JSONValue::Null => String::new(),
+        JSONValue::String(value) => value.clone(),
+        value => value.to_string(),
+    }
+}
@@ -0,0 +1,49 @@
+use std::collections::HashSet;
+
+use geobuf::geobuf_pb::{self, Data};
+
+/// Returns the indexes of the features of `data` to keep when dropping duplicates, keeping
+/// the first feature of each group
+///
+/// Without `key`, features are duplicates if their geometry and properties have the same
+/// `geobuf::hash_feature`. With `key`, they are duplicates if they have the same key, see
+/// `feature_key`; features without one are all kept.
+pub fn dedupe(data: &Data, key: Option<&str>) -> Vec<usize> {
+    let features: &[geobuf_pb::data::Feature] = match data.data_type.as_ref() {
+        Some(geobuf_pb::data::Data_type::FeatureCollection(fc)) => &fc.features,
+        Some(geobuf_pb::data::Data_type::Feature(feature)) => std::slice::from_ref(feature),
+        _ => &[],
+    };
+    let mut hashes = HashSet::new();
+    let mut keys = HashSet::new();
+    features
+        .iter()
+        .enumerate()
+        .filter(|(n, feature)| match key {
+            Some(key) => match feature_key(data, *n, key) {
+                Some(value) => keys.insert(value),
+                None => true,
+            },
+            None => hashes.insert(geobuf::hash_feature(feature, &data.keys, &data.strings)),
+        })
+        .map(|(n, _)| n)
+        .collect()
+}
+
+/// Returns the key of feature `n` as text: its id when `key` is `id` and the feature has
+/// one, or else its `key` property
+pub fn feature_key(data: &Data, n: usize, key: &str) -> Option<String> {
+    let feature = match data.data_type.as_ref()? {
+        geobuf_pb::data::Data_type::FeatureCollection(fc) => fc.features.get(n)?,
+        geobuf_pb::data::Data_type::Feature(feature) if n == 0 => feature,
+        _ => return None,
+    };
+    match feature.id_type.as_ref() {
+        Some(geobuf_pb::data::feature::Id_type::Id(id)) if key == "id" => Some(id.clone()),
+        Some(geobuf_pb::data::feature::Id_type::IntId(id)) if key == "id" => Some(id.to_string()),
+        _ => match data.get_property(n, key)? {
+            serde_json::Value::String(value) => Some(value),
+            value => Some(value.to_string()),
+        },
+    }
+}
@@ -0,0 +1,121 @@
+use serde_json::Value as JSONValue;
+
+use geobuf::diff::Diff;
+use geobuf::geobuf_pb::{self, Data};
+
+/// Returns the diff as a JSON object with `added`, `removed`, `changed` and `summary` members
+pub fn to_json(diff: &Diff, old: &Data, new: &Data) -> JSONValue {
+    let (old_features, new_features) = (features(old), features(new));
+    let added: Vec<JSONValue> = diff
+        .added
+        .iter()
+        .map(|n| serde_json::json!({"index": n, "id": id(new_features[*n])}))
+        .collect();
+    let removed: Vec<JSONValue> = diff
+        .removed
+        .iter()
+        .map(|o| serde_json::json!({"index": o, "id": id(old_features[*o])}))
+        .collect();
+    let changed: Vec<JSONValue> = diff
+        .changed
+        .iter()
+        .map(|change| {
+            let properties: Vec<JSONValue> = change
+                .properties
+                .iter()
+                .map(|property| {
+                    serde_json::json!({
+                        "key": property.key,
+                        "old": property.old,
+                        "new": property.new,
+                    })
+                })
+                .collect();
+            serde_json::json!({
+                "old_index": change.old,
+                "new_index": change.new,
+                "id": id(new_features[change.new]),
+                "geometry_changed": change.geometry_changed,
+                "properties": properties,
+            })
+        })
+        .collect();
+    serde_json::json!({
+        "added": added,
+        "removed": removed,
+        "changed": changed,
+        "summary": {
+            "added": diff.added.len(),
+            "removed": diff.removed.len(),
+            "changed": diff.changed.len(),
+        },
+    })
+}
+
+/// Returns the diff as lines starting with `-` for removed, `+` for added and `~` for
+/// changed features, followed by a summary line
+pub fn to_text(diff: &Diff, old: &Data, new: &Data) -> String {
+    let (old_features, new_features) = (features(old), features(new));
+    let mut lines = Vec::new();
+    for o in &diff.removed {
+        lines.push(format!("- feature {}{}", o, describe_id(old_features[*o])));
+    }
+    for n in &diff.added {
+        lines.push(format!("+ feature {}{}", n, describe_id(new_features[*n])));
+    }
+    for change in &diff.changed {
+        let mut changes = Vec::new();
+        if change.geometry_changed {
+            changes.push(String::from("geometry"));
+        }
+        for property in &change.properties {
+            let value = |value: &Option<JSONValue>| match value {
+                Some(value) => value.to_string(),
+                None => String::from("(none)"),
+            };
+            changes.push(format!(
+                "{}: {} -> {}",
+                property.key,
+                value(&property.old),
+                value(&property.new)
+            ));
+        }
+        lines.push(format!(
+            "~ feature {} -> {}{}: {}",
+            change.old,
+            change.new,
+            describe_id(new_features[change.new]),
+            changes.join(", ")
+        ));
+    }
+    lines.push(format!(
+        "{} added, {} removed, {} changed",
+        diff.added.len(),
+        diff.removed.len(),
+        diff.changed.len()
+    ));
+    lines.join("\n")
+}
+
+fn features(data: &Data) -> Vec<&geobuf_pb::data::Feature> {
+    match data.data_type.as_ref() {
+        Some(geobuf_pb::data::Data_type::FeatureCollection(fc)) => fc.features.iter().collect(),
+        Some(geobuf_pb::data::Data_type::Feature(feature)) => vec![feature],
+        _ => Vec::new(),
+    }
+}
+
+fn id(feature: &geobuf_pb::data::Feature) -> JSONValue {
+    match feature.id_type.as_ref() {
+        Some(geobuf_pb::data::feature::Id_type::Id(id)) => serde_json::json!(id),
+        Some(geobuf_pb::data::feature::Id_type::IntId(id)) => serde_json::json!(id),
+        _ => JSONValue::Null,
+    }
+}
+
+fn describe_id(feature: &geobuf_pb::data::Feature) -> String {
+    match id(feature) {
+        JSONValue::Null => String::new(),
+        id => format!(" (id {})", id),
+    }
+}
@@ -0,0 +1,83 @@
+use std::process;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static JSON: AtomicBool = AtomicBool::new(false);
+
+/// Format of the errors printed to stderr
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum ErrorFormat {
+    #[default]
+    Text,
+    /// One `{"code", "path", "message"}` object per line
+    Json,
+}
+
+impl FromStr for ErrorFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(ErrorFormat::Text),
+            "json" => Ok(ErrorFormat::Json),
+            _ => Err(format!(
+                "unknown error format {:?}, expected text or json",
+                s
+            )),
+        }
+    }
+}
+
+/// Prints the errors reported afterwards in `format`
+pub fn set_format(format: ErrorFormat) {
+    JSON.store(format == ErrorFormat::Json, Ordering::Relaxed);
+}
+
+/// Kind of an error, which sets the exit code of the process
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ErrorKind {
+    /// Invalid arguments or a failed operation, exit code 1
+    Other,
+    /// An input file that can't be opened or read, exit code 2
+    Input,
+    /// An input that isn't valid GeoJSON, Geobuf or CSV, exit code 3
+    Parse,
+    /// A file that failed `validate` or `verify`, exit code 4
+    Validation,
+}
+
+impl ErrorKind {
+    pub fn code(self) -> &'static str {
+        match self {
+            ErrorKind::Other => "error",
+            ErrorKind::Input => "input",
+            ErrorKind::Parse => "parse",
+            ErrorKind::Validation => "validation",
+        }
+    }
+
+    pub fn exit_code(self) -> i32 {
+        match self {
+            ErrorKind::Other => 1,
+            ErrorKind::Input => 2,
+            ErrorKind::Parse => 3,
+            ErrorKind::Validation => 4,
+        }
+    }
+}
+
+/// Prints `message` about `path` to stderr in the format set with `set_format` and exits with
+/// the exit code of `kind`
+pub fn fail(kind: ErrorKind, path: Option<&str>, message: &str) -> ! {
+    if JSON.load(Ordering::Relaxed) {
+        let error = serde_json::json!({
+            "code": kind.code(),
+            "path": path,
+            "message": message,
+        });
+        eprintln!("{}", error);
+    } else {
+        eprintln!("{}", message);
+    }
+    process::exit(kind.exit_code());
+}
@@ -0,0 +1,66 @@
+use serde_json::{Map, Value as JSONValue};
+
+/// Splits every feature of a GeoJSON Feature or FeatureCollection with a MultiPoint,
+/// MultiLineString or MultiPolygon geometry into a feature per part, and returns a
+/// FeatureCollection with the number of features before and after
+///
+/// Parts copy the properties of their feature, and get its id suffixed with `-` and the index
+/// of the part. Other features are kept as they are.
+pub fn explode(geojson: JSONValue) -> Result<(JSONValue, usize, usize), &'static str> {
+    let (mut collection, features) = match geojson["type"].as_str() {
+        Some("FeatureCollection") => {
+            let mut collection = geojson;
+            let features = match collection["features"].take() {
+                JSONValue::Array(features) => features,
+                _ => return Err("FeatureCollection without features."),
+            };
+            (collection, features)
+        }
+        Some("Feature") => {
+            let mut collection = Map::new();
+            collection.insert(String::from("type"), JSONValue::from("FeatureCollection"));
+            (JSONValue::Object(collection), vec![geojson])
+        }
+        _ => return Err("GeoJSON is not a Feature or a FeatureCollection."),
+    };
+    if let Some(collection) = collection.as_object_mut() {
+        collection.remove("bbox");
+    }
+
+    let before = features.len();
+    let mut exploded = Vec::with_capacity(before);
+    for mut feature in features {
+        let single_type = match feature["geometry"]["type"].as_str() {
+            Some("MultiPoint") => "Point",
+            Some("MultiLineString") => "LineString",
+            Some("MultiPolygon") => "Polygon",
+            _ => {
+                exploded.push(feature);
+                continue;
+            }
+        };
+        let parts = match feature["geometry"]["coordinates"].take() {
+            JSONValue::Array(parts) => parts,
+            _ => return Err("Invalid coordinates."),
+        };
+        if let Some(feature) = feature.as_object_mut() {
+            feature.remove("bbox");
+        }
+        for (n, coordinates) in parts.into_iter().enumerate() {
+            let mut part = feature.clone();
+            part["geometry"] = serde_json::json!({
+                "type": single_type,
+                "coordinates": coordinates,
+            });
+            match &feature["id"] {
+                JSONValue::Null => {}
+                JSONValue::String(id) => part["id"] = JSONValue::from(format!("{}-{}", id, n)),
+                id => part["id"] = JSONValue::from(format!("{}-{}", id, n)),
+            }
+            exploded.push(part);
+        }
+    }
+    let after = exploded.len();
+    collection["features"] = JSONValue::Array(exploded);
+    Ok((collection, before, after))
+}
@@ -0,0 +1,46 @@
+use std::ops::Range;
+
+use geobuf::geobuf_pb::{self, Data};
+
+/// Parses a feature index, or a range of indexes such as `100..200` which excludes the end
+pub fn parse_range(s: &str) -> Result<Range<usize>, String> {
+    let index = |s: &str| {
+        s.trim()
+            .parse::<usize>()
+            .map_err(|_| format!("invalid index {:?}", s))
+    };
+    match s.split_once("..") {
+        Some((start, end)) => {
+            let (start, end) = (index(start)?, index(end)?);
+            if start > end {
+                return Err(format!("range {:?} ends before it starts", s));
+            }
+            Ok(start..end)
+        }
+        None => {
+            let index = index(s)?;
+            Ok(index..index + 1)
+        }
+    }
+}
+
+/// Returns the indexes of the features whose id, string or integer, is `id`
+pub fn find_id(data: &Data, id: &str) -> Vec<usize> {
+    let features: &[geobuf_pb::data::Feature] = match data.data_type.as_ref() {
+        Some(geobuf_pb::data::Data_type::FeatureCollection(fc)) => &fc.features,
+        Some(geobuf_pb::data::Data_type::Feature(feature)) => std::slice::from_ref(feature),
+        _ => &[],
+    };
+    features
+        .iter()
+        .enumerate()
+        .filter(|(_, feature)| match feature.id_type.as_ref() {
+            Some(geobuf_pb::data::feature::Id_type::Id(feature_id)) => feature_id == id,
+            Some(geobuf_pb::data::feature::Id_type::IntId(feature_id)) => {
+                feature_id.to_string() == id
+            }
+            _ => false,
+        })
+        .map(|(n, _)| n)
+        .collect()
+}
@@ -0,0 +1,205 @@
+use std::io::Cursor;
+
+use flatgeobuf::{
+    ColumnType, FallibleStreamingIterator, FeatureProperties, FgbReader, FgbWriter,
+    FgbWriterOptions, GeometryType,
+};
+use geozero::geojson::GeoJson;
+use geozero::{ColumnValue, PropertyProcessor, ToJson};
+use serde_json::{Map, Value as JSONValue};
+
+use geobuf::geobuf_pb::{self, Data};
+
+/// Collects the properties of a FlatGeobuf feature, taking the value of `id_column` as the
+/// feature id
+struct Properties<'a> {
+    id_column: &'a str,
+    id: JSONValue,
+    properties: Map<String, JSONValue>,
+}
+
+impl<'a> PropertyProcessor for Properties<'a> {
+    fn property(
+        &mut self,
+        _: usize,
+        name: &str,
+        value: &ColumnValue,
+    ) -> geozero::error::Result<bool> {
+        let value = match value {
+            ColumnValue::Byte(value) => JSONValue::from(*value),
+            ColumnValue::UByte(value) => JSONValue::from(*value),
+            ColumnValue::Bool(value) => JSONValue::from(*value),
+            ColumnValue::Short(value) => JSONValue::from(*value),
+            ColumnValue::UShort(value) => JSONValue::from(*value),
+            ColumnValue::Int(value) => JSONValue::from(*value),
+            ColumnValue::UInt(value) => JSONValue::from(*value),
+            ColumnValue::Long(value) => JSONValue::from(*value),
+            ColumnValue::ULong(value) => JSONValue::from(*value),
+            ColumnValue::Float(value) => JSONValue::from(*value),
+            ColumnValue::Double(value) => JSONValue::from(*value),
+            ColumnValue::String(value) | ColumnValue::DateTime(value) => JSONValue::from(*value),
+            ColumnValue::Json(value) => serde_json::from_str(value).unwrap_or(JSONValue::Null),
+            ColumnValue::Binary(_) => JSONValue::Null,
+        };
+        if name == self.id_column && (value.is_string() || value.is_u64() || value.is_i64()) {
+            self.id = value;
+        } else if !value.is_null() {
+            self.properties.insert(String::from(name), value);
+        }
+        // false continues with the next property
+        Ok(false)
+    }
+}
+
+/// Encodes the features of a FlatGeobuf file into a FeatureCollection, one feature at a time
+///
+/// FlatGeobuf has no feature ids, so the values of the `id_column` property become the ids.
+pub fn from_fgb(
+    contents: &[u8],
+    id_column: &str,
+    precision: u32,
+    dim: u32,
+) -> Result<Data, String> {
+    let mut features = FgbReader::open(Cursor::new(contents))
+        .and_then(|reader| reader.select_all())
+        .map_err(|err| err.to_string())?;
+
+    let mut data = Data::new();
+    data.set_precision(precision);
+    data.set_dimensions(dim);
+    data.set_feature_collection(geobuf_pb::data::FeatureCollection::new());
+    let mut n = 0;
+    while let Some(feature) = features.next().map_err(|err| err.to_string())? {
+        let geometry: JSONValue = match feature.to_json() {
+            Ok(geometry) => serde_json::from_str(&geometry).unwrap_or(JSONValue::Null),
+            Err(_) => JSONValue::Null,
+        };
+        let mut properties = Properties {
+            id_column,
+            id: JSONValue::Null,
+            properties: Map::new(),
+        };
+        feature
+            .process_properties(&mut properties)
+            .map_err(|err| format!("feature {}: {}", n, err))?;
+        let mut feature = serde_json::json!({
+            "type": "Feature",
+            "properties": properties.properties,
+            "geometry": geometry,
+        });
+        if !properties.id.is_null() {
+            feature["id"] = properties.id;
+        }
+        data.push_feature_json(&feature)
+            .map_err(|err| format!("feature {}: {}", n, err))?;
+        n += 1;
+    }
+    Ok(data)
+}
+
+/// Returns the FlatGeobuf column type holding every non-null value of a property
+fn column_type<'a>(values: impl Iterator<Item = &'a JSONValue> + Clone) -> ColumnType {
+    let all = |predicate: fn(&JSONValue) -> bool| {
+        values
+            .clone()
+            .all(|value| value.is_null() || predicate(value))
+    };
+    if all(JSONValue::is_boolean) {
+        ColumnType::Bool
+    } else if all(JSONValue::is_i64) {
+        ColumnType::Long
+    } else if all(JSONValue::is_number) {
+        ColumnType::Double
+    } else if all(JSONValue::is_string) {
+        ColumnType::String
+    } else {
+        ColumnType::Json
+    }
+}
+
+/// Returns the column value of a property, or None if it is missing
+fn column_value<'a>(
+    column_type: ColumnType,
+    value: &'a JSONValue,
+    json: &'a str,
+) -> Option<ColumnValue<'a>> {
+    match (column_type, value) {
+        (_, JSONValue::Null) => None,
+        (ColumnType::Bool, JSONValue::Bool(value)) => Some(ColumnValue::Bool(*value)),
+        (ColumnType::Long, value) => value.as_i64().map(ColumnValue::Long),
+        (ColumnType::Double, value) => value.as_f64().map(ColumnValue::Double),
+        (ColumnType::String, JSONValue::String(value)) => Some(ColumnValue::String(value)),
+        _ => Some(ColumnValue::Json(json)),
+    }
+}
+
+/// Writes the features of `data` as a FlatGeobuf file with a spatial index, with a column per
+/// property key and the feature ids in `id_column`
+pub fn to_fgb(data: &Data, id_column: &str) -> Result<Vec<u8>, String> {
+    let mut features: Vec<JSONValue> = geobuf::decode::Decoder::features(data)?.collect();
+    let mut columns: Vec<&str> = Vec::new();
+    if features.iter().any(|feature| !feature["id"].is_null()) {
+        columns.push(id_column);
+        for feature in &mut features {
+            let id = feature["id"].take();
+            feature["properties"][id_column] = id;
+        }
+    }
+    columns.extend(
+        data.keys
+            .iter()
+            .map(String::as_str)
+            .filter(|key| *key != id_column),
+    );
+    let column_types: Vec<ColumnType> = columns
+        .iter()
+        .map(|column| {
+            column_type(
+                features
+                    .iter()
+                    .map(|feature| &feature["properties"][*column]),
+            )
+        })
+        .collect();
+
+    let options = FgbWriterOptions {
+        has_z: data.dimensions() > 2,
+        ..Default::default()
+    };
+    let mut fgb = FgbWriter::create_with_options("geobuf", GeometryType::Unknown, options)
+        .map_err(|err| err.to_string())?;
+    for (column, column_type) in columns.iter().zip(&column_types) {
+        fgb.add_column(column, *column_type, |_, column| column.nullable = true);
+    }
+    for (n, feature) in features.iter().enumerate() {
+        if feature["geometry"].is_null() {
+            return Err(format!("feature {} has no geometry", n));
+        }
+        let geometry = feature["geometry"].to_string();
+        let values: Vec<(&JSONValue, String)> = columns
+            .iter()
+            .map(|column| {
+                let value = &feature["properties"][*column];
+                (value, value.to_string())
+            })
+            .collect();
+        let mut written = Ok(());
+        fgb.add_feature_geom(GeoJson(&geometry), |writer| {
+            for (index, ((value, json), column_type)) in
+                values.iter().zip(&column_types).enumerate()
+            {
+                if let Some(column_value) = column_value(*column_type, value, json) {
+                    if let Err(err) = writer.property(index, columns[index], &column_value) {
+                        written = Err(err.to_string());
+                    }
+                }
+            }
+        })
+        .map_err(|err| format!("feature {}: {}", n, err))?;
+        written.map_err(|err| format!("feature {}: {}", n, err))?;
+    }
+
+    let mut output = Vec::new();
+    fgb.write(&mut output).map_err(|err| err.to_string())?;
+    Ok(output)
+}
@@ -0,0 +1,147 @@
+use std::io::{BufRead, Read, Write};
+
+use geobuf::filter::Expr;
+use geobuf::framed::{FramedReader, FramedWriter};
+use geobuf::geobuf_pb::{self, Data};
+
+use super::prop::PropFilter;
+
+/// Writes every feature of `data` as a frame of its own holding a Feature, and returns the
+/// number of frames written
+pub fn write<W: Write>(writer: &mut FramedWriter<W>, data: &Data) -> Result<usize, &'static str> {
+    let chunks = match data.data_type.as_ref() {
+        Some(geobuf_pb::data::Data_type::FeatureCollection(_)) => geobuf::split(data, 1)?,
+        _ => return writer.write(data).map(|_| 1),
+    };
+    for mut chunk in chunks.iter().cloned() {
+        // Trimmed down to its keys and values, a single feature stands on its own
+        let feature = chunk.mut_feature_collection().features.pop().unwrap();
+        chunk.set_feature(feature);
+        writer.write(&chunk)?;
+    }
+    Ok(chunks.len())
+}
+
+/// Encodes a GeoJSON Text Sequence into frames as its records are read, one per feature,
+/// and returns the number of frames written
+pub fn encode_seq<R: BufRead, W: Write>(
+    reader: R,
+    writer: &mut FramedWriter<W>,
+    precision: u32,
+    dim: u32,
+    props: &PropFilter,
+) -> Result<usize, String> {
+    let mut frames = 0;
+    super::seq::read(reader, |geojson| {
+        let mut data = Data::new();
+        data.set_precision(precision);
+        data.set_dimensions(dim);
+        data.set_feature_collection(geobuf_pb::data::FeatureCollection::new());
+        super::seq::push(&mut data, geojson)?;
+        props.apply(&mut data)?;
+        frames += write(writer, &data)?;
+        Ok(())
+    })?;
+    Ok(frames)
+}
+
+/// Reads the frames of a stream one at a time, keeping the features intersecting `bbox`,
+/// then leaving out the first `skip` of them and stopping after `limit` more
+pub struct Frames<R: Read> {
+    reader: FramedReader<R>,
+    bbox: Option<[f64; 4]>,
+    skip: usize,
+    limit: Option<usize>,
+}
+
+impl<R: Read> Frames<R> {
+    pub fn new(reader: R, bbox: Option<[f64; 4]>, skip: usize, limit: Option<usize>) -> Self {
+        Frames {
+            reader: FramedReader::new(reader),
+            bbox,
+            skip,
+            limit,
+        }
+    }
+
+    fn select(&mut self, data: Data) -> Result<Option<Data>, &'static str> {
+        if !matches!(
+            data.data_type,
+            Some(geobuf_pb::data::Data_type::FeatureCollection(_))
+                | Some(geobuf_pb::data::Data_type::Feature(_))
+        ) {
+            return Err("Frame holds no features.");
+        }
+        let data = match self.bbox {
+            Some(bbox) => geobuf::filter::filter(&data, &Expr::Intersects(bbox))?,
+            None => data,
+        };
+        let count = data.feature_count();
+        if count <= self.skip {
+            self.skip -= count;
+            return Ok(None);
+        }
+        let end = self
+            .limit
+            .map_or(count, |limit| self.skip.saturating_add(limit).min(count));
+        let selected = if self.skip == 0 && end == count {
+            data
+        } else {
+            geobuf::select(&data, &(self.skip..end).collect::<Vec<usize>>())?
+        };
+        if let Some(limit) = self.limit.as_mut() {
+            *limit -= end - self.skip;
+        }
+        self.skip = 0;
+        Ok(Some(selected))
+    }
+}
+
+impl<R: Read> Iterator for Frames<R> {
+    type Item = Result<Data, &'static str>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            // The rest of the stream is left unread once the limit is reached
+            if self.limit == Some(0) {
+                return None;
+            }
+            let selected = self.reader.next()?.and_then(|data| self.select(data));
+            match selected {
+                Ok(None) => continue,
+                Ok(Some(data)) => return Some(Ok(data)),
+                Err(err) => return Some(Err(err)),
+            }
+        }
+    }
+}
+
+/// Writes the features of frames as a single FeatureCollection, decoding one frame at a time
+pub fn write_geojson<I, W>(frames: I, pretty: bool, writer: &mut W) -> Result<(), String>
+where
+    I: Iterator<Item = Result<Data, &'static str>>,
+    W: Write,
+{
+    let error = |_| String::from("could not write output");
+    writer
+        .write_all(br#"{"type":"FeatureCollection","features":["#)
+        .map_err(error)?;
+    let mut first = true;
+    for data in frames {
+        let data = data?;
+        for feature in geobuf::decode::Decoder::features(&data)? {
+            if !first {
+                writer.write_all(b",").map_err(error)?;
+            }
+            first = false;
+            writer.write_all(b"\n").map_err(error)?;
+            let written = if pretty {
+                serde_json::to_writer_pretty(&mut *writer, &feature)
+            } else {
+                serde_json::to_writer(&mut *writer, &feature)
+            };
+            written.map_err(|_| String::from("could not write output"))?;
+        }
+    }
+    writer.write_all(b"\n]}\n").map_err(error)
+}
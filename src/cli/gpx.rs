@@ -0,0 +1,92 @@
+use roxmltree::{Document, Node};
+use serde_json::{Map, Value as JSONValue};
+
+use geobuf::geobuf_pb::{self, Data};
+
+/// Encodes the waypoints, routes and tracks of a GPX document into a FeatureCollection
+///
+/// Waypoints become Points, routes LineStrings and tracks MultiLineStrings with a line per
+/// segment. Their text elements, such as name, desc and time, become properties. Elevations
+/// are kept when `dim` is 3, as 0 where missing.
+pub fn encode(contents: &[u8], precision: u32, dim: u32) -> Result<Data, String> {
+    let contents =
+        std::str::from_utf8(contents).map_err(|_| String::from("input is not valid UTF-8"))?;
+    let document =
+        Document::parse(contents).map_err(|err| format!("could not parse gpx: {}", err))?;
+    let z = dim > 2;
+
+    let mut data = Data::new();
+    data.set_precision(precision);
+    data.set_dimensions(dim);
+    data.set_feature_collection(geobuf_pb::data::FeatureCollection::new());
+    let features = children(document.root_element())
+        .filter(|node| matches!(node.tag_name().name(), "wpt" | "rte" | "trk"));
+    for (n, node) in features.enumerate() {
+        let geometry = match node.tag_name().name() {
+            "wpt" => position(node, z)
+                .map(|position| serde_json::json!({"type": "Point", "coordinates": position})),
+            "rte" => line(node, "rtept", z)
+                .map(|line| serde_json::json!({"type": "LineString", "coordinates": line})),
+            _ => children(node)
+                .filter(|segment| segment.tag_name().name() == "trkseg")
+                .map(|segment| line(segment, "trkpt", z))
+                .collect::<Result<Vec<_>, &'static str>>()
+                .map(|lines| serde_json::json!({"type": "MultiLineString", "coordinates": lines})),
+        };
+        let feature = serde_json::json!({
+            "type": "Feature",
+            "properties": properties(node),
+            "geometry": geometry.map_err(|err| format!("feature {}: {}", n, err))?,
+        });
+        data.push_feature_json(&feature)
+            .map_err(|err| format!("feature {}: {}", n, err))?;
+    }
+    Ok(data)
+}
+
+/// Returns the child elements of `node`
+fn children<'a, 'input>(node: Node<'a, 'input>) -> impl Iterator<Item = Node<'a, 'input>> {
+    node.children().filter(Node::is_element)
+}
+
+/// Returns the text child elements of a waypoint, route or track, except elevations
+fn properties(node: Node) -> Map<String, JSONValue> {
+    let mut properties = Map::new();
+    for child in children(node) {
+        let name = child.tag_name().name();
+        if name == "ele" || children(child).next().is_some() {
+            continue;
+        }
+        if let Some(text) = child.text() {
+            properties.insert(String::from(name), JSONValue::from(text.trim()));
+        }
+    }
+    properties
+}
+
+/// Returns the positions of the `name` child elements of `node`
+fn line(node: Node, name: &str, z: bool) -> Result<Vec<Vec<f64>>, &'static str> {
+    children(node)
+        .filter(|point| point.tag_name().name() == name)
+        .map(|point| position(point, z))
+        .collect()
+}
+
+/// Returns the position of a `wpt`, `rtept` or `trkpt` element
+fn position(point: Node, z: bool) -> Result<Vec<f64>, &'static str> {
+    let coordinate = |name: &str| {
+        point
+            .attribute(name)
+            .and_then(|value| value.parse::<f64>().ok())
+            .ok_or("Point without valid lat and lon.")
+    };
+    let mut position = vec![coordinate("lon")?, coordinate("lat")?];
+    if z {
+        let elevation = children(point)
+            .find(|child| child.tag_name().name() == "ele")
+            .and_then(|ele| ele.text())
+            .and_then(|ele| ele.trim().parse::<f64>().ok());
+        position.push(elevation.unwrap_or(0.0));
+    }
+    Ok(position)
+}
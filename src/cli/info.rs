@@ -0,0 +1,99 @@
+use geobuf::geobuf_pb::data::value::Value_type;
+use geobuf::geobuf_pb::{self, Data};
+
+/// Returns a summary of `data`, read from a file of `size` bytes
+pub fn info(data: &Data, size: usize) -> String {
+    let mut lines = vec![
+        format!("Size: {} bytes", size),
+        format!("Type: {}", root_type(data)),
+        format!("Precision: {}", data.precision()),
+        format!("Dimensions: {}", data.dimensions()),
+    ];
+    match data.data_type.as_ref() {
+        Some(geobuf_pb::data::Data_type::FeatureCollection(fc)) => {
+            lines.push(format!("Features: {}", fc.features.len()))
+        }
+        Some(geobuf_pb::data::Data_type::Feature(_)) => lines.push(String::from("Features: 1")),
+        Some(geobuf_pb::data::Data_type::Topology(topology)) => {
+            lines.push(format!("Objects: {}", topology.objects.len()));
+            lines.push(format!("Arcs: {}", topology.lengths.len()));
+        }
+        _ => {}
+    }
+    match geobuf::bbox::bbox(data) {
+        Some([min_x, min_y, max_x, max_y]) => lines.push(format!(
+            "Bbox: [{}, {}, {}, {}]",
+            min_x, min_y, max_x, max_y
+        )),
+        None => lines.push(String::from("Bbox: none")),
+    }
+
+    let types = property_types(data);
+    lines.push(format!("Properties: {}", data.keys.len()));
+    for (key, types) in data.keys.iter().zip(types) {
+        if types.is_empty() {
+            lines.push(format!("  {}: (custom properties only)", key));
+        } else {
+            lines.push(format!("  {}: {}", key, types.join("|")));
+        }
+    }
+    lines.join("\n")
+}
+
+fn root_type(data: &Data) -> &'static str {
+    match data.data_type.as_ref() {
+        Some(geobuf_pb::data::Data_type::FeatureCollection(_)) => "FeatureCollection",
+        Some(geobuf_pb::data::Data_type::Feature(_)) => "Feature",
+        Some(geobuf_pb::data::Data_type::Geometry(_)) => "Geometry",
+        Some(geobuf_pb::data::Data_type::Topology(_)) => "Topology",
+        _ => "none",
+    }
+}
+
+/// Returns the value types seen for each key in the feature or object properties
+fn property_types(data: &Data) -> Vec<Vec<&'static str>> {
+    let mut types: Vec<Vec<&'static str>> = vec![Vec::new(); data.keys.len()];
+    let mut add = |properties: &[u32], values: &[geobuf_pb::data::Value]| {
+        for pair in properties.chunks_exact(2) {
+            let value_type = match values.get(pair[1] as usize) {
+                Some(value) => value_type(value),
+                None => continue,
+            };
+            if let Some(key_types) = types.get_mut(pair[0] as usize) {
+                if !key_types.contains(&value_type) {
+                    key_types.push(value_type);
+                }
+            }
+        }
+    };
+    match data.data_type.as_ref() {
+        Some(geobuf_pb::data::Data_type::FeatureCollection(fc)) => {
+            for feature in &fc.features {
+                add(&feature.properties, &feature.values);
+            }
+        }
+        Some(geobuf_pb::data::Data_type::Feature(feature)) => {
+            add(&feature.properties, &feature.values)
+        }
+        Some(geobuf_pb::data::Data_type::Topology(topology)) => {
+            let mut objects: Vec<&geobuf_pb::data::Geometry> = topology.objects.iter().collect();
+            while let Some(object) = objects.pop() {
+                add(&object.properties, &object.values);
+                objects.extend(&object.geometries);
+            }
+        }
+        _ => {}
+    }
+    types
+}
+
+fn value_type(value: &geobuf_pb::data::Value) -> &'static str {
+    match value.value_type.as_ref() {
+        Some(Value_type::StringValue(_)) | Some(Value_type::StringRef(_)) => "string",
+        Some(Value_type::DoubleValue(_)) => "number",
+        Some(Value_type::PosIntValue(_)) | Some(Value_type::NegIntValue(_)) => "integer",
+        Some(Value_type::BoolValue(_)) => "boolean",
+        Some(Value_type::JsonValue(_)) => "json",
+        _ => "null",
+    }
+}
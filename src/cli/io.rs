@@ -0,0 +1,363 @@
+use std::fs;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::str::FromStr;
+use std::time::Instant;
+
+use tracing::debug;
+
+use geobuf::geobuf_pb::Data;
+
+use super::compress::{self, Compression, Compressor};
+use super::error::{fail, ErrorKind};
+use super::log::timed;
+use super::parallel;
+use super::progress;
+
+/// Text format of GeoJSON inputs and outputs
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Format {
+    GeoJson,
+    /// GeoJSON Text Sequence, see `seq`
+    GeoJsonSeq,
+    /// One WKT geometry per line, output only
+    Wkt,
+    /// Points from longitude and latitude columns on input, a row per feature on output, see
+    /// `csv`
+    Csv,
+    /// A Shapefile and its DBF attributes, input only, see `shapefile`
+    Shapefile,
+    /// Placemarks of a KML document, input only, see `kml`
+    Kml,
+    /// Waypoints, routes and tracks of a GPX document, input only, see `gpx`
+    Gpx,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "geojson" => Ok(Format::GeoJson),
+            "geojsonseq" => Ok(Format::GeoJsonSeq),
+            "wkt" => Ok(Format::Wkt),
+            "csv" => Ok(Format::Csv),
+            "shapefile" => Ok(Format::Shapefile),
+            "kml" => Ok(Format::Kml),
+            "gpx" => Ok(Format::Gpx),
+            _ => Err(format!(
+                "unknown format {:?}, expected geojson, geojsonseq, wkt, csv, shapefile, kml or gpx",
+                s
+            )),
+        }
+    }
+}
+
+/// Opens `file_path`, stdin if it is "-", an HTTP(S) URL with the `http` feature, or an
+/// s3:// or gs:// URI with the `object_store` feature, decompressing gzip and zstd content
+pub fn open_input(file_path: &str) -> Box<dyn Read> {
+    let message = format!("Reading {}", file_path);
+    let reader: Box<dyn Read> = if file_path == "-" {
+        Box::new(progress::read(io::stdin(), None, &message))
+    } else if file_path.starts_with("http://") || file_path.starts_with("https://") {
+        download(file_path, &message)
+    } else if is_object_uri(file_path) {
+        get_object(file_path, &message)
+    } else {
+        match fs::File::open(file_path) {
+            Ok(file) => {
+                let len = file.metadata().map(|metadata| metadata.len()).ok();
+                Box::new(progress::read(file, len, &message))
+            }
+            Err(_) => {
+                fail(
+                    ErrorKind::Input,
+                    Some(file_path),
+                    &format!("Could not open {}", file_path),
+                );
+            }
+        }
+    };
+    match compress::decompress(reader) {
+        Ok(reader) => reader,
+        Err(_) => {
+            fail(
+                ErrorKind::Input,
+                Some(file_path),
+                &format!("Could not read {}", file_path),
+            );
+        }
+    }
+}
+
+/// Streams the body of a GET request to `url`
+#[cfg(feature = "http")]
+fn download(url: &str, message: &str) -> Box<dyn Read> {
+    match ureq::get(url).call() {
+        Ok(response) => {
+            let len = response
+                .header("Content-Length")
+                .and_then(|len| len.parse().ok());
+            Box::new(progress::read(response.into_reader(), len, message))
+        }
+        Err(err) => fail(
+            ErrorKind::Input,
+            Some(url),
+            // The error starts with the URL
+            &format!("Could not download {}", err),
+        ),
+    }
+}
+
+#[cfg(not(feature = "http"))]
+fn download(url: &str, _message: &str) -> Box<dyn Read> {
+    fail(
+        ErrorKind::Input,
+        Some(url),
+        &format!("Could not download {}: built without the http feature", url),
+    )
+}
+
+/// Streams the contents of an object from S3 or GCS
+#[cfg(feature = "object_store")]
+fn get_object(uri: &str, message: &str) -> Box<dyn Read> {
+    match super::store::open(uri) {
+        Ok((reader, len)) => Box::new(progress::read(reader, Some(len), message)),
+        Err(err) => fail(
+            ErrorKind::Input,
+            Some(uri),
+            &format!("Could not open {}: {}", uri, err),
+        ),
+    }
+}
+
+#[cfg(not(feature = "object_store"))]
+fn get_object(uri: &str, _message: &str) -> Box<dyn Read> {
+    fail(
+        ErrorKind::Input,
+        Some(uri),
+        &format!(
+            "Could not open {}: built without the object_store feature",
+            uri
+        ),
+    )
+}
+
+#[cfg(feature = "object_store")]
+fn put_object(uri: &str) -> Box<dyn Write> {
+    match super::store::create(uri) {
+        Ok(writer) => Box::new(writer),
+        Err(err) => fail(
+            ErrorKind::Other,
+            Some(uri),
+            &format!("Could not create {}: {}", uri, err),
+        ),
+    }
+}
+
+#[cfg(not(feature = "object_store"))]
+fn put_object(uri: &str) -> Box<dyn Write> {
+    fail(
+        ErrorKind::Other,
+        Some(uri),
+        &format!(
+            "Could not create {}: built without the object_store feature",
+            uri
+        ),
+    )
+}
+
+fn is_object_uri(file_path: &str) -> bool {
+    file_path.starts_with("s3://") || file_path.starts_with("gs://")
+}
+
+/// Creates `file_path`, an s3:// or gs:// URI with the `object_store` feature, or returns
+/// stdout if it is "-", compressing with `compression` or the compression implied by the file
+/// extension
+pub fn create_output(
+    file_path: &str,
+    compression: Option<Compression>,
+) -> Compressor<BufWriter<Box<dyn Write>>> {
+    let writer: Box<dyn Write> = if file_path == "-" {
+        Box::new(io::stdout())
+    } else if is_object_uri(file_path) {
+        put_object(file_path)
+    } else {
+        match fs::File::create(file_path) {
+            Ok(file) => Box::new(file),
+            Err(_) => {
+                fail(
+                    ErrorKind::Other,
+                    Some(file_path),
+                    &format!("Could not create {}", file_path),
+                );
+            }
+        }
+    };
+    let compression = compression.or_else(|| Compression::from_extension(file_path));
+    match Compressor::new(BufWriter::new(writer), compression) {
+        Ok(output) => output,
+        Err(_) => {
+            fail(
+                ErrorKind::Other,
+                Some(file_path),
+                &format!("Could not create {}", file_path),
+            );
+        }
+    }
+}
+
+pub fn write_output(file_path: &str, compression: Option<Compression>, contents: &[u8]) {
+    let start = Instant::now();
+    let mut output = create_output(file_path, compression);
+    let bar = progress::bytes(contents.len() as u64, &format!("Writing {}", file_path));
+    let written = contents
+        .chunks(1 << 16)
+        .try_for_each(|chunk| {
+            bar.inc(chunk.len() as u64);
+            output.write_all(chunk)
+        })
+        .and_then(|_| output.finish());
+    drop(bar);
+    if written.is_err() {
+        fail(
+            ErrorKind::Other,
+            Some(file_path),
+            &format!("Could not write {}", file_path),
+        );
+    }
+    debug!(
+        "Writing {} ({} bytes) took {:.1?}",
+        file_path,
+        contents.len(),
+        start.elapsed()
+    );
+}
+
+pub fn read_json_file(file_path: &str) -> serde_json::Value {
+    let buff_reader = BufReader::new(open_input(file_path));
+    let parsed = timed(&format!("Reading and parsing {}", file_path), || {
+        serde_json::from_reader(buff_reader)
+    });
+    match parsed {
+        Ok(geojson) => geojson,
+        Err(_) => {
+            fail(
+                ErrorKind::Parse,
+                Some(file_path),
+                &format!("Could not parse geojson: {}", file_path),
+            );
+        }
+    }
+}
+
+pub fn read_file(file_path: &str) -> Vec<u8> {
+    let mut contents = vec![];
+    let start = Instant::now();
+    if open_input(file_path).read_to_end(&mut contents).is_err() {
+        fail(
+            ErrorKind::Input,
+            Some(file_path),
+            &format!("Could not read {}", file_path),
+        );
+    }
+    debug!(
+        "Reading {} ({} bytes) took {:.1?}",
+        file_path,
+        contents.len(),
+        start.elapsed()
+    );
+    contents
+}
+
+pub fn read_pbf_file(file_path: &str) -> Data {
+    parse_pbf(file_path, &read_file(file_path))
+}
+
+pub fn parse_pbf(file_path: &str, contents: &[u8]) -> Data {
+    let parsed = timed(&format!("Parsing {}", file_path), || {
+        geobuf::container::from_bytes(contents)
+    });
+    match parsed {
+        Ok(data) => {
+            debug!("{} has {} features", file_path, data.feature_count());
+            data
+        }
+        Err(err) => {
+            fail(
+                ErrorKind::Parse,
+                Some(file_path),
+                &format!("Could not read {}: {}", file_path, err),
+            );
+        }
+    }
+}
+
+/// Parses the contents of a PBF or GeoJSON file, encoding GeoJSON with `precision` and `dim`
+pub fn read_data(file_path: &str, contents: &[u8], precision: u32, dim: u32) -> Data {
+    if !is_json(contents) {
+        return parse_pbf(file_path, contents);
+    }
+    let parsed = timed(&format!("Parsing {}", file_path), || {
+        serde_json::from_slice(contents)
+    });
+    let geojson: serde_json::Value = match parsed {
+        Ok(geojson) => geojson,
+        Err(_) => {
+            fail(
+                ErrorKind::Parse,
+                Some(file_path),
+                &format!("Could not parse geojson: {}", file_path),
+            );
+        }
+    };
+    let encoded = timed(&format!("Encoding {}", file_path), || {
+        geobuf::encode::Encoder::encode(&geojson, precision, dim)
+    });
+    match encoded {
+        Ok(data) => {
+            debug!("{} has {} features", file_path, data.feature_count());
+            data
+        }
+        Err(err) => {
+            fail(
+                ErrorKind::Parse,
+                Some(file_path),
+                &format!("Could not encode {}: {}", file_path, err),
+            );
+        }
+    }
+}
+
+/// Decodes `data` read from `file_path` into GeoJSON on the rayon thread pool
+pub fn decode_data(file_path: &str, data: &Data) -> serde_json::Value {
+    match parallel::decode(data) {
+        Ok(geojson) => geojson,
+        Err(err) => {
+            fail(
+                ErrorKind::Parse,
+                Some(file_path),
+                &format!("Could not decode {}: {}", file_path, err),
+            );
+        }
+    }
+}
+
+/// Encodes GeoJSON read from `file_path` on the rayon thread pool
+pub fn encode_data(file_path: &str, geojson: serde_json::Value, precision: u32, dim: u32) -> Data {
+    match parallel::encode(geojson, precision, dim) {
+        Ok(data) => data,
+        Err(err) => {
+            fail(
+                ErrorKind::Parse,
+                Some(file_path),
+                &format!("Could not encode {}: {}", file_path, err),
+            );
+        }
+    }
+}
+
+/// Returns true if `contents` looks like a JSON object rather than a Geobuf message, which
+/// can't start with `{`
+pub fn is_json(contents: &[u8]) -> bool {
+    contents.iter().find(|b| !b.is_ascii_whitespace()) == Some(&b'{')
+}
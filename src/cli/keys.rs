@@ -0,0 +1,104 @@
+use std::collections::HashSet;
+
+use serde_json::Value as JSONValue;
+
+use geobuf::geobuf_pb::Data;
+
+/// Summary of the values of a property key
+#[derive(Debug, PartialEq)]
+pub struct KeySummary {
+    pub key: String,
+    /// JSON types of the values, in the order they were first seen
+    pub types: Vec<&'static str>,
+    /// Number of features without the property or with a `null` value
+    pub nulls: usize,
+    /// Number of distinct values, counting at most `max_distinct`
+    pub distinct: usize,
+}
+
+/// Summarizes the values of every key of the key table over the features of `data`
+pub fn keys(data: &Data, max_distinct: usize) -> Vec<KeySummary> {
+    let count = data.feature_count();
+    data.keys
+        .iter()
+        .map(|key| {
+            let mut types = Vec::new();
+            let mut nulls = 0;
+            let mut distinct = HashSet::new();
+            for n in 0..count {
+                let value = match data.get_property(n, key) {
+                    Some(JSONValue::Null) | None => {
+                        nulls += 1;
+                        continue;
+                    }
+                    Some(value) => value,
+                };
+                let value_type = match &value {
+                    JSONValue::Bool(_) => "boolean",
+                    JSONValue::Number(number) if number.is_f64() => "number",
+                    JSONValue::Number(_) => "integer",
+                    JSONValue::String(_) => "string",
+                    _ => "json",
+                };
+                if !types.contains(&value_type) {
+                    types.push(value_type);
+                }
+                if distinct.len() < max_distinct {
+                    distinct.insert(value.to_string());
+                }
+            }
+            KeySummary {
+                key: key.clone(),
+                types,
+                nulls,
+                distinct: distinct.len(),
+            }
+        })
+        .collect()
+}
+
+/// Formats summaries as a table, writing `N+` for distinct counts that reached `max_distinct`
+pub fn format(summaries: &[KeySummary], max_distinct: usize) -> String {
+    let mut rows = vec![[
+        String::from("KEY"),
+        String::from("TYPE"),
+        String::from("NULLS"),
+        String::from("DISTINCT"),
+    ]];
+    for summary in summaries {
+        let types = if summary.types.is_empty() {
+            String::from("null")
+        } else {
+            summary.types.join("|")
+        };
+        let distinct = if summary.distinct >= max_distinct {
+            format!("{}+", max_distinct)
+        } else {
+            summary.distinct.to_string()
+        };
+        rows.push([
+            summary.key.clone(),
+            types,
+            summary.nulls.to_string(),
+            distinct,
+        ]);
+    }
+    let mut widths = [0; 4];
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    rows.iter()
+        .map(|row| {
+            let line = row
+                .iter()
+                .zip(widths)
+                .map(|(cell, width)| format!("{:width$}", cell, width = width))
+                .collect::<Vec<_>>()
+                .join("  ");
+            String::from(line.trim_end())
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
@@ -0,0 +1,138 @@
+use roxmltree::{Document, Node};
+use serde_json::{Map, Value as JSONValue};
+
+use geobuf::geobuf_pb::{self, Data};
+
+/// Encodes the placemarks of a KML document into a FeatureCollection
+///
+/// Placemarks keep their `id` attribute as id, and their name, description and extended data
+/// as properties. Multi-geometries become GeometryCollections and placemarks without geometry
+/// become features without geometry. Altitudes are kept when `dim` is 3, as 0 where missing.
+pub fn encode(contents: &[u8], precision: u32, dim: u32) -> Result<Data, String> {
+    let contents =
+        std::str::from_utf8(contents).map_err(|_| String::from("input is not valid UTF-8"))?;
+    let document =
+        Document::parse(contents).map_err(|err| format!("could not parse kml: {}", err))?;
+    let z = dim > 2;
+
+    let mut data = Data::new();
+    data.set_precision(precision);
+    data.set_dimensions(dim);
+    data.set_feature_collection(geobuf_pb::data::FeatureCollection::new());
+    for (n, placemark) in document
+        .descendants()
+        .filter(|node| node.tag_name().name() == "Placemark")
+        .enumerate()
+    {
+        let geometry = match children(placemark).find_map(|node| geometry(node, z).transpose()) {
+            Some(geometry) => geometry.map_err(|err| format!("placemark {}: {}", n, err))?,
+            None => JSONValue::Null,
+        };
+        let mut feature = serde_json::json!({
+            "type": "Feature",
+            "properties": properties(placemark),
+            "geometry": geometry,
+        });
+        if let Some(id) = placemark.attribute("id") {
+            feature["id"] = JSONValue::from(id);
+        }
+        data.push_feature_json(&feature)
+            .map_err(|err| format!("placemark {}: {}", n, err))?;
+    }
+    Ok(data)
+}
+
+/// Returns the child elements of `node`
+fn children<'a, 'input>(node: Node<'a, 'input>) -> impl Iterator<Item = Node<'a, 'input>> {
+    node.children().filter(Node::is_element)
+}
+
+/// Returns the first child element of `node` named `name`
+fn child<'a, 'input>(node: Node<'a, 'input>, name: &str) -> Option<Node<'a, 'input>> {
+    children(node).find(|child| child.tag_name().name() == name)
+}
+
+fn text(node: Node) -> String {
+    node.text().unwrap_or_default().trim().to_string()
+}
+
+/// Returns the name, description and extended data of a placemark
+fn properties(placemark: Node) -> Map<String, JSONValue> {
+    let mut properties = Map::new();
+    for name in ["name", "description"] {
+        if let Some(node) = child(placemark, name) {
+            properties.insert(String::from(name), JSONValue::from(text(node)));
+        }
+    }
+    if let Some(extended_data) = child(placemark, "ExtendedData") {
+        for node in extended_data.descendants() {
+            let (name, value) = match node.tag_name().name() {
+                "Data" => (node.attribute("name"), child(node, "value").map(text)),
+                "SimpleData" => (node.attribute("name"), Some(text(node))),
+                _ => continue,
+            };
+            if let (Some(name), Some(value)) = (name, value) {
+                properties.insert(String::from(name), JSONValue::from(value));
+            }
+        }
+    }
+    properties
+}
+
+/// Returns the GeoJSON geometry of a KML geometry element, or `None` for other elements
+fn geometry(node: Node, z: bool) -> Result<Option<JSONValue>, &'static str> {
+    let coordinates = |node: Node| match child(node, "coordinates") {
+        Some(coordinates) => positions(&text(coordinates), z),
+        None => Err("Geometry without coordinates."),
+    };
+    let geometry = match node.tag_name().name() {
+        "Point" => match coordinates(node)?.pop() {
+            Some(position) => serde_json::json!({"type": "Point", "coordinates": position}),
+            None => return Err("Point without coordinates."),
+        },
+        "LineString" => {
+            serde_json::json!({"type": "LineString", "coordinates": coordinates(node)?})
+        }
+        "LinearRing" => serde_json::json!({"type": "Polygon", "coordinates": [coordinates(node)?]}),
+        "Polygon" => {
+            let mut rings = Vec::new();
+            for boundary in ["outerBoundaryIs", "innerBoundaryIs"] {
+                for node in children(node).filter(|child| child.tag_name().name() == boundary) {
+                    match child(node, "LinearRing") {
+                        Some(ring) => rings.push(coordinates(ring)?),
+                        None => return Err("Polygon boundary without LinearRing."),
+                    }
+                }
+            }
+            serde_json::json!({"type": "Polygon", "coordinates": rings})
+        }
+        "MultiGeometry" => {
+            let mut geometries = Vec::new();
+            for child in children(node) {
+                geometries.extend(geometry(child, z)?);
+            }
+            serde_json::json!({"type": "GeometryCollection", "geometries": geometries})
+        }
+        _ => return Ok(None),
+    };
+    Ok(Some(geometry))
+}
+
+/// Parses whitespace separated `longitude,latitude[,altitude]` tuples
+fn positions(coordinates: &str, z: bool) -> Result<Vec<Vec<f64>>, &'static str> {
+    coordinates
+        .split_whitespace()
+        .map(|tuple| {
+            let mut position = tuple
+                .split(',')
+                .map(|value| value.parse::<f64>())
+                .collect::<Result<Vec<f64>, _>>()
+                .map_err(|_| "Invalid coordinates.")?;
+            if position.len() < 2 {
+                return Err("Invalid coordinates.");
+            }
+            position.resize(if z { 3 } else { 2 }, 0.0);
+            Ok(position)
+        })
+        .collect()
+}
@@ -0,0 +1,30 @@
+use std::time::Instant;
+
+use tracing::{debug, Level};
+
+/// Prints log events to stderr: warnings and summaries by default, stage timings and feature
+/// counts with one `-v`, details per chunk, file and tile with two, and only errors with
+/// `quiet`
+pub fn init(verbose: u64, quiet: bool) {
+    let level = match (quiet, verbose) {
+        (true, _) => Level::ERROR,
+        (false, 0) => Level::INFO,
+        (false, 1) => Level::DEBUG,
+        _ => Level::TRACE,
+    };
+    tracing_subscriber::fmt()
+        .with_writer(std::io::stderr)
+        .with_max_level(level)
+        .with_target(false)
+        .with_level(verbose > 0)
+        .without_time()
+        .init();
+}
+
+/// Runs `f` and logs how long `stage` took
+pub fn timed<T, F: FnOnce() -> T>(stage: &str, f: F) -> T {
+    let start = Instant::now();
+    let result = f();
+    debug!("{} took {:.1?}", stage, start.elapsed());
+    result
+}
@@ -0,0 +1,54 @@
+pub mod auto;
+pub mod batch;
+pub mod bbox;
+pub mod bench;
+#[cfg(feature = "geo")]
+pub mod check;
+pub mod clip;
+pub mod compact;
+pub mod compress;
+pub mod config;
+pub mod count;
+pub mod csv;
+pub mod dedupe;
+pub mod diff;
+pub mod error;
+pub mod explode;
+pub mod extract;
+#[cfg(feature = "fgb")]
+pub mod fgb;
+pub mod framed;
+#[cfg(feature = "gpx")]
+pub mod gpx;
+pub mod info;
+pub mod io;
+pub mod keys;
+#[cfg(feature = "kml")]
+pub mod kml;
+pub mod log;
+pub mod mvt;
+pub mod parallel;
+pub mod progress;
+pub mod prop;
+#[cfg(feature = "geo")]
+pub mod repair;
+#[cfg(feature = "proj")]
+pub mod reproject;
+pub mod rfc7946;
+pub mod sample;
+pub mod seq;
+pub mod serve;
+#[cfg(feature = "shapefile")]
+pub mod shapefile;
+pub mod skip;
+pub mod sort;
+pub mod split;
+pub mod stats;
+#[cfg(feature = "object_store")]
+pub mod store;
+pub mod tile;
+pub mod verify;
+pub mod wkt;
+
+#[cfg(test)]
+mod tests;
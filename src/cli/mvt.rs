@@ -0,0 +1,358 @@
+use std::collections::HashMap;
+use std::f64::consts::PI;
+
+use protobuf::CodedOutputStream;
+use serde_json::Value as JSONValue;
+
+use geobuf::geobuf_pb::Data;
+
+use super::clip::{clip_line, clip_ring, Point};
+
+/// Address of a tile in the XYZ scheme
+#[derive(Clone, Copy, Debug)]
+pub struct Tile {
+    pub z: u32,
+    pub x: u32,
+    pub y: u32,
+}
+
+/// Geometry of a feature in tile coordinates, grouped by MVT geometry type
+enum Geometry {
+    Points(Vec<Point>),
+    Lines(Vec<Vec<Point>>),
+    Polygons(Vec<Vec<Vec<Point>>>),
+}
+
+/// Keys and values tables of a layer, with the index of each entry
+#[derive(Default)]
+struct Tags {
+    keys: Vec<String>,
+    key_indexes: HashMap<String, u32>,
+    values: Vec<JSONValue>,
+    value_indexes: HashMap<String, u32>,
+}
+
+impl Tags {
+    /// Returns the tags of the non-null properties of a feature, adding their keys and values
+    /// to the tables
+    fn add(&mut self, properties: &JSONValue) -> Vec<u32> {
+        let mut tags = Vec::new();
+        let properties = match properties.as_object() {
+            Some(properties) => properties,
+            None => return tags,
+        };
+        for (key, value) in properties {
+            let value = match value {
+                JSONValue::Null => continue,
+                JSONValue::Array(_) | JSONValue::Object(_) => JSONValue::from(value.to_string()),
+                value => value.clone(),
+            };
+            let key_index = match self.key_indexes.get(key) {
+                Some(index) => *index,
+                None => {
+                    self.keys.push(key.clone());
+                    self.key_indexes
+                        .insert(key.clone(), self.keys.len() as u32 - 1);
+                    self.keys.len() as u32 - 1
+                }
+            };
+            // The JSON text tells apart values of different types, e.g. 1, 1.0 and "1"
+            let value_key = value.to_string();
+            let value_index = match self.value_indexes.get(&value_key) {
+                Some(index) => *index,
+                None => {
+                    self.values.push(value);
+                    self.value_indexes
+                        .insert(value_key, self.values.len() as u32 - 1);
+                    self.values.len() as u32 - 1
+                }
+            };
+            tags.push(key_index);
+            tags.push(value_index);
+        }
+        tags
+    }
+}
+
+/// Encodes the features of `data` intersecting `tile` into a single layer Mapbox Vector Tile
+///
+/// Coordinates are projected to Web Mercator, scaled to `extent` units per tile side, clipped
+/// to the tile grown by `buffer` units on each side and rounded. Integer feature ids are kept,
+/// nested property values are written as JSON strings and geometry collections are split
+/// into a feature per geometry.
+pub fn to_mvt(
+    data: &Data,
+    tile: Tile,
+    extent: u32,
+    buffer: u32,
+    layer: &str,
+) -> Result<Vec<u8>, String> {
+    let min = -(buffer as f64);
+    let max = (extent + buffer) as f64;
+    let mut tags = Tags::default();
+    let mut features = Vec::new();
+    for feature in geobuf::decode::Decoder::features(data)? {
+        let mut geometries = Vec::new();
+        project_geometry(&feature["geometry"], tile, extent, &mut geometries)?;
+        for geometry in geometries {
+            let (geometry_type, commands) = match clip(geometry, min, max) {
+                Geometry::Points(points) => (1, point_commands(&points)),
+                Geometry::Lines(lines) => (2, line_commands(&lines)),
+                Geometry::Polygons(polygons) => (3, polygon_commands(&polygons)),
+            };
+            if commands.is_empty() {
+                continue;
+            }
+            let feature_tags = tags.add(&feature["properties"]);
+            features.push(message(|os| {
+                if let Some(id) = feature["id"].as_u64() {
+                    os.write_uint64(1, id)?;
+                }
+                os.write_bytes(2, &packed(&feature_tags))?;
+                os.write_uint32(3, geometry_type)?;
+                os.write_bytes(4, &packed(&commands))
+            }));
+        }
+    }
+
+    let layer = message(|os| {
+        os.write_uint32(15, 2)?;
+        os.write_string(1, layer)?;
+        for feature in &features {
+            os.write_bytes(2, feature)?;
+        }
+        for key in &tags.keys {
+            os.write_string(3, key)?;
+        }
+        for value in &tags.values {
+            os.write_bytes(4, &value_message(value))?;
+        }
+        os.write_uint32(5, extent)
+    });
+    Ok(message(|os| os.write_bytes(3, &layer)))
+}
+
+/// Returns the bytes written by `write`
+fn message<F>(write: F) -> Vec<u8>
+where
+    F: FnOnce(&mut CodedOutputStream) -> protobuf::Result<()>,
+{
+    let mut bytes = Vec::new();
+    {
+        let mut os = CodedOutputStream::vec(&mut bytes);
+        // Writing to a vector can't fail
+        write(&mut os).and_then(|_| os.flush()).unwrap();
+    }
+    bytes
+}
+
+/// Returns the payload of a packed repeated uint32 field
+fn packed(values: &[u32]) -> Vec<u8> {
+    message(|os| {
+        values
+            .iter()
+            .try_for_each(|value| os.write_raw_varint32(*value))
+    })
+}
+
+fn value_message(value: &JSONValue) -> Vec<u8> {
+    message(|os| match value {
+        JSONValue::String(value) => os.write_string(1, value),
+        JSONValue::Bool(value) => os.write_bool(7, *value),
+        value => match (value.as_u64(), value.as_i64()) {
+            (Some(value), _) => os.write_uint64(5, value),
+            (None, Some(value)) => os.write_sint64(6, value),
+            _ => os.write_double(3, value.as_f64().unwrap_or(0.0)),
+        },
+    })
+}
+
+/// Projects a longitude and latitude to the coordinates of `tile` with `extent` units per side
+fn project(position: &JSONValue, tile: Tile, extent: u32) -> Result<Point, &'static str> {
+    let (lon, lat) = match (position[0].as_f64(), position[1].as_f64()) {
+        (Some(lon), Some(lat)) => (lon, lat.clamp(-85.051_128_78, 85.051_128_78)),
+        _ => return Err("Invalid position."),
+    };
+    let tiles = 2f64.powi(tile.z as i32);
+    let sin = lat.to_radians().sin();
+    let x = (lon + 180.0) / 360.0 * tiles - tile.x as f64;
+    let y = (0.5 - ((1.0 + sin) / (1.0 - sin)).ln() / (4.0 * PI)) * tiles - tile.y as f64;
+    Ok([x * extent as f64, y * extent as f64])
+}
+
+/// Adds the projection of a GeoJSON geometry to `geometries`, with one entry per member of
+/// geometry collections
+fn project_geometry(
+    geometry: &JSONValue,
+    tile: Tile,
+    extent: u32,
+    geometries: &mut Vec<Geometry>,
+) -> Result<(), &'static str> {
+    let points = |positions: &JSONValue| -> Result<Vec<Point>, &'static str> {
+        match positions.as_array() {
+            Some(positions) => positions
+                .iter()
+                .map(|position| project(position, tile, extent))
+                .collect(),
+            None => Err("Invalid coordinates."),
+        }
+    };
+    let lines = |lines: &JSONValue| -> Result<Vec<Vec<Point>>, &'static str> {
+        match lines.as_array() {
+            Some(lines) => lines.iter().map(points).collect(),
+            None => Err("Invalid coordinates."),
+        }
+    };
+    let coordinates = &geometry["coordinates"];
+    let projected = match geometry["type"].as_str() {
+        None => return Ok(()),
+        Some("Point") => Geometry::Points(vec![project(coordinates, tile, extent)?]),
+        Some("MultiPoint") => Geometry::Points(points(coordinates)?),
+        Some("LineString") => Geometry::Lines(vec![points(coordinates)?]),
+        Some("MultiLineString") => Geometry::Lines(lines(coordinates)?),
+        Some("Polygon") => Geometry::Polygons(vec![lines(coordinates)?]),
+        Some("MultiPolygon") => match coordinates.as_array() {
+            Some(polygons) => {
+                Geometry::Polygons(polygons.iter().map(lines).collect::<Result<_, _>>()?)
+            }
+            None => return Err("Invalid coordinates."),
+        },
+        Some("GeometryCollection") => {
+            for geometry in geometry["geometries"].as_array().into_iter().flatten() {
+                project_geometry(geometry, tile, extent, geometries)?;
+            }
+            return Ok(());
+        }
+        Some(_) => return Err("Unknown geometry type."),
+    };
+    geometries.push(projected);
+    Ok(())
+}
+
+/// Clips a geometry to the square from `min` to `max` on both axes
+fn clip(geometry: Geometry, min: f64, max: f64) -> Geometry {
+    let bbox = [min, min, max, max];
+    let inside = |point: &Point| point.iter().all(|value| *value >= min && *value <= max);
+    match geometry {
+        Geometry::Points(points) => Geometry::Points(points.into_iter().filter(inside).collect()),
+        Geometry::Lines(lines) => Geometry::Lines(
+            lines
+                .iter()
+                .flat_map(|line| clip_line(line, &bbox))
+                .collect(),
+        ),
+        Geometry::Polygons(polygons) => Geometry::Polygons(
+            polygons
+                .iter()
+                .map(|rings| {
+                    rings
+                        .iter()
+                        .map(|ring| clip_ring(ring, &bbox))
+                        .collect::<Vec<_>>()
+                })
+                // A polygon whose outer ring is clipped away is dropped with its holes
+                .filter(|rings| matches!(rings.first(), Some(ring) if !ring.is_empty()))
+                .collect(),
+        ),
+    }
+}
+
+/// Rounds points to integer tile coordinates, dropping repeated points
+fn quantize(points: &[Point]) -> Vec<[i32; 2]> {
+    let mut quantized: Vec<[i32; 2]> = Vec::with_capacity(points.len());
+    for point in points {
+        let point = [point[0].round() as i32, point[1].round() as i32];
+        if quantized.last() != Some(&point) {
+            quantized.push(point);
+        }
+    }
+    quantized
+}
+
+fn command(id: u32, count: usize) -> u32 {
+    id | (count as u32) << 3
+}
+
+fn zigzag(value: i32) -> u32 {
+    ((value << 1) ^ (value >> 31)) as u32
+}
+
+/// Appends the parameters of `points` relative to `cursor`, moving the cursor
+fn push_points(commands: &mut Vec<u32>, cursor: &mut [i32; 2], points: &[[i32; 2]]) {
+    for point in points {
+        commands.push(zigzag(point[0] - cursor[0]));
+        commands.push(zigzag(point[1] - cursor[1]));
+        *cursor = *point;
+    }
+}
+
+fn point_commands(points: &[Point]) -> Vec<u32> {
+    let points: Vec<[i32; 2]> = points
+        .iter()
+        .map(|point| [point[0].round() as i32, point[1].round() as i32])
+        .collect();
+    let mut commands = Vec::new();
+    if !points.is_empty() {
+        commands.push(command(1, points.len()));
+        push_points(&mut commands, &mut [0, 0], &points);
+    }
+    commands
+}
+
+fn line_commands(lines: &[Vec<Point>]) -> Vec<u32> {
+    let mut commands = Vec::new();
+    let mut cursor = [0, 0];
+    for line in lines {
+        let line = quantize(line);
+        if line.len() < 2 {
+            continue;
+        }
+        commands.push(command(1, 1));
+        push_points(&mut commands, &mut cursor, &line[..1]);
+        commands.push(command(2, line.len() - 1));
+        push_points(&mut commands, &mut cursor, &line[1..]);
+    }
+    commands
+}
+
+/// Returns twice the signed area of a ring, positive for clockwise rings in tile coordinates
+fn area(ring: &[[i32; 2]]) -> i64 {
+    let mut area = 0;
+    for (n, a) in ring.iter().enumerate() {
+        let b = ring[(n + 1) % ring.len()];
+        area += a[0] as i64 * b[1] as i64 - b[0] as i64 * a[1] as i64;
+    }
+    area
+}
+
+/// Returns the commands of polygons, with clockwise outer rings and counter-clockwise holes as
+/// MVT requires
+fn polygon_commands(polygons: &[Vec<Vec<Point>>]) -> Vec<u32> {
+    let mut commands = Vec::new();
+    let mut cursor = [0, 0];
+    for rings in polygons {
+        for (n, ring) in rings.iter().enumerate() {
+            let mut ring = quantize(ring);
+            // ClosePath returns to the first point
+            if ring.len() > 1 && ring.first() == ring.last() {
+                ring.pop();
+            }
+            let area = area(&ring);
+            if ring.len() < 3 || area == 0 {
+                if n == 0 {
+                    break;
+                }
+                continue;
+            }
+            if (n == 0) != (area > 0) {
+                ring.reverse();
+            }
+            commands.push(command(1, 1));
+            push_points(&mut commands, &mut cursor, &ring[..1]);
+            commands.push(command(2, ring.len() - 1));
+            push_points(&mut commands, &mut cursor, &ring[1..]);
+            commands.push(command(7, 1));
+        }
+    }
+    commands
+}
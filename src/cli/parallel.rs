@@ -0,0 +1,101 @@
+use rayon::prelude::*;
+use serde_json::Value as JSONValue;
+use tracing::{trace, warn};
+
+use geobuf::geobuf_pb::{self, Data};
+
+use super::log::timed;
+
+/// Smallest number of features worth encoding or decoding on another thread
+const MIN_CHUNK_SIZE: usize = 256;
+
+/// Returns the number of features per chunk to spread `count` features over the threads,
+/// or `None` if it is not worth it
+fn chunk_size(count: usize) -> Option<usize> {
+    let threads = rayon::current_num_threads();
+    if threads < 2 || count < MIN_CHUNK_SIZE * 2 {
+        return None;
+    }
+    // A few chunks per thread even out the work when features differ in size
+    Some((count / (threads * 4) + 1).max(MIN_CHUNK_SIZE))
+}
+
+/// Encodes `geojson` like `Encoder::encode`, encoding the features of large
+/// FeatureCollections in chunks on the rayon thread pool and merging the chunks
+pub fn encode(geojson: JSONValue, precision: u32, dim: u32) -> Result<Data, &'static str> {
+    timed("Encoding", || encode_chunks(geojson, precision, dim))
+}
+
+fn encode_chunks(mut geojson: JSONValue, precision: u32, dim: u32) -> Result<Data, &'static str> {
+    let count = match geojson.get("features").and_then(JSONValue::as_array) {
+        Some(features) if geojson["type"] == "FeatureCollection" => features.len(),
+        _ => return geobuf::encode::Encoder::encode(&geojson, precision, dim),
+    };
+    let chunk_size = match chunk_size(count) {
+        Some(chunk_size) => chunk_size,
+        None => return geobuf::encode::Encoder::encode(&geojson, precision, dim),
+    };
+    trace!("Encoding {} features in chunks of {}", count, chunk_size);
+
+    let mut features = geojson["features"].take();
+    let features = features.as_array_mut().unwrap();
+    // The first chunk holds no features but carries the custom properties of the collection
+    geojson["features"] = JSONValue::Array(Vec::new());
+    let mut chunks = vec![geojson];
+    while !features.is_empty() {
+        let rest = features.split_off(chunk_size.min(features.len()));
+        chunks.push(serde_json::json!({
+            "type": "FeatureCollection",
+            "features": std::mem::replace(features, rest),
+        }));
+    }
+
+    let encoded = chunks
+        .par_iter()
+        .map(|chunk| geobuf::encode::Encoder::encode(chunk, precision, dim))
+        .collect::<Result<Vec<Data>, &'static str>>()?;
+    geobuf::merge(&encoded)
+}
+
+/// Decodes `data` like `Decoder::decode`, decoding the features of large FeatureCollections
+/// in chunks on the rayon thread pool
+pub fn decode(data: &Data) -> Result<JSONValue, &'static str> {
+    timed("Decoding", || decode_chunks(data))
+}
+
+fn decode_chunks(data: &Data) -> Result<JSONValue, &'static str> {
+    let count = match data.data_type.as_ref() {
+        Some(geobuf_pb::data::Data_type::FeatureCollection(fc)) => fc.features.len(),
+        _ => return geobuf::decode::Decoder::decode(data),
+    };
+    let chunk_size = match chunk_size(count) {
+        Some(chunk_size) => chunk_size,
+        None => return geobuf::decode::Decoder::decode(data),
+    };
+    trace!("Decoding {} features in chunks of {}", count, chunk_size);
+
+    let mut decoded = geobuf::split(data, chunk_size)?
+        .par_iter()
+        .map(geobuf::decode::Decoder::decode)
+        .collect::<Result<Vec<JSONValue>, &'static str>>()?;
+    let mut features = Vec::with_capacity(count);
+    for chunk in decoded.iter_mut() {
+        if let Some(chunk_features) = chunk["features"].as_array_mut() {
+            features.append(chunk_features);
+        }
+    }
+    let mut geojson = decoded.swap_remove(0);
+    geojson["features"] = JSONValue::Array(features);
+    Ok(geojson)
+}
+
+/// Sets the number of threads of the rayon thread pool, which defaults to the number of cores
+pub fn set_threads(threads: usize) {
+    if rayon::ThreadPoolBuilder::new()
+        .num_threads(threads)
+        .build_global()
+        .is_err()
+    {
+        warn!("Could not start {} threads", threads);
+    }
+}
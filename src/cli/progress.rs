@@ -0,0 +1,61 @@
+use std::io::Read;
+use std::sync::atomic::{AtomicBool, Ordering};
+#[cfg(not(target_os = "wasi"))]
+use std::time::Duration;
+
+use indicatif::{ProgressBar, ProgressBarIter, ProgressDrawTarget, ProgressFinish, ProgressStyle};
+
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+/// Hides every progress bar created afterwards; they are also hidden when stderr is not a
+/// terminal
+pub fn set_quiet(quiet: bool) {
+    QUIET.store(quiet, Ordering::Relaxed);
+}
+
+fn new_bar(len: Option<u64>, template: &str, message: &str) -> ProgressBar {
+    let bar = match len {
+        Some(len) => ProgressBar::new(len),
+        None => ProgressBar::new_spinner(),
+    };
+    if QUIET.load(Ordering::Relaxed) {
+        bar.set_draw_target(ProgressDrawTarget::hidden());
+    }
+    bar.set_style(
+        ProgressStyle::with_template(template)
+            .unwrap()
+            .progress_chars("=> "),
+    );
+    bar.set_message(String::from(message));
+    bar.with_finish(ProgressFinish::AndClear)
+}
+
+/// Wraps `reader` in a progress bar counting the bytes read, out of `len` if known
+pub fn read<R: Read>(reader: R, len: Option<u64>, message: &str) -> ProgressBarIter<R> {
+    let template = match len {
+        Some(_) => "{msg} [{bar:40}] {bytes}/{total_bytes} ({bytes_per_sec}, {eta})",
+        None => "{spinner} {msg} {bytes} ({bytes_per_sec})",
+    };
+    new_bar(len, template, message).wrap_read(reader)
+}
+
+/// Returns a progress bar counting bytes out of `len`
+pub fn bytes(len: u64, message: &str) -> ProgressBar {
+    let template = "{msg} [{bar:40}] {bytes}/{total_bytes} ({bytes_per_sec}, {eta})";
+    new_bar(Some(len), template, message)
+}
+
+/// Returns a spinner shown until it is dropped, for steps without measurable progress
+pub fn spinner(message: &str) -> ProgressBar {
+    let bar = new_bar(None, "{spinner} {msg} ({elapsed})", message);
+    // Ticks come from a thread, which WASI can't spawn
+    #[cfg(not(target_os = "wasi"))]
+    bar.enable_steady_tick(Duration::from_millis(100));
+    bar
+}
+
+/// Returns a progress bar counting `len` processed files
+pub fn files(len: usize, message: &str) -> ProgressBar {
+    let template = "{msg} [{bar:40}] {pos}/{len} files ({eta})";
+    new_bar(Some(len as u64), template, message)
+}
@@ -0,0 +1,138 @@
+use glob::Pattern;
+use serde_json::Value as JSONValue;
+
+use geobuf::filter::Expr;
+use geobuf::geobuf_pb::{self, Data};
+
+/// Change to the properties of features
+#[derive(Clone, Debug, PartialEq)]
+pub enum Edit {
+    Set(String, JSONValue),
+    Delete(String),
+    Rename(String, String),
+}
+
+/// Properties to keep when encoding, by name or glob pattern such as `internal_*`
+#[derive(Clone, Debug, Default)]
+pub struct PropFilter {
+    keep: Vec<Pattern>,
+    drop: Vec<Pattern>,
+}
+
+impl PropFilter {
+    /// Keeps the properties matching one of `keep`, or every property if `keep` is empty,
+    /// except those matching one of `drop`
+    pub fn new(keep: &[String], drop: &[String]) -> Result<PropFilter, String> {
+        let patterns = |patterns: &[String]| {
+            patterns
+                .iter()
+                .map(|pattern| {
+                    Pattern::new(pattern)
+                        .map_err(|err| format!("Invalid pattern {}: {}", pattern, err))
+                })
+                .collect::<Result<Vec<Pattern>, String>>()
+        };
+        Ok(PropFilter {
+            keep: patterns(keep)?,
+            drop: patterns(drop)?,
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keep.is_empty() && self.drop.is_empty()
+    }
+
+    pub fn keeps(&self, key: &str) -> bool {
+        (self.keep.is_empty() || self.keep.iter().any(|pattern| pattern.matches(key)))
+            && !self.drop.iter().any(|pattern| pattern.matches(key))
+    }
+
+    /// Deletes the feature properties of `data` that the filter doesn't keep
+    ///
+    /// Keys no longer used by any feature are dropped from the key table.
+    pub fn apply(&self, data: &mut Data) -> Result<(), &'static str> {
+        if self.is_empty() {
+            return Ok(());
+        }
+        let dropped: Vec<String> = data
+            .keys
+            .iter()
+            .filter(|key| !self.keeps(key))
+            .cloned()
+            .collect();
+        if dropped.is_empty() {
+            return Ok(());
+        }
+        for n in 0..data.feature_count() {
+            for key in &dropped {
+                data.delete_property(n, key)?;
+            }
+        }
+        data.prune_keys();
+        Ok(())
+    }
+}
+
+/// Parses `key=value` into the key and the value
+pub fn parse_assignment(s: &str) -> Result<(String, String), String> {
+    match s.split_once('=') {
+        Some((key, value)) if !key.is_empty() => Ok((String::from(key), String::from(value))),
+        _ => Err(format!("expected KEY=VALUE, got {:?}", s)),
+    }
+}
+
+/// Parses a property value as JSON, e.g. `42`, `true` or `"42"`, or else takes it as a string
+pub fn parse_value(s: &str) -> JSONValue {
+    serde_json::from_str(s).unwrap_or_else(|_| JSONValue::from(s))
+}
+
+/// Applies `edit` to the features of `data` matching `filter`, or to every feature, and
+/// returns the number of features it changed
+///
+/// Keys no longer used by any feature are dropped from the key table.
+pub fn edit(data: &mut Data, edit: &Edit, filter: Option<&Expr>) -> Result<usize, &'static str> {
+    let matching: Vec<usize> = {
+        let features: &[geobuf_pb::data::Feature] = match data.data_type.as_ref() {
+            Some(geobuf_pb::data::Data_type::FeatureCollection(fc)) => &fc.features,
+            Some(geobuf_pb::data::Data_type::Feature(feature)) => std::slice::from_ref(feature),
+            _ => return Err("Data holds no features."),
+        };
+        features
+            .iter()
+            .enumerate()
+            .filter(|(_, feature)| match filter {
+                Some(filter) => filter.matches(data, feature),
+                None => true,
+            })
+            .map(|(n, _)| n)
+            .collect()
+    };
+
+    let mut changed = 0;
+    for n in matching {
+        let changes = match edit {
+            Edit::Set(key, value) => {
+                if data.get_property(n, key).as_ref() == Some(value) {
+                    false
+                } else {
+                    data.set_property(n, key, value)?;
+                    true
+                }
+            }
+            Edit::Delete(key) => data.delete_property(n, key)?,
+            Edit::Rename(old, new) => match data.get_property(n, old) {
+                Some(value) => {
+                    data.delete_property(n, old)?;
+                    data.set_property(n, new, &value)?;
+                    true
+                }
+                None => false,
+            },
+        };
+        if changes {
+            changed += 1;
+        }
+    }
+    data.prune_keys();
+    Ok(changed)
+}
@@ -0,0 +1,195 @@
+use geo::{BooleanOps, LineString, MultiPolygon, Polygon};
+use serde_json::Value as JSONValue;
+
+use super::check::{area, self_intersects};
+
+/// Repairs made by `repair`
+#[derive(Debug, Default, PartialEq)]
+pub struct Repaired {
+    /// Number of rings closed by repeating their first position
+    pub closed: usize,
+    /// Number of positions removed for repeating the previous one
+    pub duplicates: usize,
+    /// Number of self-intersecting polygons rebuilt from their rings
+    pub rebuilt: usize,
+    /// Number of rings left with fewer than 4 positions or without area and dropped, with their
+    /// polygon for outer rings
+    pub dropped: usize,
+    /// Number of features removed as nothing was left of their geometry
+    pub removed: usize,
+}
+
+/// Repairs the problems `check::check` finds in the geometries of a GeoJSON object: repeated
+/// consecutive positions are removed, rings are closed, self-intersecting polygons are rebuilt
+/// from their rings like `buffer(0)`, keeping the areas inside an odd number of rings, and
+/// other rings without area are dropped
+///
+/// Rebuilt polygons keep x and y only and may become MultiPolygons. Features of collections
+/// left without geometry are removed.
+pub fn repair(geojson: &mut JSONValue) -> Result<Repaired, &'static str> {
+    let mut repaired = Repaired::default();
+    if !repair_object(geojson, &mut repaired)? {
+        return Err("Nothing is left of the geometry.");
+    }
+    Ok(repaired)
+}
+
+/// Repairs a GeoJSON object in place, returning false if nothing is left of its geometry
+fn repair_object(geojson: &mut JSONValue, repaired: &mut Repaired) -> Result<bool, &'static str> {
+    match geojson["type"].as_str() {
+        Some("FeatureCollection") => {
+            let features = array_mut(&mut geojson["features"])
+                .map_err(|_| "FeatureCollection without features.")?;
+            let mut kept = Vec::with_capacity(features.len());
+            for mut feature in std::mem::take(features) {
+                if repair_object(&mut feature, repaired)? {
+                    kept.push(feature);
+                } else {
+                    repaired.removed += 1;
+                }
+            }
+            *features = kept;
+            Ok(true)
+        }
+        Some("Feature") if geojson["geometry"].is_null() => Ok(true),
+        Some("Feature") => repair_geometry(&mut geojson["geometry"], repaired),
+        Some(_) => repair_geometry(geojson, repaired),
+        None => Err("Missing GeoJSON type."),
+    }
+}
+
+/// Repairs a geometry in place, returning false if nothing is left of it
+fn repair_geometry(
+    geometry: &mut JSONValue,
+    repaired: &mut Repaired,
+) -> Result<bool, &'static str> {
+    // Changed coordinates would make bounding boxes stale
+    if let Some(object) = geometry.as_object_mut() {
+        object.remove("bbox");
+    }
+    match geometry["type"].as_str() {
+        Some("Point") | Some("MultiPoint") => Ok(true),
+        Some("LineString") => {
+            dedup(array_mut(&mut geometry["coordinates"])?, repaired)?;
+            Ok(true)
+        }
+        Some("MultiLineString") => {
+            for line in array_mut(&mut geometry["coordinates"])? {
+                dedup(array_mut(line)?, repaired)?;
+            }
+            Ok(true)
+        }
+        Some("Polygon") => {
+            let mut polygons = repair_polygon(geometry["coordinates"].take(), repaired)?;
+            match polygons.len() {
+                0 => return Ok(false),
+                1 => geometry["coordinates"] = polygons.pop().unwrap(),
+                _ => {
+                    geometry["type"] = JSONValue::from("MultiPolygon");
+                    geometry["coordinates"] = JSONValue::Array(polygons);
+                }
+            }
+            Ok(true)
+        }
+        Some("MultiPolygon") => {
+            let mut polygons = Vec::new();
+            for polygon in array_mut(&mut geometry["coordinates"])? {
+                polygons.extend(repair_polygon(polygon.take(), repaired)?);
+            }
+            let kept = !polygons.is_empty();
+            geometry["coordinates"] = JSONValue::Array(polygons);
+            Ok(kept)
+        }
+        Some("GeometryCollection") => {
+            let geometries = array_mut(&mut geometry["geometries"])?;
+            let mut kept = Vec::new();
+            for mut member in std::mem::take(geometries) {
+                if repair_geometry(&mut member, repaired)? {
+                    kept.push(member);
+                }
+            }
+            *geometries = kept;
+            Ok(!geometries.is_empty())
+        }
+        _ => Err("Unknown geometry type."),
+    }
+}
+
+/// Returns the polygons left from the rings of a polygon
+fn repair_polygon(
+    mut rings: JSONValue,
+    repaired: &mut Repaired,
+) -> Result<Vec<JSONValue>, &'static str> {
+    let mut kept = Vec::new();
+    for (n, ring) in array_mut(&mut rings)?.iter_mut().enumerate() {
+        let positions = array_mut(ring)?;
+        dedup(positions, repaired)?;
+        if let (Some(first), Some(last)) = (positions.first(), positions.last()) {
+            if xy(first) != xy(last) {
+                positions.push(first.clone());
+                repaired.closed += 1;
+            }
+        }
+        if positions.len() < 4 {
+            repaired.dropped += 1;
+            if n == 0 {
+                // Holes are dropped with their outer ring
+                return Ok(Vec::new());
+            }
+            continue;
+        }
+        kept.push(ring.take());
+    }
+    if kept.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let points = kept
+        .iter()
+        .map(|ring| ring.as_array().unwrap().iter().filter_map(xy).collect())
+        .collect::<Vec<Vec<(f64, f64)>>>();
+    let mut line_strings = points.iter().cloned().map(LineString::from);
+    let polygon = Polygon::new(line_strings.next().unwrap(), line_strings.collect());
+    if !self_intersects(&polygon) {
+        if area(&points[0]) == 0.0 {
+            repaired.dropped += 1;
+            return Ok(Vec::new());
+        }
+        let len = kept.len();
+        let mut points = points.iter();
+        kept.retain(|_| area(points.next().unwrap()) != 0.0);
+        repaired.dropped += len - kept.len();
+        return Ok(vec![JSONValue::Array(kept)]);
+    }
+    repaired.rebuilt += 1;
+    let rebuilt = polygon.union(&MultiPolygon::<f64>::new(Vec::new()));
+    Ok(rebuilt
+        .into_iter()
+        .map(|polygon| {
+            let rings = std::iter::once(polygon.exterior())
+                .chain(polygon.interiors())
+                .map(|ring| ring.coords().map(|coord| [coord.x, coord.y]).collect())
+                .collect::<Vec<Vec<[f64; 2]>>>();
+            serde_json::json!(rings)
+        })
+        .collect())
+}
+
+fn array_mut(coordinates: &mut JSONValue) -> Result<&mut Vec<JSONValue>, &'static str> {
+    coordinates.as_array_mut().ok_or("Invalid coordinates.")
+}
+
+fn xy(position: &JSONValue) -> Option<(f64, f64)> {
+    Some((position[0].as_f64()?, position[1].as_f64()?))
+}
+
+/// Removes positions repeating the previous one in x and y
+fn dedup(positions: &mut Vec<JSONValue>, repaired: &mut Repaired) -> Result<(), &'static str> {
+    if positions.iter().any(|position| xy(position).is_none()) {
+        return Err("Invalid position.");
+    }
+    let len = positions.len();
+    positions.dedup_by(|position, previous| xy(position) == xy(previous));
+    repaired.duplicates += len - positions.len();
+    Ok(())
+}
@@ -0,0 +1,30 @@
+use proj::Proj;
+use serde_json::Value as JSONValue;
+
+use geobuf::positions;
+
+/// Transforms the positions of `geojson` between coordinate reference systems known to PROJ,
+/// e.g. EPSG:4326 and EPSG:3857, in longitude, latitude order for geographic ones
+///
+/// Only x and y are transformed; z values are kept as they are.
+pub fn reproject(geojson: &mut JSONValue, from: &str, to: &str) -> Result<(), String> {
+    let proj = Proj::new_known_crs(from, to, None).map_err(|err| err.to_string())?;
+    positions::for_each_position_mut(geojson, &mut |position| {
+        let (x, y) = match (
+            position[0].as_f64(),
+            position.get(1).and_then(JSONValue::as_f64),
+        ) {
+            (Some(x), Some(y)) => (x, y),
+            _ => return Err("Invalid position."),
+        };
+        match proj.convert((x, y)) {
+            Ok((x, y)) if x.is_finite() && y.is_finite() => {
+                position[0] = JSONValue::from(x);
+                position[1] = JSONValue::from(y);
+                Ok(())
+            }
+            _ => Err("Position outside the area of use of the transformation."),
+        }
+    })
+    .map_err(String::from)
+}
@@ -0,0 +1,315 @@
+use serde_json::Value as JSONValue;
+
+/// Position with x, y and any further coordinates
+type Position = Vec<f64>;
+
+/// Changes made by `normalize`
+#[derive(Debug, Default, PartialEq)]
+pub struct Normalized {
+    /// Number of rings reversed to be counterclockwise, or clockwise for holes
+    pub rewound: usize,
+    /// Number of positions removed for repeating the previous one
+    pub duplicates: usize,
+    /// Number of geometries split at the antimeridian
+    pub split: usize,
+}
+
+/// Normalizes the geometries of a GeoJSON object as RFC 7946 requires: repeated consecutive
+/// positions are removed, lines and polygons crossing the antimeridian are split into
+/// MultiLineStrings and MultiPolygons, and rings are rewound to have counterclockwise outer
+/// rings and clockwise holes
+///
+/// A segment crosses the antimeridian when its longitudes are more than 180° apart. Polygons
+/// around a pole can't be split this way and come out wrong.
+pub fn normalize(geojson: &mut JSONValue) -> Result<Normalized, &'static str> {
+    let mut normalized = Normalized::default();
+    normalize_object(geojson, &mut normalized)?;
+    Ok(normalized)
+}
+
+fn normalize_object(
+    geojson: &mut JSONValue,
+    normalized: &mut Normalized,
+) -> Result<(), &'static str> {
+    // Changed coordinates would make bounding boxes stale
+    if let Some(object) = geojson.as_object_mut() {
+        object.remove("bbox");
+    }
+    match geojson["type"].as_str() {
+        Some("FeatureCollection") => match geojson["features"].as_array_mut() {
+            Some(features) => features
+                .iter_mut()
+                .try_for_each(|feature| normalize_object(feature, normalized)),
+            None => Err("FeatureCollection without features."),
+        },
+        Some("Feature") if geojson["geometry"].is_null() => Ok(()),
+        Some("Feature") => normalize_object(&mut geojson["geometry"], normalized),
+        Some("GeometryCollection") => match geojson["geometries"].as_array_mut() {
+            Some(geometries) => geometries
+                .iter_mut()
+                .try_for_each(|geometry| normalize_object(geometry, normalized)),
+            None => Err("GeometryCollection without geometries."),
+        },
+        Some(_) => normalize_geometry(geojson, normalized),
+        None => Err("Missing GeoJSON type."),
+    }
+}
+
+fn normalize_geometry(
+    geometry: &mut JSONValue,
+    normalized: &mut Normalized,
+) -> Result<(), &'static str> {
+    let coordinates = &geometry["coordinates"];
+    let (geometry_type, coordinates) = match geometry["type"].as_str() {
+        Some("Point") | Some("MultiPoint") => return Ok(()),
+        Some("LineString") => {
+            let mut lines = split_line(dedup(positions(coordinates)?, normalized));
+            if lines.len() == 1 {
+                ("LineString", serde_json::json!(lines.pop()))
+            } else {
+                normalized.split += 1;
+                ("MultiLineString", serde_json::json!(lines))
+            }
+        }
+        Some("MultiLineString") => {
+            let mut lines = Vec::new();
+            let mut split = false;
+            for line in nested(coordinates)? {
+                let parts = split_line(dedup(positions(line)?, normalized));
+                split |= parts.len() > 1;
+                lines.extend(parts);
+            }
+            if split {
+                normalized.split += 1;
+            }
+            ("MultiLineString", serde_json::json!(lines))
+        }
+        Some("Polygon") => {
+            let mut polygons = split_polygon(rings(coordinates, normalized)?);
+            for polygon in polygons.iter_mut() {
+                rewind(polygon, normalized);
+            }
+            if polygons.len() == 1 {
+                ("Polygon", serde_json::json!(polygons.pop()))
+            } else {
+                normalized.split += 1;
+                ("MultiPolygon", serde_json::json!(polygons))
+            }
+        }
+        Some("MultiPolygon") => {
+            let mut polygons = Vec::new();
+            let mut split = false;
+            for rings_coordinates in nested(coordinates)? {
+                let parts = split_polygon(rings(rings_coordinates, normalized)?);
+                split |= parts.len() > 1;
+                polygons.extend(parts);
+            }
+            for polygon in polygons.iter_mut() {
+                rewind(polygon, normalized);
+            }
+            if split {
+                normalized.split += 1;
+            }
+            ("MultiPolygon", serde_json::json!(polygons))
+        }
+        _ => return Err("Unknown geometry type."),
+    };
+    geometry["type"] = JSONValue::from(geometry_type);
+    geometry["coordinates"] = coordinates;
+    Ok(())
+}
+
+fn nested(coordinates: &JSONValue) -> Result<&Vec<JSONValue>, &'static str> {
+    coordinates.as_array().ok_or("Invalid coordinates.")
+}
+
+fn positions(coordinates: &JSONValue) -> Result<Vec<Position>, &'static str> {
+    nested(coordinates)?
+        .iter()
+        .map(|position| {
+            let position = position
+                .as_array()
+                .ok_or("Invalid position.")?
+                .iter()
+                .map(JSONValue::as_f64)
+                .collect::<Option<Position>>()
+                .ok_or("Invalid position.")?;
+            match position.len() {
+                0 | 1 => Err("Invalid position."),
+                _ => Ok(position),
+            }
+        })
+        .collect()
+}
+
+fn rings(
+    coordinates: &JSONValue,
+    normalized: &mut Normalized,
+) -> Result<Vec<Vec<Position>>, &'static str> {
+    nested(coordinates)?
+        .iter()
+        .map(|ring| Ok(dedup(positions(ring)?, normalized)))
+        .collect()
+}
+
+/// Removes positions repeating the previous one
+fn dedup(mut line: Vec<Position>, normalized: &mut Normalized) -> Vec<Position> {
+    let len = line.len();
+    line.dedup();
+    normalized.duplicates += len - line.len();
+    line
+}
+
+/// Returns the position where the segment from `a` to `b` reaches longitude `x`, with the
+/// other coordinates interpolated
+fn intersect(a: &[f64], b: &[f64], x: f64) -> Position {
+    let t = (x - a[0]) / (b[0] - a[0]);
+    let mut position: Position = a.iter().zip(b).map(|(a, b)| a + t * (b - a)).collect();
+    position[0] = x;
+    position
+}
+
+/// Splits a line at every segment crossing the antimeridian
+fn split_line(line: Vec<Position>) -> Vec<Vec<Position>> {
+    let mut parts = Vec::new();
+    let mut part: Vec<Position> = Vec::new();
+    for position in line {
+        if let Some(previous) = part.last() {
+            let dx = position[0] - previous[0];
+            if dx.abs() > 180.0 {
+                // A drop in longitude crosses 180° going east, and a rise -180° going west
+                let (edge, shift) = if dx < 0.0 {
+                    (180.0, 360.0)
+                } else {
+                    (-180.0, -360.0)
+                };
+                let mut unwrapped = position.clone();
+                unwrapped[0] += shift;
+                let crossing = intersect(previous, &unwrapped, edge);
+                let mut start = crossing.clone();
+                start[0] = -edge;
+                part.push(crossing);
+                parts.push(std::mem::replace(&mut part, vec![start]));
+            }
+        }
+        part.push(position);
+    }
+    if parts.is_empty() {
+        return vec![part];
+    }
+    parts.push(part);
+    for part in parts.iter_mut() {
+        // A line ending on the antimeridian repeats its crossing
+        part.dedup();
+    }
+    parts.into_iter().filter(|part| part.len() >= 2).collect()
+}
+
+/// Splits a polygon crossing the antimeridian into the polygons on each side of it
+fn split_polygon(rings: Vec<Vec<Position>>) -> Vec<Vec<Vec<Position>>> {
+    let crosses = |ring: &Vec<Position>| {
+        ring.windows(2)
+            .any(|segment| (segment[1][0] - segment[0][0]).abs() > 180.0)
+    };
+    if rings.is_empty() || !rings.iter().any(crosses) {
+        return vec![rings];
+    }
+    // Shifts longitudes by 360° so that no segment crosses the antimeridian, going past ±180°
+    let reference = rings[0].first().map_or(0.0, |position| position[0]);
+    let rings: Vec<Vec<Position>> = rings
+        .into_iter()
+        .map(|mut ring| {
+            let mut previous = reference;
+            for position in ring.iter_mut() {
+                position[0] += 360.0 * ((previous - position[0]) / 360.0).round();
+                previous = position[0];
+            }
+            ring
+        })
+        .collect();
+    let (min, max) = rings[0]
+        .iter()
+        .fold((f64::INFINITY, -f64::INFINITY), |(min, max), position| {
+            (min.min(position[0]), max.max(position[0]))
+        });
+
+    let mut polygons = Vec::new();
+    let first = ((min + 180.0) / 360.0).floor() as i64;
+    let last = ((max + 180.0) / 360.0).ceil() as i64;
+    for k in first..last {
+        let shift = 360.0 * k as f64;
+        let mut polygon = Vec::new();
+        for ring in &rings {
+            let mut clipped = clip_side(ring, shift - 180.0, true);
+            clipped = clip_side(&clipped, shift + 180.0, false);
+            clipped.dedup();
+            if let (Some(first), Some(last)) = (clipped.first(), clipped.last()) {
+                if first != last {
+                    clipped.push(first.clone());
+                }
+            }
+            if clipped.len() < 4 {
+                if polygon.is_empty() {
+                    // A polygon without outer ring on this side is dropped with its holes
+                    break;
+                }
+                continue;
+            }
+            for position in clipped.iter_mut() {
+                position[0] -= shift;
+            }
+            polygon.push(clipped);
+        }
+        if !polygon.is_empty() {
+            polygons.push(polygon);
+        }
+    }
+    polygons
+}
+
+/// Clips a ring to the longitudes east of `x` if `east`, or else west of it, with the
+/// Sutherland–Hodgman algorithm
+fn clip_side(ring: &[Position], x: f64, east: bool) -> Vec<Position> {
+    let inside = |position: &Position| {
+        if east {
+            position[0] >= x
+        } else {
+            position[0] <= x
+        }
+    };
+    // The closing position repeats the first one
+    let ring = match ring.split_last() {
+        Some((last, rest)) if Some(last) == rest.first() => rest,
+        _ => ring,
+    };
+    let mut clipped = Vec::new();
+    for (n, current) in ring.iter().enumerate() {
+        let previous = &ring[(n + ring.len() - 1) % ring.len()];
+        match (inside(previous), inside(current)) {
+            (true, true) => clipped.push(current.clone()),
+            (true, false) => clipped.push(intersect(previous, current, x)),
+            (false, true) => {
+                clipped.push(intersect(previous, current, x));
+                clipped.push(current.clone());
+            }
+            (false, false) => {}
+        }
+    }
+    clipped
+}
+
+/// Reverses the outer ring of a polygon if it is clockwise and its holes if they are
+/// counterclockwise
+fn rewind(polygon: &mut [Vec<Position>], normalized: &mut Normalized) {
+    for (n, ring) in polygon.iter_mut().enumerate() {
+        let area: f64 = ring
+            .windows(2)
+            .map(|segment| (segment[1][0] - segment[0][0]) * (segment[1][1] + segment[0][1]))
+            .sum();
+        // The sum is positive for clockwise rings, and zero for rings without area
+        if area != 0.0 && (area > 0.0) == (n == 0) {
+            ring.reverse();
+            normalized.rewound += 1;
+        }
+    }
+}
@@ -0,0 +1,36 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Returns the indexes kept when keeping each of `count` features with probability
+/// `fraction`, the same ones for the same `seed`, or different ones on every run without one
+pub fn sample(count: usize, fraction: f64, seed: Option<u64>) -> Vec<usize> {
+    let mut state = seed.unwrap_or_else(|| {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_nanos() as u64)
+    });
+    (0..count)
+        .filter(|_| {
+            // Uniform number in [0, 1) from the top 53 bits of the next number
+            let uniform = (split_mix64(&mut state) >> 11) as f64 / (1u64 << 53) as f64;
+            uniform < fraction
+        })
+        .collect()
+}
+
+/// Parses a fraction between 0 and 1
+pub fn parse_fraction(s: &str) -> Result<f64, String> {
+    match s.parse::<f64>() {
+        Ok(fraction) if (0.0..=1.0).contains(&fraction) => Ok(fraction),
+        _ => Err(format!("expected a number between 0 and 1, got {:?}", s)),
+    }
+}
+
+/// Advances `state` and returns the next number of the SplitMix64 generator, which is
+/// defined independently of any crate so that seeds give the same samples across versions
+fn split_mix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
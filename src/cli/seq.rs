@@ -0,0 +1,84 @@
+use std::io::{BufRead, Write};
+
+use serde_json::Value as JSONValue;
+
+use geobuf::geobuf_pb::{self, Data};
+
+/// Record separator starting each text of a GeoJSON Text Sequence (RFC 8142)
+const RS: u8 = 0x1e;
+
+/// Encodes a GeoJSON Text Sequence into a FeatureCollection, one record at a time
+///
+/// Records may start with a record separator or be plain newline-delimited JSON. Features
+/// are added as they are, the features of FeatureCollections are added one by one and
+/// geometries are wrapped into Features.
+pub fn encode<R: BufRead>(reader: R, precision: u32, dim: u32) -> Result<Data, String> {
+    let mut data = Data::new();
+    data.set_precision(precision);
+    data.set_dimensions(dim);
+    data.set_feature_collection(geobuf_pb::data::FeatureCollection::new());
+    read(reader, |geojson| push(&mut data, geojson))?;
+    Ok(data)
+}
+
+/// Parses the records of a GeoJSON Text Sequence and passes them to `f` as they are read
+pub fn read<R, F>(mut reader: R, mut f: F) -> Result<(), String>
+where
+    R: BufRead,
+    F: FnMut(JSONValue) -> Result<(), &'static str>,
+{
+    let mut line = Vec::new();
+    let mut line_number = 0;
+    loop {
+        line.clear();
+        line_number += 1;
+        match reader.read_until(b'\n', &mut line) {
+            Ok(0) => return Ok(()),
+            Ok(_) => {}
+            Err(_) => return Err(String::from("could not read input")),
+        }
+        let record = line
+            .iter()
+            .position(|b| *b != RS && !b.is_ascii_whitespace())
+            .map_or(&line[..0], |start| &line[start..]);
+        if record.is_empty() {
+            continue;
+        }
+        let geojson: JSONValue = serde_json::from_slice(record)
+            .map_err(|_| format!("line {}: could not parse geojson", line_number))?;
+        f(geojson).map_err(|err| format!("line {}: {}", line_number, err))?;
+    }
+}
+
+/// Adds a Feature, the features of a FeatureCollection, or a geometry wrapped into a Feature
+/// to the FeatureCollection `data`
+pub fn push(data: &mut Data, geojson: JSONValue) -> Result<(), &'static str> {
+    match geojson["type"].as_str() {
+        Some("Feature") => data.push_feature_json(&geojson),
+        Some("FeatureCollection") => match geojson["features"].as_array() {
+            Some(features) => features
+                .iter()
+                .try_for_each(|feature| data.push_feature_json(feature)),
+            None => Err("FeatureCollection without features."),
+        },
+        Some(_) => data.push_feature_json(&serde_json::json!({
+            "type": "Feature",
+            "properties": {},
+            "geometry": geojson,
+        })),
+        None => Err("Missing GeoJSON type."),
+    }
+}
+
+/// Writes the features of `data` as a GeoJSON Text Sequence, decoding one at a time
+pub fn write<W: Write>(data: &Data, writer: &mut W) -> Result<(), String> {
+    let features = geobuf::decode::Decoder::features(data)?;
+    for feature in features {
+        writer
+            .write_all(&[RS])
+            .and_then(|_| serde_json::to_writer(&mut *writer, &feature).map_err(Into::into))
+            .and_then(|_| writer.write_all(b"\n"))
+            .map_err(|_| String::from("could not write output"))?;
+    }
+    Ok(())
+}
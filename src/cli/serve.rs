@@ -0,0 +1,142 @@
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::time::Duration;
+
+use tracing::{debug, info};
+
+use geobuf::filter::Expr;
+use geobuf::geobuf_pb::Data;
+
+use super::bbox::parse_bbox;
+
+/// Time a client gets to send its request or take the response, so that a stalled client
+/// doesn't block the server, which handles one connection at a time
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Preview page drawing `/data.geojson` on an OpenStreetMap base map with Leaflet
+const PREVIEW: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>geobuf preview</title>
+<link rel="stylesheet" href="https://unpkg.com/leaflet@1.9.4/dist/leaflet.css">
+<script src="https://unpkg.com/leaflet@1.9.4/dist/leaflet.js"></script>
+<style>html, body, #map { height: 100%; margin: 0; }</style>
+</head>
+<body>
+<div id="map"></div>
+<script>
+const map = L.map('map').setView([0, 0], 2);
+L.tileLayer('https://tile.openstreetmap.org/{z}/{x}/{y}.png', {
+  maxZoom: 19,
+  attribution: '&copy; OpenStreetMap contributors'
+}).addTo(map);
+fetch('data.geojson' + location.search)
+  .then(response => response.json())
+  .then(geojson => {
+    const layer = L.geoJSON(geojson, {
+      onEachFeature: (feature, layer) => {
+        const properties = Object.assign({id: feature.id}, feature.properties);
+        layer.bindPopup('<pre>' + JSON.stringify(properties, null, 2)
+          .replace(/&/g, '&amp;').replace(/</g, '&lt;') + '</pre>');
+      }
+    }).addTo(map);
+    if (layer.getBounds().isValid()) {
+      map.fitBounds(layer.getBounds());
+    }
+  });
+</script>
+</body>
+</html>
+"#;
+
+/// Serves `data` over HTTP on `address` until the process is stopped
+///
+/// `/` returns a Leaflet preview page and `/data.geojson` the data as GeoJSON. Both take a
+/// `bbox=min_x,min_y,max_x,max_y` query parameter keeping only the features intersecting it.
+pub fn serve(data: &Data, address: &str) -> Result<(), String> {
+    let listener = TcpListener::bind(address)
+        .map_err(|err| format!("Could not listen on {}: {}", address, err))?;
+    info!("Serving on http://{}/", address);
+    // A failed connection only concerns its client
+    for stream in listener.incoming().flatten() {
+        let _ = handle(data, stream);
+    }
+    Ok(())
+}
+
+fn handle(data: &Data, mut stream: TcpStream) -> std::io::Result<()> {
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    stream.set_write_timeout(Some(TIMEOUT))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    // Headers are not needed, but are read so that clients see a complete exchange
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 2 {
+        header.clear();
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let (method, target) = match (parts.next(), parts.next()) {
+        (Some(method), Some(target)) => (method, target),
+        _ => return respond(&mut stream, "400 Bad Request", "text/plain", b"Bad request"),
+    };
+    if method != "GET" {
+        return respond(
+            &mut stream,
+            "405 Method Not Allowed",
+            "text/plain",
+            b"Only GET is supported",
+        );
+    }
+    debug!("{} {}", method, target);
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    match path {
+        "/" => respond(
+            &mut stream,
+            "200 OK",
+            "text/html; charset=utf-8",
+            PREVIEW.as_bytes(),
+        ),
+        "/data.geojson" => match geojson(data, query) {
+            Ok(body) => respond(&mut stream, "200 OK", "application/geo+json", &body),
+            Err(err) => respond(&mut stream, "400 Bad Request", "text/plain", err.as_bytes()),
+        },
+        _ => respond(&mut stream, "404 Not Found", "text/plain", b"Not found"),
+    }
+}
+
+/// Returns `data` as GeoJSON, keeping the features intersecting the `bbox` query parameter
+fn geojson(data: &Data, query: &str) -> Result<Vec<u8>, String> {
+    let bbox = query
+        .rsplit('&')
+        .find_map(|parameter| parameter.strip_prefix("bbox="))
+        .map(|bbox| parse_bbox(&bbox.replace("%2C", ",").replace("%2c", ",")))
+        .transpose()?;
+    let geojson = match bbox {
+        Some(bbox) => {
+            let filtered = geobuf::filter::filter(data, &Expr::Intersects(bbox))?;
+            geobuf::decode::Decoder::decode(&filtered)?
+        }
+        None => geobuf::decode::Decoder::decode(data)?,
+    };
+    serde_json::to_vec(&geojson).map_err(|err| err.to_string())
+}
+
+fn respond(
+    stream: &mut TcpStream,
+    status: &str,
+    content_type: &str,
+    body: &[u8],
+) -> std::io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nAccess-Control-Allow-Origin: *\r\nConnection: close\r\n\r\n",
+        status,
+        content_type,
+        body.len()
+    )?;
+    stream.write_all(body)?;
+    stream.flush()
+}
@@ -0,0 +1,165 @@
+use serde_json::{Map, Value as JSONValue};
+
+use ::shapefile::dbase::{FieldValue, Record};
+use ::shapefile::{Point, PointM, PointZ, PolygonRing, Shape};
+
+use geobuf::geobuf_pb::{self, Data};
+
+/// Encodes the shapes of a Shapefile and the attributes of its DBF file into a
+/// FeatureCollection, one feature at a time
+///
+/// Null shapes become features without geometry and M values are dropped. Z values are kept
+/// when `dim` is 3.
+pub fn encode(path: &str, precision: u32, dim: u32) -> Result<Data, String> {
+    let mut reader =
+        ::shapefile::Reader::from_path(path).map_err(|err| format!("could not open: {}", err))?;
+    let z = dim > 2;
+
+    let mut data = Data::new();
+    data.set_precision(precision);
+    data.set_dimensions(dim);
+    data.set_feature_collection(geobuf_pb::data::FeatureCollection::new());
+    for (n, shape_record) in reader.iter_shapes_and_records().enumerate() {
+        let (shape, record) = shape_record.map_err(|err| format!("shape {}: {}", n, err))?;
+        let feature = serde_json::json!({
+            "type": "Feature",
+            "properties": properties(record),
+            "geometry": geometry(&shape, z).map_err(|err| format!("shape {}: {}", n, err))?,
+        });
+        data.push_feature_json(&feature)
+            .map_err(|err| format!("shape {}: {}", n, err))?;
+    }
+    Ok(data)
+}
+
+/// Returns the non-empty attributes of a DBF record
+fn properties(record: Record) -> Map<String, JSONValue> {
+    let mut properties = Map::new();
+    for (name, value) in record {
+        let value = match value {
+            FieldValue::Character(Some(value)) => JSONValue::from(value.trim_end()),
+            FieldValue::Memo(value) => JSONValue::from(value),
+            FieldValue::Numeric(Some(value)) | FieldValue::Double(value) => number(value),
+            FieldValue::Currency(value) => number(value),
+            FieldValue::Float(Some(value)) => number(value as f64),
+            FieldValue::Integer(value) => JSONValue::from(value),
+            FieldValue::Logical(Some(value)) => JSONValue::from(value),
+            FieldValue::Date(Some(date)) => JSONValue::from(format!(
+                "{:04}-{:02}-{:02}",
+                date.year(),
+                date.month(),
+                date.day()
+            )),
+            _ => continue,
+        };
+        properties.insert(name, value);
+    }
+    properties
+}
+
+/// Returns a JSON integer for whole numbers, as DBF numeric fields hold both
+fn number(value: f64) -> JSONValue {
+    if value.fract() == 0.0 && value.abs() < i64::MAX as f64 {
+        JSONValue::from(value as i64)
+    } else {
+        JSONValue::from(value)
+    }
+}
+
+/// Returns the GeoJSON geometry of a shape
+fn geometry(shape: &Shape, z: bool) -> Result<JSONValue, &'static str> {
+    let geometry = match shape {
+        Shape::NullShape => JSONValue::Null,
+        Shape::Point(point) => point_json(point, z),
+        Shape::PointM(point) => point_json(point, z),
+        Shape::PointZ(point) => point_json(point, z),
+        Shape::Multipoint(multipoint) => multi_point_json(multipoint.points(), z),
+        Shape::MultipointM(multipoint) => multi_point_json(multipoint.points(), z),
+        Shape::MultipointZ(multipoint) => multi_point_json(multipoint.points(), z),
+        Shape::Polyline(polyline) => line_json(polyline.parts(), z),
+        Shape::PolylineM(polyline) => line_json(polyline.parts(), z),
+        Shape::PolylineZ(polyline) => line_json(polyline.parts(), z),
+        Shape::Polygon(polygon) => polygon_json(polygon.rings(), z),
+        Shape::PolygonM(polygon) => polygon_json(polygon.rings(), z),
+        Shape::PolygonZ(polygon) => polygon_json(polygon.rings(), z),
+        Shape::Multipatch(_) => return Err("Multipatch shapes are not supported."),
+    };
+    Ok(geometry)
+}
+
+/// A Shapefile point type
+trait Position {
+    /// Returns the coordinates of the point, with z if `z` is true
+    fn position(&self, z: bool) -> Vec<f64>;
+}
+
+impl Position for Point {
+    fn position(&self, z: bool) -> Vec<f64> {
+        if z {
+            vec![self.x, self.y, 0.0]
+        } else {
+            vec![self.x, self.y]
+        }
+    }
+}
+
+impl Position for PointM {
+    fn position(&self, z: bool) -> Vec<f64> {
+        if z {
+            vec![self.x, self.y, 0.0]
+        } else {
+            vec![self.x, self.y]
+        }
+    }
+}
+
+impl Position for PointZ {
+    fn position(&self, z: bool) -> Vec<f64> {
+        if z {
+            vec![self.x, self.y, self.z]
+        } else {
+            vec![self.x, self.y]
+        }
+    }
+}
+
+fn positions<P: Position>(points: &[P], z: bool) -> Vec<Vec<f64>> {
+    points.iter().map(|point| point.position(z)).collect()
+}
+
+fn point_json<P: Position>(point: &P, z: bool) -> JSONValue {
+    serde_json::json!({"type": "Point", "coordinates": point.position(z)})
+}
+
+fn multi_point_json<P: Position>(points: &[P], z: bool) -> JSONValue {
+    serde_json::json!({"type": "MultiPoint", "coordinates": positions(points, z)})
+}
+
+/// Returns a LineString for a single part and a MultiLineString otherwise
+fn line_json<P: Position>(parts: &[Vec<P>], z: bool) -> JSONValue {
+    match parts {
+        [part] => serde_json::json!({"type": "LineString", "coordinates": positions(part, z)}),
+        _ => {
+            let lines: Vec<_> = parts.iter().map(|part| positions(part, z)).collect();
+            serde_json::json!({"type": "MultiLineString", "coordinates": lines})
+        }
+    }
+}
+
+/// Returns a Polygon for a single outer ring and a MultiPolygon otherwise, attaching inner
+/// rings to the outer ring before them as Shapefiles list them in that order
+fn polygon_json<P: Position>(rings: &[PolygonRing<P>], z: bool) -> JSONValue {
+    let mut polygons: Vec<Vec<Vec<Vec<f64>>>> = Vec::new();
+    for ring in rings {
+        match ring {
+            PolygonRing::Inner(points) if !polygons.is_empty() => {
+                polygons.last_mut().unwrap().push(positions(points, z))
+            }
+            _ => polygons.push(vec![positions(ring.points(), z)]),
+        }
+    }
+    match polygons.len() {
+        1 => serde_json::json!({"type": "Polygon", "coordinates": polygons[0]}),
+        _ => serde_json::json!({"type": "MultiPolygon", "coordinates": polygons}),
+    }
+}
@@ -0,0 +1,77 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use serde_json::Value as JSONValue;
+
+use geobuf::encode::Encoder;
+
+/// File name of the report of skipped features
+pub const REPORT: &str = "skipped.json";
+
+/// Removes the features of a GeoJSON FeatureCollection that `Encoder::check_feature` rejects
+/// and returns an `{index, id, reason}` object for each of them
+pub fn skip_invalid(geojson: &mut JSONValue, precision: u32, dim: u32) -> Vec<JSONValue> {
+    if geojson["type"] != "FeatureCollection" {
+        return Vec::new();
+    }
+    let features = match geojson["features"].as_array_mut() {
+        Some(features) => features,
+        None => return Vec::new(),
+    };
+    let mut skipped = Vec::new();
+    let mut index = 0;
+    features.retain(|feature| {
+        let checked = Encoder::check_feature(feature, precision, dim);
+        if let Err(reason) = checked {
+            skipped.push(serde_json::json!({
+                "index": index,
+                "id": feature["id"],
+                "reason": reason,
+            }));
+        }
+        index += 1;
+        checked.is_ok()
+    });
+    skipped
+}
+
+/// Returns the path of the report of skipped features written next to `output`
+pub fn report_path(output: &str) -> PathBuf {
+    match output {
+        "-" => PathBuf::from(REPORT),
+        output => Path::new(output).with_file_name(REPORT),
+    }
+}
+
+/// Report of the features skipped in the files of a batch conversion, rewritten after each
+/// file so that it stays current when watching files
+pub struct BatchReport {
+    path: PathBuf,
+    files: Mutex<BTreeMap<PathBuf, Vec<JSONValue>>>,
+}
+
+impl BatchReport {
+    pub fn new(output_dir: &str) -> Self {
+        BatchReport {
+            path: Path::new(output_dir).join(REPORT),
+            files: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// Replaces the skipped features of `file`, adding its path to them, and writes the report
+    pub fn update(&self, file: &Path, mut skipped: Vec<JSONValue>) -> Result<(), String> {
+        for feature in skipped.iter_mut() {
+            feature["file"] = JSONValue::from(file.display().to_string());
+        }
+        let mut files = self.files.lock().unwrap();
+        files.insert(file.to_path_buf(), skipped);
+        let features: Vec<&JSONValue> = files.values().flatten().collect();
+        self.path
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| fs::write(&self.path, serde_json::to_vec_pretty(&features).unwrap()))
+            .map_err(|_| format!("could not write {}", self.path.display()))
+    }
+}
@@ -0,0 +1,143 @@
+use std::cmp::Ordering;
+use std::str::FromStr;
+
+use serde_json::Value as JSONValue;
+
+use geobuf::geobuf_pb::{self, Data};
+
+/// Order of sorted features
+#[derive(Clone, Debug, PartialEq)]
+pub enum SortBy {
+    /// Position of the bounding box center on a Hilbert curve over the extent of the data
+    Hilbert,
+    /// Value of a property
+    Property(String),
+}
+
+impl FromStr for SortBy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.strip_prefix("property:") {
+            Some(key) if !key.is_empty() => Ok(SortBy::Property(String::from(key))),
+            _ if s == "hilbert" => Ok(SortBy::Hilbert),
+            _ => Err(format!(
+                "unknown order {:?}, expected hilbert or property:NAME",
+                s
+            )),
+        }
+    }
+}
+
+/// Sorts the features of a FeatureCollection in place, keeping the order of equal features
+///
+/// Features without geometry, or without the property, go last. Properties sort booleans
+/// first, then numbers, strings and other values.
+pub fn sort(data: &mut Data, by: &SortBy) -> Result<(), &'static str> {
+    let count = match data.data_type.as_ref() {
+        Some(geobuf_pb::data::Data_type::FeatureCollection(fc)) => fc.features.len(),
+        _ => return Err("Data is not a FeatureCollection."),
+    };
+    let mut order: Vec<usize> = (0..count).collect();
+    match by {
+        SortBy::Hilbert => {
+            let indexes = hilbert_indexes(data);
+            order.sort_by_key(|n| indexes[*n]);
+        }
+        SortBy::Property(key) => {
+            let values: Vec<Option<JSONValue>> =
+                (0..count).map(|n| data.get_property(n, key)).collect();
+            order.sort_by(|a, b| compare(&values[*a], &values[*b]));
+        }
+    }
+    if let Some(geobuf_pb::data::Data_type::FeatureCollection(fc)) = data.data_type.as_mut() {
+        let mut features: Vec<Option<geobuf_pb::data::Feature>> =
+            fc.features.drain(..).map(Some).collect();
+        fc.features = order
+            .into_iter()
+            .map(|n| features[n].take().unwrap())
+            .collect();
+    }
+    Ok(())
+}
+
+/// Returns the Hilbert curve index of the bounding box center of every feature, on a 2^16 by
+/// 2^16 grid over the bounding box of `data`, or `u64::MAX` for features without geometry
+fn hilbert_indexes(data: &Data) -> Vec<u64> {
+    let features = match data.data_type.as_ref() {
+        Some(geobuf_pb::data::Data_type::FeatureCollection(fc)) => &fc.features,
+        _ => return Vec::new(),
+    };
+    let extent = match geobuf::bbox::bbox(data) {
+        Some(extent) => extent,
+        None => return vec![u64::MAX; features.len()],
+    };
+    let cells = (1u32 << 16) as f64;
+    let cell = |value: f64, min: f64, max: f64| {
+        if max > min {
+            ((value - min) / (max - min) * (cells - 1.0)).round() as u32
+        } else {
+            0
+        }
+    };
+    features
+        .iter()
+        .map(
+            |feature| match geobuf::bbox::geometry_bbox(data, &feature.geometry) {
+                Some(bbox) => hilbert_index(
+                    cell((bbox[0] + bbox[2]) / 2.0, extent[0], extent[2]),
+                    cell((bbox[1] + bbox[3]) / 2.0, extent[1], extent[3]),
+                ),
+                None => u64::MAX,
+            },
+        )
+        .collect()
+}
+
+/// Returns the distance along a Hilbert curve filling a 2^16 by 2^16 grid to cell `x`, `y`
+fn hilbert_index(mut x: u32, mut y: u32) -> u64 {
+    let n = 1u32 << 16;
+    let mut index = 0;
+    let mut s = n / 2;
+    while s > 0 {
+        let rx = ((x & s) > 0) as u32;
+        let ry = ((y & s) > 0) as u32;
+        index += s as u64 * s as u64 * ((3 * rx) ^ ry) as u64;
+        // Rotates the quadrant so that the curve is continuous
+        if ry == 0 {
+            if rx == 1 {
+                x = n - 1 - x;
+                y = n - 1 - y;
+            }
+            std::mem::swap(&mut x, &mut y);
+        }
+        s /= 2;
+    }
+    index
+}
+
+/// Compares property values, with missing values last
+fn compare(a: &Option<JSONValue>, b: &Option<JSONValue>) -> Ordering {
+    let rank = |value: &JSONValue| match value {
+        JSONValue::Bool(_) => 0,
+        JSONValue::Number(_) => 1,
+        JSONValue::String(_) => 2,
+        _ => 3,
+    };
+    match (a, b) {
+        (None, None) => Ordering::Equal,
+        (None, Some(_)) => Ordering::Greater,
+        (Some(_), None) => Ordering::Less,
+        (Some(a), Some(b)) => match (a, b) {
+            (JSONValue::Bool(a), JSONValue::Bool(b)) => a.cmp(b),
+            (JSONValue::Number(a), JSONValue::Number(b)) => a
+                .as_f64()
+                .partial_cmp(&b.as_f64())
+                .unwrap_or(Ordering::Equal),
+            (JSONValue::String(a), JSONValue::String(b)) => a.cmp(b),
+            _ => rank(a)
+                .cmp(&rank(b))
+                .then_with(|| a.to_string().cmp(&b.to_string())),
+        },
+    }
+}
@@ -0,0 +1,23 @@
+use std::path::MAIN_SEPARATOR;
+
+/// Returns the path of shard `index` out of `count`, inserting a zero-padded `-<index>`
+/// before the extensions of the `template` file name, e.g. `out.pbf.gz` becomes
+/// `out-07.pbf.gz` for the 8th of 12 shards
+pub fn shard_path(template: &str, index: usize, count: usize) -> String {
+    let width = count.saturating_sub(1).to_string().len();
+    let name_start = template
+        .rfind(['/', MAIN_SEPARATOR])
+        .map(|n| n + 1)
+        .unwrap_or(0);
+    let stem_end = template[name_start..]
+        .find('.')
+        .map(|n| n + name_start)
+        .unwrap_or(template.len());
+    format!(
+        "{}-{:0width$}{}",
+        &template[..stem_end],
+        index,
+        &template[stem_end..],
+        width = width
+    )
+}
@@ -0,0 +1,125 @@
+use protobuf::Message;
+
+use geobuf::geobuf_pb::{self, Data};
+
+/// Returns where the bytes and coordinates of `data` go, listing the `top` largest features
+pub fn stats(data: &Data, top: usize) -> String {
+    let dim = data.dimensions().max(1) as usize;
+    let total = data.compute_size();
+    let mut lines = vec![format!("Size: {} bytes", total)];
+
+    let features: Vec<&geobuf_pb::data::Feature> = match data.data_type.as_ref() {
+        Some(geobuf_pb::data::Data_type::FeatureCollection(fc)) => fc.features.iter().collect(),
+        Some(geobuf_pb::data::Data_type::Feature(feature)) => vec![feature],
+        Some(geobuf_pb::data::Data_type::Geometry(geometry)) => {
+            lines.push(format!("Positions: {}", positions(geometry, dim)));
+            return lines.join("\n");
+        }
+        Some(geobuf_pb::data::Data_type::Topology(topology)) => {
+            lines.push(format!("Arc positions: {}", topology.coords.len() / dim));
+            return lines.join("\n");
+        }
+        _ => return lines.join("\n"),
+    };
+
+    let mut vertices: Vec<u64> = features
+        .iter()
+        .map(|feature| positions(&feature.geometry, dim))
+        .collect();
+    let geometry_bytes: Vec<u64> = features
+        .iter()
+        .map(|feature| {
+            feature
+                .geometry
+                .as_ref()
+                .map_or(0, |geometry| field_size(geometry.compute_size()))
+        })
+        .collect();
+    let feature_bytes: Vec<u64> = features
+        .iter()
+        .map(|feature| feature.compute_size())
+        .collect();
+
+    lines.push(format!("Features: {}", features.len()));
+    lines.push(format!("Positions: {}", vertices.iter().sum::<u64>()));
+    lines.push(format!(
+        "Coordinates: {}",
+        vertices.iter().sum::<u64>() * dim as u64
+    ));
+    if !vertices.is_empty() {
+        vertices.sort_unstable();
+        let percentile = |p: usize| vertices[(vertices.len() - 1) * p / 100];
+        lines.push(format!(
+            "Positions per feature: min {}, median {}, p90 {}, p99 {}, max {}, mean {:.1}",
+            vertices[0],
+            percentile(50),
+            percentile(90),
+            percentile(99),
+            vertices[vertices.len() - 1],
+            vertices.iter().sum::<u64>() as f64 / vertices.len() as f64
+        ));
+    }
+
+    let geometry: u64 = geometry_bytes.iter().sum();
+    let properties: u64 = feature_bytes.iter().sum::<u64>() - geometry;
+    let mut tables = Data::new();
+    tables.keys = data.keys.clone();
+    let keys = tables.compute_size();
+    tables.keys.clear();
+    tables.strings = data.strings.clone();
+    let strings = tables.compute_size();
+    let other = total.saturating_sub(geometry + properties + keys + strings);
+    lines.push(String::from("Bytes:"));
+    for (name, bytes) in [
+        ("geometry", geometry),
+        ("properties and ids", properties),
+        ("keys", keys),
+        ("shared strings", strings),
+        ("other", other),
+    ] {
+        lines.push(format!(
+            "  {}: {} ({:.1}%)",
+            name,
+            bytes,
+            bytes as f64 * 100.0 / total.max(1) as f64
+        ));
+    }
+
+    let mut largest: Vec<usize> = (0..features.len()).collect();
+    largest.sort_by(|a, b| feature_bytes[*b].cmp(&feature_bytes[*a]));
+    largest.truncate(top);
+    if !largest.is_empty() {
+        lines.push(String::from("Largest features:"));
+        for n in largest {
+            let id = match features[n].id_type.as_ref() {
+                Some(geobuf_pb::data::feature::Id_type::Id(id)) => format!(" (id {})", id),
+                Some(geobuf_pb::data::feature::Id_type::IntId(id)) => format!(" (id {})", id),
+                _ => String::new(),
+            };
+            lines.push(format!(
+                "  #{}{}: {} bytes, {} in geometry, {} positions",
+                n,
+                id,
+                feature_bytes[n],
+                geometry_bytes[n],
+                positions(&features[n].geometry, dim)
+            ));
+        }
+    }
+    lines.join("\n")
+}
+
+/// Returns the number of positions in a geometry and its child geometries
+fn positions(geometry: &geobuf_pb::data::Geometry, dim: usize) -> u64 {
+    (geometry.coords.len() / dim) as u64
+        + geometry
+            .geometries
+            .iter()
+            .map(|child| positions(child, dim))
+            .sum::<u64>()
+}
+
+/// Returns the size of a length-delimited field holding `len` bytes, with a one byte tag
+fn field_size(len: u64) -> u64 {
+    1 + protobuf::rt::compute_raw_varint64_size(len) + len
+}
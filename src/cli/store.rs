@@ -0,0 +1,135 @@
+use std::io::{self, Read, Write};
+use std::sync::Arc;
+
+use futures::stream::{BoxStream, StreamExt};
+use object_store::aws::AmazonS3Builder;
+use object_store::buffered::BufWriter;
+use object_store::gcp::GoogleCloudStorageBuilder;
+use object_store::path::Path;
+use object_store::ObjectStore;
+use tokio::io::AsyncWriteExt;
+use tokio::runtime::Runtime;
+
+use super::error::{fail, ErrorKind};
+
+/// Returns the store of an `s3://bucket/key` or `gs://bucket/key` URI and the path of the
+/// object in it
+///
+/// Credentials and regions come from the environment, e.g. `AWS_ACCESS_KEY_ID`,
+/// `AWS_REGION`, `AWS_ENDPOINT` or `GOOGLE_SERVICE_ACCOUNT`.
+fn parse(uri: &str) -> Result<(Arc<dyn ObjectStore>, Path), String> {
+    let key = match uri.splitn(4, '/').nth(3) {
+        Some(key) if !key.is_empty() => key,
+        _ => return Err(String::from("missing object key")),
+    };
+    let store: Arc<dyn ObjectStore> = if uri.starts_with("s3://") {
+        Arc::new(
+            AmazonS3Builder::from_env()
+                .with_url(uri)
+                .build()
+                .map_err(|err| err.to_string())?,
+        )
+    } else {
+        Arc::new(
+            GoogleCloudStorageBuilder::from_env()
+                .with_url(uri)
+                .build()
+                .map_err(|err| err.to_string())?,
+        )
+    };
+    let path = Path::parse(key).map_err(|err| err.to_string())?;
+    Ok((store, path))
+}
+
+fn runtime() -> Result<Runtime, String> {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .map_err(|err| err.to_string())
+}
+
+/// Streams the contents of an object, one chunk at a time
+pub struct ObjectReader {
+    runtime: Runtime,
+    chunks: BoxStream<'static, object_store::Result<Vec<u8>>>,
+    chunk: io::Cursor<Vec<u8>>,
+}
+
+/// Starts reading the object at `uri`, returning the reader and the size of the object
+pub fn open(uri: &str) -> Result<(ObjectReader, u64), String> {
+    let (store, path) = parse(uri)?;
+    let runtime = runtime()?;
+    let result = runtime
+        .block_on(store.get(&path))
+        .map_err(|err| err.to_string())?;
+    let size = result.meta.size;
+    let chunks = result
+        .into_stream()
+        .map(|chunk| chunk.map(Vec::from))
+        .boxed();
+    let reader = ObjectReader {
+        runtime,
+        chunks,
+        chunk: io::Cursor::new(Vec::new()),
+    };
+    Ok((reader, size))
+}
+
+impl Read for ObjectReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.chunk.position() == self.chunk.get_ref().len() as u64 {
+            match self.runtime.block_on(self.chunks.next()) {
+                Some(Ok(chunk)) => self.chunk = io::Cursor::new(chunk),
+                Some(Err(err)) => return Err(io::Error::other(err)),
+                None => return Ok(0),
+            }
+        }
+        self.chunk.read(buf)
+    }
+}
+
+/// Uploads an object as it is written, in parts once it outgrows a single request
+///
+/// The upload completes when the writer is dropped, exiting on failure. Errors exit without
+/// dropping outputs, so that incomplete objects are never created.
+pub struct ObjectWriter {
+    uri: String,
+    runtime: Runtime,
+    writer: BufWriter,
+}
+
+/// Returns a writer uploading to the object at `uri`
+pub fn create(uri: &str) -> Result<ObjectWriter, String> {
+    let (store, path) = parse(uri)?;
+    Ok(ObjectWriter {
+        uri: String::from(uri),
+        runtime: runtime()?,
+        writer: BufWriter::new(store, path),
+    })
+}
+
+impl Write for ObjectWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.runtime.block_on(self.writer.write(buf))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.runtime.block_on(self.writer.flush())
+    }
+}
+
+impl Drop for ObjectWriter {
+    fn drop(&mut self) {
+        if std::thread::panicking() {
+            let _ = self.runtime.block_on(self.writer.abort());
+            return;
+        }
+        if let Err(err) = self.runtime.block_on(self.writer.shutdown()) {
+            fail(
+                ErrorKind::Other,
+                Some(&self.uri),
+                &format!("Could not write {}: {}", self.uri, err),
+            );
+        }
+    }
+}
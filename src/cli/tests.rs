@@ -0,0 +1,675 @@
+use serde_json::Value as JSONValue;
+
+use geobuf::decode::Decoder;
+use geobuf::encode::Encoder;
+
+#[cfg(feature = "geo")]
+use super::check::{area, check};
+use super::clip::clip_geometry;
+use super::compact::compact;
+use super::dedupe::{dedupe, feature_key};
+use super::mvt::{to_mvt, Tile};
+use super::prop::{edit, parse_assignment, parse_value, Edit, PropFilter};
+#[cfg(feature = "geo")]
+use super::repair::{repair, Repaired};
+use super::rfc7946::{normalize, Normalized};
+use super::sort::{sort, SortBy};
+use super::tile;
+
+const DIM: u32 = 2;
+const PRECISION: u32 = 6;
+
+/// Returns the fields of a protobuf message in order, with the value of varint and 64-bit
+/// fields and the payload of length-delimited ones
+fn fields(mut bytes: &[u8]) -> Vec<(u32, u64, &[u8])> {
+    let mut fields = Vec::new();
+    while !bytes.is_empty() {
+        let key = varint(&mut bytes);
+        let field = (key >> 3) as u32;
+        match key & 7 {
+            0 => fields.push((field, varint(&mut bytes), &[][..])),
+            1 => {
+                let (value, rest) = bytes.split_at(8);
+                fields.push((
+                    field,
+                    u64::from_le_bytes(value.try_into().unwrap()),
+                    &[][..],
+                ));
+                bytes = rest;
+            }
+            2 => {
+                let len = varint(&mut bytes) as usize;
+                let (value, rest) = bytes.split_at(len);
+                fields.push((field, 0, value));
+                bytes = rest;
+            }
+            wire_type => panic!("Unexpected wire type {}", wire_type),
+        }
+    }
+    fields
+}
+
+fn varint(bytes: &mut &[u8]) -> u64 {
+    let mut value = 0;
+    for shift in (0..64).step_by(7) {
+        let byte = bytes[0];
+        *bytes = &bytes[1..];
+        value |= ((byte & 0x7f) as u64) << shift;
+        if byte < 0x80 {
+            break;
+        }
+    }
+    value
+}
+
+/// Returns the parts of MVT geometry commands in tile coordinates, one per MoveTo
+fn mvt_parts(mut commands: &[u8]) -> Vec<Vec<[i64; 2]>> {
+    let mut parts: Vec<Vec<[i64; 2]>> = Vec::new();
+    let mut cursor = [0, 0];
+    let unzigzag = |value: u64| (value >> 1) as i64 ^ -((value & 1) as i64);
+    while !commands.is_empty() {
+        let command = varint(&mut commands);
+        for _ in 0..command >> 3 {
+            match command & 7 {
+                1 | 2 => {
+                    cursor[0] += unzigzag(varint(&mut commands));
+                    cursor[1] += unzigzag(varint(&mut commands));
+                    if command & 7 == 1 {
+                        parts.push(Vec::new());
+                    }
+                    parts.last_mut().unwrap().push(cursor);
+                }
+                _ => {}
+            }
+        }
+    }
+    parts
+}
+
+/// Returns the value of an MVT Value message as JSON
+fn mvt_value(bytes: &[u8]) -> JSONValue {
+    let (field, value, payload) = fields(bytes)[0];
+    match field {
+        1 => JSONValue::from(std::str::from_utf8(payload).unwrap()),
+        3 => JSONValue::from(f64::from_bits(value)),
+        5 => JSONValue::from(value),
+        6 => JSONValue::from((value >> 1) as i64 ^ -((value & 1) as i64)),
+        7 => JSONValue::from(value != 0),
+        _ => panic!("Unexpected value field {}", field),
+    }
+}
+
+#[test]
+fn test_to_mvt() {
+    // Tile 1/0/0 spans longitudes -180 to 0 and latitudes 0 to 85.05, so 66.51326 is at its
+    // middle and the equator at its bottom edge
+    let lat = 66.51326044311186;
+    let geojson = serde_json::json!({"type": "FeatureCollection", "features": [
+        {"type": "Feature", "id": 7, "properties": {"name": "point", "rank": -2},
+            "geometry": {"type": "Point", "coordinates": [-90.0, lat]}},
+        {"type": "Feature", "properties": {"name": "line", "tags": ["a", "b"]},
+            "geometry": {"type": "LineString", "coordinates": [[-90.0, lat], [90.0, lat]]}},
+        {"type": "Feature", "properties": {"name": "polygon", "area": 0.5},
+            "geometry": {"type": "Polygon", "coordinates": [
+                [[-135.0, 0.0], [-45.0, 0.0], [-45.0, lat], [-135.0, lat], [-135.0, 0.0]]
+            ]}},
+        {"type": "Feature", "properties": {"name": "outside"},
+            "geometry": {"type": "Point", "coordinates": [90.0, lat]}}
+    ]});
+    let data = Encoder::encode(&geojson, PRECISION, DIM).unwrap();
+    let mvt = to_mvt(&data, Tile { z: 1, x: 0, y: 0 }, 4096, 64, "layer").unwrap();
+
+    let tile = fields(&mvt);
+    assert_eq!(tile.len(), 1);
+    assert_eq!(tile[0].0, 3);
+    let layer = fields(tile[0].2);
+    let field = |number: u32| layer.iter().filter(move |field| field.0 == number);
+    assert_eq!(field(15).next().unwrap().1, 2);
+    assert_eq!(field(1).next().unwrap().2, b"layer");
+    assert_eq!(field(5).next().unwrap().1, 4096);
+    let keys: Vec<&str> = field(3)
+        .map(|key| std::str::from_utf8(key.2).unwrap())
+        .collect();
+    let values: Vec<JSONValue> = field(4).map(|value| mvt_value(value.2)).collect();
+
+    let mut features = Vec::new();
+    for feature in field(2) {
+        let mut id = None;
+        let mut properties = serde_json::Map::new();
+        let mut geometry_type = 0;
+        let mut parts = Vec::new();
+        for (number, value, payload) in fields(feature.2) {
+            match number {
+                1 => id = Some(value),
+                2 => {
+                    let mut tags = payload;
+                    while !tags.is_empty() {
+                        let key = keys[varint(&mut tags) as usize];
+                        let value = &values[varint(&mut tags) as usize];
+                        properties.insert(String::from(key), value.clone());
+                    }
+                }
+                3 => geometry_type = value,
+                4 => parts = mvt_parts(payload),
+                _ => panic!("Unexpected feature field {}", number),
+            }
+        }
+        features.push((id, JSONValue::Object(properties), geometry_type, parts));
+    }
+
+    assert_eq!(features.len(), 3);
+    assert_eq!(
+        features[0],
+        (
+            Some(7),
+            serde_json::json!({"name": "point", "rank": -2}),
+            1,
+            vec![vec![[2048, 2048]]]
+        )
+    );
+    // Lines are clipped to the tile grown by the buffer
+    assert_eq!(
+        features[1],
+        (
+            None,
+            serde_json::json!({"name": "line", "tags": "[\"a\",\"b\"]"}),
+            2,
+            vec![vec![[2048, 2048], [4160, 2048]]]
+        )
+    );
+    // Outer rings are clockwise in tile coordinates, and ClosePath ends them
+    assert_eq!(
+        features[2],
+        (
+            None,
+            serde_json::json!({"name": "polygon", "area": 0.5}),
+            3,
+            vec![vec![[1024, 2048], [3072, 2048], [3072, 4096], [1024, 4096]]]
+        )
+    );
+}
+
+/// Returns the names of the features of a Geobuf FeatureCollection in order
+fn names(data: &geobuf::geobuf_pb::Data) -> Vec<JSONValue> {
+    Decoder::features(data)
+        .unwrap()
+        .map(|feature| feature["properties"]["name"].clone())
+        .collect()
+}
+
+#[test]
+fn test_sort() {
+    assert_eq!("hilbert".parse(), Ok(SortBy::Hilbert));
+    assert_eq!(
+        "property:rank".parse(),
+        Ok(SortBy::Property(String::from("rank")))
+    );
+    assert!("property:".parse::<SortBy>().is_err());
+    assert!("name".parse::<SortBy>().is_err());
+
+    let point = |name: &str, x: f64, y: f64, rank: JSONValue| {
+        serde_json::json!({"type": "Feature", "properties": {"name": name, "rank": rank},
+            "geometry": {"type": "Point", "coordinates": [x, y]}})
+    };
+    let geojson = serde_json::json!({"type": "FeatureCollection", "features": [
+        point("ne", 10.0, 10.0, JSONValue::from(2)),
+        {"type": "Feature", "properties": {"name": "none"},
+            "geometry": {"type": "MultiPoint", "coordinates": []}},
+        point("se", 10.0, 0.0, JSONValue::from("a")),
+        point("sw", 0.0, 0.0, JSONValue::from(2)),
+        point("nw", 0.0, 10.0, JSONValue::from(true)),
+    ]});
+
+    // The curve starts at the minimum corner and ends next to it, and features without
+    // positions go last
+    let mut data = Encoder::encode(&geojson, PRECISION, DIM).unwrap();
+    sort(&mut data, &SortBy::Hilbert).unwrap();
+    assert_eq!(names(&data), ["sw", "nw", "ne", "se", "none"]);
+
+    // Booleans go before numbers and strings, equal values keep their order and missing
+    // values go last
+    let mut data = Encoder::encode(&geojson, PRECISION, DIM).unwrap();
+    sort(&mut data, &SortBy::Property(String::from("rank"))).unwrap();
+    assert_eq!(names(&data), ["nw", "ne", "sw", "se", "none"]);
+
+    let mut feature = Encoder::encode(&geojson["features"][0], PRECISION, DIM).unwrap();
+    assert!(sort(&mut feature, &SortBy::Hilbert).is_err());
+}
+
+#[test]
+fn test_clip_geometry() {
+    let bbox = [0.0, 0.0, 10.0, 10.0];
+    let clip = |geometry: JSONValue| clip_geometry(&geometry, &bbox).unwrap();
+
+    let inside = serde_json::json!({"type": "LineString", "coordinates": [[1.0, 1.0, 5.0], [2.0, 2.0, 5.0]]});
+    assert_eq!(clip(inside.clone()), Some(inside));
+    assert_eq!(
+        clip(serde_json::json!({"type": "Point", "coordinates": [11.0, 5.0]})),
+        None
+    );
+
+    // Lines are cut at the edges, and a line leaving the box and coming back is split
+    assert_eq!(
+        clip(serde_json::json!({"type": "LineString", "coordinates": [
+            [-5.0, 5.0], [5.0, 5.0], [5.0, 15.0], [8.0, 15.0], [8.0, 5.0]
+        ]})),
+        Some(
+            serde_json::json!({"type": "MultiLineString", "coordinates": [
+                [[0.0, 5.0], [5.0, 5.0], [5.0, 10.0]], [[8.0, 10.0], [8.0, 5.0]]
+            ]})
+        )
+    );
+    assert_eq!(
+        clip(serde_json::json!({"type": "LineString", "coordinates": [[-5.0, 5.0], [15.0, 5.0]]})),
+        Some(serde_json::json!({"type": "LineString", "coordinates": [[0.0, 5.0], [10.0, 5.0]]}))
+    );
+
+    // Rings are cut along the edges and closed, and holes left outside are dropped
+    assert_eq!(
+        clip(serde_json::json!({"type": "Polygon", "coordinates": [
+            [[5.0, 5.0], [15.0, 5.0], [15.0, 15.0], [5.0, 15.0], [5.0, 5.0]],
+            [[12.0, 12.0], [12.0, 13.0], [13.0, 13.0], [12.0, 12.0]]
+        ]})),
+        Some(serde_json::json!({"type": "Polygon", "coordinates": [
+            [[5.0, 5.0], [10.0, 5.0], [10.0, 10.0], [5.0, 10.0], [5.0, 5.0]]
+        ]}))
+    );
+    assert_eq!(
+        clip(serde_json::json!({"type": "MultiPolygon", "coordinates": [
+            [[[-4.0, -4.0], [-2.0, -4.0], [-2.0, -2.0], [-4.0, -4.0]]],
+            [[[8.0, 8.0], [12.0, 8.0], [12.0, 12.0], [8.0, 8.0]]]
+        ]})),
+        Some(serde_json::json!({"type": "MultiPolygon", "coordinates": [
+            [[[8.0, 8.0], [10.0, 8.0], [10.0, 10.0], [8.0, 8.0]]]
+        ]}))
+    );
+}
+
+#[test]
+fn test_tile() {
+    let bounds = tile::bounds(Tile { z: 1, x: 1, y: 0 }, 0.0);
+    assert_eq!(bounds[0], 0.0);
+    assert_eq!(bounds[1], 0.0);
+    assert_eq!(bounds[2], 180.0);
+    assert!((bounds[3] - 85.051_128_78).abs() < 1e-8);
+    let buffered = tile::bounds(Tile { z: 1, x: 1, y: 0 }, 0.5);
+    assert_eq!((buffered[0], buffered[2]), (-90.0, 270.0));
+
+    let features = tile::into_features(serde_json::json!({"type": "FeatureCollection", "features": [
+        {"type": "Feature", "id": 1, "properties": {"name": "crossing"}, "bbox": [-10.0, 10.0, 10.0, 10.0],
+            "geometry": {"type": "LineString", "coordinates": [[-10.0, 10.0], [10.0, 10.0]]}},
+        {"type": "Feature", "properties": {"name": "outside"},
+            "geometry": {"type": "Point", "coordinates": [-10.0, 10.0]}},
+        {"type": "Feature", "properties": {"name": "empty"}, "geometry": null}
+    ]}))
+    .unwrap();
+    assert_eq!(features.len(), 3);
+
+    // Clipped features keep their other members but not their bounding box
+    assert_eq!(
+        tile::clip(&features, &bounds).unwrap(),
+        [
+            serde_json::json!({"type": "Feature", "id": 1, "properties": {"name": "crossing"},
+            "geometry": {"type": "LineString", "coordinates": [[0.0, 10.0], [10.0, 10.0]]}})
+        ]
+    );
+
+    let mut tiles = Vec::new();
+    let count = tile::pyramid(features, 1, 2, 0.0, &mut |tile, features| {
+        let names: Vec<&str> = features
+            .iter()
+            .map(|feature| feature["properties"]["name"].as_str().unwrap())
+            .collect();
+        tiles.push(format!(
+            "{}/{}/{} {}",
+            tile.z,
+            tile.x,
+            tile.y,
+            names.join(",")
+        ));
+        Ok(())
+    })
+    .unwrap();
+    tiles.sort();
+    assert_eq!(count, 4);
+    assert_eq!(
+        tiles,
+        [
+            "1/0/0 crossing,outside",
+            "1/1/0 crossing",
+            "2/1/1 crossing,outside",
+            "2/2/1 crossing"
+        ]
+    );
+}
+
+#[test]
+fn test_rfc7946() {
+    // Clockwise outer ring with a repeated position and a counterclockwise hole
+    let mut geojson = serde_json::json!({"type": "Feature", "properties": {}, "bbox": [0.0, 0.0, 10.0, 10.0],
+    "geometry": {"type": "Polygon", "coordinates": [
+        [[0.0, 0.0], [0.0, 10.0], [0.0, 10.0], [10.0, 10.0], [10.0, 0.0], [0.0, 0.0]],
+        [[2.0, 2.0], [4.0, 2.0], [4.0, 4.0], [2.0, 2.0]]
+    ]}});
+    let normalized = normalize(&mut geojson).unwrap();
+    assert_eq!(
+        normalized,
+        Normalized {
+            rewound: 2,
+            duplicates: 1,
+            split: 0
+        }
+    );
+    assert_eq!(
+        geojson,
+        serde_json::json!({"type": "Feature", "properties": {},
+        "geometry": {"type": "Polygon", "coordinates": [
+            [[0.0, 0.0], [10.0, 0.0], [10.0, 10.0], [0.0, 10.0], [0.0, 0.0]],
+            [[2.0, 2.0], [4.0, 4.0], [4.0, 2.0], [2.0, 2.0]]
+        ]}})
+    );
+    // Normalized geometries are left as they are
+    assert_eq!(normalize(&mut geojson).unwrap(), Normalized::default());
+
+    // Lines are split where they cross the antimeridian, with other coordinates interpolated
+    let mut geojson = serde_json::json!({"type": "LineString", "coordinates": [
+        [170.0, 0.0, 100.0], [-170.0, 10.0, 200.0], [-160.0, 10.0, 200.0]
+    ]});
+    assert_eq!(normalize(&mut geojson).unwrap().split, 1);
+    assert_eq!(
+        geojson,
+        serde_json::json!({"type": "MultiLineString", "coordinates": [
+            [[170.0, 0.0, 100.0], [180.0, 5.0, 150.0]],
+            [[-180.0, 5.0, 150.0], [-170.0, 10.0, 200.0], [-160.0, 10.0, 200.0]]
+        ]})
+    );
+
+    // Polygons are split into a polygon on each side
+    let mut geojson = serde_json::json!({"type": "Polygon", "coordinates": [
+        [[170.0, -10.0], [-170.0, -10.0], [-170.0, 10.0], [170.0, 10.0], [170.0, -10.0]]
+    ]});
+    assert_eq!(normalize(&mut geojson).unwrap().split, 1);
+    assert_eq!(
+        geojson,
+        serde_json::json!({"type": "MultiPolygon", "coordinates": [
+            [[[170.0, -10.0], [180.0, -10.0], [180.0, 10.0], [170.0, 10.0], [170.0, -10.0]]],
+            [[[-180.0, -10.0], [-170.0, -10.0], [-170.0, 10.0], [-180.0, 10.0], [-180.0, -10.0]]]
+        ]})
+    );
+
+    let mut invalid = serde_json::json!({"type": "LineString", "coordinates": [[1.0]]});
+    assert!(normalize(&mut invalid).is_err());
+}
+
+#[cfg(feature = "geo")]
+#[test]
+fn test_repair() {
+    let mut geojson = serde_json::json!({"type": "FeatureCollection", "features": [
+        {"type": "Feature", "properties": {"name": "unclosed"},
+            "geometry": {"type": "Polygon", "coordinates": [
+                [[0.0, 0.0], [10.0, 0.0], [10.0, 0.0], [10.0, 10.0], [0.0, 10.0]]
+            ]}},
+        {"type": "Feature", "properties": {"name": "bowtie"},
+            "geometry": {"type": "Polygon", "coordinates": [
+                [[0.0, 0.0], [10.0, 10.0], [10.0, 0.0], [0.0, 10.0], [0.0, 0.0]]
+            ]}},
+        {"type": "Feature", "properties": {"name": "flat"},
+            "geometry": {"type": "Polygon", "coordinates": [
+                [[0.0, 0.0], [1.0, 1.0], [2.0, 2.0], [0.0, 0.0]]
+            ]}},
+        {"type": "Feature", "properties": {"name": "line"},
+            "geometry": {"type": "LineString", "coordinates": [
+                [0.0, 0.0, 1.0], [0.0, 0.0, 2.0], [1.0, 1.0, 3.0]
+            ]}}
+    ]});
+    assert_eq!(check(&geojson).unwrap().len(), 4);
+
+    let repaired = repair(&mut geojson).unwrap();
+    assert_eq!(
+        repaired,
+        Repaired {
+            closed: 1,
+            duplicates: 2,
+            rebuilt: 1,
+            dropped: 1,
+            removed: 1
+        }
+    );
+    assert!(check(&geojson).unwrap().is_empty());
+
+    let features = geojson["features"].as_array().unwrap();
+    assert_eq!(features.len(), 3);
+    assert_eq!(
+        features[0]["geometry"]["coordinates"],
+        serde_json::json!([[
+            [0.0, 0.0],
+            [10.0, 0.0],
+            [10.0, 10.0],
+            [0.0, 10.0],
+            [0.0, 0.0]
+        ]])
+    );
+    // The bowtie becomes its two triangles
+    let bowtie = &features[1]["geometry"];
+    assert_eq!(bowtie["type"], "MultiPolygon");
+    let areas: Vec<f64> = bowtie["coordinates"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|polygon| {
+            let ring: Vec<(f64, f64)> = polygon[0]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|position| (position[0].as_f64().unwrap(), position[1].as_f64().unwrap()))
+                .collect();
+            area(&ring)
+        })
+        .collect();
+    assert_eq!(areas, [25.0, 25.0]);
+    assert_eq!(
+        features[2]["geometry"]["coordinates"],
+        serde_json::json!([[0.0, 0.0, 1.0], [1.0, 1.0, 3.0]])
+    );
+
+    let mut flat = serde_json::json!({"type": "Polygon", "coordinates": [
+        [[0.0, 0.0], [1.0, 1.0], [0.0, 0.0]]
+    ]});
+    assert!(repair(&mut flat).is_err());
+}
+
+#[test]
+fn test_prop() {
+    assert_eq!(
+        parse_assignment("name=a=b"),
+        Ok((String::from("name"), String::from("a=b")))
+    );
+    assert!(parse_assignment("=a").is_err());
+    assert_eq!(parse_value("42"), JSONValue::from(42));
+    assert_eq!(parse_value("\"42\""), JSONValue::from("42"));
+    assert_eq!(parse_value("west"), JSONValue::from("west"));
+
+    // Foreign members index into the same values as properties
+    let geojson = serde_json::json!({"type": "FeatureCollection", "features": [
+        {"type": "Feature", "title": "a", "properties": {"name": "a", "pop": 10, "old": 1},
+            "geometry": {"type": "Point", "coordinates": [1.0, 2.0]}},
+        {"type": "Feature", "title": "b", "properties": {"name": "b", "pop": 200},
+            "geometry": {"type": "Point", "coordinates": [3.0, 4.0]}}
+    ]});
+    let mut data = Encoder::encode(&geojson, PRECISION, DIM).unwrap();
+
+    let filter = "pop > 100".parse().unwrap();
+    let set = Edit::Set(String::from("big"), JSONValue::from(true));
+    assert_eq!(edit(&mut data, &set, Some(&filter)).unwrap(), 1);
+    assert_eq!(edit(&mut data, &set, Some(&filter)).unwrap(), 0);
+    let rename = Edit::Rename(String::from("old"), String::from("new"));
+    assert_eq!(edit(&mut data, &rename, None).unwrap(), 1);
+    let delete = Edit::Delete(String::from("name"));
+    assert_eq!(edit(&mut data, &delete, None).unwrap(), 2);
+    // Keys no feature uses any more are dropped
+    assert_eq!(data.keys, ["pop", "title", "big", "new"]);
+
+    assert_eq!(
+        Decoder::decode(&data).unwrap(),
+        serde_json::json!({"type": "FeatureCollection", "features": [
+            {"type": "Feature", "title": "a", "properties": {"pop": 10, "new": 1},
+                "geometry": {"type": "Point", "coordinates": [1.0, 2.0]}},
+            {"type": "Feature", "title": "b", "properties": {"pop": 200, "big": true},
+                "geometry": {"type": "Point", "coordinates": [3.0, 4.0]}}
+        ]})
+    );
+}
+
+#[test]
+fn test_compact() {
+    let geojson = serde_json::json!({"type": "FeatureCollection", "features": [
+        {"type": "Feature", "properties": {"name": "a", "pop": 10},
+            "geometry": {"type": "LineString", "coordinates": [
+                [1.123456, 2.123456, 10.0], [3.987654, 4.987654, 20.0]
+            ]}}
+    ]});
+    let mut data = Encoder::encode(&geojson, PRECISION, 3).unwrap();
+    data.delete_property(0, "pop").unwrap();
+
+    let same = compact(&data, None, None).unwrap();
+    assert_eq!((same.precision(), same.dimensions()), (PRECISION, 3));
+    assert_eq!(
+        Decoder::decode(&same).unwrap(),
+        Decoder::decode(&data).unwrap()
+    );
+    // Keys left unused by the deleted property are dropped
+    assert_eq!(same.keys, ["name"]);
+
+    let compacted = compact(&data, Some(2), Some(2)).unwrap();
+    assert_eq!((compacted.precision(), compacted.dimensions()), (2, 2));
+    assert_eq!(
+        Decoder::decode(&compacted).unwrap()["features"][0]["geometry"]["coordinates"],
+        serde_json::json!([[1.12, 2.12], [3.99, 4.99]])
+    );
+    assert!(codec_len(&compacted) < codec_len(&data));
+
+    assert!(compact(&geobuf::geobuf_pb::Data::new(), None, None).is_err());
+}
+
+fn codec_len(data: &geobuf::geobuf_pb::Data) -> usize {
+    geobuf::container::to_bytes(data, false).unwrap().len()
+}
+
+#[test]
+fn test_dedupe() {
+    let feature = |id: u32, name: &str, x: f64| {
+        serde_json::json!({"type": "Feature", "id": id, "properties": {"name": name},
+            "geometry": {"type": "Point", "coordinates": [x, 0.0]}})
+    };
+    let geojson = serde_json::json!({"type": "FeatureCollection", "features": [
+        feature(1, "Netherlands", 1.0),
+        feature(2, "Netherlands", 1.0),
+        feature(3, "Belgium", 1.0),
+        feature(4, "Netherlands", 2.0),
+        {"type": "Feature", "properties": {}, "geometry": {"type": "Point", "coordinates": [1.0, 0.0]}},
+        {"type": "Feature", "properties": {}, "geometry": {"type": "Point", "coordinates": [1.0, 0.0]}}
+    ]});
+    let mut data = Encoder::encode(&geojson, PRECISION, DIM).unwrap();
+
+    // Ids aren't hashed, so features 0 and 1 are duplicates, and so are 4 and 5
+    assert_eq!(dedupe(&data, None), [0, 2, 3, 4]);
+    // Features without the key are all kept
+    assert_eq!(dedupe(&data, Some("name")), [0, 2, 4, 5]);
+    assert_eq!(dedupe(&data, Some("id")), [0, 1, 2, 3, 4, 5]);
+    assert_eq!(feature_key(&data, 1, "id").as_deref(), Some("2"));
+    assert_eq!(feature_key(&data, 2, "name").as_deref(), Some("Belgium"));
+    assert_eq!(feature_key(&data, 4, "name"), None);
+    assert_eq!(feature_key(&data, 6, "id"), None);
+
+    // Shared strings are hashed by their text
+    assert!(geobuf::strings::share(&mut data, 1).unwrap() > 0);
+    assert_eq!(dedupe(&data, None), [0, 2, 3, 4]);
+    assert_eq!(dedupe(&data, Some("name")), [0, 2, 4, 5]);
+}
+
+#[test]
+fn test_prop_filter() {
+    let patterns = |patterns: &[&str]| {
+        patterns
+            .iter()
+            .map(|p| String::from(*p))
+            .collect::<Vec<_>>()
+    };
+    assert!(PropFilter::new(&patterns(&["["]), &[]).is_err());
+
+    let all = PropFilter::new(&[], &[]).unwrap();
+    assert!(all.is_empty());
+    assert!(all.keeps("name"));
+
+    let props = PropFilter::new(
+        &patterns(&["name", "internal_*"]),
+        &patterns(&["internal_x"]),
+    )
+    .unwrap();
+    assert!(!props.is_empty());
+    assert!(props.keeps("name"));
+    assert!(props.keeps("internal_id"));
+    assert!(!props.keeps("internal_x"));
+    assert!(!props.keeps("pop"));
+
+    let geojson = serde_json::json!({"type": "FeatureCollection", "features": [
+        {"type": "Feature", "properties": {"name": "a", "internal_id": 1, "internal_x": 2, "pop": 3},
+            "geometry": {"type": "Point", "coordinates": [1.0, 2.0]}},
+        {"type": "Feature", "properties": {"pop": 4},
+            "geometry": {"type": "Point", "coordinates": [3.0, 4.0]}}
+    ]});
+    let mut data = Encoder::encode(&geojson, PRECISION, DIM).unwrap();
+    props.apply(&mut data).unwrap();
+    assert_eq!(data.keys, ["internal_id", "name"]);
+    assert_eq!(
+        Decoder::decode(&data).unwrap()["features"],
+        serde_json::json!([
+            {"type": "Feature", "properties": {"name": "a", "internal_id": 1},
+                "geometry": {"type": "Point", "coordinates": [1.0, 2.0]}},
+            {"type": "Feature", "geometry": {"type": "Point", "coordinates": [3.0, 4.0]}}
+        ])
+    );
+
+    // Foreign members are left alone
+    let geojson = serde_json::json!({"type": "Feature", "source": "survey",
+        "properties": {"name": "a", "pop": 3},
+        "geometry": {"type": "Point", "coordinates": [1.0, 2.0]}});
+    let mut data = Encoder::encode(&geojson, PRECISION, DIM).unwrap();
+    PropFilter::new(&[], &patterns(&["pop"]))
+        .unwrap()
+        .apply(&mut data)
+        .unwrap();
+    assert_eq!(
+        Decoder::decode(&data).unwrap(),
+        serde_json::json!({"type": "Feature", "source": "survey",
+            "properties": {"name": "a"},
+            "geometry": {"type": "Point", "coordinates": [1.0, 2.0]}})
+    );
+}
+
+#[test]
+fn test_input_arg() {
+    use clap::Parser;
+
+    let count_input =
+        |args: &[&str]| match crate::Args::try_parse_from(args).map(|args| args.commands) {
+            Ok(Some(crate::SubCommands::Count { input, .. })) => Ok(input.path()),
+            Ok(_) => unreachable!(),
+            Err(err) => Err(err.kind()),
+        };
+    let input = Ok(String::from("a.pbf"));
+    assert_eq!(count_input(&["geobuf", "count", "-i", "a.pbf"]), input);
+    assert_eq!(count_input(&["geobuf", "count", "--input", "a.pbf"]), input);
+    assert_eq!(
+        count_input(&["geobuf", "count", "a.pbf", "--by-type"]),
+        input
+    );
+    assert_eq!(
+        count_input(&["geobuf", "count"]),
+        Err(clap::ErrorKind::MissingRequiredArgument)
+    );
+    assert_eq!(
+        count_input(&["geobuf", "count", "a.pbf", "-i", "b.pbf"]),
+        Err(clap::ErrorKind::ArgumentConflict)
+    );
+}
@@ -0,0 +1,112 @@
+use std::f64::consts::PI;
+use std::rc::Rc;
+
+use rayon::prelude::*;
+use serde_json::Value as JSONValue;
+use tracing::warn;
+
+use super::clip::clip_geometry;
+use super::mvt::Tile;
+
+/// Returns the `[min_x, min_y, max_x, max_y]` longitude and latitude bounds of `tile`, grown
+/// by `buffer` times the tile size on each side
+pub fn bounds(tile: Tile, buffer: f64) -> [f64; 4] {
+    let tiles = 2f64.powi(tile.z as i32);
+    let lon = |x: f64| x / tiles * 360.0 - 180.0;
+    let lat = |y: f64| (PI * (1.0 - 2.0 * y / tiles)).sinh().atan().to_degrees();
+    [
+        lon(tile.x as f64 - buffer),
+        lat(tile.y as f64 + 1.0 + buffer),
+        lon(tile.x as f64 + 1.0 + buffer),
+        lat(tile.y as f64 - buffer),
+    ]
+}
+
+/// Returns the features of a GeoJSON Feature or FeatureCollection, warning about features
+/// without geometry, which are in no tile
+pub fn into_features(mut geojson: JSONValue) -> Result<Vec<JSONValue>, &'static str> {
+    let features = match geojson["type"].as_str() {
+        Some("FeatureCollection") => match geojson["features"].take() {
+            JSONValue::Array(features) => features,
+            _ => return Err("FeatureCollection without features."),
+        },
+        Some("Feature") => vec![geojson],
+        _ => return Err("GeoJSON is not a Feature or a FeatureCollection."),
+    };
+    let skipped = features
+        .iter()
+        .filter(|feature| feature["geometry"].is_null())
+        .count();
+    if skipped > 0 {
+        warn!("Skipping {} features without geometry", skipped);
+    }
+    Ok(features)
+}
+
+/// Clips GeoJSON features to a `[min_x, min_y, max_x, max_y]` box on the rayon thread pool
+/// and returns the features with something left, see `clip::clip_geometry`
+pub fn clip(features: &[JSONValue], bbox: &[f64; 4]) -> Result<Vec<JSONValue>, &'static str> {
+    let clipped = features
+        .par_iter()
+        .map(|feature| {
+            if feature["geometry"].is_null() {
+                return Ok(None);
+            }
+            Ok(clip_geometry(&feature["geometry"], bbox)?.map(|geometry| {
+                let mut clipped = serde_json::Map::new();
+                if let Some(feature) = feature.as_object() {
+                    for (key, value) in feature {
+                        if key != "geometry" && key != "bbox" {
+                            clipped.insert(key.clone(), value.clone());
+                        }
+                    }
+                }
+                clipped.insert(String::from("geometry"), geometry);
+                JSONValue::Object(clipped)
+            }))
+        })
+        .collect::<Result<Vec<Option<JSONValue>>, &'static str>>()?;
+    Ok(clipped.into_iter().flatten().collect())
+}
+
+/// Clips GeoJSON features to every tile from `min_zoom` to `max_zoom` holding some of them,
+/// calling `f` with each tile and the features clipped to it, and returns the number of tiles
+///
+/// Each tile is clipped from the features of its parent tile, so that features are only
+/// clipped against the tiles around them.
+pub fn pyramid<F>(
+    features: Vec<JSONValue>,
+    min_zoom: u32,
+    max_zoom: u32,
+    buffer: f64,
+    f: &mut F,
+) -> Result<usize, String>
+where
+    F: FnMut(Tile, &[JSONValue]) -> Result<(), String>,
+{
+    let mut count = 0;
+    let mut tiles = vec![(Tile { z: 0, x: 0, y: 0 }, Rc::new(features))];
+    while let Some((tile, parent)) = tiles.pop() {
+        let features = clip(&parent, &bounds(tile, buffer))?;
+        drop(parent);
+        if features.is_empty() {
+            continue;
+        }
+        if tile.z >= min_zoom {
+            f(tile, &features)?;
+            count += 1;
+        }
+        if tile.z < max_zoom {
+            let features = Rc::new(features);
+            for (dx, dy) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+                let child = Tile {
+                    z: tile.z + 1,
+                    x: tile.x * 2 + dx,
+                    y: tile.y * 2 + dy,
+                };
+                tiles.push((child, Rc::clone(&features)));
+            }
+        }
+    }
+    Ok(count)
+}
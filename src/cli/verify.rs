@@ -0,0 +1,96 @@
+use serde_json::Value as JSONValue;
+
+/// Differences between a GeoJSON object and its decoded round trip
+#[derive(Default)]
+pub struct Report {
+    pub max_error: f64,
+    /// Path of the coordinate with the largest error
+    pub max_error_path: String,
+    /// Paths of values that differ other than by coordinate rounding
+    pub mismatches: Vec<String>,
+}
+
+/// Compares `original` with `decoded`, measuring coordinate errors, including those of
+/// TopoJSON arcs, and collecting any other difference
+pub fn compare(original: &JSONValue, decoded: &JSONValue) -> Report {
+    let mut report = Report::default();
+    compare_values(original, decoded, "", false, &mut report);
+    report
+}
+
+fn compare_values(
+    original: &JSONValue,
+    decoded: &JSONValue,
+    path: &str,
+    in_coordinates: bool,
+    report: &mut Report,
+) {
+    match (original, decoded) {
+        (JSONValue::Object(original), JSONValue::Object(decoded)) => {
+            for (key, value) in original {
+                let child_path = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", path, key)
+                };
+                match decoded.get(key) {
+                    Some(decoded_value) => compare_values(
+                        value,
+                        decoded_value,
+                        &child_path,
+                        key == "coordinates" || child_path == "arcs",
+                        report,
+                    ),
+                    None if value.is_null() => {}
+                    None => report.mismatches.push(format!("{} is missing", child_path)),
+                }
+            }
+            for (key, value) in decoded {
+                if !original.contains_key(key) && !value.is_null() {
+                    report
+                        .mismatches
+                        .push(format!("{}.{} was added", path, key));
+                }
+            }
+        }
+        (JSONValue::Array(original), JSONValue::Array(decoded)) => {
+            if original.len() != decoded.len() {
+                report.mismatches.push(format!(
+                    "{} has {} items instead of {}",
+                    path,
+                    decoded.len(),
+                    original.len()
+                ));
+                return;
+            }
+            for (n, (value, decoded_value)) in original.iter().zip(decoded).enumerate() {
+                let child_path = format!("{}[{}]", path, n);
+                compare_values(value, decoded_value, &child_path, in_coordinates, report);
+            }
+        }
+        (JSONValue::Number(original), JSONValue::Number(decoded)) if in_coordinates => {
+            let error = match (original.as_f64(), decoded.as_f64()) {
+                (Some(original), Some(decoded)) => (original - decoded).abs(),
+                _ => f64::INFINITY,
+            };
+            if error > report.max_error {
+                report.max_error = error;
+                report.max_error_path = String::from(path);
+            }
+        }
+        (JSONValue::Number(original_number), JSONValue::Number(decoded_number)) => {
+            if original_number.as_f64() != decoded_number.as_f64() {
+                report
+                    .mismatches
+                    .push(format!("{}: {} became {}", path, original, decoded));
+            }
+        }
+        _ => {
+            if original != decoded {
+                report
+                    .mismatches
+                    .push(format!("{}: {} became {}", path, original, decoded));
+            }
+        }
+    }
+}
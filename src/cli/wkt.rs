@@ -0,0 +1,151 @@
+use std::io::Write;
+
+use serde_json::Value as JSONValue;
+
+use geobuf::geobuf_pb::{self, Data};
+
+/// Writes the geometry of every feature of `data` as a line of WKT, followed by a tab
+/// separated column per entry of `columns`: `id` for the feature id, or a property name.
+/// A bare geometry is written as a single line with empty columns.
+pub fn write<W: Write>(data: &Data, columns: &[String], writer: &mut W) -> Result<(), String> {
+    if let Some(geobuf_pb::data::Data_type::Geometry(_)) = data.data_type {
+        let geometry = geobuf::decode::Decoder::decode(data)?;
+        let line = format!("{}{}\n", to_wkt(&geometry)?, "\t".repeat(columns.len()));
+        return writer
+            .write_all(line.as_bytes())
+            .map_err(|_| String::from("could not write output"));
+    }
+    for feature in geobuf::decode::Decoder::features(data)? {
+        let mut line = to_wkt(&feature["geometry"])?;
+        for column in columns {
+            let value = match column.as_str() {
+                "id" => &feature["id"],
+                key => &feature["properties"][key],
+            };
+            line.push('\t');
+            match value {
+                JSONValue::Null => {}
+                JSONValue::String(value) => line.push_str(&escape(value)),
+                value => line.push_str(&value.to_string()),
+            }
+        }
+        line.push('\n');
+        writer
+            .write_all(line.as_bytes())
+            .map_err(|_| String::from("could not write output"))?;
+    }
+    Ok(())
+}
+
+/// Replaces the tabs and line breaks of a column value with spaces
+fn escape(value: &str) -> String {
+    value.replace(['\t', '\n', '\r'], " ")
+}
+
+/// Returns the WKT representation of a GeoJSON geometry, with a Z suffix for 3D positions
+pub fn to_wkt(geometry: &JSONValue) -> Result<String, &'static str> {
+    if geometry.is_null() {
+        return Ok(String::from("GEOMETRYCOLLECTION EMPTY"));
+    }
+    let coordinates = &geometry["coordinates"];
+    let (name, depth) = match geometry["type"].as_str() {
+        Some("Point") => ("POINT", 0),
+        Some("MultiPoint") => ("MULTIPOINT", 1),
+        Some("LineString") => ("LINESTRING", 1),
+        Some("MultiLineString") => ("MULTILINESTRING", 2),
+        Some("Polygon") => ("POLYGON", 2),
+        Some("MultiPolygon") => ("MULTIPOLYGON", 3),
+        Some("GeometryCollection") => {
+            let geometries = match geometry["geometries"].as_array() {
+                Some(geometries) if !geometries.is_empty() => geometries,
+                Some(_) => return Ok(String::from("GEOMETRYCOLLECTION EMPTY")),
+                None => return Err("GeometryCollection without geometries."),
+            };
+            let children = geometries
+                .iter()
+                .map(to_wkt)
+                .collect::<Result<Vec<String>, &'static str>>()?;
+            return Ok(format!("GEOMETRYCOLLECTION ({})", children.join(", ")));
+        }
+        _ => return Err("Unknown geometry type."),
+    };
+
+    let mut wkt = String::from(name);
+    if is_empty(coordinates) {
+        wkt.push_str(" EMPTY");
+        return Ok(wkt);
+    }
+    if has_z(coordinates, depth) {
+        wkt.push_str(" Z");
+    }
+    wkt.push(' ');
+    if depth == 0 {
+        wkt.push('(');
+        write_position(&mut wkt, coordinates)?;
+        wkt.push(')');
+    } else {
+        write_nested(&mut wkt, coordinates, depth)?;
+    }
+    Ok(wkt)
+}
+
+fn is_empty(coordinates: &JSONValue) -> bool {
+    match coordinates.as_array() {
+        Some(items) => items.is_empty(),
+        None => true,
+    }
+}
+
+/// Returns true if the first position, nested `depth` arrays deep, has a third value
+fn has_z(coordinates: &JSONValue, depth: usize) -> bool {
+    let mut position = coordinates;
+    for _ in 0..depth {
+        position = &position[0];
+    }
+    match position.as_array() {
+        Some(values) => values.len() > 2,
+        None => false,
+    }
+}
+
+/// Writes `depth` nested parenthesized lists of positions
+fn write_nested(
+    wkt: &mut String,
+    coordinates: &JSONValue,
+    depth: usize,
+) -> Result<(), &'static str> {
+    let items = match coordinates.as_array() {
+        Some(items) => items,
+        None => return Err("Invalid coordinates."),
+    };
+    wkt.push('(');
+    for (n, item) in items.iter().enumerate() {
+        if n > 0 {
+            wkt.push_str(", ");
+        }
+        if depth == 1 {
+            write_position(wkt, item)?;
+        } else {
+            write_nested(wkt, item, depth - 1)?;
+        }
+    }
+    wkt.push(')');
+    Ok(())
+}
+
+fn write_position(wkt: &mut String, position: &JSONValue) -> Result<(), &'static str> {
+    let values = match position.as_array() {
+        Some(values) if values.len() >= 2 => values,
+        _ => return Err("Invalid position."),
+    };
+    for (n, value) in values.iter().take(3).enumerate() {
+        if n > 0 {
+            wkt.push(' ');
+        }
+        match value.as_f64() {
+            Some(value) => wkt.push_str(&value.to_string()),
+            None => return Err("Invalid position."),
+        }
+    }
+    Ok(())
+}
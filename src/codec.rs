@@ -0,0 +1,534 @@
+//! Hand-rolled reader and writer for the fixed Geobuf schema
+//!
+//! `read` and `write` produce the same messages and bytes as the generated `protobuf` code,
+//! without going through the coded streams of the protobuf runtime. Unknown fields are
+//! skipped instead of being kept. With the `light-codec` feature, the crate parses and
+//! serializes Geobufs with this module, e.g. in the wasm exports, stream decoders and framed
+//! streams, which leaves the protobuf codec out of wasm binaries.
+use crate::runtime::{EnumOrUnknown, MessageField};
+
+use crate::framed::write_varint;
+use crate::geobuf_pb::data::{
+    feature, geometry, value, Data_type, Feature, FeatureCollection, Geometry, Topology, Transform,
+    Value,
+};
+use crate::geobuf_pb::Data;
+
+/// Nesting depth of messages past which input is rejected, like in the protobuf runtime
+const RECURSION_LIMIT: u32 = 100;
+
+const VARINT: u8 = 0;
+const FIXED64: u8 = 1;
+const LEN: u8 = 2;
+const FIXED32: u8 = 5;
+
+/// Parses a Geobuf
+///
+/// # Example
+///
+/// ```
+/// use geobuf::codec;
+/// use geobuf::encode::Encoder;
+///
+/// let geojson = serde_json::from_str(r#"{"type": "Point", "coordinates": [100.0, 0.0]}"#).unwrap();
+/// let data = Encoder::encode(&geojson, 6, 2).unwrap();
+/// assert_eq!(codec::read(&codec::write(&data)).unwrap(), data);
+/// ```
+pub fn read(bytes: &[u8]) -> Result<Data, &'static str> {
+    let mut data = Data::new();
+    read_data(&mut Reader::new(bytes), &mut data)?;
+    Ok(data)
+}
+
+/// Parses a single Feature message, e.g. one of the features of a FeatureCollection
+pub fn read_feature(bytes: &[u8]) -> Result<Feature, &'static str> {
+    read_feature_message(&mut Reader::new(bytes))
+}
+
+/// Serializes a Geobuf
+pub fn write(data: &Data) -> Vec<u8> {
+    let mut buf = Vec::new();
+    write_data(&mut buf, data);
+    buf
+}
+
+/// Parses a Geobuf with the codec selected by the `light-codec` feature
+pub(crate) fn parse(bytes: &[u8]) -> Result<Data, &'static str> {
+    #[cfg(feature = "light-codec")]
+    return read(bytes);
+    #[cfg(not(feature = "light-codec"))]
+    return protobuf::Message::parse_from_bytes(bytes).map_err(|_| "Could not parse geobuf.");
+}
+
+/// Parses a Feature message with the codec selected by the `light-codec` feature
+pub(crate) fn parse_feature(bytes: &[u8]) -> Result<Feature, &'static str> {
+    #[cfg(feature = "light-codec")]
+    return read_feature(bytes);
+    #[cfg(not(feature = "light-codec"))]
+    return protobuf::Message::parse_from_bytes(bytes).map_err(|_| "Could not parse feature.");
+}
+
+/// Serializes a Geobuf with the codec selected by the `light-codec` feature
+pub(crate) fn serialize(data: &Data) -> Result<Vec<u8>, &'static str> {
+    #[cfg(feature = "light-codec")]
+    return Ok(write(data));
+    #[cfg(not(feature = "light-codec"))]
+    return protobuf::Message::write_to_bytes(data).map_err(|_| "Could not serialize geobuf.");
+}
+
+/// Returns the serialized size of a Geobuf with the codec selected by the `light-codec` feature
+pub(crate) fn size(data: &Data) -> u64 {
+    #[cfg(feature = "light-codec")]
+    return write(data).len() as u64;
+    #[cfg(not(feature = "light-codec"))]
+    return protobuf::Message::compute_size(data);
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    depth: u32,
+}
+
+impl<'a> Reader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Reader {
+            bytes,
+            pos: 0,
+            depth: 0,
+        }
+    }
+
+    /// Returns the field number and wire type of the next field, or `None` at the end
+    fn key(&mut self) -> Result<Option<(u64, u8)>, &'static str> {
+        if self.pos == self.bytes.len() {
+            return Ok(None);
+        }
+        let key = self.varint()?;
+        Ok(Some((key >> 3, (key & 7) as u8)))
+    }
+
+    fn varint(&mut self) -> Result<u64, &'static str> {
+        let mut value: u64 = 0;
+        for i in 0..10 {
+            let byte = *self.bytes.get(self.pos).ok_or("Truncated message.")?;
+            self.pos += 1;
+            value |= ((byte & 0x7f) as u64) << (7 * i);
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err("Invalid varint.")
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], &'static str> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|end| *end <= self.bytes.len())
+            .ok_or("Truncated message.")?;
+        let bytes = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn double(&mut self) -> Result<f64, &'static str> {
+        Ok(f64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    fn len_delimited(&mut self) -> Result<&'a [u8], &'static str> {
+        let len = self.varint()?;
+        self.take(usize::try_from(len).map_err(|_| "Truncated message.")?)
+    }
+
+    fn string(&mut self) -> Result<String, &'static str> {
+        let bytes = self.len_delimited()?;
+        String::from_utf8(bytes.to_vec()).map_err(|_| "Invalid UTF-8 string.")
+    }
+
+    /// Reads an embedded message with `read`
+    fn message<T>(
+        &mut self,
+        read: impl FnOnce(&mut Reader<'a>) -> Result<T, &'static str>,
+    ) -> Result<T, &'static str> {
+        if self.depth >= RECURSION_LIMIT {
+            return Err("Messages nested too deeply.");
+        }
+        let mut reader = Reader {
+            bytes: self.len_delimited()?,
+            pos: 0,
+            depth: self.depth + 1,
+        };
+        read(&mut reader)
+    }
+
+    /// Reads the values of a repeated varint field, packed or not
+    fn repeated<T>(
+        &mut self,
+        wire_type: u8,
+        values: &mut Vec<T>,
+        convert: fn(u64) -> T,
+    ) -> Result<(), &'static str> {
+        if wire_type == VARINT {
+            values.push(convert(self.varint()?));
+            return Ok(());
+        }
+        let mut packed = Reader::new(self.len_delimited()?);
+        while packed.pos < packed.bytes.len() {
+            values.push(convert(packed.varint()?));
+        }
+        Ok(())
+    }
+
+    fn skip(&mut self, wire_type: u8) -> Result<(), &'static str> {
+        match wire_type {
+            VARINT => self.varint().map(|_| ()),
+            FIXED64 => self.take(8).map(|_| ()),
+            LEN => self.len_delimited().map(|_| ()),
+            FIXED32 => self.take(4).map(|_| ()),
+            _ => Err("Unsupported wire type."),
+        }
+    }
+}
+
+fn uint32(value: u64) -> u32 {
+    value as u32
+}
+
+fn sint32(value: u64) -> i32 {
+    let value = value as u32;
+    ((value >> 1) as i32) ^ -((value & 1) as i32)
+}
+
+fn sint64(value: u64) -> i64 {
+    ((value >> 1) as i64) ^ -((value & 1) as i64)
+}
+
+fn read_data(reader: &mut Reader, data: &mut Data) -> Result<(), &'static str> {
+    while let Some((field, wire_type)) = reader.key()? {
+        match (field, wire_type) {
+            (1, LEN) => data.keys.push(reader.string()?),
+            (2, VARINT) => data.dimensions = Some(reader.varint()? as u32),
+            (3, VARINT) => data.precision = Some(reader.varint()? as u32),
+            (4, LEN) => {
+                let collection = reader.message(read_feature_collection)?;
+                data.data_type = Some(Data_type::FeatureCollection(collection));
+            }
+            (5, LEN) => {
+                let feature = reader.message(read_feature_message)?;
+                data.data_type = Some(Data_type::Feature(feature));
+            }
+            (6, LEN) => {
+                let geometry = reader.message(read_geometry)?;
+                data.data_type = Some(Data_type::Geometry(geometry));
+            }
+            (7, LEN) => {
+                let topology = reader.message(read_topology)?;
+                data.data_type = Some(Data_type::Topology(topology));
+            }
+            (8, LEN) => data.strings.push(reader.string()?),
+            (9, LEN) => data.transform = MessageField::some(reader.message(read_transform)?),
+            _ => reader.skip(wire_type)?,
+        }
+    }
+    Ok(())
+}
+
+fn read_feature_message(reader: &mut Reader) -> Result<Feature, &'static str> {
+    let mut feature = Feature::new();
+    while let Some((field, wire_type)) = reader.key()? {
+        match (field, wire_type) {
+            (1, LEN) => feature.geometry = MessageField::some(reader.message(read_geometry)?),
+            (11, LEN) => feature.id_type = Some(feature::Id_type::Id(reader.string()?)),
+            (12, VARINT) => {
+                feature.id_type = Some(feature::Id_type::IntId(sint64(reader.varint()?)))
+            }
+            (13, LEN) => feature.values.push(reader.message(read_value)?),
+            (14, VARINT | LEN) => reader.repeated(wire_type, &mut feature.properties, uint32)?,
+            (15, VARINT | LEN) => {
+                reader.repeated(wire_type, &mut feature.custom_properties, uint32)?
+            }
+            _ => reader.skip(wire_type)?,
+        }
+    }
+    // The geometry is a required field
+    if feature.geometry.is_none() {
+        return Err("Feature without geometry.");
+    }
+    Ok(feature)
+}
+
+fn read_geometry(reader: &mut Reader) -> Result<Geometry, &'static str> {
+    let mut geometry = Geometry::new();
+    while let Some((field, wire_type)) = reader.key()? {
+        match (field, wire_type) {
+            (1, VARINT) => {
+                geometry.type_ = Some(EnumOrUnknown::from_i32(reader.varint()? as i32));
+            }
+            (2, VARINT | LEN) => reader.repeated(wire_type, &mut geometry.lengths, uint32)?,
+            (3, VARINT | LEN) => reader.repeated(wire_type, &mut geometry.coords, sint64)?,
+            (4, LEN) => geometry.geometries.push(reader.message(read_geometry)?),
+            (5, VARINT | LEN) => reader.repeated(wire_type, &mut geometry.arcs, sint32)?,
+            (11, LEN) => geometry.id_type = Some(geometry::Id_type::Id(reader.string()?)),
+            (12, VARINT) => {
+                geometry.id_type = Some(geometry::Id_type::IntId(sint64(reader.varint()?)))
+            }
+            (13, LEN) => geometry.values.push(reader.message(read_value)?),
+            (14, VARINT | LEN) => reader.repeated(wire_type, &mut geometry.properties, uint32)?,
+            (15, VARINT | LEN) => {
+                reader.repeated(wire_type, &mut geometry.custom_properties, uint32)?
+            }
+            _ => reader.skip(wire_type)?,
+        }
+    }
+    // The type is a required field
+    if geometry.type_.is_none() {
+        return Err("Geometry without type.");
+    }
+    Ok(geometry)
+}
+
+fn read_feature_collection(reader: &mut Reader) -> Result<FeatureCollection, &'static str> {
+    let mut collection = FeatureCollection::new();
+    while let Some((field, wire_type)) = reader.key()? {
+        match (field, wire_type) {
+            (1, LEN) => collection
+                .features
+                .push(reader.message(read_feature_message)?),
+            (13, LEN) => collection.values.push(reader.message(read_value)?),
+            (15, VARINT | LEN) => {
+                reader.repeated(wire_type, &mut collection.custom_properties, uint32)?
+            }
+            _ => reader.skip(wire_type)?,
+        }
+    }
+    Ok(collection)
+}
+
+fn read_topology(reader: &mut Reader) -> Result<Topology, &'static str> {
+    let mut topology = Topology::new();
+    while let Some((field, wire_type)) = reader.key()? {
+        match (field, wire_type) {
+            (1, LEN) => topology.transform = MessageField::some(reader.message(read_transform)?),
+            (2, LEN) => topology.names.push(reader.string()?),
+            (3, LEN) => topology.objects.push(reader.message(read_geometry)?),
+            (4, VARINT | LEN) => reader.repeated(wire_type, &mut topology.lengths, uint32)?,
+            (5, VARINT | LEN) => reader.repeated(wire_type, &mut topology.coords, sint64)?,
+            (13, LEN) => topology.values.push(reader.message(read_value)?),
+            (15, VARINT | LEN) => {
+                reader.repeated(wire_type, &mut topology.custom_properties, uint32)?
+            }
+            _ => reader.skip(wire_type)?,
+        }
+    }
+    Ok(topology)
+}
+
+fn read_transform(reader: &mut Reader) -> Result<Transform, &'static str> {
+    let mut transform = Transform::new();
+    while let Some((field, wire_type)) = reader.key()? {
+        match (field, wire_type) {
+            (1, FIXED64) => transform.scale_x = Some(reader.double()?),
+            (2, FIXED64) => transform.scale_y = Some(reader.double()?),
+            (3, FIXED64) => transform.translate_x = Some(reader.double()?),
+            (4, FIXED64) => transform.translate_y = Some(reader.double()?),
+            _ => reader.skip(wire_type)?,
+        }
+    }
+    Ok(transform)
+}
+
+fn read_value(reader: &mut Reader) -> Result<Value, &'static str> {
+    let mut value = Value::new();
+    while let Some((field, wire_type)) = reader.key()? {
+        let value_type = match (field, wire_type) {
+            (1, LEN) => value::Value_type::StringValue(reader.string()?),
+            (2, FIXED64) => value::Value_type::DoubleValue(reader.double()?),
+            (3, VARINT) => value::Value_type::PosIntValue(reader.varint()?),
+            (4, VARINT) => value::Value_type::NegIntValue(reader.varint()?),
+            (5, VARINT) => value::Value_type::BoolValue(reader.varint()? != 0),
+            (6, LEN) => value::Value_type::JsonValue(reader.string()?),
+            (7, VARINT) => value::Value_type::StringRef(reader.varint()? as u32),
+            _ => {
+                reader.skip(wire_type)?;
+                continue;
+            }
+        };
+        value.value_type = Some(value_type);
+    }
+    Ok(value)
+}
+
+fn write_key(buf: &mut Vec<u8>, field: u64, wire_type: u8) {
+    write_varint(buf, field << 3 | wire_type as u64);
+}
+
+fn write_uint(buf: &mut Vec<u8>, field: u64, value: u64) {
+    write_key(buf, field, VARINT);
+    write_varint(buf, value);
+}
+
+fn write_double(buf: &mut Vec<u8>, field: u64, value: f64) {
+    write_key(buf, field, FIXED64);
+    buf.extend_from_slice(&value.to_le_bytes());
+}
+
+fn write_bytes(buf: &mut Vec<u8>, field: u64, bytes: &[u8]) {
+    write_key(buf, field, LEN);
+    write_varint(buf, bytes.len() as u64);
+    buf.extend_from_slice(bytes);
+}
+
+/// Writes an embedded message serialized by `write`
+fn write_message(buf: &mut Vec<u8>, field: u64, write: impl FnOnce(&mut Vec<u8>)) {
+    let mut message = Vec::new();
+    write(&mut message);
+    write_bytes(buf, field, &message);
+}
+
+/// Writes a packed repeated varint field, omitted when empty
+fn write_packed<T: Copy>(buf: &mut Vec<u8>, field: u64, values: &[T], convert: fn(T) -> u64) {
+    if values.is_empty() {
+        return;
+    }
+    let mut packed = Vec::new();
+    for value in values {
+        write_varint(&mut packed, convert(*value));
+    }
+    write_bytes(buf, field, &packed);
+}
+
+fn zigzag32(value: i32) -> u64 {
+    ((value << 1) ^ (value >> 31)) as u32 as u64
+}
+
+fn zigzag64(value: i64) -> u64 {
+    ((value << 1) ^ (value >> 63)) as u64
+}
+
+fn write_data(buf: &mut Vec<u8>, data: &Data) {
+    for key in &data.keys {
+        write_bytes(buf, 1, key.as_bytes());
+    }
+    if let Some(dimensions) = data.dimensions {
+        write_uint(buf, 2, dimensions as u64);
+    }
+    if let Some(precision) = data.precision {
+        write_uint(buf, 3, precision as u64);
+    }
+    for string in &data.strings {
+        write_bytes(buf, 8, string.as_bytes());
+    }
+    if let Some(transform) = data.transform.as_ref() {
+        write_message(buf, 9, |buf| write_transform(buf, transform));
+    }
+    match &data.data_type {
+        Some(Data_type::FeatureCollection(collection)) => {
+            write_message(buf, 4, |buf| write_feature_collection(buf, collection))
+        }
+        Some(Data_type::Feature(feature)) => {
+            write_message(buf, 5, |buf| write_feature(buf, feature))
+        }
+        Some(Data_type::Geometry(geometry)) => {
+            write_message(buf, 6, |buf| write_geometry(buf, geometry))
+        }
+        Some(Data_type::Topology(topology)) => {
+            write_message(buf, 7, |buf| write_topology(buf, topology))
+        }
+        None => {}
+    }
+}
+
+fn write_feature(buf: &mut Vec<u8>, feature: &Feature) {
+    if let Some(geometry) = feature.geometry.as_ref() {
+        write_message(buf, 1, |buf| write_geometry(buf, geometry));
+    }
+    for value in &feature.values {
+        write_message(buf, 13, |buf| write_value(buf, value));
+    }
+    write_packed(buf, 14, &feature.properties, u64::from);
+    write_packed(buf, 15, &feature.custom_properties, u64::from);
+    match &feature.id_type {
+        Some(feature::Id_type::Id(id)) => write_bytes(buf, 11, id.as_bytes()),
+        Some(feature::Id_type::IntId(id)) => write_uint(buf, 12, zigzag64(*id)),
+        None => {}
+    }
+}
+
+fn write_geometry(buf: &mut Vec<u8>, geometry: &Geometry) {
+    if let Some(type_) = geometry.type_ {
+        // Negative enum values take ten bytes, like negative int32 values
+        write_uint(buf, 1, type_.value() as i64 as u64);
+    }
+    write_packed(buf, 2, &geometry.lengths, u64::from);
+    write_packed(buf, 3, &geometry.coords, zigzag64);
+    for child in &geometry.geometries {
+        write_message(buf, 4, |buf| write_geometry(buf, child));
+    }
+    write_packed(buf, 5, &geometry.arcs, zigzag32);
+    for value in &geometry.values {
+        write_message(buf, 13, |buf| write_value(buf, value));
+    }
+    write_packed(buf, 14, &geometry.properties, u64::from);
+    write_packed(buf, 15, &geometry.custom_properties, u64::from);
+    match &geometry.id_type {
+        Some(geometry::Id_type::Id(id)) => write_bytes(buf, 11, id.as_bytes()),
+        Some(geometry::Id_type::IntId(id)) => write_uint(buf, 12, zigzag64(*id)),
+        None => {}
+    }
+}
+
+fn write_feature_collection(buf: &mut Vec<u8>, collection: &FeatureCollection) {
+    for feature in &collection.features {
+        write_message(buf, 1, |buf| write_feature(buf, feature));
+    }
+    for value in &collection.values {
+        write_message(buf, 13, |buf| write_value(buf, value));
+    }
+    write_packed(buf, 15, &collection.custom_properties, u64::from);
+}
+
+fn write_topology(buf: &mut Vec<u8>, topology: &Topology) {
+    if let Some(transform) = topology.transform.as_ref() {
+        write_message(buf, 1, |buf| write_transform(buf, transform));
+    }
+    for name in &topology.names {
+        write_bytes(buf, 2, name.as_bytes());
+    }
+    for object in &topology.objects {
+        write_message(buf, 3, |buf| write_geometry(buf, object));
+    }
+    write_packed(buf, 4, &topology.lengths, u64::from);
+    write_packed(buf, 5, &topology.coords, zigzag64);
+    for value in &topology.values {
+        write_message(buf, 13, |buf| write_value(buf, value));
+    }
+    write_packed(buf, 15, &topology.custom_properties, u64::from);
+}
+
+fn write_transform(buf: &mut Vec<u8>, transform: &Transform) {
+    let fields = [
+        transform.scale_x,
+        transform.scale_y,
+        transform.translate_x,
+        transform.translate_y,
+    ];
+    for (field, value) in (1..).zip(fields) {
+        if let Some(value) = value {
+            write_double(buf, field, value);
+        }
+    }
+}
+
+fn write_value(buf: &mut Vec<u8>, value: &Value) {
+    match &value.value_type {
+        Some(value::Value_type::StringValue(string)) => write_bytes(buf, 1, string.as_bytes()),
+        Some(value::Value_type::DoubleValue(double)) => write_double(buf, 2, *double),
+        Some(value::Value_type::PosIntValue(int)) => write_uint(buf, 3, *int),
+        Some(value::Value_type::NegIntValue(int)) => write_uint(buf, 4, *int),
+        Some(value::Value_type::BoolValue(bool)) => write_uint(buf, 5, *bool as u64),
+        Some(value::Value_type::JsonValue(json)) => write_bytes(buf, 6, json.as_bytes()),
+        Some(value::Value_type::StringRef(index)) => write_uint(buf, 7, *index as u64),
+        None => {}
+    }
+}
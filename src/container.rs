@@ -0,0 +1,138 @@
+//! Optional container wrapper for Geobuf payloads
+//!
+//! Raw Geobuf is a bare protobuf message with no way to tell it apart from other data or to
+//! detect truncation. The container prepends a fixed 20 byte header to the payload:
+//!
+//! | bytes  | content                                 |
+//! |--------|-----------------------------------------|
+//! | 0..4   | magic bytes `GBUF`                      |
+//! | 4      | container format version                |
+//! | 5      | flags, see `FLAG_LAYERS` and `FLAG_PATCH` |
+//! | 6..8   | reserved, must be 0                     |
+//! | 8..16  | payload length (u64, little endian)     |
+//! | 16..20 | CRC32 of the payload (u32, little endian) |
+use crate::codec;
+use crate::geobuf_pb;
+
+/// Magic bytes at the start of every container
+pub const MAGIC: &[u8; 4] = b"GBUF";
+
+/// Container format version written by this crate
+pub const VERSION: u8 = 1;
+
+/// Size of the container header in bytes
+pub const HEADER_LEN: usize = 20;
+
+/// Flag set when the payload holds several named layers instead of a single Geobuf
+pub const FLAG_LAYERS: u8 = 0x01;
+
+/// Flag set when the payload holds a patch (see `patch`) instead of a single Geobuf
+pub const FLAG_PATCH: u8 = 0x02;
+
+/// Returns true if `bytes` starts with the container magic bytes
+pub fn is_container(bytes: &[u8]) -> bool {
+    bytes.len() >= MAGIC.len() && &bytes[..MAGIC.len()] == MAGIC
+}
+
+/// Returns `payload` wrapped in a container header
+pub fn write_container(payload: &[u8]) -> Vec<u8> {
+    write_container_with_flags(payload, 0)
+}
+
+/// Returns `payload` wrapped in a container header with the given flags
+pub fn write_container_with_flags(payload: &[u8], flags: u8) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(HEADER_LEN + payload.len());
+    bytes.extend_from_slice(MAGIC);
+    bytes.push(VERSION);
+    bytes.push(flags);
+    bytes.extend_from_slice(&[0, 0]);
+    bytes.extend_from_slice(&(payload.len() as u64).to_le_bytes());
+    bytes.extend_from_slice(&crc32fast::hash(payload).to_le_bytes());
+    bytes.extend_from_slice(payload);
+    bytes
+}
+
+/// Validates the container header and checksum and returns the flags and the payload
+pub fn read_container(bytes: &[u8]) -> Result<(u8, &[u8]), &'static str> {
+    if !is_container(bytes) {
+        return Err("Missing container magic bytes.");
+    }
+    if bytes.len() < HEADER_LEN {
+        return Err("Truncated container header.");
+    }
+    if bytes[4] != VERSION {
+        return Err("Unsupported container version.");
+    }
+    let flags = bytes[5];
+    if flags & !(FLAG_LAYERS | FLAG_PATCH) != 0 {
+        return Err("Unsupported container flags.");
+    }
+
+    let mut len = [0u8; 8];
+    len.copy_from_slice(&bytes[8..16]);
+    let len = u64::from_le_bytes(len) as usize;
+    let payload = &bytes[HEADER_LEN..];
+    if payload.len() < len {
+        return Err("Truncated container payload.");
+    }
+    if payload.len() > len {
+        return Err("Unexpected data after container payload.");
+    }
+
+    let mut checksum = [0u8; 4];
+    checksum.copy_from_slice(&bytes[16..20]);
+    if crc32fast::hash(payload) != u32::from_le_bytes(checksum) {
+        return Err("Container checksum mismatch.");
+    }
+    Ok((flags, payload))
+}
+
+/// Serializes `data`, optionally wrapped in a container
+pub fn to_bytes(data: &geobuf_pb::Data, container: bool) -> Result<Vec<u8>, &'static str> {
+    let payload = match codec::serialize(data) {
+        Ok(payload) => payload,
+        Err(_) => return Err("Could not serialize data."),
+    };
+    if container {
+        Ok(write_container(&payload))
+    } else {
+        Ok(payload)
+    }
+}
+
+/// Parses a `geobuf_pb::Data` from either a container or a raw Geobuf message
+///
+/// # Example
+///
+/// ```
+/// use geobuf::container;
+/// use geobuf::encode::Encoder;
+///
+/// let geojson = serde_json::from_str(r#"{"type": "Point", "coordinates": [100.0, 0.0]}"#).unwrap();
+/// let data = Encoder::encode(&geojson, 6, 2).unwrap();
+///
+/// let bytes = container::to_bytes(&data, true).unwrap();
+/// assert!(container::is_container(&bytes));
+/// assert_eq!(container::from_bytes(&bytes).unwrap(), data);
+///
+/// let truncated = &bytes[..bytes.len() - 1];
+/// assert!(container::from_bytes(truncated).is_err());
+/// ```
+pub fn from_bytes(bytes: &[u8]) -> Result<geobuf_pb::Data, &'static str> {
+    let payload = if is_container(bytes) {
+        let (flags, payload) = read_container(bytes)?;
+        if flags & FLAG_LAYERS != 0 {
+            return Err("Container holds multiple layers.");
+        }
+        if flags & FLAG_PATCH != 0 {
+            return Err("Container holds a patch.");
+        }
+        payload
+    } else {
+        bytes
+    };
+    match codec::parse(payload) {
+        Ok(data) => Ok(data),
+        Err(_) => Err("Could not parse geobuf data."),
+    }
+}
@@ -0,0 +1,222 @@
+//! Human-readable structural dump of `geobuf_pb::Data`
+//!
+//! The dump shows the header fields, the key table and, for every object, its property and
+//! value counts, coordinate counts and `lengths` arrays. Coordinates themselves are not
+//! printed.
+use std::fmt;
+
+use crate::geobuf_pb;
+
+/// Wrapper implementing `Display` with the structural dump of a `geobuf_pb::Data`
+pub struct Dump<'a>(pub &'a geobuf_pb::Data);
+
+/// Returns a structural dump of `data`
+///
+/// # Example
+///
+/// ```
+/// use geobuf::encode::Encoder;
+///
+/// let geojson = serde_json::from_str(r#"{"type": "Feature", "id": 7, "properties": {"name": "a"},
+///     "geometry": {"type": "Polygon", "coordinates": [[[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 0.0]]]}}"#).unwrap();
+/// let data = Encoder::encode(&geojson, 6, 2).unwrap();
+///
+/// let dump = geobuf::debug::dump(&data);
+/// assert!(dump.contains("keys (1): [\"name\"]"));
+/// assert!(dump.contains("Polygon: 6 coords (3 points)"));
+/// ```
+pub fn dump(data: &geobuf_pb::Data) -> String {
+    Dump(data).to_string()
+}
+
+impl<'a> fmt::Display for Dump<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let data = self.0;
+        writeln!(f, "Data")?;
+        writeln!(f, "  dimensions: {}", data.dimensions())?;
+        writeln!(f, "  precision: {}", data.precision())?;
+        if let Some(transform) = data.transform.as_ref() {
+            write_transform(f, 1, transform)?;
+        }
+        writeln!(f, "  keys ({}): {:?}", data.keys.len(), data.keys)?;
+        if !data.strings.is_empty() {
+            writeln!(f, "  shared strings: {}", data.strings.len())?;
+        }
+
+        let dim = data.dimensions().max(1) as usize;
+        match data.data_type.as_ref() {
+            Some(geobuf_pb::data::Data_type::FeatureCollection(fc)) => {
+                writeln!(f, "  FeatureCollection: {} features", fc.features.len())?;
+                write_properties(f, 2, "custom properties", &fc.custom_properties)?;
+                write_values(f, 2, &fc.values)?;
+                for (n, feature) in fc.features.iter().enumerate() {
+                    write_feature(f, 2, n, feature, dim)?;
+                }
+            }
+            Some(geobuf_pb::data::Data_type::Feature(feature)) => {
+                write_feature(f, 1, 0, feature, dim)?;
+            }
+            Some(geobuf_pb::data::Data_type::Geometry(geometry)) => {
+                write_geometry(f, 1, geometry, dim)?;
+            }
+            Some(geobuf_pb::data::Data_type::Topology(topology)) => {
+                writeln!(
+                    f,
+                    "  Topology: {} objects, {} arcs",
+                    topology.objects.len(),
+                    topology.lengths.len()
+                )?;
+                if let Some(transform) = topology.transform.as_ref() {
+                    write_transform(f, 2, transform)?;
+                }
+                writeln!(f, "    arc coords: {}", topology.coords.len())?;
+                write_lengths(f, 2, &topology.lengths)?;
+                write_properties(f, 2, "custom properties", &topology.custom_properties)?;
+                write_values(f, 2, &topology.values)?;
+                for (name, object) in topology.names.iter().zip(&topology.objects) {
+                    writeln!(f, "    object {:?}", name)?;
+                    write_geometry(f, 3, object, dim)?;
+                }
+            }
+            None => writeln!(f, "  (no data type)")?,
+        }
+        Ok(())
+    }
+}
+
+fn write_feature(
+    f: &mut fmt::Formatter<'_>,
+    depth: usize,
+    n: usize,
+    feature: &geobuf_pb::data::Feature,
+    dim: usize,
+) -> fmt::Result {
+    let indent = "  ".repeat(depth);
+    match feature.id_type.as_ref() {
+        Some(geobuf_pb::data::feature::Id_type::Id(id)) => {
+            writeln!(f, "{}Feature {} (id {:?})", indent, n, id)?
+        }
+        Some(geobuf_pb::data::feature::Id_type::IntId(id)) => {
+            writeln!(f, "{}Feature {} (id {})", indent, n, id)?
+        }
+        None => writeln!(f, "{}Feature {}", indent, n)?,
+    }
+    write_properties(f, depth + 1, "properties", &feature.properties)?;
+    write_properties(
+        f,
+        depth + 1,
+        "custom properties",
+        &feature.custom_properties,
+    )?;
+    write_values(f, depth + 1, &feature.values)?;
+    match feature.geometry.as_ref() {
+        Some(geometry) => write_geometry(f, depth + 1, geometry, dim),
+        None => writeln!(f, "{}  (no geometry)", indent),
+    }
+}
+
+fn write_geometry(
+    f: &mut fmt::Formatter<'_>,
+    depth: usize,
+    geometry: &geobuf_pb::data::Geometry,
+    dim: usize,
+) -> fmt::Result {
+    let indent = "  ".repeat(depth);
+    let name = match geometry.type_() {
+        geobuf_pb::data::geometry::Type::POINT => "Point",
+        geobuf_pb::data::geometry::Type::MULTIPOINT => "MultiPoint",
+        geobuf_pb::data::geometry::Type::LINESTRING => "LineString",
+        geobuf_pb::data::geometry::Type::MULTILINESTRING => "MultiLineString",
+        geobuf_pb::data::geometry::Type::POLYGON => "Polygon",
+        geobuf_pb::data::geometry::Type::MULTIPOLYGON => "MultiPolygon",
+        geobuf_pb::data::geometry::Type::GEOMETRYCOLLECTION => "GeometryCollection",
+    };
+    if geometry.geometries.is_empty() {
+        writeln!(
+            f,
+            "{}{}: {} coords ({} points)",
+            indent,
+            name,
+            geometry.coords.len(),
+            geometry.coords.len() / dim
+        )?;
+    } else {
+        writeln!(
+            f,
+            "{}{}: {} geometries",
+            indent,
+            name,
+            geometry.geometries.len()
+        )?;
+    }
+    write_lengths(f, depth + 1, &geometry.lengths)?;
+    if !geometry.arcs.is_empty() {
+        writeln!(f, "{}  arcs: {:?}", indent, geometry.arcs)?;
+    }
+    write_properties(f, depth + 1, "properties", &geometry.properties)?;
+    write_properties(
+        f,
+        depth + 1,
+        "custom properties",
+        &geometry.custom_properties,
+    )?;
+    write_values(f, depth + 1, &geometry.values)?;
+    for child in &geometry.geometries {
+        write_geometry(f, depth + 1, child, dim)?;
+    }
+    Ok(())
+}
+
+fn write_transform(
+    f: &mut fmt::Formatter<'_>,
+    depth: usize,
+    transform: &geobuf_pb::data::Transform,
+) -> fmt::Result {
+    writeln!(
+        f,
+        "{}transform: scale [{}, {}], translate [{}, {}]",
+        "  ".repeat(depth),
+        transform.scale_x(),
+        transform.scale_y(),
+        transform.translate_x(),
+        transform.translate_y()
+    )
+}
+
+fn write_lengths(f: &mut fmt::Formatter<'_>, depth: usize, lengths: &[u32]) -> fmt::Result {
+    if lengths.is_empty() {
+        return Ok(());
+    }
+    writeln!(f, "{}lengths: {:?}", "  ".repeat(depth), lengths)
+}
+
+fn write_properties(
+    f: &mut fmt::Formatter<'_>,
+    depth: usize,
+    label: &str,
+    properties: &[u32],
+) -> fmt::Result {
+    if properties.is_empty() {
+        return Ok(());
+    }
+    let pairs: Vec<&[u32]> = properties.chunks(2).collect();
+    writeln!(
+        f,
+        "{}{} ({}): {:?}",
+        "  ".repeat(depth),
+        label,
+        pairs.len(),
+        pairs
+    )
+}
+
+fn write_values(
+    f: &mut fmt::Formatter<'_>,
+    depth: usize,
+    values: &[geobuf_pb::data::Value],
+) -> fmt::Result {
+    if values.is_empty() {
+        return Ok(());
+    }
+    writeln!(f, "{}values: {}", "  ".repeat(depth), values.len())
+}
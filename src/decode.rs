@@ -2,12 +2,40 @@
 use serde_json::Value as JSONValue;
 
 use crate::geobuf_pb;
+use crate::props;
 
 /// Geobuf to GeoJSON Decoder
 pub struct Decoder<'a> {
     data: &'a geobuf_pb::Data,
     dim: usize,
     e: f64, // multiplier for converting coordinates into integers
+    translate: [f64; 2],
+    scale: [f64; 2],
+}
+
+/// Iterator over decoded GeoJSON Features, see `Decoder::features`
+pub struct Features<'a> {
+    decoder: Decoder<'a>,
+    features: std::slice::Iter<'a, geobuf_pb::data::Feature>,
+}
+
+impl<'a> Iterator for Features<'a> {
+    type Item = JSONValue;
+
+    fn next(&mut self) -> Option<JSONValue> {
+        let feature = self.features.next()?;
+        Some(self.decoder.decode_feature(feature))
+    }
+
+    fn nth(&mut self, n: usize) -> Option<JSONValue> {
+        // Skipped features aren't decoded
+        let feature = self.features.nth(n)?;
+        Some(self.decoder.decode_feature(feature))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.features.size_hint()
+    }
 }
 
 impl<'a> Decoder<'a> {
@@ -30,14 +58,7 @@ impl<'a> Decoder<'a> {
     /// assert_eq!(geojson["type"], "FeatureCollection");
     /// ```
     pub fn decode(data: &geobuf_pb::Data) -> Result<JSONValue, &'static str> {
-        let dim = data.dimensions() as usize;
-        let precision = data.precision() as i32;
-
-        let decoder = Decoder {
-            data,
-            dim,
-            e: 10f64.powi(precision),
-        };
+        let decoder = Decoder::new(data);
 
         let data_type = match decoder.data.data_type.as_ref() {
             Some(data_type) => data_type,
@@ -50,6 +71,52 @@ impl<'a> Decoder<'a> {
             }
             geobuf_pb::data::Data_type::Feature(feature) => Ok(decoder.decode_feature(feature)),
             geobuf_pb::data::Data_type::Geometry(geometry) => Ok(decoder.decode_geometry(geometry)),
+            geobuf_pb::data::Data_type::Topology(topology) => Ok(decoder.decode_topology(topology)),
+        }
+    }
+
+    /// Returns an iterator decoding the features of a FeatureCollection or Feature one at a
+    /// time, without building the whole GeoJSON object
+    ///
+    /// Custom properties of the FeatureCollection are not returned.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use geobuf::decode::Decoder;
+    /// use geobuf::encode::Encoder;
+    ///
+    /// let geojson = serde_json::from_str(r#"{"type": "FeatureCollection", "features": [
+    ///     {"type": "Feature", "properties": {"name": "a"}, "geometry": {"type": "Point", "coordinates": [1.0, 2.0]}},
+    ///     {"type": "Feature", "properties": {"name": "b"}, "geometry": {"type": "Point", "coordinates": [3.0, 4.0]}}
+    /// ]}"#).unwrap();
+    /// let data = Encoder::encode(&geojson, 6, 2).unwrap();
+    ///
+    /// let features: Vec<_> = Decoder::features(&data).unwrap().collect();
+    /// assert_eq!(features, geojson["features"].as_array().unwrap().clone());
+    /// ```
+    pub fn features(data: &'a geobuf_pb::Data) -> Result<Features<'a>, &'static str> {
+        let features = match data.data_type.as_ref() {
+            Some(geobuf_pb::data::Data_type::FeatureCollection(fc)) => fc.features.iter(),
+            Some(geobuf_pb::data::Data_type::Feature(feature)) => {
+                std::slice::from_ref(feature).iter()
+            }
+            _ => return Err("Data holds no features."),
+        };
+        Ok(Features {
+            decoder: Decoder::new(data),
+            features,
+        })
+    }
+
+    pub(crate) fn new(data: &'a geobuf_pb::Data) -> Self {
+        let transform = &data.transform;
+        Decoder {
+            data,
+            dim: data.dimensions() as usize,
+            e: 10f64.powi(data.precision() as i32),
+            translate: [transform.translate_x(), transform.translate_y()],
+            scale: [transform.scale_x(), transform.scale_y()],
         }
     }
 
@@ -159,6 +226,164 @@ impl<'a> Decoder<'a> {
         geometry_json
     }
 
+    fn decode_topology(&self, topology: &geobuf_pb::data::Topology) -> JSONValue {
+        let quantized = topology.transform.is_some();
+
+        let mut arcs = Vec::new();
+        let mut i: usize = 0;
+        for l in &topology.lengths {
+            let end = (*l as usize) * self.dim;
+            let coords = &topology.coords[i..i + end];
+            if quantized {
+                let points: Vec<&[i64]> = coords.chunks(self.dim).collect();
+                arcs.push(serde_json::json!(points));
+            } else {
+                arcs.push(serde_json::json!(self.decode_line(coords, false)));
+            }
+            i += end;
+        }
+
+        let mut objects = serde_json::json!({});
+        for (name, object) in topology.names.iter().zip(&topology.objects) {
+            objects[name] = self.decode_topology_geometry(object, quantized);
+        }
+
+        let mut topology_json = serde_json::json!({
+            "type": "Topology",
+            "objects": objects,
+            "arcs": arcs
+        });
+
+        if let Some(transform) = topology.transform.as_ref() {
+            topology_json["transform"] = serde_json::json!({
+                "scale": [transform.scale_x(), transform.scale_y()],
+                "translate": [transform.translate_x(), transform.translate_y()]
+            });
+        }
+
+        self.decode_properties(
+            &topology.custom_properties,
+            &topology.values,
+            &mut topology_json,
+        );
+        topology_json
+    }
+
+    fn decode_topology_geometry(
+        &self,
+        geometry: &geobuf_pb::data::Geometry,
+        quantized: bool,
+    ) -> JSONValue {
+        let mut geometry_json = serde_json::json!({});
+        let arcs = &geometry.arcs;
+        let lengths = &geometry.lengths;
+
+        match geometry.type_() {
+            geobuf_pb::data::geometry::Type::GEOMETRYCOLLECTION => {
+                geometry_json["type"] = serde_json::json!("GeometryCollection");
+                let mut geometries = Vec::new();
+                for geom in &geometry.geometries {
+                    geometries.push(self.decode_topology_geometry(geom, quantized));
+                }
+                geometry_json["geometries"] = serde_json::json!(geometries);
+            }
+            geobuf_pb::data::geometry::Type::POINT => {
+                geometry_json["type"] = serde_json::json!("Point");
+                geometry_json["coordinates"] = if quantized {
+                    serde_json::json!(geometry.coords)
+                } else {
+                    serde_json::json!(self.decode_point(&geometry.coords))
+                };
+            }
+            geobuf_pb::data::geometry::Type::MULTIPOINT => {
+                geometry_json["type"] = serde_json::json!("MultiPoint");
+                geometry_json["coordinates"] = if quantized {
+                    serde_json::json!(self.decode_quantized_line(&geometry.coords))
+                } else {
+                    serde_json::json!(self.decode_line(&geometry.coords, false))
+                };
+            }
+            geobuf_pb::data::geometry::Type::LINESTRING => {
+                geometry_json["type"] = serde_json::json!("LineString");
+                geometry_json["arcs"] = serde_json::json!(arcs);
+            }
+            geobuf_pb::data::geometry::Type::MULTILINESTRING => {
+                geometry_json["type"] = serde_json::json!("MultiLineString");
+                geometry_json["arcs"] = serde_json::json!(Decoder::split_arcs(arcs, lengths));
+            }
+            geobuf_pb::data::geometry::Type::POLYGON => {
+                geometry_json["type"] = serde_json::json!("Polygon");
+                geometry_json["arcs"] = serde_json::json!(Decoder::split_arcs(arcs, lengths));
+            }
+            geobuf_pb::data::geometry::Type::MULTIPOLYGON => {
+                geometry_json["type"] = serde_json::json!("MultiPolygon");
+                let mut polygons = Vec::new();
+                if !lengths.is_empty() {
+                    let mut i = 0;
+                    let mut j = 1;
+                    for _n in 0..lengths[0] {
+                        let num_rings = lengths[j] as usize;
+                        j += 1;
+                        let rings = Decoder::split_arcs(&arcs[i..], &lengths[j..j + num_rings]);
+                        i += rings.iter().map(|ring| ring.len()).sum::<usize>();
+                        j += num_rings;
+                        polygons.push(rings);
+                    }
+                }
+                geometry_json["arcs"] = serde_json::json!(polygons);
+            }
+        }
+
+        self.decode_properties(
+            &geometry.custom_properties,
+            &geometry.values,
+            &mut geometry_json,
+        );
+
+        match &geometry.id_type {
+            Some(geobuf_pb::data::geometry::Id_type::IntId(id)) => {
+                geometry_json["id"] = serde_json::json!(id)
+            }
+            Some(geobuf_pb::data::geometry::Id_type::Id(id)) => {
+                geometry_json["id"] = serde_json::json!(id)
+            }
+            None => {}
+        }
+
+        if !geometry.properties.is_empty() {
+            let mut properties = serde_json::json!({});
+            self.decode_properties(&geometry.properties, &geometry.values, &mut properties);
+            geometry_json["properties"] = properties;
+        }
+
+        geometry_json
+    }
+
+    fn split_arcs(arcs: &[i32], lengths: &[u32]) -> Vec<Vec<i32>> {
+        if lengths.is_empty() {
+            return vec![arcs.to_vec()];
+        }
+        let mut lines = Vec::new();
+        let mut i: usize = 0;
+        for l in lengths {
+            let end = *l as usize;
+            lines.push(arcs[i..i + end].to_vec());
+            i += end;
+        }
+        lines
+    }
+
+    fn decode_quantized_line(&self, coords: &[i64]) -> Vec<Vec<i64>> {
+        let mut points = Vec::new();
+        let mut p0 = vec![0; self.dim];
+        for delta in coords.chunks(self.dim) {
+            let point: Vec<i64> = p0.iter().zip(delta).map(|(p, d)| p + d).collect();
+            points.push(point.clone());
+            p0 = point;
+        }
+        points
+    }
+
     fn decode_properties(
         &self,
         properties: &[u32],
@@ -169,42 +394,27 @@ impl<'a> Decoder<'a> {
         for i in (0..properties.len()).step_by(2) {
             let key = &keys[properties[i] as usize];
             let value = &values[properties[i + 1] as usize];
-
-            match value.value_type.as_ref().unwrap() {
-                geobuf_pb::data::value::Value_type::StringValue(v) => {
-                    json[key] = serde_json::json!(v)
-                }
-                geobuf_pb::data::value::Value_type::DoubleValue(v) => {
-                    json[key] = serde_json::json!(v)
-                }
-                geobuf_pb::data::value::Value_type::PosIntValue(v) => {
-                    json[key] = serde_json::json!(v)
-                }
-                geobuf_pb::data::value::Value_type::NegIntValue(v) => {
-                    json[key] = serde_json::json!(-(*v as i64))
-                }
-                geobuf_pb::data::value::Value_type::BoolValue(v) => {
-                    json[key] = serde_json::json!(v)
-                }
-                geobuf_pb::data::value::Value_type::JsonValue(v) => {
-                    json[key] = serde_json::from_str(v).unwrap()
-                }
-            }
+            json[key] = props::value_to_json(value, &self.data.strings);
         }
     }
 
-    fn decode_coord(&self, coord: &i64) -> f64 {
-        *coord as f64 / self.e
+    fn decode_coord(&self, coord: i64, j: usize) -> f64 {
+        let coord = coord as f64 / self.e;
+        match j {
+            0 | 1 => coord * self.scale[j] + self.translate[j],
+            _ => coord,
+        }
     }
 
-    fn decode_point(&self, coords: &[i64]) -> Vec<f64> {
+    pub(crate) fn decode_point(&self, coords: &[i64]) -> Vec<f64> {
         coords
             .iter()
-            .map(|coord| self.decode_coord(coord))
+            .enumerate()
+            .map(|(j, coord)| self.decode_coord(*coord, j))
             .collect()
     }
 
-    fn decode_line(&self, coords: &[i64], is_closed: bool) -> Vec<Vec<f64>> {
+    pub(crate) fn decode_line(&self, coords: &[i64], is_closed: bool) -> Vec<Vec<f64>> {
         let mut points_json = Vec::new();
         let mut p0 = vec![0; self.dim];
 
@@ -214,7 +424,7 @@ impl<'a> Decoder<'a> {
             for j in 0..self.dim {
                 let coord = p0[j] + coords[i + j];
                 p.push(coord);
-                point.push(self.decode_coord(&coord));
+                point.push(self.decode_coord(coord, j));
             }
             points_json.push(point);
             p0 = p;
@@ -223,7 +433,7 @@ impl<'a> Decoder<'a> {
         if is_closed {
             let mut p = vec![0.0; self.dim];
             for j in 0..self.dim {
-                p[j] = self.decode_coord(&coords[j]);
+                p[j] = self.decode_coord(coords[j], j);
             }
             points_json.push(p);
         }
@@ -231,7 +441,7 @@ impl<'a> Decoder<'a> {
         points_json
     }
 
-    fn decode_multi_line(
+    pub(crate) fn decode_multi_line(
         &self,
         geometry: &geobuf_pb::data::Geometry,
         is_closed: bool,
@@ -254,7 +464,7 @@ impl<'a> Decoder<'a> {
         lines
     }
 
-    fn decode_multi_polygon(
+    pub(crate) fn decode_multi_polygon(
         &self,
         geometry: &geobuf_pb::data::Geometry,
     ) -> Vec<Vec<Vec<Vec<f64>>>> {
@@ -0,0 +1,228 @@
+//! Semantic diff between two Geobuf objects
+use std::collections::{HashMap, VecDeque};
+
+use serde_json::Value as JSONValue;
+
+use crate::geobuf_pb;
+use crate::hash;
+use crate::props;
+
+/// Key used to match features between two objects
+///
+/// Features with an id are matched by id. Features without one are matched by their content
+/// hash (see `hash_feature`), so they are either unchanged or reported as removed and added.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum FeatureKey {
+    Id(String),
+    IntId(i64),
+    Hash(u64),
+}
+
+impl FeatureKey {
+    /// Returns the key of a feature whose property keys refer to `keys`
+    pub fn of(feature: &geobuf_pb::data::Feature, keys: &[String], strings: &[String]) -> Self {
+        match feature.id_type.as_ref() {
+            Some(geobuf_pb::data::feature::Id_type::Id(id)) => FeatureKey::Id(id.clone()),
+            Some(geobuf_pb::data::feature::Id_type::IntId(id)) => FeatureKey::IntId(*id),
+            None => FeatureKey::Hash(hash::hash_feature(feature, keys, strings)),
+        }
+    }
+}
+
+/// Change of a single property between two versions of a feature
+#[derive(Clone, Debug, PartialEq)]
+pub struct PropertyChange {
+    pub key: String,
+    /// Value in the old feature, `None` if the property was added
+    pub old: Option<JSONValue>,
+    /// Value in the new feature, `None` if the property was removed
+    pub new: Option<JSONValue>,
+}
+
+/// A feature present in both objects whose geometry or properties differ
+#[derive(Clone, Debug, PartialEq)]
+pub struct FeatureChange {
+    /// Index of the feature in the old object
+    pub old: usize,
+    /// Index of the feature in the new object
+    pub new: usize,
+    pub geometry_changed: bool,
+    /// Property changes, sorted by key
+    pub properties: Vec<PropertyChange>,
+}
+
+/// Differences between two objects, see `diff`
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Diff {
+    /// Indexes of features only present in the new object
+    pub added: Vec<usize>,
+    /// Indexes of features only present in the old object
+    pub removed: Vec<usize>,
+    pub changed: Vec<FeatureChange>,
+}
+
+impl Diff {
+    /// Returns true if both objects hold the same features
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Returns the features added, removed and changed between `old` and `new`
+///
+/// Both objects must be FeatureCollections or Features. Geometries are compared on their
+/// quantized coordinates, rescaled if the precisions differ; custom properties are ignored.
+/// Features with duplicate ids are matched in order.
+///
+/// # Example
+///
+/// ```
+/// use geobuf::encode::Encoder;
+///
+/// let old = serde_json::from_str(r#"{"type": "FeatureCollection", "features": [
+///     {"type": "Feature", "id": 1, "properties": {"name": "a"}, "geometry": {"type": "Point", "coordinates": [1.0, 2.0]}},
+///     {"type": "Feature", "id": 2, "properties": {"name": "b"}, "geometry": {"type": "Point", "coordinates": [3.0, 4.0]}}
+/// ]}"#).unwrap();
+/// let new = serde_json::from_str(r#"{"type": "FeatureCollection", "features": [
+///     {"type": "Feature", "id": 2, "properties": {"name": "c"}, "geometry": {"type": "Point", "coordinates": [3.0, 4.0]}},
+///     {"type": "Feature", "id": 3, "properties": {"name": "d"}, "geometry": {"type": "Point", "coordinates": [5.0, 6.0]}}
+/// ]}"#).unwrap();
+///
+/// let diff = geobuf::diff(
+///     &Encoder::encode(&old, 6, 2).unwrap(),
+///     &Encoder::encode(&new, 6, 2).unwrap(),
+/// ).unwrap();
+/// assert_eq!(diff.removed, vec![0]);
+/// assert_eq!(diff.added, vec![1]);
+/// assert_eq!(diff.changed.len(), 1);
+/// assert!(!diff.changed[0].geometry_changed);
+/// assert_eq!(diff.changed[0].properties[0].key, "name");
+/// assert_eq!(diff.changed[0].properties[0].new, Some(serde_json::json!("c")));
+/// ```
+pub fn diff(old: &geobuf_pb::Data, new: &geobuf_pb::Data) -> Result<Diff, &'static str> {
+    let old_features = features(old)?;
+    let new_features = features(new)?;
+
+    let mut unmatched: HashMap<FeatureKey, VecDeque<usize>> = HashMap::new();
+    for (n, feature) in old_features.iter().enumerate() {
+        unmatched
+            .entry(FeatureKey::of(feature, &old.keys, &old.strings))
+            .or_default()
+            .push_back(n);
+    }
+
+    let precision = old.precision().max(new.precision());
+    let old_scale = 10i64.pow(precision - old.precision());
+    let new_scale = 10i64.pow(precision - new.precision());
+
+    let mut diff = Diff::default();
+    let mut matched = vec![false; old_features.len()];
+    for (n, new_feature) in new_features.iter().enumerate() {
+        let key = FeatureKey::of(new_feature, &new.keys, &new.strings);
+        let o = match unmatched.get_mut(&key).and_then(VecDeque::pop_front) {
+            Some(o) => o,
+            None => {
+                diff.added.push(n);
+                continue;
+            }
+        };
+        matched[o] = true;
+
+        let old_feature = old_features[o];
+        let geometry_changed = !same_geometry(
+            &old_feature.geometry,
+            old_scale,
+            &new_feature.geometry,
+            new_scale,
+        );
+        let properties = property_changes(old, old_feature, new, new_feature);
+        if geometry_changed || !properties.is_empty() {
+            diff.changed.push(FeatureChange {
+                old: o,
+                new: n,
+                geometry_changed,
+                properties,
+            });
+        }
+    }
+    diff.removed = (0..old_features.len()).filter(|o| !matched[*o]).collect();
+    Ok(diff)
+}
+
+/// Returns the features of a FeatureCollection or Feature
+pub(crate) fn features(
+    data: &geobuf_pb::Data,
+) -> Result<Vec<&geobuf_pb::data::Feature>, &'static str> {
+    match data.data_type.as_ref() {
+        Some(geobuf_pb::data::Data_type::FeatureCollection(fc)) => Ok(fc.features.iter().collect()),
+        Some(geobuf_pb::data::Data_type::Feature(feature)) => Ok(vec![feature]),
+        _ => Err("Data holds no features."),
+    }
+}
+
+fn same_geometry(
+    a: &geobuf_pb::data::Geometry,
+    a_scale: i64,
+    b: &geobuf_pb::data::Geometry,
+    b_scale: i64,
+) -> bool {
+    a.type_() == b.type_()
+        && a.lengths == b.lengths
+        && a.coords.len() == b.coords.len()
+        && a.coords
+            .iter()
+            .zip(&b.coords)
+            .all(|(ca, cb)| ca * a_scale == cb * b_scale)
+        && a.geometries.len() == b.geometries.len()
+        && a.geometries
+            .iter()
+            .zip(&b.geometries)
+            .all(|(ga, gb)| same_geometry(ga, a_scale, gb, b_scale))
+}
+
+fn property_changes(
+    old: &geobuf_pb::Data,
+    old_feature: &geobuf_pb::data::Feature,
+    new: &geobuf_pb::Data,
+    new_feature: &geobuf_pb::data::Feature,
+) -> Vec<PropertyChange> {
+    let old_properties = properties(old, old_feature);
+    let mut new_properties = properties(new, new_feature);
+
+    let mut changes = Vec::new();
+    for (key, old_value) in old_properties {
+        let new_value = new_properties.remove(&key);
+        if new_value.as_ref() != Some(&old_value) {
+            changes.push(PropertyChange {
+                key,
+                old: Some(old_value),
+                new: new_value,
+            });
+        }
+    }
+    for (key, new_value) in new_properties {
+        changes.push(PropertyChange {
+            key,
+            old: None,
+            new: Some(new_value),
+        });
+    }
+    changes.sort_by(|c1, c2| c1.key.cmp(&c2.key));
+    changes
+}
+
+fn properties(
+    data: &geobuf_pb::Data,
+    feature: &geobuf_pb::data::Feature,
+) -> HashMap<String, JSONValue> {
+    feature
+        .properties
+        .chunks(2)
+        .filter(|pair| pair.len() == 2)
+        .filter_map(|pair| {
+            let key = data.keys.get(pair[0] as usize)?;
+            let value = feature.values.get(pair[1] as usize)?;
+            Some((key.clone(), props::value_to_json(value, &data.strings)))
+        })
+        .collect()
+}
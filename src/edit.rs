@@ -0,0 +1,248 @@
+//! In-place edits of `geobuf_pb::Data` without a GeoJSON round trip
+use serde_json::Value as JSONValue;
+
+use crate::encode::Encoder;
+use crate::geobuf_pb;
+use crate::props;
+
+impl geobuf_pb::Data {
+    /// Returns the number of features: the collection size for a FeatureCollection, 1 for a
+    /// Feature and 0 otherwise
+    pub fn feature_count(&self) -> usize {
+        match self.data_type.as_ref() {
+            Some(geobuf_pb::data::Data_type::FeatureCollection(fc)) => fc.features.len(),
+            Some(geobuf_pb::data::Data_type::Feature(_)) => 1,
+            _ => 0,
+        }
+    }
+
+    /// Returns the value of property `key` of feature `n`
+    pub fn get_property(&self, n: usize, key: &str) -> Option<JSONValue> {
+        let feature = match self.data_type.as_ref()? {
+            geobuf_pb::data::Data_type::FeatureCollection(fc) => fc.features.get(n)?,
+            geobuf_pb::data::Data_type::Feature(feature) if n == 0 => feature,
+            _ => return None,
+        };
+        props::get(&self.keys, &feature.values, &feature.properties, key)
+            .map(|value| props::value_to_json(value, &self.strings))
+    }
+
+    /// Sets property `key` of feature `n` to `value`, adding the key to the key table if
+    /// needed
+    ///
+    /// Geobuf has no representation for `null` properties, so setting a property to `null`
+    /// deletes it.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use geobuf::encode::Encoder;
+    /// use serde_json::json;
+    ///
+    /// let geojson = serde_json::from_str(r#"{"type": "FeatureCollection", "features": [
+    ///     {"type": "Feature", "properties": {"name": "a"}, "geometry": {"type": "Point", "coordinates": [1.0, 2.0]}}
+    /// ]}"#).unwrap();
+    /// let mut data = Encoder::encode(&geojson, 6, 2).unwrap();
+    ///
+    /// data.set_property(0, "processed_at", &json!("2022-05-01T12:00:00Z")).unwrap();
+    /// data.set_property(0, "name", &json!("b")).unwrap();
+    /// assert_eq!(data.get_property(0, "name"), Some(json!("b")));
+    ///
+    /// assert!(data.delete_property(0, "name").unwrap());
+    /// assert_eq!(data.get_property(0, "name"), None);
+    /// assert_eq!(data.get_property(0, "processed_at"), Some(json!("2022-05-01T12:00:00Z")));
+    /// ```
+    pub fn set_property(
+        &mut self,
+        n: usize,
+        key: &str,
+        value: &JSONValue,
+    ) -> Result<(), &'static str> {
+        let value = match props::json_to_value(value) {
+            Some(value) => value,
+            None => return self.delete_property(n, key).map(|_| ()),
+        };
+        let (keys, feature) = feature_tables_mut(self, n)?;
+        props::set(
+            keys,
+            &mut feature.values,
+            &mut feature.properties,
+            &mut feature.custom_properties,
+            key,
+            value,
+        );
+        Ok(())
+    }
+
+    /// Deletes property `key` of feature `n`, returning true if it was present
+    ///
+    /// The key stays in the key table even if no other feature uses it.
+    pub fn delete_property(&mut self, n: usize, key: &str) -> Result<bool, &'static str> {
+        let (keys, feature) = feature_tables_mut(self, n)?;
+        Ok(props::remove(
+            keys,
+            &mut feature.values,
+            &mut feature.properties,
+            &mut feature.custom_properties,
+            key,
+        ))
+    }
+
+    /// Appends a feature whose key indexes refer to `keys`, remapping them into the key
+    /// table of this `Data`
+    ///
+    /// A Feature root is turned into a FeatureCollection holding both features and an empty
+    /// `Data` into a FeatureCollection holding only the new feature. Geometry roots can't
+    /// hold features.
+    pub fn push_feature(
+        &mut self,
+        mut feature: geobuf_pb::data::Feature,
+        keys: &[String],
+    ) -> Result<(), &'static str> {
+        match self.data_type {
+            Some(geobuf_pb::data::Data_type::Geometry(_)) => {
+                return Err("Can't add features to a geometry.")
+            }
+            Some(geobuf_pb::data::Data_type::Topology(_)) => {
+                return Err("Can't add features to a topology.")
+            }
+            _ => {}
+        }
+        let mapping = props::key_mapping(keys, &mut self.keys);
+        props::remap_feature(&mut feature, &mut |key| mapping[key as usize]);
+
+        let mut fc = match self.data_type.take() {
+            Some(geobuf_pb::data::Data_type::FeatureCollection(fc)) => fc,
+            Some(geobuf_pb::data::Data_type::Feature(existing)) => {
+                let mut fc = geobuf_pb::data::FeatureCollection::new();
+                fc.features.push(existing);
+                fc
+            }
+            _ => geobuf_pb::data::FeatureCollection::new(),
+        };
+        fc.features.push(feature);
+        self.set_feature_collection(fc);
+        Ok(())
+    }
+
+    /// Encodes a GeoJSON Feature with the precision, dimensions and transform of this `Data`
+    /// and appends it
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use geobuf::encode::Encoder;
+    /// use serde_json::json;
+    ///
+    /// let geojson = serde_json::from_str(r#"{"type": "FeatureCollection", "features": [
+    ///     {"type": "Feature", "properties": {"name": "a"}, "geometry": {"type": "Point", "coordinates": [1.0, 2.0]}}
+    /// ]}"#).unwrap();
+    /// let mut data = Encoder::encode(&geojson, 6, 2).unwrap();
+    ///
+    /// data.push_feature_json(&json!({
+    ///     "type": "Feature",
+    ///     "properties": {"name": "b", "pop": 10},
+    ///     "geometry": {"type": "Point", "coordinates": [3.0, 4.0]}
+    /// })).unwrap();
+    /// assert_eq!(data.keys, vec!["name", "pop"]);
+    /// assert_eq!(data.get_property(1, "name"), Some(json!("b")));
+    ///
+    /// data.remove_feature(0).unwrap();
+    /// data.prune_keys();
+    /// assert_eq!(data.feature_count(), 1);
+    /// assert_eq!(data.get_property(0, "pop"), Some(json!(10)));
+    /// ```
+    pub fn push_feature_json(&mut self, feature: &JSONValue) -> Result<(), &'static str> {
+        if feature["type"] != "Feature" {
+            return Err("Expected a GeoJSON Feature.");
+        }
+        let mut encoded = Encoder::encode_with_transform(
+            feature,
+            self.precision(),
+            self.dimensions(),
+            self.transform.as_ref().cloned(),
+        )?;
+        let keys = std::mem::take(&mut encoded.keys);
+        self.push_feature(encoded.take_feature(), &keys)
+    }
+
+    /// Removes and returns feature `n` of a FeatureCollection
+    ///
+    /// Keys only used by the removed feature stay in the key table; use `prune_keys` to drop
+    /// them.
+    pub fn remove_feature(&mut self, n: usize) -> Result<geobuf_pb::data::Feature, &'static str> {
+        match self.data_type.as_mut() {
+            Some(geobuf_pb::data::Data_type::FeatureCollection(fc)) if n < fc.features.len() => {
+                Ok(fc.features.remove(n))
+            }
+            Some(geobuf_pb::data::Data_type::FeatureCollection(_)) => {
+                Err("Feature index out of range.")
+            }
+            _ => Err("Data holds no feature collection."),
+        }
+    }
+
+    /// Removes keys that no property refers to from the key table
+    pub fn prune_keys(&mut self) {
+        let mut used = vec![false; self.keys.len()];
+        remap_keys(self, &mut |key| {
+            used[key as usize] = true;
+            key
+        });
+
+        let mut mapping = Vec::with_capacity(used.len());
+        let mut keys = Vec::new();
+        for (key, used) in std::mem::take(&mut self.keys).into_iter().zip(used) {
+            mapping.push(keys.len() as u32);
+            if used {
+                keys.push(key);
+            }
+        }
+        self.keys = keys;
+
+        remap_keys(self, &mut |key| mapping[key as usize]);
+    }
+}
+
+/// Returns the key table and feature `n` of `data`
+fn feature_tables_mut(
+    data: &mut geobuf_pb::Data,
+    n: usize,
+) -> Result<(&mut Vec<String>, &mut geobuf_pb::data::Feature), &'static str> {
+    let keys = &mut data.keys;
+    let feature = match data.data_type.as_mut() {
+        Some(geobuf_pb::data::Data_type::FeatureCollection(fc)) => fc.features.get_mut(n),
+        Some(geobuf_pb::data::Data_type::Feature(feature)) if n == 0 => Some(feature),
+        Some(geobuf_pb::data::Data_type::Feature(_)) => None,
+        _ => return Err("Data holds no features."),
+    };
+    match feature {
+        Some(feature) => Ok((keys, feature)),
+        None => Err("Feature index out of range."),
+    }
+}
+
+/// Rewrites every key index of `data` using `map_key`
+fn remap_keys<F: FnMut(u32) -> u32>(data: &mut geobuf_pb::Data, map_key: &mut F) {
+    match data.data_type.as_mut() {
+        Some(geobuf_pb::data::Data_type::FeatureCollection(fc)) => {
+            props::remap_pairs(&mut fc.custom_properties, map_key);
+            for feature in fc.features.iter_mut() {
+                props::remap_feature(feature, map_key);
+            }
+        }
+        Some(geobuf_pb::data::Data_type::Feature(feature)) => {
+            props::remap_feature(feature, map_key)
+        }
+        Some(geobuf_pb::data::Data_type::Geometry(geometry)) => {
+            props::remap_geometry(geometry, map_key)
+        }
+        Some(geobuf_pb::data::Data_type::Topology(topology)) => {
+            props::remap_pairs(&mut topology.custom_properties, map_key);
+            for object in topology.objects.iter_mut() {
+                props::remap_geometry(object, map_key);
+            }
+        }
+        None => {}
+    }
+}
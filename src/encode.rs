@@ -1,8 +1,9 @@
 //! GeoJSON to Geobuf encoder
-use protobuf::MessageField;
 use serde_json::Value as JSONValue;
 
+use crate::codec;
 use crate::geobuf_pb;
+use crate::runtime::MessageField;
 // use crate::geobuf_pb::{
 //     Data
 // };
@@ -16,6 +17,102 @@ pub struct Encoder {
     data: geobuf_pb::Data,
     dim: usize,
     e: f64, // multiplier for converting coordinates into integers
+    translate: [f64; 2],
+    scale: [f64; 2],
+}
+
+/// Tile that coordinates are quantized relative to, see `EncoderOptions::tile`
+#[derive(Clone, Debug, PartialEq)]
+pub struct Tile {
+    /// Tile bounds as `[min_x, min_y, max_x, max_y]`, in the coordinate space of the input
+    pub bounds: [f64; 4],
+    /// Size of the integer grid covering the tile, e.g. 4096
+    pub extent: u32,
+}
+
+/// Translation and scale applied to x and y, see `EncoderOptions::affine`
+#[derive(Clone, Debug, PartialEq)]
+pub struct Affine {
+    /// Origin subtracted from x and y before quantization
+    pub translate: [f64; 2],
+    /// Size of one unit of x and y after translation
+    pub scale: [f64; 2],
+}
+
+impl Affine {
+    /// Returns a transform moving the origin to `origin` without scaling
+    pub fn origin(origin: [f64; 2]) -> Self {
+        Affine {
+            translate: origin,
+            scale: [1.0, 1.0],
+        }
+    }
+}
+
+/// Options for `Encoder::encode_with_options`
+#[derive(Clone, Debug, PartialEq)]
+pub struct EncoderOptions {
+    /// Max number of digits after the decimal point in coordinates
+    pub precision: u32,
+    /// Number of dimensions in coordinates
+    pub dim: u32,
+    /// Quantizes x and y to integers in `0..=extent` relative to the tile bounds instead of
+    /// using `precision`, with y pointing down like in vector tiles. The mapping is stored
+    /// in the `transform` field, which the decoder uses to restore the input coordinates.
+    pub tile: Option<Tile>,
+    /// Maps x and y to `(x - translate) / scale` before quantizing them with `precision`.
+    /// The transform is stored in the `transform` field and reversed by the decoder, which
+    /// avoids losing precision on coordinates far from the origin, e.g. in a local
+    /// engineering grid. Can't be combined with `tile`.
+    pub affine: Option<Affine>,
+}
+
+impl Default for EncoderOptions {
+    fn default() -> Self {
+        EncoderOptions {
+            precision: 6,
+            dim: 2,
+            tile: None,
+            affine: None,
+        }
+    }
+}
+
+impl EncoderOptions {
+    /// Returns the precision and transform used to quantize coordinates
+    fn quantization(&self) -> Result<(u32, Option<geobuf_pb::data::Transform>), &'static str> {
+        match (&self.tile, &self.affine) {
+            (Some(_), Some(_)) => Err("Tile and affine transform can't be combined."),
+            (None, Some(affine)) => {
+                if affine
+                    .scale
+                    .iter()
+                    .any(|scale| *scale == 0.0 || !scale.is_finite())
+                {
+                    return Err("Invalid affine transform.");
+                }
+                let mut transform = geobuf_pb::data::Transform::new();
+                transform.set_scale_x(affine.scale[0]);
+                transform.set_scale_y(affine.scale[1]);
+                transform.set_translate_x(affine.translate[0]);
+                transform.set_translate_y(affine.translate[1]);
+                Ok((self.precision, Some(transform)))
+            }
+            (Some(tile), None) => {
+                let [min_x, min_y, max_x, max_y] = tile.bounds;
+                if tile.extent == 0 || max_x <= min_x || max_y <= min_y {
+                    return Err("Invalid tile.");
+                }
+                let mut transform = geobuf_pb::data::Transform::new();
+                transform.set_scale_x((max_x - min_x) / tile.extent as f64);
+                transform.set_scale_y((min_y - max_y) / tile.extent as f64);
+                transform.set_translate_x(min_x);
+                transform.set_translate_y(max_y);
+                Ok((0, Some(transform)))
+            }
+            (None, None) => Ok((self.precision, None)),
+        }
+    }
 }
 
 impl Encoder {
@@ -45,15 +142,193 @@ impl Encoder {
         precision: u32,
         dim: u32,
     ) -> Result<geobuf_pb::Data, &'static str> {
-        let mut data = geobuf_pb::Data::new();
-        data.set_precision(precision);
-        data.set_dimensions(dim);
+        Encoder::encode_with_transform(geojson, precision, dim, None)
+    }
 
-        let mut encoder = Encoder {
-            data,
-            dim: dim as usize,
-            e: 10f64.powi(precision as i32),
+    /// Returns a Geobuf encoded object from the given geojson value using `options`
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use geobuf::decode::Decoder;
+    /// use geobuf::encode::{Affine, Encoder, EncoderOptions, Tile};
+    ///
+    /// let geojson = serde_json::from_str(r#"{"type": "Point", "coordinates": [10.25, 20.75]}"#).unwrap();
+    /// let options = EncoderOptions {
+    ///     tile: Some(Tile { bounds: [10.0, 20.0, 11.0, 21.0], extent: 4096 }),
+    ///     ..Default::default()
+    /// };
+    /// let geobuf = Encoder::encode_with_options(&geojson, &options).unwrap();
+    /// assert_eq!(geobuf.geometry().coords, vec![1024, 1024]);
+    /// assert_eq!(Decoder::decode(&geobuf).unwrap(), geojson);
+    ///
+    /// let geojson = serde_json::from_str(r#"{"type": "Point", "coordinates": [2500000.125, 1200000.5]}"#).unwrap();
+    /// let options = EncoderOptions {
+    ///     precision: 3,
+    ///     affine: Some(Affine::origin([2500000.0, 1200000.0])),
+    ///     ..Default::default()
+    /// };
+    /// let geobuf = Encoder::encode_with_options(&geojson, &options).unwrap();
+    /// assert_eq!(geobuf.geometry().coords, vec![125, 500]);
+    /// assert_eq!(Decoder::decode(&geobuf).unwrap(), geojson);
+    /// ```
+    pub fn encode_with_options(
+        geojson: &JSONValue,
+        options: &EncoderOptions,
+    ) -> Result<geobuf_pb::Data, &'static str> {
+        let (precision, transform) = options.quantization()?;
+        Encoder::encode_with_transform(geojson, precision, options.dim, transform)
+    }
+
+    /// Returns the size in bytes that encoding `geojson` with `options` would produce,
+    /// without building the protobuf message
+    ///
+    /// The size is computed from the same quantization and property tables as the encoder
+    /// uses, so it matches the serialized size of a successful encode. Invalid input yields
+    /// an estimate of what could be encoded. TopoJSON input is encoded to be measured.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use geobuf::encode::{Encoder, EncoderOptions};
+    ///
+    /// let geojson = serde_json::from_str(r#"{"type": "Feature", "properties": {"name": "a"},
+    ///     "geometry": {"type": "LineString", "coordinates": [[0.0, 0.0], [1.5, 2.25]]}}"#).unwrap();
+    /// let options = EncoderOptions::default();
+    /// let size = Encoder::estimate_size(&geojson, &options);
+    /// let data = Encoder::encode_with_options(&geojson, &options).unwrap();
+    /// assert_eq!(size, geobuf::codec::write(&data).len());
+    /// ```
+    pub fn estimate_size(geojson: &JSONValue, options: &EncoderOptions) -> usize {
+        let (precision, transform) = match options.quantization() {
+            Ok(quantization) => quantization,
+            Err(_) => return 0,
         };
+        if geojson["type"] == "Topology" {
+            return match Encoder::encode_with_transform(geojson, precision, options.dim, transform)
+            {
+                Ok(data) => codec::size(&data) as usize,
+                Err(_) => 0,
+            };
+        }
+
+        let mut encoder = Encoder::new(precision, options.dim, transform);
+        let body = match geojson["type"].as_str() {
+            Some("FeatureCollection") => encoder.estimate_feature_collection(geojson),
+            Some("Feature") => encoder.estimate_feature(geojson),
+            _ => encoder.estimate_geometry(geojson),
+        };
+
+        let data = &encoder.data;
+        let mut size = message_field_size(body);
+        size += uint_field_size(data.dimensions().into());
+        size += uint_field_size(data.precision().into());
+        for key in &data.keys {
+            size += string_field_size(key);
+        }
+        if let Some(transform) = data.transform.as_ref() {
+            let doubles = [
+                transform.scale_x,
+                transform.scale_y,
+                transform.translate_x,
+                transform.translate_y,
+            ];
+            size += message_field_size(9 * doubles.iter().flatten().count() as u64);
+        }
+        size as usize
+    }
+
+    /// Returns the smallest precision, up to 6, and the number of dimensions, at least 2,
+    /// that keep every coordinate of `geojson`, like the analysis pass of the JavaScript
+    /// encoder
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use geobuf::encode::Encoder;
+    ///
+    /// let geojson = serde_json::from_str(r#"{"type": "LineString", "coordinates": [[1.5, 2.0], [3.25, 4.0, 10.0]]}"#).unwrap();
+    /// assert_eq!(Encoder::analyze(&geojson), (2, 3));
+    /// ```
+    pub fn analyze(geojson: &JSONValue) -> (u32, u32) {
+        let mut analysis = (0, 2);
+        analyze_object(geojson, &mut analysis);
+        analysis
+    }
+
+    /// Returns why encoding the GeoJSON `feature` with `precision` and `dim` would fail or
+    /// give a wrong result, or `Ok` if it can be encoded
+    ///
+    /// Problems are geometries that are missing or malformed, positions with fewer than
+    /// `dim` coordinates, coordinates too large to quantize, and ids that aren't strings or
+    /// 64 bit integers.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use geobuf::encode::Encoder;
+    ///
+    /// let feature = serde_json::from_str(r#"{"type": "Feature", "properties": {},
+    ///     "geometry": {"type": "LineString", "coordinates": [[0.0, 0.0], [1.0]]}}"#).unwrap();
+    /// assert_eq!(Encoder::check_feature(&feature, 6, 2), Err("Position with too few coordinates."));
+    /// assert_eq!(Encoder::check_feature(&feature, 6, 1), Ok(()));
+    /// ```
+    pub fn check_feature(
+        feature: &JSONValue,
+        precision: u32,
+        dim: u32,
+    ) -> Result<(), &'static str> {
+        match &feature["id"] {
+            JSONValue::Number(id) if id.as_i64().is_none() => return Err("Invalid id."),
+            _ => {}
+        }
+        check_geometry(
+            &feature["geometry"],
+            10f64.powi(precision as i32),
+            dim as usize,
+        )
+    }
+
+    /// Returns the first problem that would make encoding `geojson` fail or panic, prefixed
+    /// with the index of its feature in FeatureCollections, or `Ok` if it can be encoded
+    ///
+    /// Features are checked with `check_feature`, and geometries as the geometry of a
+    /// feature. Topologies are checked for malformed arcs and objects, arc indexes that don't
+    /// fit in 32 bits, and non-integer coordinates in quantized arcs.
+    pub fn check(geojson: &JSONValue, precision: u32, dim: u32) -> Result<(), String> {
+        match geojson["type"].as_str() {
+            Some("FeatureCollection") => {
+                let features = geojson["features"]
+                    .as_array()
+                    .ok_or("FeatureCollection without features.")?;
+                for (n, feature) in features.iter().enumerate() {
+                    Encoder::check_feature(feature, precision, dim)
+                        .map_err(|err| format!("Feature {}: {}", n, err))?;
+                }
+                Ok(())
+            }
+            Some("Feature") => Ok(Encoder::check_feature(geojson, precision, dim)?),
+            Some("Topology") => {
+                let e = 10f64.powi(precision as i32);
+                Ok(check_topology(geojson, e, dim as usize)?)
+            }
+            Some(_) => {
+                let feature = serde_json::json!({"type": "Feature", "geometry": geojson});
+                Ok(Encoder::check_feature(&feature, precision, dim)?)
+            }
+            None => Err(String::from("Missing GeoJSON type.")),
+        }
+    }
+
+    /// Encodes `geojson`, mapping x and y through the inverse of `transform` before
+    /// quantization
+    pub(crate) fn encode_with_transform(
+        geojson: &JSONValue,
+        precision: u32,
+        dim: u32,
+        transform: Option<geobuf_pb::data::Transform>,
+    ) -> Result<geobuf_pb::Data, &'static str> {
+        let mut encoder = Encoder::new(precision, dim, transform);
 
         match geojson["type"].as_str().unwrap() {
             "FeatureCollection" => match encoder.encode_feature_collection(geojson) {
@@ -64,6 +339,13 @@ impl Encoder {
                 Ok(f) => encoder.data.set_feature(f),
                 Err(err) => return Err(err),
             },
+            "Topology" if encoder.data.transform.is_some() => {
+                return Err("Topologies can't be encoded with a transform.")
+            }
+            "Topology" => match encoder.encode_topology(geojson) {
+                Ok(t) => encoder.data.set_topology(t),
+                Err(err) => return Err(err),
+            },
             _ => match encoder.encode_geometry(geojson) {
                 Ok(g) => encoder.data.set_geometry(g),
                 Err(err) => return Err(err),
@@ -73,6 +355,29 @@ impl Encoder {
         Ok(encoder.data)
     }
 
+    fn new(precision: u32, dim: u32, transform: Option<geobuf_pb::data::Transform>) -> Encoder {
+        let mut data = geobuf_pb::Data::new();
+        data.set_precision(precision);
+        data.set_dimensions(dim);
+
+        let (translate, scale) = match &transform {
+            Some(t) => (
+                [t.translate_x(), t.translate_y()],
+                [t.scale_x(), t.scale_y()],
+            ),
+            None => ([0.0, 0.0], [1.0, 1.0]),
+        };
+        data.transform = transform.into();
+
+        Encoder {
+            data,
+            dim: dim as usize,
+            e: 10f64.powi(precision as i32),
+            translate,
+            scale,
+        }
+    }
+
     fn encode_feature_collection(
         &mut self,
         geojson: &JSONValue,
@@ -176,8 +481,13 @@ impl Encoder {
             }
             "Point" => {
                 geometry.set_type(geobuf_pb::data::geometry::Type::POINT);
-                for coord in geometry_json["coordinates"].as_array().unwrap() {
-                    self.add_coord(&mut geometry.coords, coord.as_f64().unwrap());
+                for (j, coord) in geometry_json["coordinates"]
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .enumerate()
+                {
+                    self.add_coord(&mut geometry.coords, coord.as_f64().unwrap(), j);
                 }
             }
             "MultiPoint" => {
@@ -226,6 +536,187 @@ impl Encoder {
         Ok(geometry)
     }
 
+    fn encode_topology(
+        &mut self,
+        topology_json: &JSONValue,
+    ) -> Result<geobuf_pb::data::Topology, &'static str> {
+        let mut topology = geobuf_pb::data::Topology::new();
+
+        let custom_properties = self.encode_custom_properties(
+            &mut topology.values,
+            topology_json,
+            vec!["type", "transform", "objects", "arcs"],
+        );
+        topology.custom_properties = custom_properties;
+
+        // Quantized topologies already store integer, delta-encoded arcs; keep them as is.
+        let transform_json = &topology_json["transform"];
+        if transform_json.is_object() {
+            let mut transform = geobuf_pb::data::Transform::new();
+            let scale = &transform_json["scale"];
+            let translate = &transform_json["translate"];
+            transform.scale_x = scale[0].as_f64();
+            transform.scale_y = scale[1].as_f64();
+            transform.translate_x = translate[0].as_f64();
+            transform.translate_y = translate[1].as_f64();
+            topology.transform = MessageField::some(transform);
+            self.e = 1.0;
+        }
+
+        if let Some(arcs) = topology_json["arcs"].as_array() {
+            for arc_json in arcs {
+                let points = match arc_json.as_array() {
+                    Some(points) => points,
+                    None => return Err("Invalid topology arc"),
+                };
+                topology.lengths.push(points.len() as u32);
+                if topology.transform.is_some() {
+                    for point in points {
+                        for j in 0..self.dim {
+                            topology.coords.push(quantized_coord(point, j)?);
+                        }
+                    }
+                } else {
+                    check_coordinates(arc_json, 1, false, self.e, self.dim)?;
+                    self.add_line(&mut topology.coords, points, false);
+                }
+            }
+        }
+
+        if let Some(objects) = topology_json["objects"].as_object() {
+            for (name, object_json) in objects.iter() {
+                match self.encode_topology_geometry(object_json) {
+                    Ok(g) => {
+                        topology.names.push(String::from(name));
+                        topology.objects.push(g);
+                    }
+                    Err(err) => return Err(err),
+                }
+            }
+        }
+
+        Ok(topology)
+    }
+
+    fn encode_topology_geometry(
+        &mut self,
+        geometry_json: &JSONValue,
+    ) -> Result<geobuf_pb::data::Geometry, &'static str> {
+        if !geometry_json.is_object() {
+            return Err("Invalid topology object.");
+        }
+        let mut geometry = geobuf_pb::data::Geometry::new();
+
+        match &geometry_json["id"] {
+            JSONValue::Number(id) => match id.as_i64() {
+                Some(id) => geometry.set_int_id(id),
+                None => return Err("Invalid id."),
+            },
+            JSONValue::String(id) => geometry.set_id(String::from(id)),
+            _ => {}
+        }
+
+        if let Some(properties_json) = geometry_json["properties"].as_object() {
+            let mut properties: Vec<u32> = Vec::new();
+            for (key, value) in properties_json.iter() {
+                self.encode_property(
+                    String::from(key),
+                    value,
+                    &mut properties,
+                    &mut geometry.values,
+                );
+            }
+            geometry.properties = properties;
+        }
+
+        let custom_properties = self.encode_custom_properties(
+            &mut geometry.values,
+            geometry_json,
+            vec![
+                "type",
+                "id",
+                "coordinates",
+                "arcs",
+                "geometries",
+                "properties",
+            ],
+        );
+        geometry.custom_properties = custom_properties;
+
+        let arcs_json = &geometry_json["arcs"];
+        match geometry_json["type"].as_str() {
+            Some("GeometryCollection") => {
+                geometry.set_type(geobuf_pb::data::geometry::Type::GEOMETRYCOLLECTION);
+                let geometries = geometry_json["geometries"]
+                    .as_array()
+                    .ok_or("Invalid geometry collection.")?;
+                for geom_json in geometries {
+                    match self.encode_topology_geometry(geom_json) {
+                        Ok(g) => geometry.geometries.push(g),
+                        Err(err) => return Err(err),
+                    }
+                }
+            }
+            Some("Point") => {
+                geometry.set_type(geobuf_pb::data::geometry::Type::POINT);
+                check_geometry(geometry_json, self.e, self.dim)?;
+                for (j, coord) in geometry_json["coordinates"]
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .enumerate()
+                {
+                    self.add_coord(&mut geometry.coords, coord.as_f64().unwrap(), j);
+                }
+            }
+            Some("MultiPoint") => {
+                geometry.set_type(geobuf_pb::data::geometry::Type::MULTIPOINT);
+                check_geometry(geometry_json, self.e, self.dim)?;
+                self.add_line(
+                    &mut geometry.coords,
+                    geometry_json["coordinates"].as_array().unwrap(),
+                    false,
+                );
+            }
+            Some("LineString") => {
+                geometry.set_type(geobuf_pb::data::geometry::Type::LINESTRING);
+                Encoder::add_arcs(&mut geometry.arcs, arcs_json)?;
+            }
+            Some("MultiLineString") | Some("Polygon") => {
+                if geometry_json["type"] == "Polygon" {
+                    geometry.set_type(geobuf_pb::data::geometry::Type::POLYGON);
+                } else {
+                    geometry.set_type(geobuf_pb::data::geometry::Type::MULTILINESTRING);
+                }
+                for line_json in topology_arcs(arcs_json)? {
+                    geometry
+                        .lengths
+                        .push(topology_arcs(line_json)?.len() as u32);
+                    Encoder::add_arcs(&mut geometry.arcs, line_json)?;
+                }
+            }
+            Some("MultiPolygon") => {
+                geometry.set_type(geobuf_pb::data::geometry::Type::MULTIPOLYGON);
+                let polygons_json = topology_arcs(arcs_json)?;
+                geometry.lengths.push(polygons_json.len() as u32);
+                for rings_json in polygons_json {
+                    let rings = topology_arcs(rings_json)?;
+                    geometry.lengths.push(rings.len() as u32);
+                    for ring_json in rings {
+                        geometry
+                            .lengths
+                            .push(topology_arcs(ring_json)?.len() as u32);
+                        Encoder::add_arcs(&mut geometry.arcs, ring_json)?;
+                    }
+                }
+            }
+            _ => {
+                return Err("Invalid geometry type");
+            }
+        }
+        Ok(geometry)
+    }
+
     fn encode_custom_properties(
         &mut self,
         values: &mut Vec<geobuf_pb::data::Value>,
@@ -292,8 +783,23 @@ impl Encoder {
         }
     }
 
-    fn add_coord(&self, coords: &mut Vec<i64>, coord: f64) {
-        coords.push((coord * self.e).round() as i64);
+    fn add_coord(&self, coords: &mut Vec<i64>, coord: f64, j: usize) {
+        coords.push(self.quantize(coord, j));
+    }
+
+    fn quantize(&self, coord: f64, j: usize) -> i64 {
+        let coord = match j {
+            0 | 1 => (coord - self.translate[j]) / self.scale[j],
+            _ => coord,
+        };
+        (coord * self.e).round() as i64
+    }
+
+    fn add_arcs(arcs: &mut Vec<i32>, arcs_json: &JSONValue) -> Result<(), &'static str> {
+        for arc in topology_arcs(arcs_json)? {
+            arcs.push(arc_index(arc)?);
+        }
+        Ok(())
     }
 
     fn add_line(&self, coords: &mut Vec<i64>, points: &[JSONValue], is_closed: bool) {
@@ -301,7 +807,7 @@ impl Encoder {
         for point in points.iter().take(points.len() - is_closed as usize) {
             for j in 0..self.dim {
                 let coord = point[j].as_f64().unwrap();
-                let n = (coord * self.e).round() as i64 - sum[j];
+                let n = self.quantize(coord, j) - sum[j];
                 coords.push(n);
                 sum[j] += n;
             }
@@ -357,3 +863,438 @@ impl Encoder {
         }
     }
 }
+
+/// Sizes of the values and property index tables of an object, see `Encoder::estimate_size`
+#[derive(Default)]
+struct TableSize {
+    values: u32,
+    values_size: u64,
+}
+
+impl Encoder {
+    fn estimate_feature_collection(&mut self, geojson: &JSONValue) -> u64 {
+        let mut table = TableSize::default();
+        let custom_properties =
+            self.estimate_custom_properties(&mut table, geojson, &["type", "features"]);
+        let mut size = table.values_size + packed_field_size(custom_properties);
+        if let Some(features) = geojson["features"].as_array() {
+            for feature in features {
+                size += message_field_size(self.estimate_feature(feature));
+            }
+        }
+        size
+    }
+
+    fn estimate_feature(&mut self, feature_json: &JSONValue) -> u64 {
+        let mut size = 0;
+        match &feature_json["id"] {
+            JSONValue::Number(id) => size += uint_field_size(zigzag(id.as_i64().unwrap_or(0))),
+            JSONValue::String(id) => size += string_field_size(id),
+            _ => {}
+        }
+
+        let mut table = TableSize::default();
+        let mut properties = 0;
+        if let Some(properties_json) = feature_json["properties"].as_object() {
+            for (key, value) in properties_json.iter() {
+                properties += self.estimate_property(key, value, &mut table);
+            }
+        }
+        let custom_properties = self.estimate_custom_properties(
+            &mut table,
+            feature_json,
+            &["type", "id", "properties", "geometry"],
+        );
+        size += table.values_size;
+        size += packed_field_size(properties);
+        size += packed_field_size(custom_properties);
+
+        size + message_field_size(self.estimate_geometry(&feature_json["geometry"]))
+    }
+
+    fn estimate_geometry(&mut self, geometry_json: &JSONValue) -> u64 {
+        let mut table = TableSize::default();
+        let custom_properties = self.estimate_custom_properties(
+            &mut table,
+            geometry_json,
+            &[
+                "type",
+                "id",
+                "coordinates",
+                "arcs",
+                "geometries",
+                "properties",
+            ],
+        );
+        let mut size = table.values_size + packed_field_size(custom_properties);
+
+        let coordinates = &geometry_json["coordinates"];
+        let mut lengths = 0;
+        let mut coords = 0;
+        match geometry_json["type"].as_str() {
+            Some("GeometryCollection") => {
+                if let Some(geometries) = geometry_json["geometries"].as_array() {
+                    for geometry in geometries {
+                        size += message_field_size(self.estimate_geometry(geometry));
+                    }
+                }
+            }
+            Some("Point") => {
+                if let Some(point) = coordinates.as_array() {
+                    for (j, coord) in point.iter().enumerate() {
+                        let coord = self.quantize(coord.as_f64().unwrap_or(0.0), j);
+                        coords += varint_size(zigzag(coord));
+                    }
+                }
+            }
+            Some("MultiPoint") | Some("LineString") => {
+                coords += self.estimate_line(coordinates, false);
+            }
+            Some("MultiLineString") | Some("Polygon") => {
+                let is_closed = geometry_json["type"] == "Polygon";
+                let lines = coordinates.as_array().map(Vec::as_slice).unwrap_or(&[]);
+                for line in lines {
+                    if lines.len() != 1 {
+                        lengths += varint_size(line_len(line, is_closed));
+                    }
+                    coords += self.estimate_line(line, is_closed);
+                }
+            }
+            Some("MultiPolygon") => {
+                let polygons = coordinates.as_array().map(Vec::as_slice).unwrap_or(&[]);
+                let single = polygons.len() == 1
+                    && polygons[0].as_array().map(|rings| rings.len()) == Some(1);
+                if !single {
+                    lengths += varint_size(polygons.len() as u64);
+                }
+                for rings in polygons {
+                    let rings = rings.as_array().map(Vec::as_slice).unwrap_or(&[]);
+                    if !single {
+                        lengths += varint_size(rings.len() as u64);
+                    }
+                    for ring in rings {
+                        if !single {
+                            lengths += varint_size(line_len(ring, true));
+                        }
+                        coords += self.estimate_line(ring, true);
+                    }
+                }
+            }
+            _ => return size,
+        }
+
+        // The geometry type is always written and fits in a single byte.
+        size + 2 + packed_field_size(lengths) + packed_field_size(coords)
+    }
+
+    /// Returns the size of the packed delta-encoded coordinates of a line
+    fn estimate_line(&self, points_json: &JSONValue, is_closed: bool) -> u64 {
+        let points = points_json.as_array().map(Vec::as_slice).unwrap_or(&[]);
+        let mut size = 0;
+        let mut sum = vec![0; self.dim];
+        for point in points
+            .iter()
+            .take(points.len().saturating_sub(is_closed as usize))
+        {
+            for j in 0..self.dim {
+                let n = self.quantize(point[j].as_f64().unwrap_or(0.0), j) - sum[j];
+                size += varint_size(zigzag(n));
+                sum[j] += n;
+            }
+        }
+        size
+    }
+
+    fn estimate_custom_properties(
+        &mut self,
+        table: &mut TableSize,
+        json: &JSONValue,
+        exclude: &[&str],
+    ) -> u64 {
+        let mut size = 0;
+        if let Some(object) = json.as_object() {
+            for (key, value) in object.iter() {
+                if !exclude.contains(&key.as_str()) {
+                    size += self.estimate_property(key, value, table);
+                }
+            }
+        }
+        size
+    }
+
+    /// Adds the value of a property to `table` and returns the size of its index pair
+    fn estimate_property(&mut self, key: &str, value: &JSONValue, table: &mut TableSize) -> u64 {
+        let key_index = match self.data.keys.iter().position(|k| k == key) {
+            Some(key_index) => key_index,
+            None => {
+                self.data.keys.push(String::from(key));
+                self.data.keys.len() - 1
+            }
+        };
+
+        let value_size = match value {
+            JSONValue::String(v) => Some(string_field_size(v)),
+            JSONValue::Bool(_) => Some(2),
+            JSONValue::Number(v) => Some(match (v.as_u64(), v.as_i64()) {
+                (Some(v), _) => uint_field_size(v),
+                (None, Some(v)) => uint_field_size(v.unsigned_abs()),
+                _ => 9,
+            }),
+            JSONValue::Object(_) | JSONValue::Array(_) => {
+                Some(string_field_size(&value.to_string()))
+            }
+            JSONValue::Null => None,
+        };
+        if let Some(value_size) = value_size {
+            table.values += 1;
+            table.values_size += message_field_size(value_size);
+        }
+        varint_size(key_index as u64) + varint_size(table.values.saturating_sub(1) as u64)
+    }
+}
+
+/// Checks a geometry for `Encoder::check_feature`, `e` being the quantization multiplier
+fn check_geometry(geometry: &JSONValue, e: f64, dim: usize) -> Result<(), &'static str> {
+    let depth = match geometry["type"].as_str() {
+        Some("GeometryCollection") => {
+            return match geometry["geometries"].as_array() {
+                Some(geometries) => geometries
+                    .iter()
+                    .try_for_each(|geometry| check_geometry(geometry, e, dim)),
+                None => Err("Invalid geometry collection."),
+            };
+        }
+        Some("Point") => 0,
+        Some("MultiPoint") | Some("LineString") => 1,
+        Some("MultiLineString") | Some("Polygon") => 2,
+        Some("MultiPolygon") => 3,
+        _ => return Err("Invalid geometry type."),
+    };
+    let rings = matches!(
+        geometry["type"].as_str(),
+        Some("Polygon") | Some("MultiPolygon")
+    );
+    check_coordinates(&geometry["coordinates"], depth, rings, e, dim)?;
+    if depth == 0 && geometry["coordinates"].as_array().map(Vec::len) != Some(dim) {
+        return Err("Point with a number of coordinates other than the dimensions.");
+    }
+    Ok(())
+}
+
+/// Checks coordinates nested `depth` arrays deep above positions, rings being the arrays of
+/// positions when `rings` is true
+fn check_coordinates(
+    coordinates: &JSONValue,
+    depth: usize,
+    rings: bool,
+    e: f64,
+    dim: usize,
+) -> Result<(), &'static str> {
+    let values = coordinates.as_array().ok_or("Invalid coordinates.")?;
+    if depth > 0 {
+        if depth == 1 && rings && values.is_empty() {
+            return Err("Empty ring.");
+        }
+        return values
+            .iter()
+            .try_for_each(|value| check_coordinates(value, depth - 1, rings, e, dim));
+    }
+    if values.len() < dim {
+        return Err("Position with too few coordinates.");
+    }
+    // Deltas between quantized coordinates must fit in an i64 too
+    let max = (i64::MAX / 2) as f64;
+    for value in values {
+        match value.as_f64() {
+            Some(coord) if (coord * e).abs() < max => {}
+            Some(_) => return Err("Coordinate too large to quantize."),
+            None => return Err("Invalid coordinates."),
+        }
+    }
+    Ok(())
+}
+
+/// Checks a TopoJSON topology for `Encoder::check`, `e` being the quantization multiplier
+/// of unquantized topologies
+fn check_topology(topology: &JSONValue, e: f64, dim: usize) -> Result<(), &'static str> {
+    // Quantized topologies keep their integer coordinates as they are
+    let quantized = topology["transform"].is_object();
+    let e = if quantized { 1.0 } else { e };
+    if let Some(arcs) = topology["arcs"].as_array() {
+        for arc in arcs {
+            let points = arc.as_array().ok_or("Invalid topology arc")?;
+            if !quantized {
+                check_coordinates(arc, 1, false, e, dim)?;
+                continue;
+            }
+            for point in points {
+                for j in 0..dim {
+                    quantized_coord(point, j)?;
+                }
+            }
+        }
+    }
+    match topology["objects"].as_object() {
+        Some(objects) => objects
+            .values()
+            .try_for_each(|object| check_topology_geometry(object, e, dim)),
+        None => Ok(()),
+    }
+}
+
+/// Checks a geometry of the objects of a topology, whose lines refer to arcs by index
+fn check_topology_geometry(geometry: &JSONValue, e: f64, dim: usize) -> Result<(), &'static str> {
+    if !geometry.is_object() {
+        return Err("Invalid topology object.");
+    }
+    match &geometry["id"] {
+        JSONValue::Number(id) if id.as_i64().is_none() => return Err("Invalid id."),
+        _ => {}
+    }
+    let depth = match geometry["type"].as_str() {
+        Some("GeometryCollection") => {
+            return match geometry["geometries"].as_array() {
+                Some(geometries) => geometries
+                    .iter()
+                    .try_for_each(|geometry| check_topology_geometry(geometry, e, dim)),
+                None => Err("Invalid geometry collection."),
+            };
+        }
+        Some("Point") | Some("MultiPoint") => return check_geometry(geometry, e, dim),
+        Some("LineString") => 0,
+        Some("MultiLineString") | Some("Polygon") => 1,
+        Some("MultiPolygon") => 2,
+        _ => return Err("Invalid geometry type."),
+    };
+    check_arc_indexes(&geometry["arcs"], depth)
+}
+
+/// Checks arc indexes nested `depth` arrays deep
+fn check_arc_indexes(arcs: &JSONValue, depth: usize) -> Result<(), &'static str> {
+    let values = topology_arcs(arcs)?;
+    match depth {
+        0 => values.iter().try_for_each(|arc| arc_index(arc).map(|_| ())),
+        _ => values
+            .iter()
+            .try_for_each(|value| check_arc_indexes(value, depth - 1)),
+    }
+}
+
+fn topology_arcs(arcs: &JSONValue) -> Result<&Vec<JSONValue>, &'static str> {
+    arcs.as_array().ok_or("Invalid topology arcs.")
+}
+
+/// Returns a TopoJSON arc index, negative for reversed arcs, if it fits in the 32 bit arcs
+/// of Geobuf geometries
+fn arc_index(arc: &JSONValue) -> Result<i32, &'static str> {
+    match arc.as_i64().map(i32::try_from) {
+        Some(Ok(arc)) => Ok(arc),
+        _ => Err("Invalid arc index."),
+    }
+}
+
+/// Returns coordinate `j` of a position of a quantized topology, which must be an integer
+fn quantized_coord(point: &JSONValue, j: usize) -> Result<i64, &'static str> {
+    point[j]
+        .as_i64()
+        .ok_or("Non-integer coordinate in a quantized topology.")
+}
+
+/// Largest precision picked by `Encoder::analyze`
+const MAX_ANALYZED_PRECISION: u32 = 6;
+
+/// Updates the `(precision, dim)` of `Encoder::analyze` with the positions of a GeoJSON or
+/// TopoJSON object, leaving out properties
+fn analyze_object(geojson: &JSONValue, analysis: &mut (u32, u32)) {
+    let children: &[&str] = match geojson["type"].as_str() {
+        Some("FeatureCollection") => &["features"],
+        Some("Feature") => &["geometry"],
+        Some("GeometryCollection") => &["geometries"],
+        // Geometries of topologies refer to arcs by index
+        Some("Topology") => {
+            analyze_positions(&geojson["arcs"], analysis);
+            if let Some(objects) = geojson["objects"].as_object() {
+                for object in objects.values() {
+                    analyze_object(object, analysis);
+                }
+            }
+            return;
+        }
+        _ => {
+            analyze_positions(&geojson["coordinates"], analysis);
+            return;
+        }
+    };
+    for child in children {
+        match &geojson[child] {
+            JSONValue::Array(objects) => {
+                for object in objects {
+                    analyze_object(object, analysis);
+                }
+            }
+            object => analyze_object(object, analysis),
+        }
+    }
+}
+
+fn analyze_positions(coordinates: &JSONValue, analysis: &mut (u32, u32)) {
+    let values = match coordinates.as_array() {
+        Some(values) => values,
+        None => return,
+    };
+    if !matches!(values.first(), Some(JSONValue::Number(_))) {
+        for value in values {
+            analyze_positions(value, analysis);
+        }
+        return;
+    }
+    let (precision, dim) = analysis;
+    *dim = (*dim).max(values.len() as u32);
+    for coord in values.iter().filter_map(JSONValue::as_f64) {
+        let mut e = 10f64.powi(*precision as i32);
+        while *precision < MAX_ANALYZED_PRECISION && (coord * e).round() / e != coord {
+            *precision += 1;
+            e *= 10.0;
+        }
+    }
+}
+
+/// Returns the number of points stored for a line, without the closing point of rings
+fn line_len(points: &JSONValue, is_closed: bool) -> u64 {
+    let len = points.as_array().map(Vec::len).unwrap_or(0);
+    len.saturating_sub(is_closed as usize) as u64
+}
+
+/// Returns the size of an embedded message field with a one byte tag
+fn message_field_size(len: u64) -> u64 {
+    1 + varint_size(len) + len
+}
+
+/// Returns the size of a packed repeated field with a one byte tag holding `len` bytes
+fn packed_field_size(len: u64) -> u64 {
+    match len {
+        0 => 0,
+        len => 1 + varint_size(len) + len,
+    }
+}
+
+/// Returns the size of a varint field with a one byte tag
+fn uint_field_size(n: u64) -> u64 {
+    1 + varint_size(n)
+}
+
+/// Returns the size of a string field with a one byte tag
+fn string_field_size(s: &str) -> u64 {
+    message_field_size(s.len() as u64)
+}
+
+fn varint_size(n: u64) -> u64 {
+    match n {
+        0 => 1,
+        n => (70 - u64::from(n.leading_zeros())) / 7,
+    }
+}
+
+fn zigzag(n: i64) -> u64 {
+    ((n << 1) ^ (n >> 63)) as u64
+}
@@ -0,0 +1,202 @@
+//! C bindings, declared in `include/geobuf.h`
+//!
+//! Built with the `ffi` feature, the cdylib exports `geobuf_encode` and `geobuf_decode` for C,
+//! C++ and other runtimes with a C FFI. Inputs are borrowed for the duration of a call.
+//! Outputs are allocated by the library, handed over in a `GeobufBuffer` and have to be
+//! released with `geobuf_buffer_free`. Functions return 0 on success, and on failure -1 with
+//! the reason available from `geobuf_last_error`. Panics are caught and reported the same way
+//! instead of unwinding into the caller.
+//!
+//! The header is generated with
+//! `cbindgen --config cbindgen.toml --output include/geobuf.h src/ffi.rs`.
+use std::cell::RefCell;
+use std::ffi::{c_char, CString};
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+use std::slice;
+
+use crate::codec;
+use crate::decode::Decoder;
+use crate::encode::Encoder;
+use crate::validate::validate;
+
+thread_local! {
+    /// Reason of the last failed call on this thread
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Bytes owned by the library
+///
+/// An empty buffer has a null `data` pointer and a `len` of 0.
+#[repr(C)]
+pub struct GeobufBuffer {
+    pub data: *mut u8,
+    pub len: usize,
+}
+
+impl GeobufBuffer {
+    fn empty() -> Self {
+        GeobufBuffer {
+            data: ptr::null_mut(),
+            len: 0,
+        }
+    }
+}
+
+impl From<Vec<u8>> for GeobufBuffer {
+    fn from(bytes: Vec<u8>) -> Self {
+        let len = bytes.len();
+        let data = Box::into_raw(bytes.into_boxed_slice()) as *mut u8;
+        GeobufBuffer { data, len }
+    }
+}
+
+/// Encodes the `len` bytes of UTF-8 GeoJSON at `json` into a Geobuf stored in `out`
+///
+/// Returns 0 on success, or -1 if `json` isn't valid JSON or holds GeoJSON that can't be
+/// encoded, leaving `out` empty.
+///
+/// # Safety
+///
+/// `json` must point to `len` readable bytes, and `out` to a writable `GeobufBuffer`. The
+/// buffer previously held by `out` isn't freed.
+#[no_mangle]
+pub unsafe extern "C" fn geobuf_encode(
+    json: *const u8,
+    len: usize,
+    precision: u32,
+    dim: u32,
+    out: *mut GeobufBuffer,
+) -> i32 {
+    let result = input(json, len).and_then(|json| catch(|| encode(json, precision, dim)));
+    output(result, out)
+}
+
+/// Decodes the `len` bytes of the Geobuf at `data` into UTF-8 GeoJSON stored in `out`
+///
+/// The GeoJSON isn't null terminated. Returns 0 on success, or -1 if `data` isn't a valid
+/// Geobuf, leaving `out` empty.
+///
+/// # Safety
+///
+/// `data` must point to `len` readable bytes, and `out` to a writable `GeobufBuffer`. The
+/// buffer previously held by `out` isn't freed.
+#[no_mangle]
+pub unsafe extern "C" fn geobuf_decode(data: *const u8, len: usize, out: *mut GeobufBuffer) -> i32 {
+    let result = input(data, len).and_then(|data| catch(|| decode(data)));
+    output(result, out)
+}
+
+/// Frees the bytes of a buffer filled by `geobuf_encode` or `geobuf_decode` and empties it
+///
+/// Freeing an empty buffer does nothing.
+///
+/// # Safety
+///
+/// `buffer` must be null or point to a buffer filled by this library and not modified since.
+#[no_mangle]
+pub unsafe extern "C" fn geobuf_buffer_free(buffer: *mut GeobufBuffer) {
+    let buffer = match buffer.as_mut() {
+        Some(buffer) => buffer,
+        None => return,
+    };
+    if !buffer.data.is_null() {
+        drop(Box::from_raw(ptr::slice_from_raw_parts_mut(
+            buffer.data,
+            buffer.len,
+        )));
+    }
+    *buffer = GeobufBuffer::empty();
+}
+
+/// Returns the reason of the last failed call on the calling thread as a null terminated
+/// string, or null if no call failed
+///
+/// The string is owned by the library and stays valid until the next failed call on the same
+/// thread.
+#[no_mangle]
+pub extern "C" fn geobuf_last_error() -> *const c_char {
+    LAST_ERROR.with(|error| {
+        error
+            .borrow()
+            .as_ref()
+            .map_or(ptr::null(), |error| error.as_ptr())
+    })
+}
+
+/// Borrows the input of a call
+unsafe fn input<'a>(data: *const u8, len: usize) -> Result<&'a [u8], String> {
+    if data.is_null() {
+        // Empty inputs may come as null pointers
+        return match len {
+            0 => Ok(&[]),
+            _ => Err(String::from("Null input.")),
+        };
+    }
+    Ok(slice::from_raw_parts(data, len))
+}
+
+/// Stores the result of a call in `out` or the last error, and returns the status code
+unsafe fn output(result: Result<Vec<u8>, String>, out: *mut GeobufBuffer) -> i32 {
+    let out = match out.as_mut() {
+        Some(out) => out,
+        None => {
+            set_error(String::from("Null output."));
+            return -1;
+        }
+    };
+    match result {
+        Ok(bytes) => {
+            *out = GeobufBuffer::from(bytes);
+            0
+        }
+        Err(err) => {
+            set_error(err);
+            *out = GeobufBuffer::empty();
+            -1
+        }
+    }
+}
+
+/// Runs a call, turning a panic into an error, since unwinding out of an `extern "C"`
+/// function aborts the process
+pub(crate) fn catch(call: impl FnOnce() -> Result<Vec<u8>, String>) -> Result<Vec<u8>, String> {
+    match panic::catch_unwind(AssertUnwindSafe(call)) {
+        Ok(result) => result,
+        Err(payload) => {
+            let message = match payload.downcast_ref::<&str>() {
+                Some(message) => String::from(*message),
+                None => payload
+                    .downcast_ref::<String>()
+                    .cloned()
+                    .unwrap_or_default(),
+            };
+            Err(format!("Panicked: {}", message))
+        }
+    }
+}
+
+fn set_error(err: String) {
+    // Messages come from this crate and never contain null bytes
+    let err = CString::new(err).unwrap_or_default();
+    LAST_ERROR.with(|error| *error.borrow_mut() = Some(err));
+}
+
+fn encode(json: &[u8], precision: u32, dim: u32) -> Result<Vec<u8>, String> {
+    let geojson =
+        serde_json::from_slice(json).map_err(|err| format!("Could not parse geojson: {}", err))?;
+    // Gives the reason for malformed geometries, which encoding would panic on
+    Encoder::check(&geojson, precision, dim)?;
+    let data = Encoder::encode(&geojson, precision, dim)?;
+    Ok(codec::serialize(&data)?)
+}
+
+fn decode(data: &[u8]) -> Result<Vec<u8>, String> {
+    let geobuf = codec::parse(data)?;
+    // Decoding panics on out of range indexes and lengths
+    if let Some(problem) = validate(&geobuf).first() {
+        return Err(format!("Invalid geobuf: {}", problem));
+    }
+    let geojson = Decoder::decode(&geobuf)?;
+    serde_json::to_vec(&geojson).map_err(|err| err.to_string())
+}
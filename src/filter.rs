@@ -0,0 +1,358 @@
+//! Selecting features with property expressions such as `population > 100000 && state == 'CA'`
+//!
+//! Expressions compare feature properties with literals using `==`, `!=`, `<`, `<=`, `>` and
+//! `>=`, and combine comparisons with `&&`, `||`, `!` and parentheses. Literals are numbers,
+//! strings in single or double quotes, `true`, `false` and `null`. A property name on its own
+//! tests that the property is set. Comparisons are evaluated on the property tables, without
+//! converting values to JSON. Bounding box tests (`Expr::Intersects`) can't be written in the
+//! expression syntax and are combined with parsed expressions using `Expr::and`.
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use crate::bbox;
+use crate::diff;
+use crate::geobuf_pb;
+use crate::geobuf_pb::data::value::Value_type;
+use crate::split;
+
+/// Comparison operator
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Op {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+/// Literal compared with a property value
+#[derive(Clone, Debug, PartialEq)]
+pub enum Literal {
+    Number(f64),
+    String(String),
+    Bool(bool),
+    Null,
+}
+
+/// Parsed filter expression
+#[derive(Clone, Debug, PartialEq)]
+pub enum Expr {
+    /// True if the property is set
+    Has(String),
+    Compare(String, Op, Literal),
+    /// True if the bounding box of the geometry intersects `[min_x, min_y, max_x, max_y]`
+    Intersects([f64; 4]),
+    Not(Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+}
+
+impl FromStr for Expr {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Expr::parse(s)
+    }
+}
+
+impl Expr {
+    /// Parses an expression, see the module documentation for the syntax
+    pub fn parse(s: &str) -> Result<Self, &'static str> {
+        let mut parser = Parser {
+            tokens: tokenize(s)?,
+            pos: 0,
+        };
+        let expr = parser.or()?;
+        if parser.pos != parser.tokens.len() {
+            return Err("Unexpected token in filter expression.");
+        }
+        Ok(expr)
+    }
+
+    /// Returns an expression matching features that match both `self` and `other`
+    pub fn and(self, other: Expr) -> Expr {
+        Expr::And(Box::new(self), Box::new(other))
+    }
+
+    /// Returns true if `feature`, whose property keys refer to `data`, matches the expression
+    pub fn matches(&self, data: &geobuf_pb::Data, feature: &geobuf_pb::data::Feature) -> bool {
+        self.eval(data, &key_indexes(&data.keys), feature)
+    }
+
+    fn eval(
+        &self,
+        data: &geobuf_pb::Data,
+        keys: &HashMap<&str, u32>,
+        feature: &geobuf_pb::data::Feature,
+    ) -> bool {
+        let strings = &data.strings;
+        match self {
+            Expr::Has(key) => property(keys, feature, key).is_some(),
+            Expr::Compare(key, op, literal) => {
+                let value = property(keys, feature, key);
+                match (value, literal, op) {
+                    (_, Literal::Null, _) => compare_null(value, *op),
+                    // A missing property or a value of another type is never equal to the literal
+                    (Some(value), _, Op::Ne) => !compare(value, strings, Op::Eq, literal),
+                    (Some(value), _, _) => compare(value, strings, *op, literal),
+                    (None, _, op) => *op == Op::Ne,
+                }
+            }
+            Expr::Intersects(extent) => match bbox::geometry_bbox(data, &feature.geometry) {
+                Some(feature_extent) => bbox::intersects(&feature_extent, extent),
+                None => false,
+            },
+            Expr::Not(expr) => !expr.eval(data, keys, feature),
+            Expr::And(a, b) => a.eval(data, keys, feature) && b.eval(data, keys, feature),
+            Expr::Or(a, b) => a.eval(data, keys, feature) || b.eval(data, keys, feature),
+        }
+    }
+}
+
+/// Returns a FeatureCollection holding the features of `data` matching `expr`
+///
+/// `data` must be a FeatureCollection or a Feature. The result keeps the header and the
+/// collection custom properties of `data`, with key and shared string tables trimmed down to
+/// the entries the kept features use. Comparisons involving a missing property or a value of
+/// another type are false, except `!=`, and `key == null` matches missing and null values.
+///
+/// # Example
+///
+/// ```
+/// use geobuf::encode::Encoder;
+/// use geobuf::filter::Expr;
+///
+/// let geojson = serde_json::from_str(r#"{"type": "FeatureCollection", "features": [
+///     {"type": "Feature", "properties": {"state": "CA", "population": 3900000}, "geometry": {"type": "Point", "coordinates": [-118.2, 34.1]}},
+///     {"type": "Feature", "properties": {"state": "CA", "population": 25000}, "geometry": {"type": "Point", "coordinates": [-120.7, 35.3]}},
+///     {"type": "Feature", "properties": {"state": "NY", "population": 8400000}, "geometry": {"type": "Point", "coordinates": [-74.0, 40.7]}}
+/// ]}"#).unwrap();
+/// let data = Encoder::encode(&geojson, 6, 2).unwrap();
+///
+/// let expr: Expr = "population > 100000 && state == 'CA'".parse().unwrap();
+/// let filtered = geobuf::filter::filter(&data, &expr).unwrap();
+/// assert_eq!(filtered.feature_collection().features.len(), 1);
+///
+/// let west = Expr::Intersects([-125.0, 30.0, -100.0, 50.0]);
+/// let filtered = geobuf::filter::filter(&data, &west).unwrap();
+/// assert_eq!(filtered.feature_collection().features.len(), 2);
+/// ```
+pub fn filter(data: &geobuf_pb::Data, expr: &Expr) -> Result<geobuf_pb::Data, &'static str> {
+    let features = diff::features(data)?;
+    let keys = key_indexes(&data.keys);
+
+    let mut collection = geobuf_pb::data::FeatureCollection::new();
+    if let Some(geobuf_pb::data::Data_type::FeatureCollection(fc)) = data.data_type.as_ref() {
+        collection.values = fc.values.clone();
+        collection.custom_properties = fc.custom_properties.clone();
+    }
+    collection.features = features
+        .into_iter()
+        .filter(|feature| expr.eval(data, &keys, feature))
+        .cloned()
+        .collect();
+    split::standalone(data, collection)
+}
+
+/// Maps key names to their first index in `keys`
+fn key_indexes(keys: &[String]) -> HashMap<&str, u32> {
+    let mut indexes = HashMap::with_capacity(keys.len());
+    for (n, key) in keys.iter().enumerate().rev() {
+        indexes.insert(key.as_str(), n as u32);
+    }
+    indexes
+}
+
+fn property<'a>(
+    keys: &HashMap<&str, u32>,
+    feature: &'a geobuf_pb::data::Feature,
+    key: &str,
+) -> Option<&'a geobuf_pb::data::Value> {
+    let key = *keys.get(key)?;
+    feature
+        .properties
+        .chunks_exact(2)
+        .find(|pair| pair[0] == key)
+        .and_then(|pair| feature.values.get(pair[1] as usize))
+}
+
+fn compare_null(value: Option<&geobuf_pb::data::Value>, op: Op) -> bool {
+    let is_null = match value.map(|value| value.value_type.as_ref()) {
+        None | Some(None) => true,
+        Some(Some(Value_type::JsonValue(json))) => json == "null",
+        Some(Some(_)) => false,
+    };
+    match op {
+        Op::Eq => is_null,
+        Op::Ne => !is_null,
+        _ => false,
+    }
+}
+
+fn compare(value: &geobuf_pb::data::Value, strings: &[String], op: Op, literal: &Literal) -> bool {
+    let ordering = match (value.value_type.as_ref(), literal) {
+        (Some(Value_type::PosIntValue(v)), Literal::Number(n)) => (*v as f64).partial_cmp(n),
+        (Some(Value_type::NegIntValue(v)), Literal::Number(n)) => (-(*v as f64)).partial_cmp(n),
+        (Some(Value_type::DoubleValue(v)), Literal::Number(n)) => v.partial_cmp(n),
+        (Some(Value_type::StringValue(v)), Literal::String(s)) => Some(v.as_str().cmp(s)),
+        (Some(Value_type::StringRef(index)), Literal::String(s)) => {
+            strings.get(*index as usize).map(|v| v.as_str().cmp(s))
+        }
+        (Some(Value_type::BoolValue(v)), Literal::Bool(b)) if matches!(op, Op::Eq | Op::Ne) => {
+            Some(v.cmp(b))
+        }
+        _ => None,
+    };
+    match ordering {
+        Some(ordering) => match op {
+            Op::Eq => ordering.is_eq(),
+            Op::Ne => ordering.is_ne(),
+            Op::Lt => ordering.is_lt(),
+            Op::Le => ordering.is_le(),
+            Op::Gt => ordering.is_gt(),
+            Op::Ge => ordering.is_ge(),
+        },
+        None => false,
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Ident(String),
+    Literal(Literal),
+    Op(Op),
+    And,
+    Or,
+    Not,
+    Open,
+    Close,
+}
+
+fn tokenize(s: &str) -> Result<Vec<Token>, &'static str> {
+    let mut tokens = Vec::new();
+    let mut chars = s.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        let mut next_is = |expected: char| chars.next_if(|(_, c)| *c == expected).is_some();
+        let token = match c {
+            _ if c.is_whitespace() => continue,
+            '(' => Token::Open,
+            ')' => Token::Close,
+            '&' if next_is('&') => Token::And,
+            '|' if next_is('|') => Token::Or,
+            '=' if next_is('=') => Token::Op(Op::Eq),
+            '!' if next_is('=') => Token::Op(Op::Ne),
+            '!' => Token::Not,
+            '<' if next_is('=') => Token::Op(Op::Le),
+            '<' => Token::Op(Op::Lt),
+            '>' if next_is('=') => Token::Op(Op::Ge),
+            '>' => Token::Op(Op::Gt),
+            '\'' | '"' => {
+                let mut string = String::new();
+                loop {
+                    match chars.next() {
+                        Some((_, '\\')) => match chars.next() {
+                            Some((_, escaped)) => string.push(escaped),
+                            None => return Err("Unterminated string in filter expression."),
+                        },
+                        Some((_, end)) if end == c => break,
+                        Some((_, other)) => string.push(other),
+                        None => return Err("Unterminated string in filter expression."),
+                    }
+                }
+                Token::Literal(Literal::String(string))
+            }
+            _ if c.is_ascii_digit() || c == '-' || c == '.' => {
+                let mut end = start + c.len_utf8();
+                while let Some((n, c)) = chars.next_if(|(_, c)| {
+                    c.is_ascii_alphanumeric() || *c == '.' || *c == '+' || *c == '-'
+                }) {
+                    end = n + c.len_utf8();
+                }
+                match s[start..end].parse::<f64>() {
+                    Ok(number) if number.is_finite() => Token::Literal(Literal::Number(number)),
+                    _ => return Err("Invalid number in filter expression."),
+                }
+            }
+            _ if c.is_alphabetic() || c == '_' => {
+                let mut end = start + c.len_utf8();
+                while let Some((n, c)) = chars.next_if(|(_, c)| c.is_alphanumeric() || *c == '_') {
+                    end = n + c.len_utf8();
+                }
+                match &s[start..end] {
+                    "true" => Token::Literal(Literal::Bool(true)),
+                    "false" => Token::Literal(Literal::Bool(false)),
+                    "null" => Token::Literal(Literal::Null),
+                    ident => Token::Ident(String::from(ident)),
+                }
+            }
+            _ => return Err("Unexpected character in filter expression."),
+        };
+        tokens.push(token);
+    }
+    Ok(tokens)
+}
+
+/// Recursive descent parser, `||` binding looser than `&&`, which binds looser than `!`
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn eat(&mut self, token: &Token) -> bool {
+        if self.tokens.get(self.pos) == Some(token) {
+            self.pos += 1;
+            return true;
+        }
+        false
+    }
+
+    fn or(&mut self) -> Result<Expr, &'static str> {
+        let mut expr = self.and()?;
+        while self.eat(&Token::Or) {
+            expr = Expr::Or(Box::new(expr), Box::new(self.and()?));
+        }
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<Expr, &'static str> {
+        let mut expr = self.unary()?;
+        while self.eat(&Token::And) {
+            expr = Expr::And(Box::new(expr), Box::new(self.unary()?));
+        }
+        Ok(expr)
+    }
+
+    fn unary(&mut self) -> Result<Expr, &'static str> {
+        match self.next() {
+            Some(Token::Not) => Ok(Expr::Not(Box::new(self.unary()?))),
+            Some(Token::Open) => {
+                let expr = self.or()?;
+                if !self.eat(&Token::Close) {
+                    return Err("Missing closing parenthesis in filter expression.");
+                }
+                Ok(expr)
+            }
+            Some(Token::Ident(key)) => match self.tokens.get(self.pos) {
+                Some(Token::Op(op)) => {
+                    let op = *op;
+                    self.pos += 1;
+                    match self.next() {
+                        Some(Token::Literal(literal)) => Ok(Expr::Compare(key, op, literal)),
+                        _ => Err("Expected a literal in filter expression."),
+                    }
+                }
+                _ => Ok(Expr::Has(key)),
+            },
+            None => Err("Unexpected end of filter expression."),
+            _ => Err("Expected a property name in filter expression."),
+        }
+    }
+}
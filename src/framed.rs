@@ -0,0 +1,194 @@
+//! Length-delimited framed stream of Geobuf messages
+//!
+//! A framed stream is a sequence of frames, each one a varint byte length followed by a
+//! serialized `geobuf_pb::Data` message. Every frame is a self-contained Geobuf (with its
+//! own key table), so frames can be appended to a stream over time and read back one at a
+//! time without loading the whole stream into memory.
+use std::io::{Read, Write};
+
+use serde_json::Value as JSONValue;
+
+use crate::codec;
+use crate::encode::Encoder;
+use crate::geobuf_pb;
+
+/// Writes `geobuf_pb::Data` messages as length-delimited frames
+pub struct FramedWriter<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> FramedWriter<W> {
+    /// Returns a new writer that appends frames to `writer`
+    pub fn new(writer: W) -> Self {
+        FramedWriter { writer }
+    }
+
+    /// Writes `data` as a single frame and returns the number of bytes written
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use geobuf::encode::Encoder;
+    /// use geobuf::framed::{FramedReader, FramedWriter};
+    ///
+    /// let geojson = serde_json::from_str(r#"{"type": "Point", "coordinates": [100.0, 0.0]}"#).unwrap();
+    /// let data = Encoder::encode(&geojson, 6, 2).unwrap();
+    ///
+    /// let mut writer = FramedWriter::new(Vec::new());
+    /// writer.write(&data).unwrap();
+    /// writer.write(&data).unwrap();
+    /// let bytes = writer.into_inner();
+    ///
+    /// let frames: Vec<_> = FramedReader::new(&bytes[..]).collect();
+    /// assert_eq!(frames.len(), 2);
+    /// assert_eq!(frames[0].as_ref().unwrap(), &data);
+    /// ```
+    pub fn write(&mut self, data: &geobuf_pb::Data) -> Result<usize, &'static str> {
+        let msg = match codec::serialize(data) {
+            Ok(msg) => msg,
+            Err(_) => return Err("Could not serialize frame."),
+        };
+        let mut header = Vec::with_capacity(10);
+        write_varint(&mut header, msg.len() as u64);
+        if self.writer.write_all(&header).is_err() || self.writer.write_all(&msg).is_err() {
+            return Err("Could not write frame.");
+        }
+        Ok(header.len() + msg.len())
+    }
+
+    /// Encodes every feature of a GeoJSON object into its own frame
+    ///
+    /// A FeatureCollection is written as one frame per feature; any other GeoJSON object is
+    /// written as a single frame. Returns the number of frames written.
+    pub fn write_geojson(
+        &mut self,
+        geojson: &JSONValue,
+        precision: u32,
+        dim: u32,
+    ) -> Result<usize, &'static str> {
+        match geojson["type"].as_str() {
+            Some("FeatureCollection") => {
+                let features = match geojson["features"].as_array() {
+                    Some(features) => features,
+                    None => return Err("Missing features."),
+                };
+                for feature in features {
+                    self.write(&Encoder::encode(feature, precision, dim)?)?;
+                }
+                Ok(features.len())
+            }
+            _ => {
+                self.write(&Encoder::encode(geojson, precision, dim)?)?;
+                Ok(1)
+            }
+        }
+    }
+
+    /// Flushes the underlying writer
+    pub fn flush(&mut self) -> Result<(), &'static str> {
+        self.writer.flush().map_err(|_| "Could not flush frames.")
+    }
+
+    /// Returns the underlying writer
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+/// Reads length-delimited `geobuf_pb::Data` frames
+///
+/// The reader is an iterator that yields one decoded frame at a time and stops at the end of
+/// the stream. A stream that ends in the middle of a frame yields an error.
+pub struct FramedReader<R: Read> {
+    reader: R,
+    done: bool,
+}
+
+impl<R: Read> FramedReader<R> {
+    /// Returns a new reader over the frames in `reader`
+    pub fn new(reader: R) -> Self {
+        FramedReader {
+            reader,
+            done: false,
+        }
+    }
+
+    /// Returns the raw bytes of the next frame, or `None` at the end of the stream
+    pub fn next_frame(&mut self) -> Result<Option<Vec<u8>>, &'static str> {
+        let len = match read_varint(&mut self.reader)? {
+            Some(len) => len,
+            None => return Ok(None),
+        };
+        read_frame(&mut self.reader, len).map(Some)
+    }
+
+    /// Returns the underlying reader
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+impl<R: Read> Iterator for FramedReader<R> {
+    type Item = Result<geobuf_pb::Data, &'static str>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let frame = match self.next_frame() {
+            Ok(Some(frame)) => frame,
+            Ok(None) => {
+                self.done = true;
+                return None;
+            }
+            Err(err) => {
+                self.done = true;
+                return Some(Err(err));
+            }
+        };
+        match codec::parse(&frame) {
+            Ok(data) => Some(Ok(data)),
+            Err(_) => {
+                self.done = true;
+                Some(Err("Could not parse frame."))
+            }
+        }
+    }
+}
+
+pub(crate) fn write_varint(buf: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        buf.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    buf.push(value as u8);
+}
+
+/// Reads a frame of `len` bytes into a buffer that grows as they arrive, so that a corrupt
+/// length can't allocate more than the stream holds
+pub(crate) fn read_frame<R: Read>(reader: &mut R, len: u64) -> Result<Vec<u8>, &'static str> {
+    let mut buf = Vec::new();
+    match reader.take(len).read_to_end(&mut buf) {
+        Ok(n) if n as u64 == len => Ok(buf),
+        _ => Err("Truncated frame."),
+    }
+}
+
+/// Reads a varint, returning `None` on a clean end of stream before its first byte
+pub(crate) fn read_varint<R: Read>(reader: &mut R) -> Result<Option<u64>, &'static str> {
+    let mut value: u64 = 0;
+    let mut byte = [0u8; 1];
+    for i in 0..10 {
+        match reader.read(&mut byte) {
+            Ok(0) if i == 0 => return Ok(None),
+            Ok(0) => return Err("Truncated frame length."),
+            Ok(_) => {}
+            Err(_) => return Err("Could not read frame length."),
+        }
+        value |= ((byte[0] & 0x7f) as u64) << (7 * i);
+        if byte[0] & 0x80 == 0 {
+            return Ok(Some(value));
+        }
+    }
+    Err("Invalid frame length.")
+}
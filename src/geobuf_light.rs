@@ -0,0 +1,1290 @@
+// Message types of `geobuf_pb.rs` for builds without the protobuf runtime, derived from the
+// generated file by dropping its `Message` implementations, which `codec` stands in for.
+// The two files are kept in sync by hand.
+
+// https://github.com/rust-lang/rust-clippy/issues/702
+#![allow(unknown_lints)]
+#![allow(clippy::all)]
+
+#![allow(unused_attributes)]
+#![cfg_attr(rustfmt, rustfmt::skip)]
+
+#![allow(dead_code)]
+#![allow(missing_docs)]
+#![allow(non_camel_case_types)]
+#![allow(non_snake_case)]
+#![allow(non_upper_case_globals)]
+#![allow(trivial_casts)]
+#![allow(unused_results)]
+#![allow(unused_mut)]
+
+//! Message types of `protos/geobuf.proto` without the protobuf runtime
+
+use std::marker::PhantomData;
+use std::ops::Deref;
+
+/// Shared empty instance of a message, returned for message fields that aren't set
+pub trait DefaultInstance: 'static {
+    fn default_instance() -> &'static Self;
+}
+
+/// Optional message field, like `protobuf::MessageField`
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MessageField<T>(pub Option<Box<T>>);
+
+impl<T> MessageField<T> {
+    pub const fn none() -> Self {
+        MessageField(None)
+    }
+
+    pub fn some(value: T) -> Self {
+        MessageField(Some(Box::new(value)))
+    }
+
+    pub fn is_some(&self) -> bool {
+        self.0.is_some()
+    }
+
+    pub fn is_none(&self) -> bool {
+        self.0.is_none()
+    }
+
+    pub fn as_ref(&self) -> Option<&T> {
+        self.0.as_deref()
+    }
+
+    pub fn as_mut(&mut self) -> Option<&mut T> {
+        self.0.as_deref_mut()
+    }
+
+    pub fn take(&mut self) -> Option<T> {
+        self.0.take().map(|value| *value)
+    }
+
+    pub fn clear(&mut self) {
+        self.0 = None;
+    }
+
+    pub fn unwrap(self) -> T {
+        *self.0.unwrap()
+    }
+}
+
+impl<T: Default> MessageField<T> {
+    pub fn mut_or_insert_default(&mut self) -> &mut T {
+        self.0.get_or_insert_with(Default::default)
+    }
+}
+
+impl<T: DefaultInstance> Deref for MessageField<T> {
+    type Target = T;
+
+    /// Returns the message, or the default instance when the field isn't set
+    fn deref(&self) -> &T {
+        match self.as_ref() {
+            Some(value) => value,
+            None => T::default_instance(),
+        }
+    }
+}
+
+impl<T> From<Option<T>> for MessageField<T> {
+    fn from(value: Option<T>) -> Self {
+        MessageField(value.map(Box::new))
+    }
+}
+
+/// Value of an enum field, which may be unknown to this schema, like
+/// `protobuf::EnumOrUnknown`
+pub struct EnumOrUnknown<E> {
+    value: i32,
+    enum_type: PhantomData<E>,
+}
+
+impl<E> EnumOrUnknown<E> {
+    pub fn from_i32(value: i32) -> Self {
+        EnumOrUnknown {
+            value,
+            enum_type: PhantomData,
+        }
+    }
+
+    pub fn value(&self) -> i32 {
+        self.value
+    }
+}
+
+impl EnumOrUnknown<data::geometry::Type> {
+    pub fn new(value: data::geometry::Type) -> Self {
+        EnumOrUnknown::from_i32(value.value())
+    }
+
+    pub fn enum_value(&self) -> Result<data::geometry::Type, i32> {
+        data::geometry::Type::from_i32(self.value).ok_or(self.value)
+    }
+
+    pub fn enum_value_or(&self, default: data::geometry::Type) -> data::geometry::Type {
+        self.enum_value().unwrap_or(default)
+    }
+
+    pub fn enum_value_or_default(&self) -> data::geometry::Type {
+        self.enum_value_or(Default::default())
+    }
+}
+
+// Derives would require the marker type to implement the traits too
+impl<E> Clone for EnumOrUnknown<E> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<E> Copy for EnumOrUnknown<E> {}
+
+impl<E> PartialEq for EnumOrUnknown<E> {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl<E> Eq for EnumOrUnknown<E> {}
+
+impl<E> std::hash::Hash for EnumOrUnknown<E> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.value.hash(state)
+    }
+}
+
+impl std::fmt::Debug for EnumOrUnknown<data::geometry::Type> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self.enum_value() {
+            Ok(value) => value.fmt(f),
+            Err(value) => value.fmt(f),
+        }
+    }
+}
+
+#[derive(PartialEq,Clone,Default,Debug)]
+// @@protoc_insertion_point(message:Data)
+pub struct Data {
+    // message fields
+    // @@protoc_insertion_point(field:Data.keys)
+    pub keys: ::std::vec::Vec<::std::string::String>,
+    // @@protoc_insertion_point(field:Data.dimensions)
+    pub dimensions: ::std::option::Option<u32>,
+    // @@protoc_insertion_point(field:Data.precision)
+    pub precision: ::std::option::Option<u32>,
+    // @@protoc_insertion_point(field:Data.strings)
+    pub strings: ::std::vec::Vec<::std::string::String>,
+    // @@protoc_insertion_point(field:Data.transform)
+    pub transform: crate::geobuf_pb::MessageField<data::Transform>,
+    // message oneof groups
+    pub data_type: ::std::option::Option<data::Data_type>,
+}
+
+impl<'a> ::std::default::Default for &'a Data {
+    fn default() -> &'a Data {
+        <Data as crate::geobuf_pb::DefaultInstance>::default_instance()
+    }
+}
+
+impl Data {
+    pub fn new() -> Data {
+        ::std::default::Default::default()
+    }
+
+    // optional uint32 dimensions = 2;
+
+    pub fn dimensions(&self) -> u32 {
+        self.dimensions.unwrap_or(2u32)
+    }
+
+    pub fn clear_dimensions(&mut self) {
+        self.dimensions = ::std::option::Option::None;
+    }
+
+    pub fn has_dimensions(&self) -> bool {
+        self.dimensions.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_dimensions(&mut self, v: u32) {
+        self.dimensions = ::std::option::Option::Some(v);
+    }
+
+    // optional uint32 precision = 3;
+
+    pub fn precision(&self) -> u32 {
+        self.precision.unwrap_or(6u32)
+    }
+
+    pub fn clear_precision(&mut self) {
+        self.precision = ::std::option::Option::None;
+    }
+
+    pub fn has_precision(&self) -> bool {
+        self.precision.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_precision(&mut self, v: u32) {
+        self.precision = ::std::option::Option::Some(v);
+    }
+
+    // optional .Data.FeatureCollection feature_collection = 4;
+
+    pub fn feature_collection(&self) -> &data::FeatureCollection {
+        match self.data_type {
+            ::std::option::Option::Some(data::Data_type::FeatureCollection(ref v)) => v,
+            _ => <data::FeatureCollection as crate::geobuf_pb::DefaultInstance>::default_instance(),
+        }
+    }
+
+    pub fn clear_feature_collection(&mut self) {
+        self.data_type = ::std::option::Option::None;
+    }
+
+    pub fn has_feature_collection(&self) -> bool {
+        match self.data_type {
+            ::std::option::Option::Some(data::Data_type::FeatureCollection(..)) => true,
+            _ => false,
+        }
+    }
+
+    // Param is passed by value, moved
+    pub fn set_feature_collection(&mut self, v: data::FeatureCollection) {
+        self.data_type = ::std::option::Option::Some(data::Data_type::FeatureCollection(v))
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_feature_collection(&mut self) -> &mut data::FeatureCollection {
+        if let ::std::option::Option::Some(data::Data_type::FeatureCollection(_)) = self.data_type {
+        } else {
+            self.data_type = ::std::option::Option::Some(data::Data_type::FeatureCollection(data::FeatureCollection::new()));
+        }
+        match self.data_type {
+            ::std::option::Option::Some(data::Data_type::FeatureCollection(ref mut v)) => v,
+            _ => panic!(),
+        }
+    }
+
+    // Take field
+    pub fn take_feature_collection(&mut self) -> data::FeatureCollection {
+        if self.has_feature_collection() {
+            match self.data_type.take() {
+                ::std::option::Option::Some(data::Data_type::FeatureCollection(v)) => v,
+                _ => panic!(),
+            }
+        } else {
+            data::FeatureCollection::new()
+        }
+    }
+
+    // optional .Data.Feature feature = 5;
+
+    pub fn feature(&self) -> &data::Feature {
+        match self.data_type {
+            ::std::option::Option::Some(data::Data_type::Feature(ref v)) => v,
+            _ => <data::Feature as crate::geobuf_pb::DefaultInstance>::default_instance(),
+        }
+    }
+
+    pub fn clear_feature(&mut self) {
+        self.data_type = ::std::option::Option::None;
+    }
+
+    pub fn has_feature(&self) -> bool {
+        match self.data_type {
+            ::std::option::Option::Some(data::Data_type::Feature(..)) => true,
+            _ => false,
+        }
+    }
+
+    // Param is passed by value, moved
+    pub fn set_feature(&mut self, v: data::Feature) {
+        self.data_type = ::std::option::Option::Some(data::Data_type::Feature(v))
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_feature(&mut self) -> &mut data::Feature {
+        if let ::std::option::Option::Some(data::Data_type::Feature(_)) = self.data_type {
+        } else {
+            self.data_type = ::std::option::Option::Some(data::Data_type::Feature(data::Feature::new()));
+        }
+        match self.data_type {
+            ::std::option::Option::Some(data::Data_type::Feature(ref mut v)) => v,
+            _ => panic!(),
+        }
+    }
+
+    // Take field
+    pub fn take_feature(&mut self) -> data::Feature {
+        if self.has_feature() {
+            match self.data_type.take() {
+                ::std::option::Option::Some(data::Data_type::Feature(v)) => v,
+                _ => panic!(),
+            }
+        } else {
+            data::Feature::new()
+        }
+    }
+
+    // optional .Data.Geometry geometry = 6;
+
+    pub fn geometry(&self) -> &data::Geometry {
+        match self.data_type {
+            ::std::option::Option::Some(data::Data_type::Geometry(ref v)) => v,
+            _ => <data::Geometry as crate::geobuf_pb::DefaultInstance>::default_instance(),
+        }
+    }
+
+    pub fn clear_geometry(&mut self) {
+        self.data_type = ::std::option::Option::None;
+    }
+
+    pub fn has_geometry(&self) -> bool {
+        match self.data_type {
+            ::std::option::Option::Some(data::Data_type::Geometry(..)) => true,
+            _ => false,
+        }
+    }
+
+    // Param is passed by value, moved
+    pub fn set_geometry(&mut self, v: data::Geometry) {
+        self.data_type = ::std::option::Option::Some(data::Data_type::Geometry(v))
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_geometry(&mut self) -> &mut data::Geometry {
+        if let ::std::option::Option::Some(data::Data_type::Geometry(_)) = self.data_type {
+        } else {
+            self.data_type = ::std::option::Option::Some(data::Data_type::Geometry(data::Geometry::new()));
+        }
+        match self.data_type {
+            ::std::option::Option::Some(data::Data_type::Geometry(ref mut v)) => v,
+            _ => panic!(),
+        }
+    }
+
+    // Take field
+    pub fn take_geometry(&mut self) -> data::Geometry {
+        if self.has_geometry() {
+            match self.data_type.take() {
+                ::std::option::Option::Some(data::Data_type::Geometry(v)) => v,
+                _ => panic!(),
+            }
+        } else {
+            data::Geometry::new()
+        }
+    }
+
+    // optional .Data.Topology topology = 7;
+
+    pub fn topology(&self) -> &data::Topology {
+        match self.data_type {
+            ::std::option::Option::Some(data::Data_type::Topology(ref v)) => v,
+            _ => <data::Topology as crate::geobuf_pb::DefaultInstance>::default_instance(),
+        }
+    }
+
+    pub fn clear_topology(&mut self) {
+        self.data_type = ::std::option::Option::None;
+    }
+
+    pub fn has_topology(&self) -> bool {
+        match self.data_type {
+            ::std::option::Option::Some(data::Data_type::Topology(..)) => true,
+            _ => false,
+        }
+    }
+
+    // Param is passed by value, moved
+    pub fn set_topology(&mut self, v: data::Topology) {
+        self.data_type = ::std::option::Option::Some(data::Data_type::Topology(v))
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_topology(&mut self) -> &mut data::Topology {
+        if let ::std::option::Option::Some(data::Data_type::Topology(_)) = self.data_type {
+        } else {
+            self.data_type = ::std::option::Option::Some(data::Data_type::Topology(data::Topology::new()));
+        }
+        match self.data_type {
+            ::std::option::Option::Some(data::Data_type::Topology(ref mut v)) => v,
+            _ => panic!(),
+        }
+    }
+
+    // Take field
+    pub fn take_topology(&mut self) -> data::Topology {
+        if self.has_topology() {
+            match self.data_type.take() {
+                ::std::option::Option::Some(data::Data_type::Topology(v)) => v,
+                _ => panic!(),
+            }
+        } else {
+            data::Topology::new()
+        }
+    }
+}
+
+impl crate::geobuf_pb::DefaultInstance for Data {
+    fn default_instance() -> &'static Data {
+        static instance: Data = Data {
+            keys: ::std::vec::Vec::new(),
+            dimensions: ::std::option::Option::None,
+            precision: ::std::option::Option::None,
+            strings: ::std::vec::Vec::new(),
+            transform: crate::geobuf_pb::MessageField::none(),
+            data_type: ::std::option::Option::None,
+        };
+        &instance
+    }
+}
+
+/// Nested message and enums of message `Data`
+pub mod data {
+
+    #[derive(Clone,PartialEq,Debug)]
+    #[non_exhaustive]
+    // @@protoc_insertion_point(oneof:Data.data_type)
+    pub enum Data_type {
+        // @@protoc_insertion_point(oneof_field:Data.feature_collection)
+        FeatureCollection(FeatureCollection),
+        // @@protoc_insertion_point(oneof_field:Data.feature)
+        Feature(Feature),
+        // @@protoc_insertion_point(oneof_field:Data.geometry)
+        Geometry(Geometry),
+        // @@protoc_insertion_point(oneof_field:Data.topology)
+        Topology(Topology),
+    }
+
+    impl Data_type {
+    }
+    #[derive(PartialEq,Clone,Default,Debug)]
+    // @@protoc_insertion_point(message:Data.Feature)
+    pub struct Feature {
+        // message fields
+        // @@protoc_insertion_point(field:Data.Feature.geometry)
+        pub geometry: crate::geobuf_pb::MessageField<Geometry>,
+        // @@protoc_insertion_point(field:Data.Feature.values)
+        pub values: ::std::vec::Vec<Value>,
+        // @@protoc_insertion_point(field:Data.Feature.properties)
+        pub properties: ::std::vec::Vec<u32>,
+        // @@protoc_insertion_point(field:Data.Feature.custom_properties)
+        pub custom_properties: ::std::vec::Vec<u32>,
+        // message oneof groups
+        pub id_type: ::std::option::Option<feature::Id_type>,
+    }
+
+    impl<'a> ::std::default::Default for &'a Feature {
+        fn default() -> &'a Feature {
+            <Feature as crate::geobuf_pb::DefaultInstance>::default_instance()
+        }
+    }
+
+    impl Feature {
+        pub fn new() -> Feature {
+            ::std::default::Default::default()
+        }
+
+        // optional string id = 11;
+
+        pub fn id(&self) -> &str {
+            match self.id_type {
+                ::std::option::Option::Some(feature::Id_type::Id(ref v)) => v,
+                _ => "",
+            }
+        }
+
+        pub fn clear_id(&mut self) {
+            self.id_type = ::std::option::Option::None;
+        }
+
+        pub fn has_id(&self) -> bool {
+            match self.id_type {
+                ::std::option::Option::Some(feature::Id_type::Id(..)) => true,
+                _ => false,
+            }
+        }
+
+        // Param is passed by value, moved
+        pub fn set_id(&mut self, v: ::std::string::String) {
+            self.id_type = ::std::option::Option::Some(feature::Id_type::Id(v))
+        }
+
+        // Mutable pointer to the field.
+        pub fn mut_id(&mut self) -> &mut ::std::string::String {
+            if let ::std::option::Option::Some(feature::Id_type::Id(_)) = self.id_type {
+            } else {
+                self.id_type = ::std::option::Option::Some(feature::Id_type::Id(::std::string::String::new()));
+            }
+            match self.id_type {
+                ::std::option::Option::Some(feature::Id_type::Id(ref mut v)) => v,
+                _ => panic!(),
+            }
+        }
+
+        // Take field
+        pub fn take_id(&mut self) -> ::std::string::String {
+            if self.has_id() {
+                match self.id_type.take() {
+                    ::std::option::Option::Some(feature::Id_type::Id(v)) => v,
+                    _ => panic!(),
+                }
+            } else {
+                ::std::string::String::new()
+            }
+        }
+
+        // optional sint64 int_id = 12;
+
+        pub fn int_id(&self) -> i64 {
+            match self.id_type {
+                ::std::option::Option::Some(feature::Id_type::IntId(v)) => v,
+                _ => 0,
+            }
+        }
+
+        pub fn clear_int_id(&mut self) {
+            self.id_type = ::std::option::Option::None;
+        }
+
+        pub fn has_int_id(&self) -> bool {
+            match self.id_type {
+                ::std::option::Option::Some(feature::Id_type::IntId(..)) => true,
+                _ => false,
+            }
+        }
+
+        // Param is passed by value, moved
+        pub fn set_int_id(&mut self, v: i64) {
+            self.id_type = ::std::option::Option::Some(feature::Id_type::IntId(v))
+        }
+    }
+
+    impl crate::geobuf_pb::DefaultInstance for Feature {
+        fn default_instance() -> &'static Feature {
+            static instance: Feature = Feature {
+                geometry: crate::geobuf_pb::MessageField::none(),
+                values: ::std::vec::Vec::new(),
+                properties: ::std::vec::Vec::new(),
+                custom_properties: ::std::vec::Vec::new(),
+                id_type: ::std::option::Option::None,
+            };
+            &instance
+        }
+    }
+
+    /// Nested message and enums of message `Feature`
+    pub mod feature {
+
+        #[derive(Clone,PartialEq,Debug)]
+        #[non_exhaustive]
+        // @@protoc_insertion_point(oneof:Data.Feature.id_type)
+        pub enum Id_type {
+            // @@protoc_insertion_point(oneof_field:Data.Feature.id)
+            Id(::std::string::String),
+            // @@protoc_insertion_point(oneof_field:Data.Feature.int_id)
+            IntId(i64),
+        }
+
+        impl Id_type {
+        }
+    }
+
+    #[derive(PartialEq,Clone,Default,Debug)]
+    // @@protoc_insertion_point(message:Data.Geometry)
+    pub struct Geometry {
+        // message fields
+        // @@protoc_insertion_point(field:Data.Geometry.type)
+        pub type_: ::std::option::Option<crate::geobuf_pb::EnumOrUnknown<geometry::Type>>,
+        // @@protoc_insertion_point(field:Data.Geometry.lengths)
+        pub lengths: ::std::vec::Vec<u32>,
+        // @@protoc_insertion_point(field:Data.Geometry.coords)
+        pub coords: ::std::vec::Vec<i64>,
+        // @@protoc_insertion_point(field:Data.Geometry.geometries)
+        pub geometries: ::std::vec::Vec<Geometry>,
+        // @@protoc_insertion_point(field:Data.Geometry.arcs)
+        pub arcs: ::std::vec::Vec<i32>,
+        // @@protoc_insertion_point(field:Data.Geometry.values)
+        pub values: ::std::vec::Vec<Value>,
+        // @@protoc_insertion_point(field:Data.Geometry.properties)
+        pub properties: ::std::vec::Vec<u32>,
+        // @@protoc_insertion_point(field:Data.Geometry.custom_properties)
+        pub custom_properties: ::std::vec::Vec<u32>,
+        // message oneof groups
+        pub id_type: ::std::option::Option<geometry::Id_type>,
+    }
+
+    impl<'a> ::std::default::Default for &'a Geometry {
+        fn default() -> &'a Geometry {
+            <Geometry as crate::geobuf_pb::DefaultInstance>::default_instance()
+        }
+    }
+
+    impl Geometry {
+        pub fn new() -> Geometry {
+            ::std::default::Default::default()
+        }
+
+        // required .Data.Geometry.Type type = 1;
+
+        pub fn type_(&self) -> geometry::Type {
+            match self.type_ {
+                Some(e) => e.enum_value_or(geometry::Type::POINT),
+                None => geometry::Type::POINT,
+            }
+        }
+
+        pub fn clear_type_(&mut self) {
+            self.type_ = ::std::option::Option::None;
+        }
+
+        pub fn has_type(&self) -> bool {
+            self.type_.is_some()
+        }
+
+        // Param is passed by value, moved
+        pub fn set_type(&mut self, v: geometry::Type) {
+            self.type_ = ::std::option::Option::Some(crate::geobuf_pb::EnumOrUnknown::new(v));
+        }
+
+        // optional string id = 11;
+
+        pub fn id(&self) -> &str {
+            match self.id_type {
+                ::std::option::Option::Some(geometry::Id_type::Id(ref v)) => v,
+                _ => "",
+            }
+        }
+
+        pub fn clear_id(&mut self) {
+            self.id_type = ::std::option::Option::None;
+        }
+
+        pub fn has_id(&self) -> bool {
+            match self.id_type {
+                ::std::option::Option::Some(geometry::Id_type::Id(..)) => true,
+                _ => false,
+            }
+        }
+
+        // Param is passed by value, moved
+        pub fn set_id(&mut self, v: ::std::string::String) {
+            self.id_type = ::std::option::Option::Some(geometry::Id_type::Id(v))
+        }
+
+        // Mutable pointer to the field.
+        pub fn mut_id(&mut self) -> &mut ::std::string::String {
+            if let ::std::option::Option::Some(geometry::Id_type::Id(_)) = self.id_type {
+            } else {
+                self.id_type = ::std::option::Option::Some(geometry::Id_type::Id(::std::string::String::new()));
+            }
+            match self.id_type {
+                ::std::option::Option::Some(geometry::Id_type::Id(ref mut v)) => v,
+                _ => panic!(),
+            }
+        }
+
+        // Take field
+        pub fn take_id(&mut self) -> ::std::string::String {
+            if self.has_id() {
+                match self.id_type.take() {
+                    ::std::option::Option::Some(geometry::Id_type::Id(v)) => v,
+                    _ => panic!(),
+                }
+            } else {
+                ::std::string::String::new()
+            }
+        }
+
+        // optional sint64 int_id = 12;
+
+        pub fn int_id(&self) -> i64 {
+            match self.id_type {
+                ::std::option::Option::Some(geometry::Id_type::IntId(v)) => v,
+                _ => 0,
+            }
+        }
+
+        pub fn clear_int_id(&mut self) {
+            self.id_type = ::std::option::Option::None;
+        }
+
+        pub fn has_int_id(&self) -> bool {
+            match self.id_type {
+                ::std::option::Option::Some(geometry::Id_type::IntId(..)) => true,
+                _ => false,
+            }
+        }
+
+        // Param is passed by value, moved
+        pub fn set_int_id(&mut self, v: i64) {
+            self.id_type = ::std::option::Option::Some(geometry::Id_type::IntId(v))
+        }
+    }
+
+    impl crate::geobuf_pb::DefaultInstance for Geometry {
+        fn default_instance() -> &'static Geometry {
+            static instance: Geometry = Geometry {
+                type_: ::std::option::Option::None,
+                lengths: ::std::vec::Vec::new(),
+                coords: ::std::vec::Vec::new(),
+                geometries: ::std::vec::Vec::new(),
+                arcs: ::std::vec::Vec::new(),
+                values: ::std::vec::Vec::new(),
+                properties: ::std::vec::Vec::new(),
+                custom_properties: ::std::vec::Vec::new(),
+                id_type: ::std::option::Option::None,
+            };
+            &instance
+        }
+    }
+
+    /// Nested message and enums of message `Geometry`
+    pub mod geometry {
+
+        #[derive(Clone,PartialEq,Debug)]
+        #[non_exhaustive]
+        // @@protoc_insertion_point(oneof:Data.Geometry.id_type)
+        pub enum Id_type {
+            // @@protoc_insertion_point(oneof_field:Data.Geometry.id)
+            Id(::std::string::String),
+            // @@protoc_insertion_point(oneof_field:Data.Geometry.int_id)
+            IntId(i64),
+        }
+
+        impl Id_type {
+        }
+        #[derive(Clone,Copy,PartialEq,Eq,Debug,Hash)]
+        // @@protoc_insertion_point(enum:Data.Geometry.Type)
+        pub enum Type {
+            // @@protoc_insertion_point(enum_value:Data.Geometry.Type.POINT)
+            POINT = 0,
+            // @@protoc_insertion_point(enum_value:Data.Geometry.Type.MULTIPOINT)
+            MULTIPOINT = 1,
+            // @@protoc_insertion_point(enum_value:Data.Geometry.Type.LINESTRING)
+            LINESTRING = 2,
+            // @@protoc_insertion_point(enum_value:Data.Geometry.Type.MULTILINESTRING)
+            MULTILINESTRING = 3,
+            // @@protoc_insertion_point(enum_value:Data.Geometry.Type.POLYGON)
+            POLYGON = 4,
+            // @@protoc_insertion_point(enum_value:Data.Geometry.Type.MULTIPOLYGON)
+            MULTIPOLYGON = 5,
+            // @@protoc_insertion_point(enum_value:Data.Geometry.Type.GEOMETRYCOLLECTION)
+            GEOMETRYCOLLECTION = 6,
+        }
+
+        impl Type {
+
+            pub fn value(&self) -> i32 {
+                *self as i32
+            }
+
+            pub fn from_i32(value: i32) -> ::std::option::Option<Type> {
+                match value {
+                    0 => ::std::option::Option::Some(Type::POINT),
+                    1 => ::std::option::Option::Some(Type::MULTIPOINT),
+                    2 => ::std::option::Option::Some(Type::LINESTRING),
+                    3 => ::std::option::Option::Some(Type::MULTILINESTRING),
+                    4 => ::std::option::Option::Some(Type::POLYGON),
+                    5 => ::std::option::Option::Some(Type::MULTIPOLYGON),
+                    6 => ::std::option::Option::Some(Type::GEOMETRYCOLLECTION),
+                    _ => ::std::option::Option::None
+                }
+            }
+
+            pub const VALUES: &'static [Type] = &[
+                Type::POINT,
+                Type::MULTIPOINT,
+                Type::LINESTRING,
+                Type::MULTILINESTRING,
+                Type::POLYGON,
+                Type::MULTIPOLYGON,
+                Type::GEOMETRYCOLLECTION,
+            ];
+        }
+
+        impl ::std::default::Default for Type {
+            fn default() -> Self {
+                Type::POINT
+            }
+        }
+
+    }
+
+    #[derive(PartialEq,Clone,Default,Debug)]
+    // @@protoc_insertion_point(message:Data.FeatureCollection)
+    pub struct FeatureCollection {
+        // message fields
+        // @@protoc_insertion_point(field:Data.FeatureCollection.features)
+        pub features: ::std::vec::Vec<Feature>,
+        // @@protoc_insertion_point(field:Data.FeatureCollection.values)
+        pub values: ::std::vec::Vec<Value>,
+        // @@protoc_insertion_point(field:Data.FeatureCollection.custom_properties)
+        pub custom_properties: ::std::vec::Vec<u32>,
+    }
+
+    impl<'a> ::std::default::Default for &'a FeatureCollection {
+        fn default() -> &'a FeatureCollection {
+            <FeatureCollection as crate::geobuf_pb::DefaultInstance>::default_instance()
+        }
+    }
+
+    impl FeatureCollection {
+        pub fn new() -> FeatureCollection {
+            ::std::default::Default::default()
+        }
+    }
+
+    impl crate::geobuf_pb::DefaultInstance for FeatureCollection {
+        fn default_instance() -> &'static FeatureCollection {
+            static instance: FeatureCollection = FeatureCollection {
+                features: ::std::vec::Vec::new(),
+                values: ::std::vec::Vec::new(),
+                custom_properties: ::std::vec::Vec::new(),
+            };
+            &instance
+        }
+    }
+
+    #[derive(PartialEq,Clone,Default,Debug)]
+    // @@protoc_insertion_point(message:Data.Topology)
+    pub struct Topology {
+        // message fields
+        // @@protoc_insertion_point(field:Data.Topology.transform)
+        pub transform: crate::geobuf_pb::MessageField<Transform>,
+        // @@protoc_insertion_point(field:Data.Topology.names)
+        pub names: ::std::vec::Vec<::std::string::String>,
+        // @@protoc_insertion_point(field:Data.Topology.objects)
+        pub objects: ::std::vec::Vec<Geometry>,
+        // @@protoc_insertion_point(field:Data.Topology.lengths)
+        pub lengths: ::std::vec::Vec<u32>,
+        // @@protoc_insertion_point(field:Data.Topology.coords)
+        pub coords: ::std::vec::Vec<i64>,
+        // @@protoc_insertion_point(field:Data.Topology.values)
+        pub values: ::std::vec::Vec<Value>,
+        // @@protoc_insertion_point(field:Data.Topology.custom_properties)
+        pub custom_properties: ::std::vec::Vec<u32>,
+    }
+
+    impl<'a> ::std::default::Default for &'a Topology {
+        fn default() -> &'a Topology {
+            <Topology as crate::geobuf_pb::DefaultInstance>::default_instance()
+        }
+    }
+
+    impl Topology {
+        pub fn new() -> Topology {
+            ::std::default::Default::default()
+        }
+    }
+
+    impl crate::geobuf_pb::DefaultInstance for Topology {
+        fn default_instance() -> &'static Topology {
+            static instance: Topology = Topology {
+                transform: crate::geobuf_pb::MessageField::none(),
+                names: ::std::vec::Vec::new(),
+                objects: ::std::vec::Vec::new(),
+                lengths: ::std::vec::Vec::new(),
+                coords: ::std::vec::Vec::new(),
+                values: ::std::vec::Vec::new(),
+                custom_properties: ::std::vec::Vec::new(),
+            };
+            &instance
+        }
+    }
+
+    #[derive(PartialEq,Clone,Default,Debug)]
+    // @@protoc_insertion_point(message:Data.Transform)
+    pub struct Transform {
+        // message fields
+        // @@protoc_insertion_point(field:Data.Transform.scale_x)
+        pub scale_x: ::std::option::Option<f64>,
+        // @@protoc_insertion_point(field:Data.Transform.scale_y)
+        pub scale_y: ::std::option::Option<f64>,
+        // @@protoc_insertion_point(field:Data.Transform.translate_x)
+        pub translate_x: ::std::option::Option<f64>,
+        // @@protoc_insertion_point(field:Data.Transform.translate_y)
+        pub translate_y: ::std::option::Option<f64>,
+    }
+
+    impl<'a> ::std::default::Default for &'a Transform {
+        fn default() -> &'a Transform {
+            <Transform as crate::geobuf_pb::DefaultInstance>::default_instance()
+        }
+    }
+
+    impl Transform {
+        pub fn new() -> Transform {
+            ::std::default::Default::default()
+        }
+
+        // optional double scale_x = 1;
+
+        pub fn scale_x(&self) -> f64 {
+            self.scale_x.unwrap_or(1f64)
+        }
+
+        pub fn clear_scale_x(&mut self) {
+            self.scale_x = ::std::option::Option::None;
+        }
+
+        pub fn has_scale_x(&self) -> bool {
+            self.scale_x.is_some()
+        }
+
+        // Param is passed by value, moved
+        pub fn set_scale_x(&mut self, v: f64) {
+            self.scale_x = ::std::option::Option::Some(v);
+        }
+
+        // optional double scale_y = 2;
+
+        pub fn scale_y(&self) -> f64 {
+            self.scale_y.unwrap_or(1f64)
+        }
+
+        pub fn clear_scale_y(&mut self) {
+            self.scale_y = ::std::option::Option::None;
+        }
+
+        pub fn has_scale_y(&self) -> bool {
+            self.scale_y.is_some()
+        }
+
+        // Param is passed by value, moved
+        pub fn set_scale_y(&mut self, v: f64) {
+            self.scale_y = ::std::option::Option::Some(v);
+        }
+
+        // optional double translate_x = 3;
+
+        pub fn translate_x(&self) -> f64 {
+            self.translate_x.unwrap_or(0.)
+        }
+
+        pub fn clear_translate_x(&mut self) {
+            self.translate_x = ::std::option::Option::None;
+        }
+
+        pub fn has_translate_x(&self) -> bool {
+            self.translate_x.is_some()
+        }
+
+        // Param is passed by value, moved
+        pub fn set_translate_x(&mut self, v: f64) {
+            self.translate_x = ::std::option::Option::Some(v);
+        }
+
+        // optional double translate_y = 4;
+
+        pub fn translate_y(&self) -> f64 {
+            self.translate_y.unwrap_or(0.)
+        }
+
+        pub fn clear_translate_y(&mut self) {
+            self.translate_y = ::std::option::Option::None;
+        }
+
+        pub fn has_translate_y(&self) -> bool {
+            self.translate_y.is_some()
+        }
+
+        // Param is passed by value, moved
+        pub fn set_translate_y(&mut self, v: f64) {
+            self.translate_y = ::std::option::Option::Some(v);
+        }
+    }
+
+    impl crate::geobuf_pb::DefaultInstance for Transform {
+        fn default_instance() -> &'static Transform {
+            static instance: Transform = Transform {
+                scale_x: ::std::option::Option::None,
+                scale_y: ::std::option::Option::None,
+                translate_x: ::std::option::Option::None,
+                translate_y: ::std::option::Option::None,
+            };
+            &instance
+        }
+    }
+
+    #[derive(PartialEq,Clone,Default,Debug)]
+    // @@protoc_insertion_point(message:Data.Value)
+    pub struct Value {
+        // message oneof groups
+        pub value_type: ::std::option::Option<value::Value_type>,
+    }
+
+    impl<'a> ::std::default::Default for &'a Value {
+        fn default() -> &'a Value {
+            <Value as crate::geobuf_pb::DefaultInstance>::default_instance()
+        }
+    }
+
+    impl Value {
+        pub fn new() -> Value {
+            ::std::default::Default::default()
+        }
+
+        // optional string string_value = 1;
+
+        pub fn string_value(&self) -> &str {
+            match self.value_type {
+                ::std::option::Option::Some(value::Value_type::StringValue(ref v)) => v,
+                _ => "",
+            }
+        }
+
+        pub fn clear_string_value(&mut self) {
+            self.value_type = ::std::option::Option::None;
+        }
+
+        pub fn has_string_value(&self) -> bool {
+            match self.value_type {
+                ::std::option::Option::Some(value::Value_type::StringValue(..)) => true,
+                _ => false,
+            }
+        }
+
+        // Param is passed by value, moved
+        pub fn set_string_value(&mut self, v: ::std::string::String) {
+            self.value_type = ::std::option::Option::Some(value::Value_type::StringValue(v))
+        }
+
+        // Mutable pointer to the field.
+        pub fn mut_string_value(&mut self) -> &mut ::std::string::String {
+            if let ::std::option::Option::Some(value::Value_type::StringValue(_)) = self.value_type {
+            } else {
+                self.value_type = ::std::option::Option::Some(value::Value_type::StringValue(::std::string::String::new()));
+            }
+            match self.value_type {
+                ::std::option::Option::Some(value::Value_type::StringValue(ref mut v)) => v,
+                _ => panic!(),
+            }
+        }
+
+        // Take field
+        pub fn take_string_value(&mut self) -> ::std::string::String {
+            if self.has_string_value() {
+                match self.value_type.take() {
+                    ::std::option::Option::Some(value::Value_type::StringValue(v)) => v,
+                    _ => panic!(),
+                }
+            } else {
+                ::std::string::String::new()
+            }
+        }
+
+        // optional double double_value = 2;
+
+        pub fn double_value(&self) -> f64 {
+            match self.value_type {
+                ::std::option::Option::Some(value::Value_type::DoubleValue(v)) => v,
+                _ => 0.,
+            }
+        }
+
+        pub fn clear_double_value(&mut self) {
+            self.value_type = ::std::option::Option::None;
+        }
+
+        pub fn has_double_value(&self) -> bool {
+            match self.value_type {
+                ::std::option::Option::Some(value::Value_type::DoubleValue(..)) => true,
+                _ => false,
+            }
+        }
+
+        // Param is passed by value, moved
+        pub fn set_double_value(&mut self, v: f64) {
+            self.value_type = ::std::option::Option::Some(value::Value_type::DoubleValue(v))
+        }
+
+        // optional uint64 pos_int_value = 3;
+
+        pub fn pos_int_value(&self) -> u64 {
+            match self.value_type {
+                ::std::option::Option::Some(value::Value_type::PosIntValue(v)) => v,
+                _ => 0,
+            }
+        }
+
+        pub fn clear_pos_int_value(&mut self) {
+            self.value_type = ::std::option::Option::None;
+        }
+
+        pub fn has_pos_int_value(&self) -> bool {
+            match self.value_type {
+                ::std::option::Option::Some(value::Value_type::PosIntValue(..)) => true,
+                _ => false,
+            }
+        }
+
+        // Param is passed by value, moved
+        pub fn set_pos_int_value(&mut self, v: u64) {
+            self.value_type = ::std::option::Option::Some(value::Value_type::PosIntValue(v))
+        }
+
+        // optional uint64 neg_int_value = 4;
+
+        pub fn neg_int_value(&self) -> u64 {
+            match self.value_type {
+                ::std::option::Option::Some(value::Value_type::NegIntValue(v)) => v,
+                _ => 0,
+            }
+        }
+
+        pub fn clear_neg_int_value(&mut self) {
+            self.value_type = ::std::option::Option::None;
+        }
+
+        pub fn has_neg_int_value(&self) -> bool {
+            match self.value_type {
+                ::std::option::Option::Some(value::Value_type::NegIntValue(..)) => true,
+                _ => false,
+            }
+        }
+
+        // Param is passed by value, moved
+        pub fn set_neg_int_value(&mut self, v: u64) {
+            self.value_type = ::std::option::Option::Some(value::Value_type::NegIntValue(v))
+        }
+
+        // optional bool bool_value = 5;
+
+        pub fn bool_value(&self) -> bool {
+            match self.value_type {
+                ::std::option::Option::Some(value::Value_type::BoolValue(v)) => v,
+                _ => false,
+            }
+        }
+
+        pub fn clear_bool_value(&mut self) {
+            self.value_type = ::std::option::Option::None;
+        }
+
+        pub fn has_bool_value(&self) -> bool {
+            match self.value_type {
+                ::std::option::Option::Some(value::Value_type::BoolValue(..)) => true,
+                _ => false,
+            }
+        }
+
+        // Param is passed by value, moved
+        pub fn set_bool_value(&mut self, v: bool) {
+            self.value_type = ::std::option::Option::Some(value::Value_type::BoolValue(v))
+        }
+
+        // optional string json_value = 6;
+
+        pub fn json_value(&self) -> &str {
+            match self.value_type {
+                ::std::option::Option::Some(value::Value_type::JsonValue(ref v)) => v,
+                _ => "",
+            }
+        }
+
+        pub fn clear_json_value(&mut self) {
+            self.value_type = ::std::option::Option::None;
+        }
+
+        pub fn has_json_value(&self) -> bool {
+            match self.value_type {
+                ::std::option::Option::Some(value::Value_type::JsonValue(..)) => true,
+                _ => false,
+            }
+        }
+
+        // Param is passed by value, moved
+        pub fn set_json_value(&mut self, v: ::std::string::String) {
+            self.value_type = ::std::option::Option::Some(value::Value_type::JsonValue(v))
+        }
+
+        // Mutable pointer to the field.
+        pub fn mut_json_value(&mut self) -> &mut ::std::string::String {
+            if let ::std::option::Option::Some(value::Value_type::JsonValue(_)) = self.value_type {
+            } else {
+                self.value_type = ::std::option::Option::Some(value::Value_type::JsonValue(::std::string::String::new()));
+            }
+            match self.value_type {
+                ::std::option::Option::Some(value::Value_type::JsonValue(ref mut v)) => v,
+                _ => panic!(),
+            }
+        }
+
+        // Take field
+        pub fn take_json_value(&mut self) -> ::std::string::String {
+            if self.has_json_value() {
+                match self.value_type.take() {
+                    ::std::option::Option::Some(value::Value_type::JsonValue(v)) => v,
+                    _ => panic!(),
+                }
+            } else {
+                ::std::string::String::new()
+            }
+        }
+
+        // optional uint32 string_ref = 7;
+
+        pub fn string_ref(&self) -> u32 {
+            match self.value_type {
+                ::std::option::Option::Some(value::Value_type::StringRef(v)) => v,
+                _ => 0,
+            }
+        }
+
+        pub fn clear_string_ref(&mut self) {
+            self.value_type = ::std::option::Option::None;
+        }
+
+        pub fn has_string_ref(&self) -> bool {
+            match self.value_type {
+                ::std::option::Option::Some(value::Value_type::StringRef(..)) => true,
+                _ => false,
+            }
+        }
+
+        // Param is passed by value, moved
+        pub fn set_string_ref(&mut self, v: u32) {
+            self.value_type = ::std::option::Option::Some(value::Value_type::StringRef(v))
+        }
+    }
+
+    impl crate::geobuf_pb::DefaultInstance for Value {
+        fn default_instance() -> &'static Value {
+            static instance: Value = Value {
+                value_type: ::std::option::Option::None,
+            };
+            &instance
+        }
+    }
+
+    /// Nested message and enums of message `Value`
+    pub mod value {
+
+        #[derive(Clone,PartialEq,Debug)]
+        #[non_exhaustive]
+        // @@protoc_insertion_point(oneof:Data.Value.value_type)
+        pub enum Value_type {
+            // @@protoc_insertion_point(oneof_field:Data.Value.string_value)
+            StringValue(::std::string::String),
+            // @@protoc_insertion_point(oneof_field:Data.Value.double_value)
+            DoubleValue(f64),
+            // @@protoc_insertion_point(oneof_field:Data.Value.pos_int_value)
+            PosIntValue(u64),
+            // @@protoc_insertion_point(oneof_field:Data.Value.neg_int_value)
+            NegIntValue(u64),
+            // @@protoc_insertion_point(oneof_field:Data.Value.bool_value)
+            BoolValue(bool),
+            // @@protoc_insertion_point(oneof_field:Data.Value.json_value)
+            JsonValue(::std::string::String),
+            // @@protoc_insertion_point(oneof_field:Data.Value.string_ref)
+            StringRef(u32),
+        }
+
+        impl Value_type {
+        }
+    }
+}
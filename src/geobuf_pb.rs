@@ -36,6 +36,10 @@ pub struct Data {
     pub dimensions: ::std::option::Option<u32>,
     // @@protoc_insertion_point(field:Data.precision)
     pub precision: ::std::option::Option<u32>,
+    // @@protoc_insertion_point(field:Data.strings)
+    pub strings: ::std::vec::Vec<::std::string::String>,
+    // @@protoc_insertion_point(field:Data.transform)
+    pub transform: ::protobuf::MessageField<data::Transform>,
     // message oneof groups
     pub data_type: ::std::option::Option<data::Data_type>,
     // special fields
@@ -238,12 +242,66 @@ impl Data {
             data::Geometry::new()
         }
     }
+
+    // optional .Data.Topology topology = 7;
+
+    pub fn topology(&self) -> &data::Topology {
+        match self.data_type {
+            ::std::option::Option::Some(data::Data_type::Topology(ref v)) => v,
+            _ => <data::Topology as ::protobuf::Message>::default_instance(),
+        }
+    }
+
+    pub fn clear_topology(&mut self) {
+        self.data_type = ::std::option::Option::None;
+    }
+
+    pub fn has_topology(&self) -> bool {
+        match self.data_type {
+            ::std::option::Option::Some(data::Data_type::Topology(..)) => true,
+            _ => false,
+        }
+    }
+
+    // Param is passed by value, moved
+    pub fn set_topology(&mut self, v: data::Topology) {
+        self.data_type = ::std::option::Option::Some(data::Data_type::Topology(v))
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_topology(&mut self) -> &mut data::Topology {
+        if let ::std::option::Option::Some(data::Data_type::Topology(_)) = self.data_type {
+        } else {
+            self.data_type = ::std::option::Option::Some(data::Data_type::Topology(data::Topology::new()));
+        }
+        match self.data_type {
+            ::std::option::Option::Some(data::Data_type::Topology(ref mut v)) => v,
+            _ => panic!(),
+        }
+    }
+
+    // Take field
+    pub fn take_topology(&mut self) -> data::Topology {
+        if self.has_topology() {
+            match self.data_type.take() {
+                ::std::option::Option::Some(data::Data_type::Topology(v)) => v,
+                _ => panic!(),
+            }
+        } else {
+            data::Topology::new()
+        }
+    }
 }
 
 impl ::protobuf::Message for Data {
     const NAME: &'static str = "Data";
 
     fn is_initialized(&self) -> bool {
+        for v in &self.transform {
+            if !v.is_initialized() {
+                return false;
+            }
+        };
         if let Some(data::Data_type::FeatureCollection(ref v)) = self.data_type {
             if !v.is_initialized() {
                 return false;
@@ -259,6 +317,11 @@ impl ::protobuf::Message for Data {
                 return false;
             }
         }
+        if let Some(data::Data_type::Topology(ref v)) = self.data_type {
+            if !v.is_initialized() {
+                return false;
+            }
+        }
         true
     }
 
@@ -283,6 +346,15 @@ impl ::protobuf::Message for Data {
                 50 => {
                     self.data_type = ::std::option::Option::Some(data::Data_type::Geometry(is.read_message()?));
                 },
+                58 => {
+                    self.data_type = ::std::option::Option::Some(data::Data_type::Topology(is.read_message()?));
+                },
+                66 => {
+                    self.strings.push(is.read_string()?);
+                },
+                74 => {
+                    ::protobuf::rt::read_singular_message_into_field(is, &mut self.transform)?;
+                },
                 tag => {
                     ::protobuf::rt::read_unknown_or_skip_group(tag, is, self.special_fields.mut_unknown_fields())?;
                 },
@@ -304,6 +376,13 @@ impl ::protobuf::Message for Data {
         if let Some(v) = self.precision {
             my_size += ::protobuf::rt::uint32_size(3, v);
         }
+        for value in &self.strings {
+            my_size += ::protobuf::rt::string_size(8, &value);
+        };
+        if let Some(v) = self.transform.as_ref() {
+            let len = v.compute_size();
+            my_size += 1 + ::protobuf::rt::compute_raw_varint64_size(len) + len;
+        }
         if let ::std::option::Option::Some(ref v) = self.data_type {
             match v {
                 &data::Data_type::FeatureCollection(ref v) => {
@@ -318,6 +397,10 @@ impl ::protobuf::Message for Data {
                     let len = v.compute_size();
                     my_size += 1 + ::protobuf::rt::compute_raw_varint64_size(len) + len;
                 },
+                &data::Data_type::Topology(ref v) => {
+                    let len = v.compute_size();
+                    my_size += 1 + ::protobuf::rt::compute_raw_varint64_size(len) + len;
+                },
             };
         }
         my_size += ::protobuf::rt::unknown_fields_size(self.special_fields.unknown_fields());
@@ -335,6 +418,12 @@ impl ::protobuf::Message for Data {
         if let Some(v) = self.precision {
             os.write_uint32(3, v)?;
         }
+        for v in &self.strings {
+            os.write_string(8, &v)?;
+        };
+        if let Some(v) = self.transform.as_ref() {
+            ::protobuf::rt::write_message_field_with_cached_size(9, v, os)?;
+        }
         if let ::std::option::Option::Some(ref v) = self.data_type {
             match v {
                 &data::Data_type::FeatureCollection(ref v) => {
@@ -346,6 +435,9 @@ impl ::protobuf::Message for Data {
                 &data::Data_type::Geometry(ref v) => {
                     ::protobuf::rt::write_message_field_with_cached_size(6, v, os)?;
                 },
+                &data::Data_type::Topology(ref v) => {
+                    ::protobuf::rt::write_message_field_with_cached_size(7, v, os)?;
+                },
             };
         }
         os.write_unknown_fields(self.special_fields.unknown_fields())?;
@@ -368,6 +460,9 @@ impl ::protobuf::Message for Data {
         self.keys.clear();
         self.dimensions = ::std::option::Option::None;
         self.precision = ::std::option::Option::None;
+        self.strings.clear();
+        self.transform.clear();
+        self.data_type = ::std::option::Option::None;
         self.data_type = ::std::option::Option::None;
         self.data_type = ::std::option::Option::None;
         self.data_type = ::std::option::Option::None;
@@ -379,6 +474,8 @@ impl ::protobuf::Message for Data {
             keys: ::std::vec::Vec::new(),
             dimensions: ::std::option::Option::None,
             precision: ::std::option::Option::None,
+            strings: ::std::vec::Vec::new(),
+            transform: ::protobuf::MessageField::none(),
             data_type: ::std::option::Option::None,
             special_fields: ::protobuf::SpecialFields::new(),
         };
@@ -399,6 +496,8 @@ pub mod data {
         Feature(Feature),
         // @@protoc_insertion_point(oneof_field:Data.geometry)
         Geometry(Geometry),
+        // @@protoc_insertion_point(oneof_field:Data.topology)
+        Topology(Topology),
     }
 
     impl ::protobuf::Oneof for Data_type {
@@ -685,10 +784,16 @@ pub mod data {
         pub coords: ::std::vec::Vec<i64>,
         // @@protoc_insertion_point(field:Data.Geometry.geometries)
         pub geometries: ::std::vec::Vec<Geometry>,
+        // @@protoc_insertion_point(field:Data.Geometry.arcs)
+        pub arcs: ::std::vec::Vec<i32>,
         // @@protoc_insertion_point(field:Data.Geometry.values)
         pub values: ::std::vec::Vec<Value>,
+        // @@protoc_insertion_point(field:Data.Geometry.properties)
+        pub properties: ::std::vec::Vec<u32>,
         // @@protoc_insertion_point(field:Data.Geometry.custom_properties)
         pub custom_properties: ::std::vec::Vec<u32>,
+        // message oneof groups
+        pub id_type: ::std::option::Option<geometry::Id_type>,
         // special fields
         // @@protoc_insertion_point(special_field:Data.Geometry.special_fields)
         pub special_fields: ::protobuf::SpecialFields,
@@ -726,6 +831,80 @@ pub mod data {
         pub fn set_type(&mut self, v: geometry::Type) {
             self.type_ = ::std::option::Option::Some(::protobuf::EnumOrUnknown::new(v));
         }
+
+        // optional string id = 11;
+
+        pub fn id(&self) -> &str {
+            match self.id_type {
+                ::std::option::Option::Some(geometry::Id_type::Id(ref v)) => v,
+                _ => "",
+            }
+        }
+
+        pub fn clear_id(&mut self) {
+            self.id_type = ::std::option::Option::None;
+        }
+
+        pub fn has_id(&self) -> bool {
+            match self.id_type {
+                ::std::option::Option::Some(geometry::Id_type::Id(..)) => true,
+                _ => false,
+            }
+        }
+
+        // Param is passed by value, moved
+        pub fn set_id(&mut self, v: ::std::string::String) {
+            self.id_type = ::std::option::Option::Some(geometry::Id_type::Id(v))
+        }
+
+        // Mutable pointer to the field.
+        pub fn mut_id(&mut self) -> &mut ::std::string::String {
+            if let ::std::option::Option::Some(geometry::Id_type::Id(_)) = self.id_type {
+            } else {
+                self.id_type = ::std::option::Option::Some(geometry::Id_type::Id(::std::string::String::new()));
+            }
+            match self.id_type {
+                ::std::option::Option::Some(geometry::Id_type::Id(ref mut v)) => v,
+                _ => panic!(),
+            }
+        }
+
+        // Take field
+        pub fn take_id(&mut self) -> ::std::string::String {
+            if self.has_id() {
+                match self.id_type.take() {
+                    ::std::option::Option::Some(geometry::Id_type::Id(v)) => v,
+                    _ => panic!(),
+                }
+            } else {
+                ::std::string::String::new()
+            }
+        }
+
+        // optional sint64 int_id = 12;
+
+        pub fn int_id(&self) -> i64 {
+            match self.id_type {
+                ::std::option::Option::Some(geometry::Id_type::IntId(v)) => v,
+                _ => 0,
+            }
+        }
+
+        pub fn clear_int_id(&mut self) {
+            self.id_type = ::std::option::Option::None;
+        }
+
+        pub fn has_int_id(&self) -> bool {
+            match self.id_type {
+                ::std::option::Option::Some(geometry::Id_type::IntId(..)) => true,
+                _ => false,
+            }
+        }
+
+        // Param is passed by value, moved
+        pub fn set_int_id(&mut self, v: i64) {
+            self.id_type = ::std::option::Option::Some(geometry::Id_type::IntId(v))
+        }
     }
 
     impl ::protobuf::Message for Geometry {
@@ -769,9 +948,27 @@ pub mod data {
                     34 => {
                         self.geometries.push(is.read_message()?);
                     },
+                    42 => {
+                        is.read_repeated_packed_sint32_into(&mut self.arcs)?;
+                    },
+                    40 => {
+                        self.arcs.push(is.read_sint32()?);
+                    },
+                    90 => {
+                        self.id_type = ::std::option::Option::Some(geometry::Id_type::Id(is.read_string()?));
+                    },
+                    96 => {
+                        self.id_type = ::std::option::Option::Some(geometry::Id_type::IntId(is.read_sint64()?));
+                    },
                     106 => {
                         self.values.push(is.read_message()?);
                     },
+                    114 => {
+                        is.read_repeated_packed_uint32_into(&mut self.properties)?;
+                    },
+                    112 => {
+                        self.properties.push(is.read_uint32()?);
+                    },
                     122 => {
                         is.read_repeated_packed_uint32_into(&mut self.custom_properties)?;
                     },
@@ -799,11 +996,23 @@ pub mod data {
                 let len = value.compute_size();
                 my_size += 1 + ::protobuf::rt::compute_raw_varint64_size(len) + len;
             };
+            my_size += ::protobuf::rt::vec_packed_sint32_size(5, &self.arcs);
             for value in &self.values {
                 let len = value.compute_size();
                 my_size += 1 + ::protobuf::rt::compute_raw_varint64_size(len) + len;
             };
+            my_size += ::protobuf::rt::vec_packed_uint32_size(14, &self.properties);
             my_size += ::protobuf::rt::vec_packed_uint32_size(15, &self.custom_properties);
+            if let ::std::option::Option::Some(ref v) = self.id_type {
+                match v {
+                    &geometry::Id_type::Id(ref v) => {
+                        my_size += ::protobuf::rt::string_size(11, &v);
+                    },
+                    &geometry::Id_type::IntId(v) => {
+                        my_size += ::protobuf::rt::sint64_size(12, v);
+                    },
+                };
+            }
             my_size += ::protobuf::rt::unknown_fields_size(self.special_fields.unknown_fields());
             self.special_fields.cached_size().set(my_size as u32);
             my_size
@@ -818,10 +1027,22 @@ pub mod data {
             for v in &self.geometries {
                 ::protobuf::rt::write_message_field_with_cached_size(4, v, os)?;
             };
+            os.write_repeated_packed_sint32(5, &self.arcs)?;
             for v in &self.values {
                 ::protobuf::rt::write_message_field_with_cached_size(13, v, os)?;
             };
+            os.write_repeated_packed_uint32(14, &self.properties)?;
             os.write_repeated_packed_uint32(15, &self.custom_properties)?;
+            if let ::std::option::Option::Some(ref v) = self.id_type {
+                match v {
+                    &geometry::Id_type::Id(ref v) => {
+                        os.write_string(11, v)?;
+                    },
+                    &geometry::Id_type::IntId(v) => {
+                        os.write_sint64(12, v)?;
+                    },
+                };
+            }
             os.write_unknown_fields(self.special_fields.unknown_fields())?;
             ::std::result::Result::Ok(())
         }
@@ -843,7 +1064,11 @@ pub mod data {
             self.lengths.clear();
             self.coords.clear();
             self.geometries.clear();
+            self.arcs.clear();
+            self.id_type = ::std::option::Option::None;
+            self.id_type = ::std::option::Option::None;
             self.values.clear();
+            self.properties.clear();
             self.custom_properties.clear();
             self.special_fields.clear();
         }
@@ -854,8 +1079,11 @@ pub mod data {
                 lengths: ::std::vec::Vec::new(),
                 coords: ::std::vec::Vec::new(),
                 geometries: ::std::vec::Vec::new(),
+                arcs: ::std::vec::Vec::new(),
                 values: ::std::vec::Vec::new(),
+                properties: ::std::vec::Vec::new(),
                 custom_properties: ::std::vec::Vec::new(),
+                id_type: ::std::option::Option::None,
                 special_fields: ::protobuf::SpecialFields::new(),
             };
             &instance
@@ -864,6 +1092,22 @@ pub mod data {
 
     /// Nested message and enums of message `Geometry`
     pub mod geometry {
+
+        #[derive(Clone,PartialEq,Debug)]
+        #[non_exhaustive]
+        // @@protoc_insertion_point(oneof:Data.Geometry.id_type)
+        pub enum Id_type {
+            // @@protoc_insertion_point(oneof_field:Data.Geometry.id)
+            Id(::std::string::String),
+            // @@protoc_insertion_point(oneof_field:Data.Geometry.int_id)
+            IntId(i64),
+        }
+
+        impl ::protobuf::Oneof for Id_type {
+        }
+
+        impl Id_type {
+        }
         #[derive(Clone,Copy,PartialEq,Eq,Debug,Hash)]
         // @@protoc_insertion_point(enum:Data.Geometry.Type)
         pub enum Type {
@@ -1049,6 +1293,394 @@ pub mod data {
         }
     }
 
+    #[derive(PartialEq,Clone,Default,Debug)]
+    // @@protoc_insertion_point(message:Data.Topology)
+    pub struct Topology {
+        // message fields
+        // @@protoc_insertion_point(field:Data.Topology.transform)
+        pub transform: ::protobuf::MessageField<Transform>,
+        // @@protoc_insertion_point(field:Data.Topology.names)
+        pub names: ::std::vec::Vec<::std::string::String>,
+        // @@protoc_insertion_point(field:Data.Topology.objects)
+        pub objects: ::std::vec::Vec<Geometry>,
+        // @@protoc_insertion_point(field:Data.Topology.lengths)
+        pub lengths: ::std::vec::Vec<u32>,
+        // @@protoc_insertion_point(field:Data.Topology.coords)
+        pub coords: ::std::vec::Vec<i64>,
+        // @@protoc_insertion_point(field:Data.Topology.values)
+        pub values: ::std::vec::Vec<Value>,
+        // @@protoc_insertion_point(field:Data.Topology.custom_properties)
+        pub custom_properties: ::std::vec::Vec<u32>,
+        // special fields
+        // @@protoc_insertion_point(special_field:Data.Topology.special_fields)
+        pub special_fields: ::protobuf::SpecialFields,
+    }
+
+    impl<'a> ::std::default::Default for &'a Topology {
+        fn default() -> &'a Topology {
+            <Topology as ::protobuf::Message>::default_instance()
+        }
+    }
+
+    impl Topology {
+        pub fn new() -> Topology {
+            ::std::default::Default::default()
+        }
+    }
+
+    impl ::protobuf::Message for Topology {
+        const NAME: &'static str = "Topology";
+
+        fn is_initialized(&self) -> bool {
+            for v in &self.transform {
+                if !v.is_initialized() {
+                    return false;
+                }
+            };
+            for v in &self.objects {
+                if !v.is_initialized() {
+                    return false;
+                }
+            };
+            for v in &self.values {
+                if !v.is_initialized() {
+                    return false;
+                }
+            };
+            true
+        }
+
+        fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::Result<()> {
+            while let Some(tag) = is.read_raw_tag_or_eof()? {
+                match tag {
+                    10 => {
+                        ::protobuf::rt::read_singular_message_into_field(is, &mut self.transform)?;
+                    },
+                    18 => {
+                        self.names.push(is.read_string()?);
+                    },
+                    26 => {
+                        self.objects.push(is.read_message()?);
+                    },
+                    34 => {
+                        is.read_repeated_packed_uint32_into(&mut self.lengths)?;
+                    },
+                    32 => {
+                        self.lengths.push(is.read_uint32()?);
+                    },
+                    42 => {
+                        is.read_repeated_packed_sint64_into(&mut self.coords)?;
+                    },
+                    40 => {
+                        self.coords.push(is.read_sint64()?);
+                    },
+                    106 => {
+                        self.values.push(is.read_message()?);
+                    },
+                    122 => {
+                        is.read_repeated_packed_uint32_into(&mut self.custom_properties)?;
+                    },
+                    120 => {
+                        self.custom_properties.push(is.read_uint32()?);
+                    },
+                    tag => {
+                        ::protobuf::rt::read_unknown_or_skip_group(tag, is, self.special_fields.mut_unknown_fields())?;
+                    },
+                };
+            }
+            ::std::result::Result::Ok(())
+        }
+
+        // Compute sizes of nested messages
+        #[allow(unused_variables)]
+        fn compute_size(&self) -> u64 {
+            let mut my_size = 0;
+            if let Some(v) = self.transform.as_ref() {
+                let len = v.compute_size();
+                my_size += 1 + ::protobuf::rt::compute_raw_varint64_size(len) + len;
+            }
+            for value in &self.names {
+                my_size += ::protobuf::rt::string_size(2, &value);
+            };
+            for value in &self.objects {
+                let len = value.compute_size();
+                my_size += 1 + ::protobuf::rt::compute_raw_varint64_size(len) + len;
+            };
+            my_size += ::protobuf::rt::vec_packed_uint32_size(4, &self.lengths);
+            my_size += ::protobuf::rt::vec_packed_sint64_size(5, &self.coords);
+            for value in &self.values {
+                let len = value.compute_size();
+                my_size += 1 + ::protobuf::rt::compute_raw_varint64_size(len) + len;
+            };
+            my_size += ::protobuf::rt::vec_packed_uint32_size(15, &self.custom_properties);
+            my_size += ::protobuf::rt::unknown_fields_size(self.special_fields.unknown_fields());
+            self.special_fields.cached_size().set(my_size as u32);
+            my_size
+        }
+
+        fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::Result<()> {
+            if let Some(v) = self.transform.as_ref() {
+                ::protobuf::rt::write_message_field_with_cached_size(1, v, os)?;
+            }
+            for v in &self.names {
+                os.write_string(2, &v)?;
+            };
+            for v in &self.objects {
+                ::protobuf::rt::write_message_field_with_cached_size(3, v, os)?;
+            };
+            os.write_repeated_packed_uint32(4, &self.lengths)?;
+            os.write_repeated_packed_sint64(5, &self.coords)?;
+            for v in &self.values {
+                ::protobuf::rt::write_message_field_with_cached_size(13, v, os)?;
+            };
+            os.write_repeated_packed_uint32(15, &self.custom_properties)?;
+            os.write_unknown_fields(self.special_fields.unknown_fields())?;
+            ::std::result::Result::Ok(())
+        }
+
+        fn special_fields(&self) -> &::protobuf::SpecialFields {
+            &self.special_fields
+        }
+
+        fn mut_special_fields(&mut self) -> &mut ::protobuf::SpecialFields {
+            &mut self.special_fields
+        }
+
+        fn new() -> Topology {
+            Topology::new()
+        }
+
+        fn clear(&mut self) {
+            self.transform.clear();
+            self.names.clear();
+            self.objects.clear();
+            self.lengths.clear();
+            self.coords.clear();
+            self.values.clear();
+            self.custom_properties.clear();
+            self.special_fields.clear();
+        }
+
+        fn default_instance() -> &'static Topology {
+            static instance: Topology = Topology {
+                transform: ::protobuf::MessageField::none(),
+                names: ::std::vec::Vec::new(),
+                objects: ::std::vec::Vec::new(),
+                lengths: ::std::vec::Vec::new(),
+                coords: ::std::vec::Vec::new(),
+                values: ::std::vec::Vec::new(),
+                custom_properties: ::std::vec::Vec::new(),
+                special_fields: ::protobuf::SpecialFields::new(),
+            };
+            &instance
+        }
+    }
+
+    #[derive(PartialEq,Clone,Default,Debug)]
+    // @@protoc_insertion_point(message:Data.Transform)
+    pub struct Transform {
+        // message fields
+        // @@protoc_insertion_point(field:Data.Transform.scale_x)
+        pub scale_x: ::std::option::Option<f64>,
+        // @@protoc_insertion_point(field:Data.Transform.scale_y)
+        pub scale_y: ::std::option::Option<f64>,
+        // @@protoc_insertion_point(field:Data.Transform.translate_x)
+        pub translate_x: ::std::option::Option<f64>,
+        // @@protoc_insertion_point(field:Data.Transform.translate_y)
+        pub translate_y: ::std::option::Option<f64>,
+        // special fields
+        // @@protoc_insertion_point(special_field:Data.Transform.special_fields)
+        pub special_fields: ::protobuf::SpecialFields,
+    }
+
+    impl<'a> ::std::default::Default for &'a Transform {
+        fn default() -> &'a Transform {
+            <Transform as ::protobuf::Message>::default_instance()
+        }
+    }
+
+    impl Transform {
+        pub fn new() -> Transform {
+            ::std::default::Default::default()
+        }
+
+        // optional double scale_x = 1;
+
+        pub fn scale_x(&self) -> f64 {
+            self.scale_x.unwrap_or(1f64)
+        }
+
+        pub fn clear_scale_x(&mut self) {
+            self.scale_x = ::std::option::Option::None;
+        }
+
+        pub fn has_scale_x(&self) -> bool {
+            self.scale_x.is_some()
+        }
+
+        // Param is passed by value, moved
+        pub fn set_scale_x(&mut self, v: f64) {
+            self.scale_x = ::std::option::Option::Some(v);
+        }
+
+        // optional double scale_y = 2;
+
+        pub fn scale_y(&self) -> f64 {
+            self.scale_y.unwrap_or(1f64)
+        }
+
+        pub fn clear_scale_y(&mut self) {
+            self.scale_y = ::std::option::Option::None;
+        }
+
+        pub fn has_scale_y(&self) -> bool {
+            self.scale_y.is_some()
+        }
+
+        // Param is passed by value, moved
+        pub fn set_scale_y(&mut self, v: f64) {
+            self.scale_y = ::std::option::Option::Some(v);
+        }
+
+        // optional double translate_x = 3;
+
+        pub fn translate_x(&self) -> f64 {
+            self.translate_x.unwrap_or(0.)
+        }
+
+        pub fn clear_translate_x(&mut self) {
+            self.translate_x = ::std::option::Option::None;
+        }
+
+        pub fn has_translate_x(&self) -> bool {
+            self.translate_x.is_some()
+        }
+
+        // Param is passed by value, moved
+        pub fn set_translate_x(&mut self, v: f64) {
+            self.translate_x = ::std::option::Option::Some(v);
+        }
+
+        // optional double translate_y = 4;
+
+        pub fn translate_y(&self) -> f64 {
+            self.translate_y.unwrap_or(0.)
+        }
+
+        pub fn clear_translate_y(&mut self) {
+            self.translate_y = ::std::option::Option::None;
+        }
+
+        pub fn has_translate_y(&self) -> bool {
+            self.translate_y.is_some()
+        }
+
+        // Param is passed by value, moved
+        pub fn set_translate_y(&mut self, v: f64) {
+            self.translate_y = ::std::option::Option::Some(v);
+        }
+    }
+
+    impl ::protobuf::Message for Transform {
+        const NAME: &'static str = "Transform";
+
+        fn is_initialized(&self) -> bool {
+            true
+        }
+
+        fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::Result<()> {
+            while let Some(tag) = is.read_raw_tag_or_eof()? {
+                match tag {
+                    9 => {
+                        self.scale_x = ::std::option::Option::Some(is.read_double()?);
+                    },
+                    17 => {
+                        self.scale_y = ::std::option::Option::Some(is.read_double()?);
+                    },
+                    25 => {
+                        self.translate_x = ::std::option::Option::Some(is.read_double()?);
+                    },
+                    33 => {
+                        self.translate_y = ::std::option::Option::Some(is.read_double()?);
+                    },
+                    tag => {
+                        ::protobuf::rt::read_unknown_or_skip_group(tag, is, self.special_fields.mut_unknown_fields())?;
+                    },
+                };
+            }
+            ::std::result::Result::Ok(())
+        }
+
+        // Compute sizes of nested messages
+        #[allow(unused_variables)]
+        fn compute_size(&self) -> u64 {
+            let mut my_size = 0;
+            if let Some(v) = self.scale_x {
+                my_size += 1 + 8;
+            }
+            if let Some(v) = self.scale_y {
+                my_size += 1 + 8;
+            }
+            if let Some(v) = self.translate_x {
+                my_size += 1 + 8;
+            }
+            if let Some(v) = self.translate_y {
+                my_size += 1 + 8;
+            }
+            my_size += ::protobuf::rt::unknown_fields_size(self.special_fields.unknown_fields());
+            self.special_fields.cached_size().set(my_size as u32);
+            my_size
+        }
+
+        fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::Result<()> {
+            if let Some(v) = self.scale_x {
+                os.write_double(1, v)?;
+            }
+            if let Some(v) = self.scale_y {
+                os.write_double(2, v)?;
+            }
+            if let Some(v) = self.translate_x {
+                os.write_double(3, v)?;
+            }
+            if let Some(v) = self.translate_y {
+                os.write_double(4, v)?;
+            }
+            os.write_unknown_fields(self.special_fields.unknown_fields())?;
+            ::std::result::Result::Ok(())
+        }
+
+        fn special_fields(&self) -> &::protobuf::SpecialFields {
+            &self.special_fields
+        }
+
+        fn mut_special_fields(&mut self) -> &mut ::protobuf::SpecialFields {
+            &mut self.special_fields
+        }
+
+        fn new() -> Transform {
+            Transform::new()
+        }
+
+        fn clear(&mut self) {
+            self.scale_x = ::std::option::Option::None;
+            self.scale_y = ::std::option::Option::None;
+            self.translate_x = ::std::option::Option::None;
+            self.translate_y = ::std::option::Option::None;
+            self.special_fields.clear();
+        }
+
+        fn default_instance() -> &'static Transform {
+            static instance: Transform = Transform {
+                scale_x: ::std::option::Option::None,
+                scale_y: ::std::option::Option::None,
+                translate_x: ::std::option::Option::None,
+                translate_y: ::std::option::Option::None,
+                special_fields: ::protobuf::SpecialFields::new(),
+            };
+            &instance
+        }
+    }
+
     #[derive(PartialEq,Clone,Default,Debug)]
     // @@protoc_insertion_point(message:Data.Value)
     pub struct Value {
@@ -1267,6 +1899,31 @@ pub mod data {
                 ::std::string::String::new()
             }
         }
+
+        // optional uint32 string_ref = 7;
+
+        pub fn string_ref(&self) -> u32 {
+            match self.value_type {
+                ::std::option::Option::Some(value::Value_type::StringRef(v)) => v,
+                _ => 0,
+            }
+        }
+
+        pub fn clear_string_ref(&mut self) {
+            self.value_type = ::std::option::Option::None;
+        }
+
+        pub fn has_string_ref(&self) -> bool {
+            match self.value_type {
+                ::std::option::Option::Some(value::Value_type::StringRef(..)) => true,
+                _ => false,
+            }
+        }
+
+        // Param is passed by value, moved
+        pub fn set_string_ref(&mut self, v: u32) {
+            self.value_type = ::std::option::Option::Some(value::Value_type::StringRef(v))
+        }
     }
 
     impl ::protobuf::Message for Value {
@@ -1297,6 +1954,9 @@ pub mod data {
                     50 => {
                         self.value_type = ::std::option::Option::Some(value::Value_type::JsonValue(is.read_string()?));
                     },
+                    56 => {
+                        self.value_type = ::std::option::Option::Some(value::Value_type::StringRef(is.read_uint32()?));
+                    },
                     tag => {
                         ::protobuf::rt::read_unknown_or_skip_group(tag, is, self.special_fields.mut_unknown_fields())?;
                     },
@@ -1329,6 +1989,9 @@ pub mod data {
                     &value::Value_type::JsonValue(ref v) => {
                         my_size += ::protobuf::rt::string_size(6, &v);
                     },
+                    &value::Value_type::StringRef(v) => {
+                        my_size += ::protobuf::rt::uint32_size(7, v);
+                    },
                 };
             }
             my_size += ::protobuf::rt::unknown_fields_size(self.special_fields.unknown_fields());
@@ -1357,6 +2020,9 @@ pub mod data {
                     &value::Value_type::JsonValue(ref v) => {
                         os.write_string(6, v)?;
                     },
+                    &value::Value_type::StringRef(v) => {
+                        os.write_uint32(7, v)?;
+                    },
                 };
             }
             os.write_unknown_fields(self.special_fields.unknown_fields())?;
@@ -1382,6 +2048,7 @@ pub mod data {
             self.value_type = ::std::option::Option::None;
             self.value_type = ::std::option::Option::None;
             self.value_type = ::std::option::Option::None;
+            self.value_type = ::std::option::Option::None;
             self.special_fields.clear();
         }
 
@@ -1413,6 +2080,8 @@ pub mod data {
             BoolValue(bool),
             // @@protoc_insertion_point(oneof_field:Data.Value.json_value)
             JsonValue(::std::string::String),
+            // @@protoc_insertion_point(oneof_field:Data.Value.string_ref)
+            StringRef(u32),
         }
 
         impl ::protobuf::Oneof for Value_type {
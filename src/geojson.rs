@@ -0,0 +1,79 @@
+//! Conversions between `Data` and the `GeoJson` type of the geojson crate
+//!
+//! Geobufs are encoded with the precision, up to 6, and dimensions that keep every
+//! coordinate, as picked by `Encoder::analyze`. Topologies have no `GeoJson` equivalent and can't be
+//! converted to it.
+use std::fmt;
+
+use geojson::GeoJson;
+use serde_json::Value as JSONValue;
+
+use crate::decode::Decoder;
+use crate::encode::Encoder;
+use crate::geobuf_pb::Data;
+use crate::validate::validate;
+
+/// Error of a conversion between `Data` and `GeoJson`
+#[derive(Debug)]
+pub enum Error {
+    /// The GeoJSON can't be encoded, or the Geobuf is invalid and can't be decoded
+    Geobuf(String),
+    /// The decoded GeoJSON isn't accepted by the geojson crate
+    GeoJson(geojson::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Geobuf(err) => f.write_str(err),
+            Error::GeoJson(err) => err.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Geobuf(_) => None,
+            Error::GeoJson(err) => Some(err),
+        }
+    }
+}
+
+/// Encodes a `GeoJson` object, rounding coordinates to at most 6 decimals
+///
+/// # Example
+///
+/// ```
+/// use geobuf::geobuf_pb::Data;
+/// use geojson::GeoJson;
+///
+/// let geojson: GeoJson = r#"{"type": "Point", "coordinates": [1.25, 2.0, 3.0]}"#.parse().unwrap();
+/// let data = Data::try_from(&geojson).unwrap();
+/// assert_eq!((data.precision(), data.dimensions()), (2, 3));
+/// assert_eq!(GeoJson::try_from(&data).unwrap(), geojson);
+/// ```
+impl TryFrom<&GeoJson> for Data {
+    type Error = Error;
+
+    fn try_from(geojson: &GeoJson) -> Result<Self, Self::Error> {
+        let geojson = JSONValue::from(geojson.clone());
+        let (precision, dim) = Encoder::analyze(&geojson);
+        Encoder::check(&geojson, precision, dim).map_err(Error::Geobuf)?;
+        Encoder::encode(&geojson, precision, dim).map_err(|err| Error::Geobuf(String::from(err)))
+    }
+}
+
+/// Decodes a Geobuf holding a FeatureCollection, Feature or Geometry
+impl TryFrom<&Data> for GeoJson {
+    type Error = Error;
+
+    fn try_from(data: &Data) -> Result<Self, Self::Error> {
+        // Decoding panics on out of range indexes and lengths
+        if let Some(problem) = validate(data).first() {
+            return Err(Error::Geobuf(format!("Invalid geobuf: {}", problem)));
+        }
+        let geojson = Decoder::decode(data).map_err(|err| Error::Geobuf(String::from(err)))?;
+        GeoJson::from_json_value(geojson).map_err(Error::GeoJson)
+    }
+}
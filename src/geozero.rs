@@ -0,0 +1,593 @@
+//! Integration with the geozero processing API
+//!
+//! With the `geozero` feature, `Data` implements `GeozeroGeometry` and `GeozeroDatasource`, so
+//! that Geobufs can be passed to any geozero processor, such as the WKT, WKB, SVG or GeoJSON
+//! writers, and `GeobufWriter` is a processor encoding any geozero source into a Geobuf.
+//! Geometries are passed to processors straight from the Geobuf, and features are encoded
+//! one at a time. Geozero has no place for feature ids, so they are passed on as an `id`
+//! property and aren't read back, and Topologies can't be processed.
+use geozero::error::{GeozeroError, Result};
+use geozero::{
+    ColumnValue, CoordDimensions, FeatureProcessor, GeomProcessor, GeozeroDatasource,
+    GeozeroGeometry, PropertyProcessor,
+};
+use serde_json::{Map, Value as JSONValue};
+
+use crate::decode::Decoder;
+use crate::encode::Encoder;
+use crate::geobuf_pb::data::feature::Id_type;
+use crate::geobuf_pb::data::geometry::Type;
+use crate::geobuf_pb::data::value::Value_type;
+use crate::geobuf_pb::data::{Data_type, Feature, FeatureCollection, Geometry};
+use crate::geobuf_pb::Data;
+use crate::validate::validate;
+
+impl GeozeroGeometry for Data {
+    /// Processes the Geometry of the Geobuf, or the geometries of its features
+    fn process_geom<P: GeomProcessor>(&self, processor: &mut P) -> Result<()> {
+        let decoder = Decoder::new(checked(self)?);
+        if let Some(Data_type::Geometry(geometry)) = &self.data_type {
+            return process_geometry(&decoder, geometry, 0, processor);
+        }
+        for (idx, feature) in features(self)?.iter().enumerate() {
+            if let Some(geometry) = feature.geometry.as_ref() {
+                process_geometry(&decoder, geometry, idx, processor)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn dims(&self) -> CoordDimensions {
+        match self.dimensions() {
+            0..=2 => CoordDimensions::xy(),
+            3 => CoordDimensions::xyz(),
+            _ => CoordDimensions::xyzm(),
+        }
+    }
+}
+
+impl GeozeroDatasource for Data {
+    /// Processes the features of the Geobuf as a dataset, or its Geometry on its own
+    fn process<P: FeatureProcessor>(&mut self, processor: &mut P) -> Result<()> {
+        if let Some(Data_type::Geometry(_)) = self.data_type {
+            return GeozeroGeometry::process_geom(self, processor);
+        }
+        let decoder = Decoder::new(checked(self)?);
+        processor.dataset_begin(None)?;
+        for (idx, feature) in features(self)?.iter().enumerate() {
+            processor.feature_begin(idx as u64)?;
+            if !feature.properties.is_empty() || feature.id_type.is_some() {
+                processor.properties_begin()?;
+                process_properties(self, feature, processor)?;
+                processor.properties_end()?;
+            }
+            if let Some(geometry) = feature.geometry.as_ref() {
+                processor.geometry_begin()?;
+                // Geometries of features are numbered on their own
+                process_geometry(&decoder, geometry, 0, processor)?;
+                processor.geometry_end()?;
+            }
+            processor.feature_end(idx as u64)?;
+        }
+        processor.dataset_end()
+    }
+}
+
+/// Returns `data` if it can be decoded without panicking
+fn checked(data: &Data) -> Result<&Data> {
+    match validate(data).first() {
+        Some(problem) => Err(GeozeroError::Dataset(format!(
+            "Invalid geobuf: {}",
+            problem
+        ))),
+        None => Ok(data),
+    }
+}
+
+/// Returns the features of `data`, or an error for Topologies
+fn features(data: &Data) -> Result<&[Feature]> {
+    match data.data_type.as_ref() {
+        Some(Data_type::FeatureCollection(fc)) => Ok(&fc.features),
+        Some(Data_type::Feature(feature)) => Ok(std::slice::from_ref(feature)),
+        Some(Data_type::Topology(_)) => Err(GeozeroError::Dataset(String::from(
+            "Topologies can't be processed.",
+        ))),
+        _ => Err(GeozeroError::Dataset(String::from(
+            "Data holds no features.",
+        ))),
+    }
+}
+
+fn geometry_error(err: &str) -> GeozeroError {
+    GeozeroError::Geometry(String::from(err))
+}
+
+/// Passes a geometry to `processor`, with positions delta decoded by `decoder`
+fn process_geometry<P: GeomProcessor>(
+    decoder: &Decoder,
+    geometry: &Geometry,
+    idx: usize,
+    processor: &mut P,
+) -> Result<()> {
+    match geometry.type_() {
+        Type::POINT => {
+            processor.point_begin(idx)?;
+            process_position(&decoder.decode_point(&geometry.coords), 0, processor)?;
+            processor.point_end(idx)
+        }
+        Type::MULTIPOINT => {
+            let points = decoder.decode_line(&geometry.coords, false);
+            processor.multipoint_begin(points.len(), idx)?;
+            for (n, point) in points.iter().enumerate() {
+                process_position(point, n, processor)?;
+            }
+            processor.multipoint_end(idx)
+        }
+        Type::LINESTRING => {
+            let line = decoder.decode_line(&geometry.coords, false);
+            process_line(&line, true, idx, processor)
+        }
+        Type::MULTILINESTRING => {
+            let lines = decoder.decode_multi_line(geometry, false);
+            processor.multilinestring_begin(lines.len(), idx)?;
+            for (n, line) in lines.iter().enumerate() {
+                process_line(line, false, n, processor)?;
+            }
+            processor.multilinestring_end(idx)
+        }
+        Type::POLYGON => {
+            let rings = decoder.decode_multi_line(geometry, true);
+            process_polygon(&rings, true, idx, processor)
+        }
+        Type::MULTIPOLYGON => {
+            let polygons = decoder.decode_multi_polygon(geometry);
+            processor.multipolygon_begin(polygons.len(), idx)?;
+            for (n, polygon) in polygons.iter().enumerate() {
+                process_polygon(polygon, false, n, processor)?;
+            }
+            processor.multipolygon_end(idx)
+        }
+        Type::GEOMETRYCOLLECTION => {
+            processor.geometrycollection_begin(geometry.geometries.len(), idx)?;
+            for (n, geometry) in geometry.geometries.iter().enumerate() {
+                process_geometry(decoder, geometry, n, processor)?;
+            }
+            processor.geometrycollection_end(idx)
+        }
+    }
+}
+
+fn process_line<P: GeomProcessor>(
+    line: &[Vec<f64>],
+    tagged: bool,
+    idx: usize,
+    processor: &mut P,
+) -> Result<()> {
+    processor.linestring_begin(tagged, line.len(), idx)?;
+    for (n, position) in line.iter().enumerate() {
+        process_position(position, n, processor)?;
+    }
+    processor.linestring_end(tagged, idx)
+}
+
+fn process_polygon<P: GeomProcessor>(
+    rings: &[Vec<Vec<f64>>],
+    tagged: bool,
+    idx: usize,
+    processor: &mut P,
+) -> Result<()> {
+    processor.polygon_begin(tagged, rings.len(), idx)?;
+    for (n, ring) in rings.iter().enumerate() {
+        process_line(ring, false, n, processor)?;
+    }
+    processor.polygon_end(tagged, idx)
+}
+
+/// Passes the x and y of a position, and its third and fourth coordinates as z and m when the
+/// processor asks for more dimensions
+fn process_position<P: GeomProcessor>(
+    position: &[f64],
+    idx: usize,
+    processor: &mut P,
+) -> Result<()> {
+    let (x, y) = match position {
+        [x, y, ..] => (*x, *y),
+        _ => return Err(GeozeroError::Coord),
+    };
+    if processor.multi_dim() {
+        let (z, m) = (position.get(2).copied(), position.get(3).copied());
+        processor.coordinate(x, y, z, m, None, None, idx)
+    } else {
+        processor.xy(x, y, idx)
+    }
+}
+
+/// Passes the id of a feature as an `id` property, since geozero has no place for ids,
+/// unless it has a property named so, and then its properties, with arrays and objects as
+/// JSON and without nulls
+fn process_properties<P: PropertyProcessor>(
+    data: &Data,
+    feature: &Feature,
+    processor: &mut P,
+) -> Result<()> {
+    let pairs = feature.properties.chunks(2).map(|pair| {
+        (
+            &data.keys[pair[0] as usize],
+            &feature.values[pair[1] as usize],
+        )
+    });
+    let id = match &feature.id_type {
+        Some(Id_type::Id(id)) => Some(ColumnValue::String(id)),
+        Some(Id_type::IntId(id)) => Some(ColumnValue::Long(*id)),
+        None => None,
+    };
+    let mut idx = 0;
+    if let Some(id) = id {
+        if !pairs.clone().any(|(key, _)| key == "id") {
+            // true asks to stop processing the properties
+            if processor.property(idx, "id", &id)? {
+                return Ok(());
+            }
+            idx += 1;
+        }
+    }
+    for (key, value) in pairs {
+        let value = match value.value_type.as_ref() {
+            Some(Value_type::StringValue(value)) => ColumnValue::String(value),
+            Some(Value_type::DoubleValue(value)) => ColumnValue::Double(*value),
+            Some(Value_type::PosIntValue(value)) => ColumnValue::ULong(*value),
+            Some(Value_type::NegIntValue(value)) => {
+                ColumnValue::Long((*value as i64).wrapping_neg())
+            }
+            Some(Value_type::BoolValue(value)) => ColumnValue::Bool(*value),
+            Some(Value_type::JsonValue(value)) => ColumnValue::Json(value),
+            Some(Value_type::StringRef(index)) => match data.strings.get(*index as usize) {
+                Some(value) => ColumnValue::String(value),
+                None => continue,
+            },
+            None => continue,
+        };
+        if processor.property(idx, key, &value)? {
+            break;
+        }
+        idx += 1;
+    }
+    Ok(())
+}
+
+/// Processor encoding the features or the geometry of a geozero source into a Geobuf
+///
+/// Processed features are encoded as soon as they end into a FeatureCollection, and a
+/// geometry processed outside of any feature becomes the Geometry of the Geobuf. The writer
+/// asks sources for z coordinates when `dim` is 3 and for m values when it is 4, and the
+/// positions of the source need at least `dim` coordinates.
+///
+/// # Example
+///
+/// ```
+/// use geobuf::decode::Decoder;
+/// use geobuf::geozero::GeobufWriter;
+/// use geozero::geojson::GeoJson;
+/// use geozero::GeozeroDatasource;
+///
+/// let mut geojson = GeoJson(r#"{"type": "FeatureCollection", "features": [
+///     {"type": "Feature", "properties": {"name": "a"}, "geometry": {"type": "Point", "coordinates": [1.0, 2.0]}}
+/// ]}"#);
+/// let mut writer = GeobufWriter::new(6, 2);
+/// geojson.process(&mut writer).unwrap();
+/// let data = writer.finish();
+/// assert_eq!(Decoder::decode(&data).unwrap(), serde_json::from_str::<serde_json::Value>(geojson.0).unwrap());
+/// ```
+pub struct GeobufWriter {
+    data: Data,
+    /// Geometries being built, from the outermost one
+    geometries: Vec<PartialGeometry>,
+    /// Properties and geometry of the feature being processed
+    feature: Option<(Map<String, JSONValue>, JSONValue)>,
+}
+
+/// Geometry whose coordinates, or geometries for GeometryCollections, are being processed
+struct PartialGeometry {
+    type_: &'static str,
+    items: Vec<JSONValue>,
+}
+
+impl GeobufWriter {
+    pub fn new(precision: u32, dim: u32) -> Self {
+        let mut data = Data::new();
+        data.set_precision(precision);
+        data.set_dimensions(dim);
+        GeobufWriter {
+            data,
+            geometries: Vec::new(),
+            feature: None,
+        }
+    }
+
+    /// Returns the Geobuf holding what was processed
+    ///
+    /// Geobufs are left without data when nothing was processed.
+    pub fn finish(self) -> Data {
+        self.data
+    }
+
+    fn begin(&mut self, type_: &'static str) -> Result<()> {
+        self.geometries.push(PartialGeometry {
+            type_,
+            items: Vec::new(),
+        });
+        Ok(())
+    }
+
+    /// Adds the geometry that ends to its parent, or to the feature or Geobuf if it is the
+    /// outermost one
+    fn end(&mut self) -> Result<()> {
+        let geometry = self.geometries.pop().ok_or(GeozeroError::GeometryFormat)?;
+        let (key, value) = match geometry.type_ {
+            "GeometryCollection" => ("geometries", JSONValue::Array(geometry.items)),
+            "Point" => match geometry.items.into_iter().next() {
+                Some(position) => ("coordinates", position),
+                None => return Err(geometry_error("Point without coordinates.")),
+            },
+            _ => ("coordinates", JSONValue::Array(geometry.items)),
+        };
+        match self.geometries.last_mut() {
+            // Members of multi geometries and rings only add their coordinates
+            Some(parent) if parent.type_ != "GeometryCollection" => parent.items.push(value),
+            parent => {
+                let mut object = Map::new();
+                object.insert(String::from("type"), JSONValue::from(geometry.type_));
+                object.insert(String::from(key), value);
+                let object = JSONValue::Object(object);
+                match (parent, self.feature.as_mut()) {
+                    (Some(parent), _) => parent.items.push(object),
+                    (None, Some((_, feature_geometry))) => *feature_geometry = object,
+                    (None, None) => self.set_geometry(&object)?,
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn set_geometry(&mut self, geometry: &JSONValue) -> Result<()> {
+        let (precision, dim) = (self.data.precision(), self.data.dimensions());
+        Encoder::check(geometry, precision, dim).map_err(GeozeroError::Geometry)?;
+        self.data = Encoder::encode(geometry, precision, dim).map_err(geometry_error)?;
+        Ok(())
+    }
+
+    fn position(&mut self, position: Vec<f64>) -> Result<()> {
+        match self.geometries.last_mut() {
+            Some(geometry) => {
+                geometry.items.push(JSONValue::from(position));
+                Ok(())
+            }
+            None => Err(GeozeroError::Coord),
+        }
+    }
+}
+
+impl GeomProcessor for GeobufWriter {
+    fn dimensions(&self) -> CoordDimensions {
+        match self.data.dimensions() {
+            0..=2 => CoordDimensions::xy(),
+            3 => CoordDimensions::xyz(),
+            _ => CoordDimensions::xyzm(),
+        }
+    }
+
+    fn xy(&mut self, x: f64, y: f64, _idx: usize) -> Result<()> {
+        self.position(vec![x, y])
+    }
+
+    fn coordinate(
+        &mut self,
+        x: f64,
+        y: f64,
+        z: Option<f64>,
+        m: Option<f64>,
+        _t: Option<f64>,
+        _tm: Option<u64>,
+        _idx: usize,
+    ) -> Result<()> {
+        let mut position = vec![x, y];
+        position.extend(z);
+        position.extend(m);
+        position.truncate(self.data.dimensions().max(2) as usize);
+        self.position(position)
+    }
+
+    fn point_begin(&mut self, _idx: usize) -> Result<()> {
+        self.begin("Point")
+    }
+
+    fn point_end(&mut self, _idx: usize) -> Result<()> {
+        self.end()
+    }
+
+    fn multipoint_begin(&mut self, _size: usize, _idx: usize) -> Result<()> {
+        self.begin("MultiPoint")
+    }
+
+    fn multipoint_end(&mut self, _idx: usize) -> Result<()> {
+        self.end()
+    }
+
+    fn linestring_begin(&mut self, _tagged: bool, _size: usize, _idx: usize) -> Result<()> {
+        self.begin("LineString")
+    }
+
+    fn linestring_end(&mut self, _tagged: bool, _idx: usize) -> Result<()> {
+        self.end()
+    }
+
+    fn multilinestring_begin(&mut self, _size: usize, _idx: usize) -> Result<()> {
+        self.begin("MultiLineString")
+    }
+
+    fn multilinestring_end(&mut self, _idx: usize) -> Result<()> {
+        self.end()
+    }
+
+    fn polygon_begin(&mut self, _tagged: bool, _size: usize, _idx: usize) -> Result<()> {
+        self.begin("Polygon")
+    }
+
+    fn polygon_end(&mut self, _tagged: bool, _idx: usize) -> Result<()> {
+        self.end()
+    }
+
+    fn multipolygon_begin(&mut self, _size: usize, _idx: usize) -> Result<()> {
+        self.begin("MultiPolygon")
+    }
+
+    fn multipolygon_end(&mut self, _idx: usize) -> Result<()> {
+        self.end()
+    }
+
+    fn geometrycollection_begin(&mut self, _size: usize, _idx: usize) -> Result<()> {
+        self.begin("GeometryCollection")
+    }
+
+    fn geometrycollection_end(&mut self, _idx: usize) -> Result<()> {
+        self.end()
+    }
+
+    // Curves, triangles and surfaces have no GeoJSON equivalent
+
+    fn circularstring_begin(&mut self, _size: usize, _idx: usize) -> Result<()> {
+        Err(geometry_error("CircularStrings can't be encoded."))
+    }
+
+    fn compoundcurve_begin(&mut self, _size: usize, _idx: usize) -> Result<()> {
+        Err(geometry_error("CompoundCurves can't be encoded."))
+    }
+
+    fn curvepolygon_begin(&mut self, _size: usize, _idx: usize) -> Result<()> {
+        Err(geometry_error("CurvePolygons can't be encoded."))
+    }
+
+    fn multicurve_begin(&mut self, _size: usize, _idx: usize) -> Result<()> {
+        Err(geometry_error("MultiCurves can't be encoded."))
+    }
+
+    fn multisurface_begin(&mut self, _size: usize, _idx: usize) -> Result<()> {
+        Err(geometry_error("MultiSurfaces can't be encoded."))
+    }
+
+    fn triangle_begin(&mut self, _tagged: bool, _size: usize, _idx: usize) -> Result<()> {
+        Err(geometry_error("Triangles can't be encoded."))
+    }
+
+    fn polyhedralsurface_begin(&mut self, _size: usize, _idx: usize) -> Result<()> {
+        Err(geometry_error("PolyhedralSurfaces can't be encoded."))
+    }
+
+    fn tin_begin(&mut self, _size: usize, _idx: usize) -> Result<()> {
+        Err(geometry_error("TINs can't be encoded."))
+    }
+}
+
+impl PropertyProcessor for GeobufWriter {
+    fn property(&mut self, _idx: usize, name: &str, value: &ColumnValue) -> Result<bool> {
+        let value = match value {
+            ColumnValue::Byte(value) => JSONValue::from(*value),
+            ColumnValue::UByte(value) => JSONValue::from(*value),
+            ColumnValue::Bool(value) => JSONValue::from(*value),
+            ColumnValue::Short(value) => JSONValue::from(*value),
+            ColumnValue::UShort(value) => JSONValue::from(*value),
+            ColumnValue::Int(value) => JSONValue::from(*value),
+            ColumnValue::UInt(value) => JSONValue::from(*value),
+            ColumnValue::Long(value) => JSONValue::from(*value),
+            ColumnValue::ULong(value) => JSONValue::from(*value),
+            ColumnValue::Float(value) => JSONValue::from(*value),
+            ColumnValue::Double(value) => JSONValue::from(*value),
+            ColumnValue::String(value) | ColumnValue::DateTime(value) => JSONValue::from(*value),
+            ColumnValue::Json(value) => serde_json::from_str(value)
+                .map_err(|err| GeozeroError::Property(err.to_string()))?,
+            ColumnValue::Binary(_) => {
+                return Err(GeozeroError::Property(format!(
+                    "Binary property {} can't be encoded.",
+                    name
+                )))
+            }
+        };
+        if let Some((properties, _)) = self.feature.as_mut() {
+            properties.insert(String::from(name), value);
+        }
+        // false continues with the next property
+        Ok(false)
+    }
+}
+
+impl FeatureProcessor for GeobufWriter {
+    fn dataset_begin(&mut self, _name: Option<&str>) -> Result<()> {
+        self.data.set_feature_collection(FeatureCollection::new());
+        Ok(())
+    }
+
+    fn feature_begin(&mut self, _idx: u64) -> Result<()> {
+        // Features can be processed without a dataset around them
+        if !self.data.has_feature_collection() {
+            self.data.set_feature_collection(FeatureCollection::new());
+        }
+        self.feature = Some((Map::new(), JSONValue::Null));
+        Ok(())
+    }
+
+    fn feature_end(&mut self, idx: u64) -> Result<()> {
+        let (properties, geometry) =
+            self.feature
+                .take()
+                .ok_or(GeozeroError::Feature(String::from(
+                    "Feature ended before it began.",
+                )))?;
+        let feature = serde_json::json!({
+            "type": "Feature",
+            "properties": properties,
+            "geometry": geometry,
+        });
+        let (precision, dim) = (self.data.precision(), self.data.dimensions());
+        Encoder::check_feature(&feature, precision, dim)
+            .and_then(|_| self.data.push_feature_json(&feature))
+            .map_err(|err| GeozeroError::Feature(format!("Feature {}: {}", idx, err)))
+    }
+}
+
+/// Encodes a geozero geometry into a Geobuf holding a Geometry
+///
+/// # Example
+///
+/// ```
+/// use geobuf::geozero::ToGeobuf;
+/// use geozero::wkt::WktStr;
+/// use geozero::ToWkt;
+///
+/// let data = WktStr("LINESTRING(1 2,3 4)").to_geobuf(6, 2).unwrap();
+/// assert_eq!(data.geometry().coords, vec![1000000, 2000000, 2000000, 2000000]);
+/// assert_eq!(data.to_wkt().unwrap(), "LINESTRING(1 2,3 4)");
+/// ```
+pub trait ToGeobuf {
+    fn to_geobuf(&self, precision: u32, dim: u32) -> Result<Data>;
+}
+
+impl<T: GeozeroGeometry> ToGeobuf for T {
+    fn to_geobuf(&self, precision: u32, dim: u32) -> Result<Data> {
+        let mut writer = GeobufWriter::new(precision, dim);
+        self.process_geom(&mut writer)?;
+        Ok(writer.finish())
+    }
+}
+
+/// Encodes the features of a geozero datasource into a Geobuf holding a FeatureCollection
+pub trait ProcessToGeobuf {
+    fn to_geobuf(&mut self, precision: u32, dim: u32) -> Result<Data>;
+}
+
+impl<T: GeozeroDatasource> ProcessToGeobuf for T {
+    fn to_geobuf(&mut self, precision: u32, dim: u32) -> Result<Data> {
+        let mut writer = GeobufWriter::new(precision, dim);
+        self.process(&mut writer)?;
+        Ok(writer.finish())
+    }
+}
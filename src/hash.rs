@@ -0,0 +1,148 @@
+//! Stable content hashes of features
+use crate::geobuf_pb;
+
+const FNV_OFFSET: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+/// Returns a 64-bit FNV-1a hash of the geometry and properties of a feature, whose key
+/// indexes and shared string references point into `keys` and `strings`
+///
+/// The hash is canonical: it doesn't depend on the order of the properties, on the position
+/// of their keys in the key table or on whether string values are shared, so it survives
+/// GeoJSON round trips and re-encoding as long as the precision stays the same. Ids and
+/// custom properties are not part of the hash. The value is stable across platforms and
+/// crate versions, so it can be persisted, e.g. as a cache key.
+///
+/// # Example
+///
+/// ```
+/// use geobuf::encode::Encoder;
+///
+/// let a = serde_json::from_str(r#"{"type": "Feature", "id": 1, "properties": {"a": 1, "b": "x"},
+///     "geometry": {"type": "Point", "coordinates": [1.0, 2.0]}}"#).unwrap();
+/// let b = serde_json::from_str(r#"{"type": "Feature", "id": 2, "properties": {"b": "x", "a": 1},
+///     "geometry": {"type": "Point", "coordinates": [1.0, 2.0]}}"#).unwrap();
+/// let a = Encoder::encode(&a, 6, 2).unwrap();
+/// let b = Encoder::encode(&b, 6, 2).unwrap();
+///
+/// assert_eq!(
+///     geobuf::hash_feature(a.feature(), &a.keys, &a.strings),
+///     geobuf::hash_feature(b.feature(), &b.keys, &b.strings)
+/// );
+/// ```
+pub fn hash_feature(
+    feature: &geobuf_pb::data::Feature,
+    keys: &[String],
+    strings: &[String],
+) -> u64 {
+    let mut hasher = Fnv::new();
+    hash_geometry(&mut hasher, &feature.geometry);
+
+    let mut properties: Vec<(&str, &geobuf_pb::data::Value)> = feature
+        .properties
+        .chunks(2)
+        .filter(|pair| pair.len() == 2)
+        .filter_map(|pair| {
+            let key = keys.get(pair[0] as usize)?;
+            let value = feature.values.get(pair[1] as usize)?;
+            Some((key.as_str(), value))
+        })
+        .collect();
+    properties.sort_by_key(|(key, _)| *key);
+
+    hasher.write_u64(properties.len() as u64);
+    for (key, value) in properties {
+        hasher.write_str(key);
+        hash_value(&mut hasher, value, strings);
+    }
+    hasher.finish()
+}
+
+fn hash_geometry(hasher: &mut Fnv, geometry: &geobuf_pb::data::Geometry) {
+    hasher.write_u64(geometry.type_() as u64);
+    hasher.write_u64(geometry.lengths.len() as u64);
+    for length in &geometry.lengths {
+        hasher.write_u64(*length as u64);
+    }
+    hasher.write_u64(geometry.coords.len() as u64);
+    for coord in &geometry.coords {
+        hasher.write_u64(*coord as u64);
+    }
+    hasher.write_u64(geometry.geometries.len() as u64);
+    for child in &geometry.geometries {
+        hash_geometry(hasher, child);
+    }
+}
+
+fn hash_value(hasher: &mut Fnv, value: &geobuf_pb::data::Value, strings: &[String]) {
+    match value.value_type.as_ref() {
+        Some(geobuf_pb::data::value::Value_type::StringValue(v)) => {
+            hasher.write_u8(1);
+            hasher.write_str(v);
+        }
+        Some(geobuf_pb::data::value::Value_type::DoubleValue(v)) => {
+            hasher.write_u8(2);
+            hasher.write_u64(v.to_bits());
+        }
+        Some(geobuf_pb::data::value::Value_type::PosIntValue(v)) => {
+            hasher.write_u8(3);
+            hasher.write_u64(*v);
+        }
+        Some(geobuf_pb::data::value::Value_type::NegIntValue(v)) => {
+            hasher.write_u8(4);
+            hasher.write_u64(*v);
+        }
+        Some(geobuf_pb::data::value::Value_type::BoolValue(v)) => {
+            hasher.write_u8(5);
+            hasher.write_u8(*v as u8);
+        }
+        Some(geobuf_pb::data::value::Value_type::JsonValue(v)) => {
+            hasher.write_u8(6);
+            hasher.write_str(v);
+        }
+        Some(geobuf_pb::data::value::Value_type::StringRef(v)) => match strings.get(*v as usize) {
+            Some(v) => {
+                hasher.write_u8(1);
+                hasher.write_str(v);
+            }
+            None => {
+                hasher.write_u8(7);
+                hasher.write_u64(*v as u64);
+            }
+        },
+        None => hasher.write_u8(0),
+    }
+}
+
+/// 64-bit FNV-1a hasher with a fixed byte order
+struct Fnv(u64);
+
+impl Fnv {
+    fn new() -> Self {
+        Fnv(FNV_OFFSET)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(FNV_PRIME);
+        }
+    }
+
+    fn write_u8(&mut self, n: u8) {
+        self.write(&[n]);
+    }
+
+    fn write_u64(&mut self, n: u64) {
+        self.write(&n.to_le_bytes());
+    }
+
+    fn write_str(&mut self, s: &str) {
+        self.write_u64(s.len() as u64);
+        self.write(s.as_bytes());
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
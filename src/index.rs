@@ -0,0 +1,370 @@
+//! Sidecar index (`.gbi`) for framed streams
+//!
+//! An index records the byte range of every frame in a framed stream (see `framed`) along
+//! with the id and bounding box of the feature it holds, so a single frame, or the frames in
+//! an area, can be read without scanning the stream. Offsets and lengths refer to the
+//! serialized message, excluding the varint length prefix.
+//!
+//! The index file starts with the magic bytes `GBI` and a version byte, followed by the
+//! number of entries and, for each entry, the offset, length, id and bounding box of a
+//! frame. All numbers are varints; the id is a varint length (0 for none) followed by UTF-8
+//! bytes, and the bounding box a varint 0 for none or 1 followed by `min_x, min_y, max_x,
+//! max_y` as little endian f64s. Version 1 indexes have no bounding boxes.
+use std::collections::HashMap;
+use std::io::{Read, Seek, SeekFrom};
+
+use crate::bbox::{bbox, intersects};
+use crate::codec;
+use crate::framed::{read_frame, read_varint, write_varint, FramedReader};
+use crate::geobuf_pb;
+
+/// Magic bytes at the start of every index file
+pub const MAGIC: &[u8; 3] = b"GBI";
+
+/// Index format version written by this crate
+pub const VERSION: u8 = 2;
+
+/// Location of a single frame in a framed stream
+#[derive(Clone, Debug, PartialEq)]
+pub struct IndexEntry {
+    /// Byte offset of the serialized message
+    pub offset: u64,
+    /// Byte length of the serialized message
+    pub len: u64,
+    /// Id of the feature held by the frame, with integer ids formatted as strings
+    pub id: Option<String>,
+    /// Bounding box of the frame as `[min_x, min_y, max_x, max_y]`, `None` without
+    /// coordinates or in version 1 indexes
+    pub bbox: Option<[f64; 4]>,
+}
+
+/// Byte range covering consecutive frames, see `Index::ranges`
+#[derive(Clone, Debug, PartialEq)]
+pub struct FrameRange {
+    /// Offset of the first byte
+    pub start: u64,
+    /// Offset past the last byte
+    pub end: u64,
+    /// Positions of the frames in the range, whose bytes are at `offset - start`
+    pub entries: Vec<usize>,
+}
+
+/// Index over the frames of a framed stream
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Index {
+    pub entries: Vec<IndexEntry>,
+    /// Position of the first entry with each id, built with the index
+    ids: HashMap<String, usize>,
+}
+
+impl Index {
+    /// Builds an index by scanning every frame of a framed stream
+    pub fn build<R: Read>(reader: R) -> Result<Self, &'static str> {
+        let mut frames = FramedReader::new(reader);
+        let mut entries = Vec::new();
+        let mut offset: u64 = 0;
+        while let Some(frame) = frames.next_frame()? {
+            let len = frame.len() as u64;
+            let mut prefix = Vec::with_capacity(10);
+            write_varint(&mut prefix, len);
+            offset += prefix.len() as u64;
+
+            let data = match codec::parse(&frame) {
+                Ok(data) => data,
+                Err(_) => return Err("Could not parse frame."),
+            };
+            entries.push(IndexEntry {
+                offset,
+                len,
+                id: feature_id(&data),
+                bbox: bbox(&data),
+            });
+            offset += len;
+        }
+        Ok(Index::from_entries(entries))
+    }
+
+    fn from_entries(entries: Vec<IndexEntry>) -> Self {
+        let mut ids = HashMap::new();
+        for (n, entry) in entries.iter().enumerate() {
+            if let Some(id) = &entry.id {
+                ids.entry(id.clone()).or_insert(n);
+            }
+        }
+        Index { entries, ids }
+    }
+
+    /// Returns the number of indexed frames
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true if the index has no entries
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the position of the first frame holding a feature with the given id
+    pub fn position(&self, id: &str) -> Option<usize> {
+        self.ids.get(id).copied()
+    }
+
+    /// Returns the positions of the frames whose bounding box intersects `extent`
+    pub fn intersecting(&self, extent: &[f64; 4]) -> Vec<usize> {
+        (0..self.entries.len())
+            .filter(|n| {
+                self.entries[*n]
+                    .bbox
+                    .is_some_and(|bbox| intersects(&bbox, extent))
+            })
+            .collect()
+    }
+
+    /// Groups the frames at `positions` into byte ranges to read at once, e.g. with HTTP range
+    /// requests
+    ///
+    /// Frames less than `max_gap` bytes apart share a range, reading the bytes between them
+    /// instead of making another request.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use geobuf::framed::FramedWriter;
+    /// use geobuf::index::Index;
+    ///
+    /// let geojson = serde_json::from_str(r#"{"type": "FeatureCollection", "features": [
+    ///     {"type": "Feature", "geometry": {"type": "Point", "coordinates": [1.0, 2.0]}},
+    ///     {"type": "Feature", "geometry": {"type": "Point", "coordinates": [30.0, 40.0]}},
+    ///     {"type": "Feature", "geometry": {"type": "Point", "coordinates": [5.0, 6.0]}}
+    /// ]}"#).unwrap();
+    /// let mut writer = FramedWriter::new(Vec::new());
+    /// writer.write_geojson(&geojson, 6, 2).unwrap();
+    /// let index = Index::build(&writer.into_inner()[..]).unwrap();
+    ///
+    /// let positions = index.intersecting(&[0.0, 0.0, 10.0, 10.0]);
+    /// assert_eq!(positions, vec![0, 2]);
+    /// assert_eq!(index.ranges(&positions, 0).len(), 2);
+    /// let ranges = index.ranges(&positions, 1024);
+    /// assert_eq!(ranges.len(), 1);
+    /// assert_eq!(ranges[0].entries, vec![0, 2]);
+    /// ```
+    pub fn ranges(&self, positions: &[usize], max_gap: u64) -> Vec<FrameRange> {
+        let mut positions: Vec<usize> = positions
+            .iter()
+            .copied()
+            .filter(|n| *n < self.entries.len())
+            .collect();
+        positions.sort_by_key(|n| self.entries[*n].offset);
+        positions.dedup();
+
+        let mut ranges: Vec<FrameRange> = Vec::new();
+        for n in positions {
+            let entry = &self.entries[n];
+            let end = entry.offset + entry.len;
+            match ranges.last_mut() {
+                Some(range) if entry.offset <= range.end.saturating_add(max_gap) => {
+                    range.end = range.end.max(end);
+                    range.entries.push(n);
+                }
+                _ => ranges.push(FrameRange {
+                    start: entry.offset,
+                    end,
+                    entries: vec![n],
+                }),
+            }
+        }
+        ranges
+    }
+
+    /// Serializes the index
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(VERSION);
+        write_varint(&mut bytes, self.entries.len() as u64);
+        for entry in &self.entries {
+            write_varint(&mut bytes, entry.offset);
+            write_varint(&mut bytes, entry.len);
+            match &entry.id {
+                Some(id) => {
+                    write_varint(&mut bytes, id.len() as u64 + 1);
+                    bytes.extend_from_slice(id.as_bytes());
+                }
+                None => write_varint(&mut bytes, 0),
+            }
+            match &entry.bbox {
+                Some(bbox) => {
+                    write_varint(&mut bytes, 1);
+                    for value in bbox {
+                        bytes.extend_from_slice(&value.to_le_bytes());
+                    }
+                }
+                None => write_varint(&mut bytes, 0),
+            }
+        }
+        bytes
+    }
+
+    /// Parses a serialized index
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, &'static str> {
+        if bytes.len() < MAGIC.len() + 1 || &bytes[..MAGIC.len()] != MAGIC {
+            return Err("Missing index magic bytes.");
+        }
+        let version = bytes[MAGIC.len()];
+        if version == 0 || version > VERSION {
+            return Err("Unsupported index version.");
+        }
+        let mut bytes = &bytes[MAGIC.len() + 1..];
+
+        let count = read_number(&mut bytes)?;
+        let mut entries = Vec::new();
+        for _ in 0..count {
+            let offset = read_number(&mut bytes)?;
+            let len = read_number(&mut bytes)?;
+            let id = match read_number(&mut bytes)? as usize {
+                0 => None,
+                id_len => {
+                    let id_len = id_len - 1;
+                    if bytes.len() < id_len {
+                        return Err("Truncated index.");
+                    }
+                    let (id, rest) = bytes.split_at(id_len);
+                    bytes = rest;
+                    match std::str::from_utf8(id) {
+                        Ok(id) => Some(String::from(id)),
+                        Err(_) => return Err("Invalid feature id in index."),
+                    }
+                }
+            };
+            let bbox = match version {
+                1 => None,
+                _ => read_bbox(&mut bytes)?,
+            };
+            entries.push(IndexEntry {
+                offset,
+                len,
+                id,
+                bbox,
+            });
+        }
+        Ok(Index::from_entries(entries))
+    }
+}
+
+/// Random access reader over a framed stream and its index
+pub struct IndexedReader<R: Read + Seek> {
+    reader: R,
+    index: Index,
+}
+
+impl<R: Read + Seek> IndexedReader<R> {
+    /// Returns a reader over `reader` using a previously built `index`
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::io::Cursor;
+    ///
+    /// use geobuf::framed::FramedWriter;
+    /// use geobuf::index::{Index, IndexedReader};
+    ///
+    /// let geojson = serde_json::from_str(r#"{"type": "FeatureCollection", "features": [
+    ///     {"type": "Feature", "id": "a", "geometry": {"type": "Point", "coordinates": [1.0, 2.0]}},
+    ///     {"type": "Feature", "id": 42, "geometry": {"type": "Point", "coordinates": [3.0, 4.0]}}
+    /// ]}"#).unwrap();
+    /// let mut writer = FramedWriter::new(Vec::new());
+    /// writer.write_geojson(&geojson, 6, 2).unwrap();
+    /// let stream = writer.into_inner();
+    ///
+    /// let index = Index::build(&stream[..]).unwrap();
+    /// let index = Index::from_bytes(&index.to_bytes()).unwrap();
+    /// assert_eq!(index.position("42"), Some(1));
+    ///
+    /// let mut reader = IndexedReader::new(Cursor::new(stream), index);
+    /// let data = reader.get(1).unwrap();
+    /// assert_eq!(data.feature().int_id(), 42);
+    /// assert!(reader.get_by_id("a").unwrap().is_some());
+    /// ```
+    pub fn new(reader: R, index: Index) -> Self {
+        IndexedReader { reader, index }
+    }
+
+    /// Returns the index used by this reader
+    pub fn index(&self) -> &Index {
+        &self.index
+    }
+
+    /// Returns the number of frames in the stream
+    pub fn len(&self) -> usize {
+        self.index.len()
+    }
+
+    /// Returns true if the stream has no frames
+    pub fn is_empty(&self) -> bool {
+        self.index.is_empty()
+    }
+
+    /// Reads the frame at position `n`
+    pub fn get(&mut self, n: usize) -> Result<geobuf_pb::Data, &'static str> {
+        let entry = match self.index.entries.get(n) {
+            Some(entry) => entry,
+            None => return Err("Frame index out of range."),
+        };
+        if self.reader.seek(SeekFrom::Start(entry.offset)).is_err() {
+            return Err("Could not seek to frame.");
+        }
+        let buf = read_frame(&mut self.reader, entry.len)?;
+        match codec::parse(&buf) {
+            Ok(data) => Ok(data),
+            Err(_) => Err("Could not parse frame."),
+        }
+    }
+
+    /// Reads the first frame holding a feature with the given id
+    pub fn get_by_id(&mut self, id: &str) -> Result<Option<geobuf_pb::Data>, &'static str> {
+        match self.index.position(id) {
+            Some(n) => self.get(n).map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Returns the index file path for a framed stream, i.e. the path with a `.gbi` extension
+pub fn sidecar_path(path: &str) -> String {
+    match path.rfind('.') {
+        Some(dot) if !path[dot..].contains('/') => format!("{}.gbi", &path[..dot]),
+        _ => format!("{}.gbi", path),
+    }
+}
+
+fn feature_id(data: &geobuf_pb::Data) -> Option<String> {
+    match data.data_type.as_ref()? {
+        geobuf_pb::data::Data_type::Feature(feature) => match feature.id_type.as_ref()? {
+            geobuf_pb::data::feature::Id_type::Id(id) => Some(id.clone()),
+            geobuf_pb::data::feature::Id_type::IntId(id) => Some(id.to_string()),
+        },
+        _ => None,
+    }
+}
+
+fn read_bbox(bytes: &mut &[u8]) -> Result<Option<[f64; 4]>, &'static str> {
+    if read_number(bytes)? == 0 {
+        return Ok(None);
+    }
+    if bytes.len() < 32 {
+        return Err("Truncated index.");
+    }
+    let mut bbox = [0.0; 4];
+    for (n, value) in bbox.iter_mut().enumerate() {
+        *value = f64::from_le_bytes(bytes[n * 8..n * 8 + 8].try_into().unwrap());
+    }
+    *bytes = &bytes[32..];
+    Ok(Some(bbox))
+}
+
+fn read_number(bytes: &mut &[u8]) -> Result<u64, &'static str> {
+    match read_varint(bytes)? {
+        Some(n) => Ok(n),
+        None => Err("Truncated index."),
+    }
+}
@@ -0,0 +1,177 @@
+//! Multiple named Geobuf layers in a single file
+//!
+//! A layers file is a container (see `container`) with the `FLAG_LAYERS` flag set. Its
+//! payload is a sequence of layers, each stored as a varint-prefixed UTF-8 name followed by a
+//! varint-prefixed serialized `geobuf_pb::Data` message.
+use crate::codec;
+use crate::container;
+use crate::framed::{read_varint, write_varint};
+use crate::geobuf_pb;
+
+/// An ordered set of named `geobuf_pb::Data` layers
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Layers {
+    layers: Vec<(String, geobuf_pb::Data)>,
+}
+
+impl Layers {
+    /// Returns an empty set of layers
+    pub fn new() -> Self {
+        Layers { layers: Vec::new() }
+    }
+
+    /// Returns the number of layers
+    pub fn len(&self) -> usize {
+        self.layers.len()
+    }
+
+    /// Returns true if there are no layers
+    pub fn is_empty(&self) -> bool {
+        self.layers.is_empty()
+    }
+
+    /// Returns the layer names in insertion order
+    pub fn names(&self) -> Vec<&str> {
+        self.layers.iter().map(|(name, _)| name.as_str()).collect()
+    }
+
+    /// Returns the layer with the given name
+    pub fn get(&self, name: &str) -> Option<&geobuf_pb::Data> {
+        self.layers
+            .iter()
+            .find(|(layer_name, _)| layer_name == name)
+            .map(|(_, data)| data)
+    }
+
+    /// Adds a layer, replacing any existing layer with the same name
+    pub fn insert(&mut self, name: &str, data: geobuf_pb::Data) {
+        match self
+            .layers
+            .iter_mut()
+            .find(|(layer_name, _)| layer_name == name)
+        {
+            Some(layer) => layer.1 = data,
+            None => self.layers.push((String::from(name), data)),
+        }
+    }
+
+    /// Removes and returns the layer with the given name
+    pub fn remove(&mut self, name: &str) -> Option<geobuf_pb::Data> {
+        let index = self
+            .layers
+            .iter()
+            .position(|(layer_name, _)| layer_name == name)?;
+        Some(self.layers.remove(index).1)
+    }
+
+    /// Returns an iterator over `(name, data)` pairs in insertion order
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &geobuf_pb::Data)> {
+        self.layers.iter().map(|(name, data)| (name.as_str(), data))
+    }
+
+    /// Serializes all layers into a layers container
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use geobuf::encode::Encoder;
+    /// use geobuf::layers::{self, Layers};
+    ///
+    /// let roads = serde_json::from_str(r#"{"type": "LineString", "coordinates": [[0.0, 0.0], [1.0, 1.0]]}"#).unwrap();
+    /// let pois = serde_json::from_str(r#"{"type": "Point", "coordinates": [100.0, 0.0]}"#).unwrap();
+    ///
+    /// let mut file = Layers::new();
+    /// file.insert("roads", Encoder::encode(&roads, 6, 2).unwrap());
+    /// file.insert("pois", Encoder::encode(&pois, 6, 2).unwrap());
+    /// let bytes = file.to_bytes().unwrap();
+    ///
+    /// assert_eq!(layers::list(&bytes).unwrap(), vec!["roads", "pois"]);
+    /// let pois_data = layers::read_layer(&bytes, "pois").unwrap().unwrap();
+    /// assert_eq!(&pois_data, file.get("pois").unwrap());
+    /// assert_eq!(Layers::from_bytes(&bytes).unwrap(), file);
+    /// ```
+    pub fn to_bytes(&self) -> Result<Vec<u8>, &'static str> {
+        let mut payload = Vec::new();
+        for (name, data) in &self.layers {
+            let msg = match codec::serialize(data) {
+                Ok(msg) => msg,
+                Err(_) => return Err("Could not serialize layer."),
+            };
+            write_varint(&mut payload, name.len() as u64);
+            payload.extend_from_slice(name.as_bytes());
+            write_varint(&mut payload, msg.len() as u64);
+            payload.extend_from_slice(&msg);
+        }
+        Ok(container::write_container_with_flags(
+            &payload,
+            container::FLAG_LAYERS,
+        ))
+    }
+
+    /// Parses every layer from a layers container
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, &'static str> {
+        let mut layers = Layers::new();
+        for (name, msg) in raw_layers(bytes)? {
+            layers.layers.push((name, parse_layer(msg)?));
+        }
+        Ok(layers)
+    }
+}
+
+/// Returns the layer names stored in a layers container without parsing the layers
+pub fn list(bytes: &[u8]) -> Result<Vec<String>, &'static str> {
+    Ok(raw_layers(bytes)?
+        .into_iter()
+        .map(|(name, _)| name)
+        .collect())
+}
+
+/// Parses only the layer with the given name from a layers container
+pub fn read_layer(bytes: &[u8], name: &str) -> Result<Option<geobuf_pb::Data>, &'static str> {
+    match raw_layers(bytes)?
+        .into_iter()
+        .find(|(layer_name, _)| layer_name == name)
+    {
+        Some((_, msg)) => Ok(Some(parse_layer(msg)?)),
+        None => Ok(None),
+    }
+}
+
+fn parse_layer(msg: &[u8]) -> Result<geobuf_pb::Data, &'static str> {
+    match codec::parse(msg) {
+        Ok(data) => Ok(data),
+        Err(_) => Err("Could not parse layer."),
+    }
+}
+
+fn raw_layers(bytes: &[u8]) -> Result<Vec<(String, &[u8])>, &'static str> {
+    let (flags, mut payload) = container::read_container(bytes)?;
+    if flags & container::FLAG_LAYERS == 0 {
+        return Err("Container does not hold layers.");
+    }
+
+    let mut layers = Vec::new();
+    while !payload.is_empty() {
+        let name = read_chunk(&mut payload)?;
+        let name = match std::str::from_utf8(name) {
+            Ok(name) => String::from(name),
+            Err(_) => return Err("Invalid layer name."),
+        };
+        let msg = read_chunk(&mut payload)?;
+        layers.push((name, msg));
+    }
+    Ok(layers)
+}
+
+fn read_chunk<'a>(payload: &mut &'a [u8]) -> Result<&'a [u8], &'static str> {
+    let len = match read_varint(payload)? {
+        Some(len) => len as usize,
+        None => return Err("Truncated layer."),
+    };
+    if payload.len() < len {
+        return Err("Truncated layer.");
+    }
+    let (chunk, rest) = payload.split_at(len);
+    *payload = rest;
+    Ok(chunk)
+}
@@ -7,21 +7,89 @@
 //! let geojson = decode::Decoder::decode(&geobuf).unwrap();
 //! assert_eq!(original_geojson, geojson);
 //! ```
+#[cfg(not(any(feature = "protobuf", feature = "light-codec")))]
+compile_error!("Enable the protobuf or the light-codec feature to read and write Geobufs.");
+
+#[cfg(feature = "arrow")]
+pub mod arrow;
+pub mod bbox;
+pub mod codec;
+pub mod container;
+pub mod debug;
 pub mod decode;
+pub mod diff;
+mod edit;
 pub mod encode;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod filter;
+pub mod framed;
+#[cfg(feature = "protobuf")]
+pub mod geobuf_pb;
+#[cfg(not(feature = "protobuf"))]
+#[path = "geobuf_light.rs"]
 pub mod geobuf_pb;
+#[cfg(feature = "geojson")]
+pub mod geojson;
+#[cfg(feature = "geozero")]
+pub mod geozero;
+mod hash;
+pub mod index;
+pub mod layers;
+mod merge;
+pub mod metadata;
+#[cfg(feature = "node")]
+pub mod node;
+#[cfg(feature = "parquet")]
+pub mod parquet;
+pub mod patch;
+pub mod positions;
+mod props;
+pub mod schema;
+pub mod simplify;
+mod split;
+pub mod stream;
+pub mod strings;
+pub mod validate;
 #[cfg(feature = "wasm")]
 pub mod wasm;
+#[cfg(feature = "wkb")]
+pub mod wkb;
+#[cfg(feature = "wkt")]
+pub mod wkt;
+
+/// Field wrappers of the message types, from the protobuf runtime or the light message types
+mod runtime {
+    #[cfg(not(feature = "protobuf"))]
+    pub(crate) use crate::geobuf_pb::{EnumOrUnknown, MessageField};
+    #[cfg(feature = "protobuf")]
+    pub(crate) use protobuf::{EnumOrUnknown, MessageField};
+}
+
+pub use diff::{diff, Diff};
+pub use hash::hash_feature;
+pub use merge::merge;
+pub use patch::{apply_patch, make_patch, Patch};
+pub use split::{select, split, split_by_size};
 
 #[cfg(test)]
 mod tests {
     use std::fs::File;
     use std::io::BufReader;
 
+    #[cfg(feature = "protobuf")]
+    use protobuf::Message;
     use serde_json::Value as JSONValue;
 
+    use super::codec;
     use super::decode::Decoder;
-    use super::encode::Encoder;
+    use super::encode::{Affine, Encoder, EncoderOptions, Tile};
+    use super::filter;
+    use super::framed::{FramedReader, FramedWriter};
+    use super::index::{Index, IndexedReader};
+    use super::metadata::Metadata;
+    use super::stream::{FramedStreamDecoder, StreamDecoder};
+    use super::strings;
 
     const DIM: u32 = 2;
     const PRECISION: u32 = 6;
@@ -147,8 +215,1306 @@ mod tests {
         test_geojson("fixtures/singlemultipolygon.json");
     }
 
+    #[test]
+    fn test_topology() {
+        test_geojson("fixtures/topology.json");
+    }
+
+    #[test]
+    fn test_topology_unquantized() {
+        test_geojson("fixtures/topology-unquantized.json");
+    }
+
     #[test]
     fn test_us_states() {
         test_geojson("fixtures/us-states.json");
     }
+
+    #[test]
+    fn test_framed_featurecollection() {
+        let file = File::open("fixtures/featurecollection.json").unwrap();
+        let original_geojson: JSONValue = serde_json::from_reader(BufReader::new(file)).unwrap();
+
+        let mut writer = FramedWriter::new(Vec::new());
+        let count = writer
+            .write_geojson(&original_geojson, PRECISION, DIM)
+            .unwrap();
+        let bytes = writer.into_inner();
+
+        let features: Vec<JSONValue> = FramedReader::new(&bytes[..])
+            .map(|data| Decoder::decode(&data.unwrap()).unwrap())
+            .collect();
+        assert_eq!(features.len(), count);
+        compare_geojsons(&original_geojson["features"], &JSONValue::from(features));
+    }
+
+    #[test]
+    fn test_framed_truncated() {
+        let geojson =
+            serde_json::from_str(r#"{"type": "Point", "coordinates": [100.0, 0.0]}"#).unwrap();
+        let mut writer = FramedWriter::new(Vec::new());
+        writer.write_geojson(&geojson, PRECISION, DIM).unwrap();
+        let bytes = writer.into_inner();
+
+        let mut reader = FramedReader::new(&bytes[..bytes.len() - 1]);
+        assert!(reader.next().unwrap().is_err());
+        assert!(reader.next().is_none());
+
+        // A corrupt length larger than the stream isn't allocated up front
+        let mut reader = FramedReader::new(&b"\xff\xff\xff\xff\xff\xff\xff\xff\x7f\x00"[..]);
+        assert_eq!(reader.next_frame(), Err("Truncated frame."));
+
+        let mut index = Index::build(&bytes[..]).unwrap();
+        index.entries[0].len = u64::MAX;
+        let mut reader = IndexedReader::new(std::io::Cursor::new(&bytes), index);
+        assert_eq!(reader.get(0), Err("Truncated frame."));
+    }
+
+    #[test]
+    fn test_stream_decoder() {
+        let file = File::open("fixtures/us-states.json").unwrap();
+        let original_geojson: JSONValue = serde_json::from_reader(BufReader::new(file)).unwrap();
+        let bytes = codec::write(&Encoder::encode(&original_geojson, PRECISION, DIM).unwrap());
+
+        let mut decoder = StreamDecoder::new();
+        let mut features = Vec::new();
+        for chunk in bytes.chunks(100) {
+            features.extend(decoder.push(chunk).unwrap());
+        }
+        assert_eq!(
+            features.len(),
+            original_geojson["features"].as_array().unwrap().len()
+        );
+        assert!(decoder.finish().unwrap().is_empty());
+        compare_geojsons(&original_geojson["features"], &JSONValue::from(features));
+    }
+
+    #[test]
+    fn test_stream_decoder_shared_strings() {
+        let file = File::open("fixtures/us-states.json").unwrap();
+        let mut original_geojson: JSONValue =
+            serde_json::from_reader(BufReader::new(file)).unwrap();
+        for feature in original_geojson["features"].as_array_mut().unwrap() {
+            feature["properties"]["country"] = JSONValue::from("United States of America");
+        }
+        let mut data = Encoder::encode(&original_geojson, PRECISION, DIM).unwrap();
+        strings::share(&mut data, 2).unwrap();
+        let bytes = codec::write(&data);
+
+        // The shared strings come after the features, which wait for them
+        let mut decoder = StreamDecoder::new();
+        assert!(decoder.push(&bytes).unwrap().is_empty());
+        let features = decoder.finish().unwrap();
+        compare_geojsons(&original_geojson["features"], &JSONValue::from(features));
+    }
+
+    #[test]
+    fn test_stream_decoder_truncated() {
+        let file = File::open("fixtures/featurecollection.json").unwrap();
+        let original_geojson: JSONValue = serde_json::from_reader(BufReader::new(file)).unwrap();
+        let bytes = codec::write(&Encoder::encode(&original_geojson, PRECISION, DIM).unwrap());
+
+        let mut decoder = StreamDecoder::new();
+        decoder.push(&bytes[..bytes.len() - 1]).unwrap();
+        assert!(decoder.finish().is_err());
+    }
+
+    #[test]
+    fn test_framed_stream_decoder() {
+        let file = File::open("fixtures/us-states.json").unwrap();
+        let original_geojson: JSONValue = serde_json::from_reader(BufReader::new(file)).unwrap();
+        let mut writer = FramedWriter::new(Vec::new());
+        writer
+            .write_geojson(&original_geojson, PRECISION, DIM)
+            .unwrap();
+        let bytes = writer.into_inner();
+
+        let mut decoder = FramedStreamDecoder::new();
+        let mut features = Vec::new();
+        for chunk in bytes.chunks(100) {
+            features.extend(decoder.push(chunk).unwrap());
+        }
+        decoder.finish().unwrap();
+        compare_geojsons(&original_geojson["features"], &JSONValue::from(features));
+
+        let mut decoder = FramedStreamDecoder::new();
+        decoder.push(&bytes[..bytes.len() - 1]).unwrap();
+        assert!(decoder.finish().is_err());
+    }
+
+    #[test]
+    fn test_codec() {
+        // Compares against the bytes of the generated protobuf code
+        #[cfg(feature = "protobuf")]
+        for name in [
+            "featurecollection",
+            "geometrycollection",
+            "props",
+            "topology",
+            "us-states",
+        ] {
+            let file = File::open(format!("fixtures/{}.json", name)).unwrap();
+            let geojson: JSONValue = serde_json::from_reader(BufReader::new(file)).unwrap();
+            let mut data = Encoder::encode(&geojson, PRECISION, DIM).unwrap();
+            strings::share(&mut data, 2).unwrap();
+            let bytes = data.write_to_bytes().unwrap();
+
+            assert_eq!(codec::write(&data), bytes);
+            assert_eq!(codec::read(&bytes).unwrap(), data);
+            assert!(codec::read(&bytes[..bytes.len() - 1]).is_err());
+        }
+
+        let file = File::open("fixtures/point.json").unwrap();
+        let geojson: JSONValue = serde_json::from_reader(BufReader::new(file)).unwrap();
+        let options = EncoderOptions {
+            affine: Some(Affine::origin([100.0, 0.0])),
+            ..EncoderOptions::default()
+        };
+        let data = Encoder::encode_with_options(&geojson, &options).unwrap();
+        assert_eq!(codec::read(&codec::write(&data)).unwrap(), data);
+    }
+
+    #[test]
+    fn test_index_bboxes() {
+        let file = File::open("fixtures/us-states.json").unwrap();
+        let original_geojson: JSONValue = serde_json::from_reader(BufReader::new(file)).unwrap();
+        let mut writer = FramedWriter::new(Vec::new());
+        writer
+            .write_geojson(&original_geojson, PRECISION, DIM)
+            .unwrap();
+        let stream = writer.into_inner();
+
+        let index = Index::build(&stream[..]).unwrap();
+        assert!(index.entries.iter().all(|entry| entry.bbox.is_some()));
+        assert_eq!(Index::from_bytes(&index.to_bytes()).unwrap(), index);
+
+        let extent = [-110.0, 35.0, -100.0, 45.0];
+        let data = Encoder::encode(&original_geojson, PRECISION, DIM).unwrap();
+        let filtered = filter::filter(&data, &filter::Expr::Intersects(extent)).unwrap();
+        let positions = index.intersecting(&extent);
+        assert_eq!(positions.len(), filtered.feature_count());
+
+        // Every frame is read back from its range
+        for range in index.ranges(&positions, 4096) {
+            let bytes = &stream[range.start as usize..range.end as usize];
+            for n in range.entries {
+                let entry = &index.entries[n];
+                let start = (entry.offset - range.start) as usize;
+                codec::read(&bytes[start..start + entry.len as usize]).unwrap();
+            }
+        }
+
+        // Ids are looked up in both built and parsed indexes, resolving to their first frame
+        let point = serde_json::json!({"type": "Point", "coordinates": [1.0, 2.0]});
+        let geojson = serde_json::json!({"type": "FeatureCollection", "features": [
+            {"type": "Feature", "id": "a", "properties": {}, "geometry": point},
+            {"type": "Feature", "id": 7, "properties": {}, "geometry": point},
+            {"type": "Feature", "id": "a", "properties": {}, "geometry": point},
+        ]});
+        let mut writer = FramedWriter::new(Vec::new());
+        writer.write_geojson(&geojson, PRECISION, DIM).unwrap();
+        let index = Index::build(&writer.into_inner()[..]).unwrap();
+        for index in [Index::from_bytes(&index.to_bytes()).unwrap(), index] {
+            assert_eq!(index.position("a"), Some(0));
+            assert_eq!(index.position("7"), Some(1));
+            assert_eq!(index.position("b"), None);
+        }
+
+        // Version 1 indexes have no bounding boxes
+        let index = Index::from_bytes(b"GBI\x01\x01\x01\x05\x00").unwrap();
+        assert_eq!(index.entries[0].bbox, None);
+        assert!(index.intersecting(&extent).is_empty());
+    }
+
+    #[cfg(feature = "ffi")]
+    #[test]
+    fn test_ffi() {
+        use std::ffi::CStr;
+
+        use super::ffi::{
+            catch, geobuf_buffer_free, geobuf_decode, geobuf_encode, geobuf_last_error,
+            GeobufBuffer,
+        };
+
+        let json = std::fs::read("fixtures/featurecollection.json").unwrap();
+        let original_geojson: JSONValue = serde_json::from_slice(&json).unwrap();
+        let mut encoded = GeobufBuffer {
+            data: std::ptr::null_mut(),
+            len: 0,
+        };
+        let mut decoded = GeobufBuffer {
+            data: std::ptr::null_mut(),
+            len: 0,
+        };
+        unsafe {
+            assert_eq!(
+                geobuf_encode(json.as_ptr(), json.len(), PRECISION, DIM, &mut encoded),
+                0
+            );
+            assert_eq!(geobuf_decode(encoded.data, encoded.len, &mut decoded), 0);
+            let geojson: JSONValue =
+                serde_json::from_slice(std::slice::from_raw_parts(decoded.data, decoded.len))
+                    .unwrap();
+            assert_eq!(geojson, original_geojson);
+            geobuf_buffer_free(&mut encoded);
+            geobuf_buffer_free(&mut decoded);
+            assert!(encoded.data.is_null());
+
+            assert_eq!(geobuf_decode(b"\x2a".as_ptr(), 1, &mut decoded), -1);
+            assert!(decoded.data.is_null());
+            assert!(!CStr::from_ptr(geobuf_last_error()).to_bytes().is_empty());
+            assert_eq!(
+                geobuf_encode(std::ptr::null(), 1, PRECISION, DIM, &mut encoded),
+                -1
+            );
+            assert_eq!(
+                CStr::from_ptr(geobuf_last_error()).to_str(),
+                Ok("Null input.")
+            );
+
+            let topology = br#"{"type": "Topology", "objects": {"a": {"type": "LineString", "arcs": [1e10]}}}"#;
+            assert_eq!(
+                geobuf_encode(
+                    topology.as_ptr(),
+                    topology.len(),
+                    PRECISION,
+                    DIM,
+                    &mut encoded
+                ),
+                -1
+            );
+            assert_eq!(
+                CStr::from_ptr(geobuf_last_error()).to_str(),
+                Ok("Invalid arc index.")
+            );
+        }
+        assert_eq!(
+            catch(|| panic!("Out of range.")),
+            Err(String::from("Panicked: Out of range."))
+        );
+        assert_eq!(catch(|| Ok(vec![1])), Ok(vec![1]));
+    }
+
+    #[cfg(feature = "wasm")]
+    #[test]
+    fn test_wasm_encode() {
+        use super::wasm::encode_checked;
+
+        let file = File::open("fixtures/topology.json").unwrap();
+        let geojson: JSONValue = serde_json::from_reader(BufReader::new(file)).unwrap();
+        let bytes = encode_checked(&geojson, PRECISION, DIM).unwrap();
+        compare_geojsons(
+            &geojson,
+            &Decoder::decode(&codec::read(&bytes).unwrap()).unwrap(),
+        );
+
+        let topology = serde_json::json!({
+            "type": "Topology",
+            "arcs": [[[0.0, 0.0], [1.0, 1.0]]],
+            "objects": {"a": {"type": "Polygon", "arcs": [[0], [1e10]]}},
+        });
+        assert_eq!(
+            encode_checked(&topology, PRECISION, DIM),
+            Err(String::from("Invalid arc index."))
+        );
+    }
+
+    #[cfg(feature = "geozero")]
+    #[test]
+    fn test_geozero() {
+        use geozero::geojson::GeoJson;
+        use geozero::ProcessToJson;
+
+        use super::geozero::ProcessToGeobuf;
+
+        let json = std::fs::read_to_string("fixtures/featurecollection.json").unwrap();
+        let original_geojson: JSONValue = serde_json::from_str(&json).unwrap();
+        let mut data = GeoJson(&json).to_geobuf(PRECISION, DIM).unwrap();
+        // Properties of the collection itself aren't passed by geozero
+        let features = &original_geojson["features"];
+        compare_geojsons(features, &Decoder::decode(&data).unwrap()["features"]);
+
+        // geozero writes floats without fractional part as integers
+        let geojson: JSONValue = serde_json::from_str(&data.to_json().unwrap()).unwrap();
+        for (n, feature) in features.as_array().unwrap().iter().enumerate() {
+            compare_geojsons(&feature["geometry"], &geojson["features"][n]["geometry"]);
+        }
+
+        // Ids are passed as properties, and shared strings are resolved
+        let point = serde_json::json!({"type": "Point", "coordinates": [1.5, 2.0]});
+        let original_geojson = serde_json::json!({"type": "FeatureCollection", "features": [
+            {"type": "Feature", "id": "a", "properties": {"name": "x", "n": -3}, "geometry": point},
+            {"type": "Feature", "id": 7, "properties": {"id": "own", "name": "x"}, "geometry": point},
+        ]});
+        let mut data = Encoder::encode(&original_geojson, PRECISION, DIM).unwrap();
+        strings::share(&mut data, 2).unwrap();
+        let geojson: JSONValue = serde_json::from_str(&data.to_json().unwrap()).unwrap();
+        assert_eq!(
+            geojson["features"][0]["properties"],
+            serde_json::json!({"id": "a", "name": "x", "n": -3})
+        );
+        assert_eq!(
+            geojson["features"][1]["properties"],
+            serde_json::json!({"id": "own", "name": "x"})
+        );
+        compare_geojsons(&point, &geojson["features"][1]["geometry"]);
+    }
+
+    #[cfg(feature = "wkb")]
+    #[test]
+    fn test_wkb() {
+        use super::geobuf_pb::data::Data_type;
+        use super::wkb::{geometry_to_wkb, wkb_to_geometry};
+
+        for fixture in [
+            "featurecollection",
+            "geometrycollection",
+            "multipolygon",
+            "single-multipoly",
+            "us-states",
+        ] {
+            let file = File::open(format!("fixtures/{}.json", fixture)).unwrap();
+            let geojson: JSONValue = serde_json::from_reader(BufReader::new(file)).unwrap();
+            let data = Encoder::encode(&geojson, PRECISION, DIM).unwrap();
+            let geometries: Vec<_> = match data.data_type.as_ref().unwrap() {
+                Data_type::FeatureCollection(fc) => fc
+                    .features
+                    .iter()
+                    .map(|feature| &*feature.geometry)
+                    .collect(),
+                Data_type::Geometry(geometry) => vec![geometry],
+                _ => unreachable!(),
+            };
+            for geometry in geometries {
+                let wkb = geometry_to_wkb(geometry, DIM, PRECISION).unwrap();
+                assert_eq!(&wkb_to_geometry(&wkb, DIM, PRECISION).unwrap(), geometry);
+            }
+        }
+
+        // Big-endian extended WKB with an SRID and a Z coordinate dropped for 2 dimensions
+        let mut ewkb = vec![0, 0xa0, 0, 0, 1, 0, 0, 0x10, 0xe6];
+        for coord in [1.5f64, -2.0, 10.0] {
+            ewkb.extend_from_slice(&coord.to_be_bytes());
+        }
+        let point = wkb_to_geometry(&ewkb, DIM, PRECISION).unwrap();
+        assert_eq!(point.coords, vec![1500000, -2000000]);
+        assert!(wkb_to_geometry(&ewkb[..20], DIM, PRECISION).is_err());
+        assert!(wkb_to_geometry(
+            &geometry_to_wkb(&point, DIM, PRECISION).unwrap(),
+            3,
+            PRECISION
+        )
+        .is_err());
+    }
+
+    #[cfg(feature = "wkt")]
+    #[test]
+    fn test_wkt() {
+        use super::wkt::{feature_from_wkt, from_wkt, to_wkt};
+
+        for fixture in [
+            "geometrycollection",
+            "multipoint",
+            "multipolygon",
+            "polygon",
+            "single-multipoly",
+        ] {
+            let file = File::open(format!("fixtures/{}.json", fixture)).unwrap();
+            let geojson: JSONValue = serde_json::from_reader(BufReader::new(file)).unwrap();
+            let geometry = match geojson["type"].as_str() {
+                Some("Feature") => &geojson["geometry"],
+                _ => &geojson,
+            };
+            compare_geojsons(geometry, &from_wkt(&to_wkt(&geojson).unwrap()).unwrap());
+        }
+
+        let feature = feature_from_wkt("POINT ZM (1 2 3 4)", serde_json::Map::new()).unwrap();
+        assert_eq!(
+            feature["geometry"]["coordinates"],
+            serde_json::json!([1.0, 2.0, 3.0, 4.0])
+        );
+        assert_eq!(to_wkt(&feature).unwrap(), "POINT ZM(1 2 3 4)");
+        assert!(from_wkt("POINT (1)").is_err());
+        assert!(to_wkt(&serde_json::json!({"type": "Point", "coordinates": ["a", 1.0]})).is_err());
+    }
+
+    #[cfg(feature = "geojson")]
+    #[test]
+    fn test_geojson_conversions() {
+        use ::geojson::GeoJson;
+
+        use super::geobuf_pb::Data;
+
+        for fixture in [
+            "featurecollection",
+            "feature",
+            "geometrycollection",
+            "us-states",
+        ] {
+            let json = std::fs::read_to_string(format!("fixtures/{}.json", fixture)).unwrap();
+            let geojson: GeoJson = json.parse().unwrap();
+            let data = Data::try_from(&geojson).unwrap();
+            compare_geojsons(
+                &serde_json::from_str(&json).unwrap(),
+                &Decoder::decode(&data).unwrap(),
+            );
+            assert_eq!(GeoJson::try_from(&data).unwrap(), geojson);
+        }
+
+        let json = std::fs::read_to_string("fixtures/precision.json").unwrap();
+        let data = Data::try_from(&json.parse::<GeoJson>().unwrap()).unwrap();
+        assert_eq!(data.precision(), 6);
+        compare_geojsons(
+            &serde_json::from_str(&json).unwrap(),
+            &Decoder::decode(&data).unwrap(),
+        );
+
+        let file = File::open("fixtures/topology.json").unwrap();
+        let topology: JSONValue = serde_json::from_reader(BufReader::new(file)).unwrap();
+        let data = Encoder::encode(&topology, PRECISION, DIM).unwrap();
+        assert!(GeoJson::try_from(&data).is_err());
+    }
+
+    #[cfg(feature = "arrow")]
+    #[test]
+    fn test_arrow() {
+        use super::arrow::{from_record_batches, to_record_batch};
+
+        for fixture in ["featurecollection", "feature", "us-states"] {
+            let file = File::open(format!("fixtures/{}.json", fixture)).unwrap();
+            let geojson: JSONValue = serde_json::from_reader(BufReader::new(file)).unwrap();
+            let data = Encoder::encode(&geojson, PRECISION, DIM).unwrap();
+
+            let batch = to_record_batch(&data).unwrap();
+            assert_eq!(batch.num_rows(), data.feature_count());
+            let round_trip = from_record_batches(&[batch.clone(), batch], PRECISION, DIM).unwrap();
+            let features: Vec<_> = Decoder::features(&data).unwrap().collect();
+            let round_trip: Vec<_> = Decoder::features(&round_trip).unwrap().collect();
+            assert_eq!(round_trip.len(), 2 * features.len());
+            for (feature, round_trip) in features.iter().chain(&features).zip(&round_trip) {
+                compare_geojsons(&feature["geometry"], &round_trip["geometry"]);
+                assert_eq!(feature["id"], round_trip["id"]);
+                // Columns mixing integers and floats hold floats
+                for (key, value) in feature["properties"].as_object().unwrap() {
+                    match value.as_f64() {
+                        Some(value) => {
+                            assert_eq!(Some(value), round_trip["properties"][key].as_f64())
+                        }
+                        None => assert_eq!(value, &round_trip["properties"][key]),
+                    }
+                }
+            }
+        }
+
+        let file = File::open("fixtures/topology.json").unwrap();
+        let topology: JSONValue = serde_json::from_reader(BufReader::new(file)).unwrap();
+        let data = Encoder::encode(&topology, PRECISION, DIM).unwrap();
+        assert!(to_record_batch(&data).is_err());
+
+        let feature = serde_json::json!({"type": "Feature", "id": 1, "properties": {"id": 2},
+            "geometry": {"type": "Point", "coordinates": [1.0, 2.0]}});
+        let data = Encoder::encode(&feature, PRECISION, DIM).unwrap();
+        assert_eq!(
+            to_record_batch(&data).unwrap_err(),
+            "Property id clashes with the id column."
+        );
+    }
+
+    #[cfg(feature = "parquet")]
+    #[test]
+    fn test_parquet() {
+        use std::sync::Arc;
+
+        use ::parquet::arrow::ArrowWriter;
+        use ::parquet::file::properties::WriterProperties;
+        use ::parquet::file::reader::{FileReader, SerializedFileReader};
+        use ::parquet::format::KeyValue;
+        use arrow_schema::{Field, Schema};
+
+        use super::arrow::to_record_batch;
+
+        let dir = std::env::temp_dir();
+        let file = File::open("fixtures/us-states.json").unwrap();
+        let geojson: JSONValue = serde_json::from_reader(BufReader::new(file)).unwrap();
+        let data = Encoder::encode(&geojson, PRECISION, DIM).unwrap();
+        // The density column mixes integers and floats, so only names are compared
+        let check = |round_trip: &super::geobuf_pb::Data| {
+            let features: Vec<_> = Decoder::features(round_trip).unwrap().collect();
+            assert_eq!(features.len(), data.feature_count());
+            for (feature, round_trip) in geojson["features"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .zip(&features)
+            {
+                compare_geojsons(&feature["geometry"], &round_trip["geometry"]);
+                assert_eq!(
+                    feature["properties"]["name"],
+                    round_trip["properties"]["name"]
+                );
+            }
+        };
+
+        let path = dir.join("geobuf-test-us-states.parquet");
+        super::parquet::write(&data, File::create(&path).unwrap()).unwrap();
+        let reader = SerializedFileReader::new(File::open(&path).unwrap()).unwrap();
+        let metadata = reader
+            .metadata()
+            .file_metadata()
+            .key_value_metadata()
+            .unwrap();
+        let geo: JSONValue = serde_json::from_str(
+            metadata
+                .iter()
+                .find(|kv| kv.key == "geo")
+                .unwrap()
+                .value
+                .as_ref()
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(geo["primary_column"], "geometry");
+        assert_eq!(
+            geo["columns"]["geometry"]["geometry_types"],
+            serde_json::json!(["MultiPolygon", "Polygon"])
+        );
+        assert_eq!(
+            JSONValue::from(super::bbox::bbox(&data).unwrap().to_vec()),
+            geo["columns"]["geometry"]["bbox"]
+        );
+
+        check(&super::parquet::read(File::open(&path).unwrap(), PRECISION, DIM).unwrap());
+
+        // Files written by other tools name the geometry column in the metadata only
+        let batch = to_record_batch(&data).unwrap();
+        let mut fields: Vec<_> = batch.schema().fields().iter().cloned().collect();
+        let geometry = fields.pop().unwrap();
+        fields.push(Arc::new(Field::new(
+            "geom",
+            geometry.data_type().clone(),
+            false,
+        )));
+        let batch = arrow_array::RecordBatch::try_new(
+            Arc::new(Schema::new(fields)),
+            batch.columns().to_vec(),
+        )
+        .unwrap();
+        let geo = r#"{"version": "1.0.0", "primary_column": "geom", "columns": {"geom": {"encoding": "WKB", "geometry_types": []}}}"#;
+        let properties = WriterProperties::builder()
+            .set_key_value_metadata(Some(vec![KeyValue::new(
+                String::from("geo"),
+                String::from(geo),
+            )]))
+            .build();
+        let path = dir.join("geobuf-test-geom.parquet");
+        let mut writer = ArrowWriter::try_new(
+            File::create(&path).unwrap(),
+            batch.schema(),
+            Some(properties),
+        )
+        .unwrap();
+        writer.write(&batch).unwrap();
+        writer.close().unwrap();
+        check(&super::parquet::read(File::open(&path).unwrap(), PRECISION, DIM).unwrap());
+    }
+
+    #[test]
+    fn test_metadata_round_trip() {
+        let file = File::open("fixtures/featurecollection.json").unwrap();
+        let original_geojson: JSONValue = serde_json::from_reader(BufReader::new(file)).unwrap();
+        let mut data = Encoder::encode(&original_geojson, PRECISION, DIM).unwrap();
+
+        let metadata = Metadata {
+            crs: Some(String::from("EPSG:4326")),
+            attribution: Some(String::from("© contributors")),
+            created: Some(String::from("2022-05-01T12:00:00Z")),
+            ..Default::default()
+        };
+        metadata.write(&mut data).unwrap();
+
+        let geojson = Decoder::decode(&data).unwrap();
+        assert_eq!(geojson["geobuf:metadata"]["crs"], "EPSG:4326");
+        compare_geojsons(&original_geojson, &geojson);
+
+        let data = Encoder::encode(&geojson, PRECISION, DIM).unwrap();
+        assert_eq!(Metadata::read(&data).unwrap(), Some(metadata.clone()));
+
+        // Feature roots keep their properties in the same values as the metadata
+        let original_geojson = serde_json::json!({"type": "Feature", "title": "t", "properties": {"a": 1},
+            "geometry": {"type": "Point", "coordinates": [1.0, 2.0]}});
+        let mut data = Encoder::encode(&original_geojson, PRECISION, DIM).unwrap();
+        metadata.write(&mut data).unwrap();
+        let mut metadata = metadata;
+        metadata.crs = None;
+        metadata.write(&mut data).unwrap();
+        assert!(Metadata::clear(&mut data));
+        assert_eq!(Decoder::decode(&data).unwrap(), original_geojson);
+    }
+
+    #[test]
+    fn test_merge() {
+        let file = File::open("fixtures/featurecollection.json").unwrap();
+        let fc: JSONValue = serde_json::from_reader(BufReader::new(file)).unwrap();
+        let file = File::open("fixtures/props.json").unwrap();
+        let props: JSONValue = serde_json::from_reader(BufReader::new(file)).unwrap();
+
+        let merged = super::merge(&[
+            Encoder::encode(&fc, PRECISION, DIM).unwrap(),
+            Encoder::encode(&props, 4, DIM).unwrap(),
+        ])
+        .unwrap();
+        assert_eq!(merged.precision(), PRECISION);
+
+        let mut features = fc["features"].as_array().unwrap().clone();
+        features.extend(props["features"].as_array().unwrap().iter().cloned());
+        let geojson = Decoder::decode(&merged).unwrap();
+        compare_geojsons(&JSONValue::from(features), &geojson["features"]);
+
+        let data = Encoder::encode(&fc, PRECISION, DIM).unwrap();
+        let mut high_precision = data.clone();
+        high_precision.set_precision(30);
+        assert_eq!(
+            super::merge(&[data.clone(), high_precision]),
+            Err("Precision too high to rescale.")
+        );
+
+        let mut invalid_key = data.clone();
+        invalid_key.mut_feature_collection().features[0].properties[0] = 100;
+        assert_eq!(super::merge(&[invalid_key]), Err("Invalid key index."));
+        let mut invalid_key = data.clone();
+        invalid_key.mut_feature_collection().custom_properties = vec![100, 0];
+        assert_eq!(super::merge(&[invalid_key]), Err("Invalid key index."));
+        let mut odd = data.clone();
+        odd.mut_feature_collection().features[0].properties.pop();
+        assert_eq!(super::merge(&[odd]), Err("Odd-length property table."));
+        let mut odd = data;
+        odd.mut_feature_collection().custom_properties = vec![0];
+        assert_eq!(super::merge(&[odd]), Err("Odd-length property table."));
+    }
+
+    #[test]
+    fn test_split() {
+        let file = File::open("fixtures/us-states.json").unwrap();
+        let original_geojson: JSONValue = serde_json::from_reader(BufReader::new(file)).unwrap();
+        let data = Encoder::encode(&original_geojson, PRECISION, DIM).unwrap();
+
+        let chunks = super::split(&data, 7).unwrap();
+        assert!(chunks.len() > 1);
+        assert!(chunks
+            .iter()
+            .all(|chunk| chunk.feature_collection().features.len() <= 7));
+
+        let merged = super::merge(&chunks).unwrap();
+        compare_geojsons(&original_geojson, &Decoder::decode(&merged).unwrap());
+
+        let mut invalid = data.clone();
+        invalid.mut_feature_collection().features[0].properties[0] = 99;
+        assert_eq!(super::split(&invalid, 7), Err("Invalid key index."));
+        assert_eq!(
+            super::split_by_size(&invalid, 20000),
+            Err("Invalid key index.")
+        );
+        assert_eq!(super::select(&invalid, &[0]), Err("Invalid key index."));
+        assert!(super::select(&invalid, &[1]).is_ok());
+        let mut invalid = data.clone();
+        invalid.mut_feature_collection().features[0]
+            .properties
+            .pop();
+        assert_eq!(super::split(&invalid, 7), Err("Odd-length property table."));
+    }
+
+    #[test]
+    fn test_split_by_size() {
+        let file = File::open("fixtures/us-states.json").unwrap();
+        let original_geojson: JSONValue = serde_json::from_reader(BufReader::new(file)).unwrap();
+        let data = Encoder::encode(&original_geojson, PRECISION, DIM).unwrap();
+
+        let chunks = super::split_by_size(&data, 20000).unwrap();
+        assert!(chunks.len() > 1);
+        assert!(chunks.iter().all(|chunk| {
+            codec::size(chunk) <= 20000 || chunk.feature_collection().features.len() == 1
+        }));
+
+        let merged = super::merge(&chunks).unwrap();
+        compare_geojsons(&original_geojson, &Decoder::decode(&merged).unwrap());
+    }
+
+    #[test]
+    fn test_select() {
+        let file = File::open("fixtures/us-states.json").unwrap();
+        let original_geojson: JSONValue = serde_json::from_reader(BufReader::new(file)).unwrap();
+        let data = Encoder::encode(&original_geojson, PRECISION, DIM).unwrap();
+
+        let selected = super::select(&data, &[3, 0, 100]).unwrap();
+        let geojson = Decoder::decode(&selected).unwrap();
+        let features = geojson["features"].as_array().unwrap();
+        assert_eq!(features.len(), 2);
+        compare_geojsons(&original_geojson["features"][3], &features[0]);
+        compare_geojsons(&original_geojson["features"][0], &features[1]);
+    }
+
+    #[test]
+    fn test_filter() {
+        let file = File::open("fixtures/us-states.json").unwrap();
+        let original_geojson: JSONValue = serde_json::from_reader(BufReader::new(file)).unwrap();
+        let data = Encoder::encode(&original_geojson, PRECISION, DIM).unwrap();
+        let count = |expr: &str| {
+            let expr: filter::Expr = expr.parse().unwrap();
+            filter::filter(&data, &expr)
+                .unwrap()
+                .feature_collection()
+                .features
+                .len()
+        };
+
+        assert_eq!(count("density > 100"), 26);
+        assert_eq!(count("density > 100 && name < 'N'"), 14);
+        assert_eq!(count("!(density > 100) || name == \"Ohio\""), 27);
+        assert_eq!(count("name != 'Texas'"), 51);
+        assert_eq!(count("name && population == null"), 52);
+        assert_eq!(count("population != 5"), 52);
+        assert_eq!(count("name > 5"), 0);
+
+        for expr in [
+            "",
+            "density >",
+            "density > 'a",
+            "(name",
+            "name == 1 1",
+            "density > 1e",
+            "#",
+        ] {
+            assert!(expr.parse::<filter::Expr>().is_err(), "{}", expr);
+        }
+    }
+
+    #[test]
+    fn test_set_property() {
+        let file = File::open("fixtures/us-states.json").unwrap();
+        let mut original_geojson: JSONValue =
+            serde_json::from_reader(BufReader::new(file)).unwrap();
+        let mut data = Encoder::encode(&original_geojson, PRECISION, DIM).unwrap();
+
+        for n in 0..data.feature_count() {
+            data.set_property(n, "processed", &JSONValue::from(true))
+                .unwrap();
+            data.delete_property(n, "name").unwrap();
+        }
+        for feature in original_geojson["features"].as_array_mut().unwrap() {
+            let properties = feature["properties"].as_object_mut().unwrap();
+            properties.insert(String::from("processed"), JSONValue::from(true));
+            properties.remove("name");
+        }
+
+        let geojson = Decoder::decode(&data).unwrap();
+        compare_geojsons(&original_geojson, &geojson);
+        compare_geojsons(&geojson, &original_geojson);
+
+        // Foreign members index into the same values as properties
+        let original_geojson = serde_json::json!({"type": "FeatureCollection", "features": [
+            {"type": "Feature", "title": "t", "properties": {"a": 1, "b": 2},
+                "geometry": {"type": "Point", "coordinates": [1.0, 2.0]}}
+        ]});
+        let mut data = Encoder::encode(&original_geojson, PRECISION, DIM).unwrap();
+        assert!(data.delete_property(0, "a").unwrap());
+        data.set_property(0, "b", &JSONValue::from(3)).unwrap();
+        assert_eq!(
+            Decoder::decode(&data).unwrap(),
+            serde_json::json!({"type": "FeatureCollection", "features": [
+                {"type": "Feature", "title": "t", "properties": {"b": 3},
+                    "geometry": {"type": "Point", "coordinates": [1.0, 2.0]}}
+            ]})
+        );
+    }
+
+    #[test]
+    fn test_shared_strings() {
+        let file = File::open("fixtures/us-states.json").unwrap();
+        let mut original_geojson: JSONValue =
+            serde_json::from_reader(BufReader::new(file)).unwrap();
+        for feature in original_geojson["features"].as_array_mut().unwrap() {
+            feature["properties"]["country"] = JSONValue::from("United States of America");
+        }
+        let mut data = Encoder::encode(&original_geojson, PRECISION, DIM).unwrap();
+        let size = codec::size(&data);
+
+        assert_eq!(strings::share(&mut data, 2).unwrap(), 1);
+        assert!(codec::size(&data) < size);
+        compare_geojsons(&original_geojson, &Decoder::decode(&data).unwrap());
+
+        let chunks = super::split(&data, 10).unwrap();
+        assert!(chunks.iter().all(|chunk| chunk.strings == data.strings));
+        let merged = super::merge(&chunks).unwrap();
+        compare_geojsons(&original_geojson, &Decoder::decode(&merged).unwrap());
+    }
+
+    fn max_coordinate_error(coords1: &JSONValue, coords2: &JSONValue) -> f64 {
+        match (coords1.as_array(), coords2.as_array()) {
+            (Some(coords1), Some(coords2)) => coords1
+                .iter()
+                .zip(coords2)
+                .map(|(c1, c2)| max_coordinate_error(c1, c2))
+                .fold(0.0, f64::max),
+            _ => (coords1.as_f64().unwrap() - coords2.as_f64().unwrap()).abs(),
+        }
+    }
+
+    #[test]
+    fn test_tile_quantization() {
+        let file = File::open("fixtures/us-states.json").unwrap();
+        let original_geojson: JSONValue = serde_json::from_reader(BufReader::new(file)).unwrap();
+        let options = EncoderOptions {
+            tile: Some(Tile {
+                bounds: [-180.0, 0.0, -60.0, 90.0],
+                extent: 4096,
+            }),
+            ..Default::default()
+        };
+        let data = Encoder::encode_with_options(&original_geojson, &options).unwrap();
+        assert_eq!(data.precision(), 0);
+
+        let geojson = Decoder::decode(&data).unwrap();
+        let features = original_geojson["features"].as_array().unwrap();
+        for (n, feature) in features.iter().enumerate() {
+            let coords1 = &feature["geometry"]["coordinates"];
+            let coords2 = &geojson["features"][n]["geometry"]["coordinates"];
+            assert!(max_coordinate_error(coords1, coords2) <= 120.0 / 4096.0 / 2.0 + 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_estimate_size() {
+        let options = [
+            EncoderOptions::default(),
+            EncoderOptions {
+                precision: 2,
+                ..Default::default()
+            },
+            EncoderOptions {
+                tile: Some(Tile {
+                    bounds: [-180.0, -90.0, 180.0, 90.0],
+                    extent: 4096,
+                }),
+                ..Default::default()
+            },
+        ];
+        for entry in std::fs::read_dir("fixtures").unwrap() {
+            let file = File::open(entry.unwrap().path()).unwrap();
+            let geojson: JSONValue = serde_json::from_reader(BufReader::new(file)).unwrap();
+            for options in &options {
+                let data = Encoder::encode_with_options(&geojson, options);
+                let size = data.map(|data| codec::size(&data) as usize).unwrap_or(0);
+                assert_eq!(Encoder::estimate_size(&geojson, options), size);
+            }
+        }
+    }
+
+    #[test]
+    fn test_hash_feature() {
+        let file = File::open("fixtures/us-states.json").unwrap();
+        let original_geojson: JSONValue = serde_json::from_reader(BufReader::new(file)).unwrap();
+        let data = Encoder::encode(&original_geojson, PRECISION, DIM).unwrap();
+        let hashes: Vec<u64> = data
+            .feature_collection()
+            .features
+            .iter()
+            .map(|feature| super::hash_feature(feature, &data.keys, &data.strings))
+            .collect();
+
+        let geojson = Decoder::decode(&data).unwrap();
+        let mut data = Encoder::encode(&geojson, PRECISION, DIM).unwrap();
+        data.keys.reverse();
+        for feature in data.mut_feature_collection().features.iter_mut() {
+            for i in (0..feature.properties.len()).step_by(2) {
+                feature.properties[i] = 1 - feature.properties[i];
+            }
+        }
+        strings::share(&mut data, 1).unwrap();
+        for (n, feature) in data.feature_collection().features.iter().enumerate() {
+            let hash = super::hash_feature(feature, &data.keys, &data.strings);
+            assert_eq!(hash, hashes[n]);
+        }
+
+        data.set_property(0, "density", &JSONValue::from(1))
+            .unwrap();
+        let feature = &data.feature_collection().features[0];
+        assert_ne!(
+            super::hash_feature(feature, &data.keys, &data.strings),
+            hashes[0]
+        );
+    }
+
+    #[test]
+    fn test_diff() {
+        let file = File::open("fixtures/us-states.json").unwrap();
+        let original_geojson: JSONValue = serde_json::from_reader(BufReader::new(file)).unwrap();
+        let old = Encoder::encode(&original_geojson, PRECISION, DIM).unwrap();
+        let mut new = Encoder::encode(&original_geojson, PRECISION, DIM).unwrap();
+        assert!(super::diff(&old, &new).unwrap().is_empty());
+
+        strings::share(&mut new, 1).unwrap();
+        new.set_property(3, "density", &JSONValue::from(1)).unwrap();
+        new.set_property(5, "population", &JSONValue::from(10))
+            .unwrap();
+        new.mut_feature_collection().features[5]
+            .geometry
+            .mut_or_insert_default()
+            .coords[0] += 1;
+        let removed = new.mut_feature_collection().features.remove(0);
+        new.mut_feature_collection().features.push(removed.clone());
+        let mut added = removed;
+        added.set_int_id(1000);
+        new.mut_feature_collection().features.push(added);
+
+        let diff = super::diff(&old, &new).unwrap();
+        assert!(diff.removed.is_empty());
+        assert_eq!(diff.added, vec![52]);
+        assert_eq!(diff.changed.len(), 2);
+        assert_eq!((diff.changed[0].old, diff.changed[0].new), (3, 2));
+        assert!(!diff.changed[0].geometry_changed);
+        assert_eq!(diff.changed[0].properties[0].key, "density");
+        assert_eq!(diff.changed[0].properties[0].new, Some(JSONValue::from(1)));
+        assert_eq!((diff.changed[1].old, diff.changed[1].new), (5, 4));
+        assert!(diff.changed[1].geometry_changed);
+        assert_eq!(diff.changed[1].properties[0].key, "population");
+        assert_eq!(diff.changed[1].properties[0].old, None);
+
+        let geometry = Encoder::encode(
+            &serde_json::json!({"type": "Point", "coordinates": [1.0, 2.0]}),
+            PRECISION,
+            DIM,
+        )
+        .unwrap();
+        assert!(super::diff(&old, &geometry).is_err());
+    }
+
+    #[test]
+    fn test_patch() {
+        let file = File::open("fixtures/us-states.json").unwrap();
+        let original_geojson: JSONValue = serde_json::from_reader(BufReader::new(file)).unwrap();
+        let old = Encoder::encode(&original_geojson, PRECISION, DIM).unwrap();
+        let mut new = Encoder::encode(&original_geojson, 7, DIM).unwrap();
+        new.set_property(3, "density", &JSONValue::from(1)).unwrap();
+        new.set_property(5, "region", &JSONValue::from("west"))
+            .unwrap();
+        new.mut_feature_collection().features.remove(0);
+        let mut added = new.feature_collection().features[10].clone();
+        added.set_int_id(-1000);
+        new.mut_feature_collection().features.push(added);
+        let mut new = Encoder::encode(&Decoder::decode(&new).unwrap(), PRECISION, DIM).unwrap();
+        strings::share(&mut new, 1).unwrap();
+
+        let patch = super::make_patch(&old, &new).unwrap();
+        assert_eq!(patch.removed.len(), 1);
+        assert_eq!(patch.replaced, 2);
+        assert_eq!(patch.features.feature_collection().features.len(), 3);
+        let bytes = patch.to_bytes().unwrap();
+        assert!(bytes.len() < codec::write(&new).len() / 10);
+        let patch = super::Patch::from_bytes(&bytes).unwrap();
+
+        let mut data = old.clone();
+        super::apply_patch(&mut data, &patch).unwrap();
+        assert!(super::diff(&data, &new).unwrap().is_empty());
+        assert_eq!(
+            Decoder::decode(&data).unwrap(),
+            Decoder::decode(&new).unwrap()
+        );
+
+        let mut data = old.clone();
+        data.mut_feature_collection().features.remove(0);
+        assert!(super::apply_patch(&mut data, &patch).is_err());
+        assert_eq!(data.feature_collection().features.len(), 51);
+
+        // Patches with indexes past their tables leave the tables of `data` unchanged
+        let mut invalid = super::Patch::from_bytes(&bytes).unwrap();
+        let features = &mut invalid.features.mut_feature_collection().features;
+        features[2].properties[0] = 99;
+        let mut data = old.clone();
+        assert_eq!(
+            super::apply_patch(&mut data, &invalid),
+            Err("Invalid patch.")
+        );
+        assert_eq!(data, old);
+
+        assert!(super::make_patch(&new, &new).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_bbox() {
+        let file = File::open("fixtures/us-states.json").unwrap();
+        let original_geojson: JSONValue = serde_json::from_reader(BufReader::new(file)).unwrap();
+        let data = Encoder::encode(&original_geojson, PRECISION, DIM).unwrap();
+        assert_eq!(
+            super::bbox::bbox(&data),
+            Some([-188.90491, 17.929556, -65.626797, 71.351633])
+        );
+
+        let alaska = data.feature_collection().features[1]
+            .geometry
+            .as_ref()
+            .unwrap();
+        let bbox = super::bbox::geometry_bbox(&data, alaska).unwrap();
+        assert_eq!(bbox, [-188.90491, 51.61274, -129.986323, 71.351633]);
+        assert!(super::bbox::intersects(
+            &bbox,
+            &[-150.0, 60.0, -140.0, 61.0]
+        ));
+        assert!(!super::bbox::intersects(
+            &bbox,
+            &[-100.0, 60.0, -90.0, 61.0]
+        ));
+
+        let file = File::open("fixtures/topology.json").unwrap();
+        let original_geojson: JSONValue = serde_json::from_reader(BufReader::new(file)).unwrap();
+        let data = Encoder::encode(&original_geojson, PRECISION, DIM).unwrap();
+        let bbox = super::bbox::bbox(&data).unwrap();
+        for (a, b) in bbox.iter().zip(&[100.0, 0.0, 105.0, 1.0]) {
+            assert!((a - b).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_validate() {
+        for fixture in [
+            "us-states",
+            "props",
+            "precision",
+            "single-multipoly",
+            "topology",
+            "topology-unquantized",
+        ] {
+            let file = File::open(format!("fixtures/{}.json", fixture)).unwrap();
+            let original_geojson: JSONValue =
+                serde_json::from_reader(BufReader::new(file)).unwrap();
+            let data = Encoder::encode(&original_geojson, PRECISION, DIM).unwrap();
+            assert_eq!(super::validate::validate(&data), vec![], "{}", fixture);
+        }
+
+        let file = File::open("fixtures/us-states.json").unwrap();
+        let original_geojson: JSONValue = serde_json::from_reader(BufReader::new(file)).unwrap();
+        let mut data = Encoder::encode(&original_geojson, PRECISION, DIM).unwrap();
+        data.mut_feature_collection().features[0]
+            .geometry
+            .mut_or_insert_default()
+            .coords
+            .pop();
+        data.mut_feature_collection().features[1]
+            .geometry
+            .mut_or_insert_default()
+            .lengths[2] += 1;
+        data.mut_feature_collection().features[2].properties[0] = 9;
+        let problems: Vec<String> = super::validate::validate(&data)
+            .iter()
+            .map(|p| p.to_string())
+            .collect();
+        assert_eq!(
+            problems,
+            vec![
+                "features[0].geometry.coords: 63 coordinates is not a multiple of 2 dimensions",
+                "features[1].geometry.lengths: lengths add up to 1036 positions, found 1035",
+                "features[2].properties: key index 9 out of range (2 keys)",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_analyze() {
+        for (fixture, analysis) in [
+            ("us-states", (6, 2)),
+            ("props", (0, 2)),
+            ("precision", (6, 2)),
+            ("topology", (0, 2)),
+        ] {
+            let file = File::open(format!("fixtures/{}.json", fixture)).unwrap();
+            let original_geojson: JSONValue =
+                serde_json::from_reader(BufReader::new(file)).unwrap();
+            assert_eq!(Encoder::analyze(&original_geojson), analysis, "{}", fixture);
+        }
+
+        let geojson = serde_json::json!({
+            "type": "Feature",
+            "properties": {"values": [0.125, 1.0, 2.0, 3.0]},
+            "geometry": {"type": "GeometryCollection", "geometries": [
+                {"type": "Point", "coordinates": [1.5, 2.0, 3.0]},
+                {"type": "Polygon", "coordinates": [[[0.0, 0.0, 0.0], [0.25, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 0.0]]]},
+            ]},
+        });
+        assert_eq!(Encoder::analyze(&geojson), (2, 3));
+        let data = Encoder::encode(&geojson, 2, 3).unwrap();
+        assert_eq!(Decoder::decode(&data).unwrap(), geojson);
+    }
+
+    #[test]
+    fn test_check_feature() {
+        let file = File::open("fixtures/us-states.json").unwrap();
+        let original_geojson: JSONValue = serde_json::from_reader(BufReader::new(file)).unwrap();
+        for feature in original_geojson["features"].as_array().unwrap() {
+            assert_eq!(Encoder::check_feature(feature, PRECISION, DIM), Ok(()));
+        }
+
+        let feature = |id: JSONValue, geometry: JSONValue| {
+            serde_json::json!({
+                "type": "Feature",
+                "id": id,
+                "properties": {},
+                "geometry": geometry,
+            })
+        };
+        let point = serde_json::json!({"type": "Point", "coordinates": [1.0, 2.0]});
+        for (id, geometry, err) in [
+            (serde_json::json!(1.5), point.clone(), "Invalid id."),
+            (
+                serde_json::json!(1),
+                JSONValue::Null,
+                "Invalid geometry type.",
+            ),
+            (
+                serde_json::json!(1),
+                serde_json::json!({"type": "Point", "coordinates": [1.0, 2.0, 3.0]}),
+                "Point with a number of coordinates other than the dimensions.",
+            ),
+            (
+                serde_json::json!(1),
+                serde_json::json!({"type": "LineString", "coordinates": [[1.0, 2.0], 3.0]}),
+                "Invalid coordinates.",
+            ),
+            (
+                serde_json::json!(1),
+                serde_json::json!({"type": "Polygon", "coordinates": [[]]}),
+                "Empty ring.",
+            ),
+            (
+                serde_json::json!(1),
+                serde_json::json!({"type": "MultiPoint", "coordinates": [[1.0, 1e300]]}),
+                "Coordinate too large to quantize.",
+            ),
+            (
+                serde_json::json!(1),
+                serde_json::json!({"type": "GeometryCollection", "geometries": [point.clone(),
+                {"type": "LineString", "coordinates": [[1.0, 2.0], [3.0]]}]}),
+                "Position with too few coordinates.",
+            ),
+        ] {
+            assert_eq!(
+                Encoder::check_feature(&feature(id, geometry), PRECISION, DIM),
+                Err(err)
+            );
+        }
+        let feature = feature(serde_json::json!("a"), point);
+        assert_eq!(Encoder::check_feature(&feature, PRECISION, DIM), Ok(()));
+    }
+
+    #[test]
+    fn test_check_topology() {
+        for name in ["topology", "topology-unquantized"] {
+            let file = File::open(format!("fixtures/{}.json", name)).unwrap();
+            let geojson: JSONValue = serde_json::from_reader(BufReader::new(file)).unwrap();
+            assert_eq!(Encoder::check(&geojson, PRECISION, DIM), Ok(()));
+        }
+
+        let topology = |transform: JSONValue, arcs: JSONValue, object: JSONValue| {
+            serde_json::json!({
+                "type": "Topology",
+                "transform": transform,
+                "arcs": arcs,
+                "objects": {"a": object},
+            })
+        };
+        let quantized = serde_json::json!({"scale": [0.5, 0.5], "translate": [10.0, 20.0]});
+        let arcs = serde_json::json!([[[0, 0], [1, 1]], [[1, 1], [0, 2]]]);
+        let line = serde_json::json!({"type": "LineString", "arcs": [0, -2]});
+        for (transform, arcs, object, err) in [
+            (
+                quantized.clone(),
+                serde_json::json!([[[0, 0], [1.5, 1]]]),
+                line.clone(),
+                "Non-integer coordinate in a quantized topology.",
+            ),
+            (
+                quantized.clone(),
+                serde_json::json!([[[0, 0], [1]]]),
+                line.clone(),
+                "Non-integer coordinate in a quantized topology.",
+            ),
+            (
+                JSONValue::Null,
+                serde_json::json!([[[0.0, 0.0], [1e300, 1.0]]]),
+                line.clone(),
+                "Coordinate too large to quantize.",
+            ),
+            (
+                quantized.clone(),
+                serde_json::json!([5]),
+                line.clone(),
+                "Invalid topology arc",
+            ),
+            (
+                quantized.clone(),
+                arcs.clone(),
+                serde_json::json!({"type": "LineString", "arcs": [0, 1e10]}),
+                "Invalid arc index.",
+            ),
+            (
+                quantized.clone(),
+                arcs.clone(),
+                serde_json::json!({"type": "LineString", "arcs": [4294967296i64]}),
+                "Invalid arc index.",
+            ),
+            (
+                quantized.clone(),
+                arcs.clone(),
+                serde_json::json!({"type": "Polygon", "arcs": [0, 1]}),
+                "Invalid topology arcs.",
+            ),
+            (
+                quantized.clone(),
+                arcs.clone(),
+                serde_json::json!({"type": "MultiPolygon", "arcs": [[0]]}),
+                "Invalid topology arcs.",
+            ),
+            (
+                quantized.clone(),
+                arcs.clone(),
+                serde_json::json!({"type": "Point"}),
+                "Invalid coordinates.",
+            ),
+            (
+                quantized.clone(),
+                arcs.clone(),
+                serde_json::json!({"type": "MultiPoint", "coordinates": [[1, "a"]]}),
+                "Invalid coordinates.",
+            ),
+            (
+                quantized.clone(),
+                arcs.clone(),
+                serde_json::json!({"type": "GeometryCollection"}),
+                "Invalid geometry collection.",
+            ),
+            (
+                quantized.clone(),
+                arcs.clone(),
+                serde_json::json!({"type": "LineString", "id": 1.5, "arcs": [0]}),
+                "Invalid id.",
+            ),
+            (
+                quantized.clone(),
+                arcs.clone(),
+                serde_json::json!(5),
+                "Invalid topology object.",
+            ),
+        ] {
+            let geojson = topology(transform, arcs, object);
+            assert_eq!(
+                Encoder::check(&geojson, PRECISION, DIM),
+                Err(String::from(err))
+            );
+            assert_eq!(Encoder::encode(&geojson, PRECISION, DIM), Err(err));
+        }
+        let geojson = topology(quantized, arcs, line);
+        assert_eq!(Encoder::check(&geojson, PRECISION, DIM), Ok(()));
+        compare_geojsons(
+            &geojson,
+            &Decoder::decode(&Encoder::encode(&geojson, PRECISION, DIM).unwrap()).unwrap(),
+        );
+    }
 }
@@ -0,0 +1,194 @@
+//! Merging several Geobuf objects into one FeatureCollection
+use crate::runtime::MessageField;
+
+use crate::geobuf_pb;
+use crate::props;
+use crate::strings;
+
+/// Returns a FeatureCollection holding the features of every input, in order
+///
+/// FeatureCollections contribute all of their features, Features are added as they are and
+/// bare Geometries are wrapped into Features. Property key indexes are remapped into a
+/// single merged key table, and shared string references (see `strings`) into a single
+/// merged string table, so no decoding to GeoJSON is needed. Inputs encoded with a lower
+/// precision are rescaled to the highest precision among the inputs. Custom properties of
+/// input FeatureCollections are kept, with the first input winning on conflicts.
+///
+/// # Example
+///
+/// ```
+/// use geobuf::encode::Encoder;
+///
+/// let a = serde_json::from_str(r#"{"type": "Feature", "properties": {"name": "a"},
+///     "geometry": {"type": "Point", "coordinates": [1.0, 2.0]}}"#).unwrap();
+/// let b = serde_json::from_str(r#"{"type": "Feature", "properties": {"pop": 10, "name": "b"},
+///     "geometry": {"type": "Point", "coordinates": [3.0, 4.0]}}"#).unwrap();
+///
+/// let merged = geobuf::merge(&[
+///     Encoder::encode(&a, 6, 2).unwrap(),
+///     Encoder::encode(&b, 6, 2).unwrap(),
+/// ]).unwrap();
+/// assert_eq!(merged.keys, vec!["name", "pop"]);
+/// assert_eq!(merged.feature_collection().features.len(), 2);
+/// ```
+pub fn merge(inputs: &[geobuf_pb::Data]) -> Result<geobuf_pb::Data, &'static str> {
+    let mut merged = geobuf_pb::Data::new();
+    let first = match inputs.first() {
+        Some(first) => first,
+        None => return Err("Nothing to merge."),
+    };
+    let dim = first.dimensions();
+    if inputs.iter().any(|data| data.dimensions() != dim) {
+        return Err("Inputs have different dimensions.");
+    }
+    if inputs.iter().any(|data| data.transform != first.transform) {
+        return Err("Inputs have different transforms.");
+    }
+    let precision = inputs.iter().map(|data| data.precision()).max().unwrap();
+    merged.set_dimensions(dim);
+    merged.set_precision(precision);
+    merged.transform = first.transform.clone();
+
+    let mut feature_collection = geobuf_pb::data::FeatureCollection::new();
+    for data in inputs {
+        let mapping = props::key_mapping(&data.keys, &mut merged.keys);
+        let string_mapping = props::key_mapping(&data.strings, &mut merged.strings);
+        let scale = match 10i64.checked_pow(precision - data.precision()) {
+            Some(scale) => scale,
+            None => return Err("Precision too high to rescale."),
+        };
+
+        let data_type = match data.data_type.as_ref() {
+            Some(data_type) => data_type,
+            None => return Err("Missing data type."),
+        };
+        let mut features = match data_type {
+            geobuf_pb::data::Data_type::FeatureCollection(fc) => {
+                merge_custom_properties(&mut feature_collection, fc, &mapping, &string_mapping)?;
+                fc.features.clone()
+            }
+            geobuf_pb::data::Data_type::Feature(feature) => vec![feature.clone()],
+            geobuf_pb::data::Data_type::Geometry(geometry) => {
+                let mut feature = geobuf_pb::data::Feature::new();
+                feature.geometry = MessageField::some(geometry.clone());
+                vec![feature]
+            }
+            geobuf_pb::data::Data_type::Topology(_) => return Err("Can't merge topologies."),
+        };
+
+        for feature in features.iter_mut() {
+            remap_feature(feature, &mapping, &string_mapping)?;
+            if scale != 1 {
+                if let Some(geometry) = feature.geometry.as_mut() {
+                    rescale_geometry(geometry, scale);
+                }
+            }
+        }
+        feature_collection.features.append(&mut features);
+    }
+
+    merged.set_feature_collection(feature_collection);
+    Ok(merged)
+}
+
+fn merge_custom_properties(
+    target: &mut geobuf_pb::data::FeatureCollection,
+    source: &geobuf_pb::data::FeatureCollection,
+    mapping: &[u32],
+    string_mapping: &[u32],
+) -> Result<(), &'static str> {
+    if !source.custom_properties.len().is_multiple_of(2) {
+        return Err("Odd-length property table.");
+    }
+    for pair in source.custom_properties.chunks(2) {
+        let key_index = match mapping.get(pair[0] as usize) {
+            Some(&key_index) => key_index,
+            None => return Err("Invalid key index."),
+        };
+        let exists = target
+            .custom_properties
+            .chunks(2)
+            .any(|target_pair| target_pair[0] == key_index);
+        if exists {
+            continue;
+        }
+        if let Some(value) = source.values.get(pair[1] as usize) {
+            let mut value = value.clone();
+            let mut invalid_ref = false;
+            strings::remap_refs(std::slice::from_mut(&mut value), &mut |index| {
+                remap_index(string_mapping, index, &mut invalid_ref)
+            });
+            if invalid_ref {
+                return Err("Invalid string index.");
+            }
+            target.values.push(value);
+            target.custom_properties.push(key_index);
+            target
+                .custom_properties
+                .push(target.values.len() as u32 - 1);
+        }
+    }
+    Ok(())
+}
+
+/// Rewrites the key indexes and string references of a feature through `mapping` and
+/// `string_mapping`, failing if one is out of range or a property table has an odd length
+pub(crate) fn remap_feature(
+    feature: &mut geobuf_pb::data::Feature,
+    mapping: &[u32],
+    string_mapping: &[u32],
+) -> Result<(), &'static str> {
+    if !paired_feature(feature) {
+        return Err("Odd-length property table.");
+    }
+    let mut invalid_key = false;
+    props::remap_feature(feature, &mut |key| {
+        remap_index(mapping, key, &mut invalid_key)
+    });
+    if invalid_key {
+        return Err("Invalid key index.");
+    }
+    let mut invalid_ref = false;
+    strings::remap_feature_refs(feature, &mut |index| {
+        remap_index(string_mapping, index, &mut invalid_ref)
+    });
+    if invalid_ref {
+        return Err("Invalid string index.");
+    }
+    Ok(())
+}
+
+/// Maps an index through `mapping`, setting `invalid` and keeping the index when it's out
+/// of range
+fn remap_index(mapping: &[u32], index: u32, invalid: &mut bool) -> u32 {
+    match mapping.get(index as usize) {
+        Some(&index) => index,
+        None => {
+            *invalid = true;
+            index
+        }
+    }
+}
+
+/// Returns true if the property tables of a feature and its geometries hold key and value
+/// pairs
+pub(crate) fn paired_feature(feature: &geobuf_pb::data::Feature) -> bool {
+    feature.properties.len().is_multiple_of(2)
+        && feature.custom_properties.len().is_multiple_of(2)
+        && feature.geometry.as_ref().is_none_or(paired_geometry)
+}
+
+fn paired_geometry(geometry: &geobuf_pb::data::Geometry) -> bool {
+    geometry.properties.len().is_multiple_of(2)
+        && geometry.custom_properties.len().is_multiple_of(2)
+        && geometry.geometries.iter().all(paired_geometry)
+}
+
+pub(crate) fn rescale_geometry(geometry: &mut geobuf_pb::data::Geometry, scale: i64) {
+    for coord in geometry.coords.iter_mut() {
+        *coord *= scale;
+    }
+    for child in geometry.geometries.iter_mut() {
+        rescale_geometry(child, scale);
+    }
+}
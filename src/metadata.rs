@@ -0,0 +1,140 @@
+//! Dataset metadata block
+//!
+//! Metadata is stored as a JSON object in a custom property named `geobuf:metadata` on the
+//! root object (FeatureCollection, Feature or Geometry). Decoders that don't know about it
+//! see an ordinary foreign member, so the metadata survives GeoJSON round trips.
+use serde_json::Value as JSONValue;
+
+use crate::geobuf_pb;
+use crate::props;
+
+/// Name of the custom property holding the metadata block
+pub const METADATA_KEY: &str = "geobuf:metadata";
+
+/// Well-known dataset metadata
+///
+/// Timestamps are RFC 3339 strings, e.g. `2022-05-01T12:00:00Z`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Metadata {
+    /// Coordinate reference system identifier, e.g. `EPSG:4326`
+    pub crs: Option<String>,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub attribution: Option<String>,
+    pub created: Option<String>,
+    pub modified: Option<String>,
+}
+
+const FIELDS: [&str; 6] = [
+    "crs",
+    "title",
+    "description",
+    "attribution",
+    "created",
+    "modified",
+];
+
+impl Metadata {
+    /// Returns the metadata stored in `data`, or `None` if there is none
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use geobuf::encode::Encoder;
+    /// use geobuf::metadata::Metadata;
+    ///
+    /// let geojson = serde_json::from_str(r#"{"type": "FeatureCollection", "features": []}"#).unwrap();
+    /// let mut data = Encoder::encode(&geojson, 6, 2).unwrap();
+    ///
+    /// let metadata = Metadata {
+    ///     crs: Some(String::from("EPSG:4326")),
+    ///     title: Some(String::from("Roads")),
+    ///     ..Default::default()
+    /// };
+    /// metadata.write(&mut data).unwrap();
+    /// assert_eq!(Metadata::read(&data).unwrap(), Some(metadata));
+    /// ```
+    pub fn read(data: &geobuf_pb::Data) -> Result<Option<Self>, &'static str> {
+        let (values, properties) = match props::root_tables(data) {
+            Some(tables) => tables,
+            None => return Err("Missing data type."),
+        };
+        match props::get(&data.keys, values, properties, METADATA_KEY) {
+            Some(value) => {
+                Metadata::from_json(&props::value_to_json(value, &data.strings)).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Stores the metadata in `data`, replacing any existing metadata
+    pub fn write(&self, data: &mut geobuf_pb::Data) -> Result<(), &'static str> {
+        let (keys, values, properties, other) = match props::root_tables_mut(data) {
+            Some(tables) => tables,
+            None => return Err("Missing data type."),
+        };
+        let value = props::json_to_value(&self.to_json()).unwrap();
+        props::set(keys, values, properties, other, METADATA_KEY, value);
+        Ok(())
+    }
+
+    /// Removes any metadata from `data`, returning true if there was some
+    pub fn clear(data: &mut geobuf_pb::Data) -> bool {
+        match props::root_tables_mut(data) {
+            Some((keys, values, properties, other)) => {
+                props::remove(keys, values, properties, other, METADATA_KEY)
+            }
+            None => false,
+        }
+    }
+
+    /// Returns the metadata as a JSON object, omitting unset fields
+    pub fn to_json(&self) -> JSONValue {
+        let mut json = serde_json::json!({});
+        for (name, field) in FIELDS.iter().zip(self.fields()) {
+            if let Some(value) = field {
+                json[*name] = serde_json::json!(value);
+            }
+        }
+        json
+    }
+
+    /// Parses metadata from a JSON object, ignoring unknown fields
+    pub fn from_json(json: &JSONValue) -> Result<Self, &'static str> {
+        let object = match json.as_object() {
+            Some(object) => object,
+            None => return Err("Metadata must be an object."),
+        };
+        let mut metadata = Metadata::default();
+        for (name, field) in FIELDS.iter().zip(metadata.fields_mut()) {
+            match object.get(*name) {
+                Some(JSONValue::String(value)) => *field = Some(value.clone()),
+                Some(JSONValue::Null) | None => {}
+                Some(_) => return Err("Metadata fields must be strings."),
+            }
+        }
+        Ok(metadata)
+    }
+
+    fn fields(&self) -> [&Option<String>; 6] {
+        [
+            &self.crs,
+            &self.title,
+            &self.description,
+            &self.attribution,
+            &self.created,
+            &self.modified,
+        ]
+    }
+
+    fn fields_mut(&mut self) -> [&mut Option<String>; 6] {
+        [
+            &mut self.crs,
+            &mut self.title,
+            &mut self.description,
+            &mut self.attribution,
+            &mut self.created,
+            &mut self.modified,
+        ]
+    }
+}
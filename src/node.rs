@@ -0,0 +1,104 @@
+//! Node.js bindings built with napi-rs
+//!
+//! Built with the `node` feature, the cdylib is a native addon taking and returning Node
+//! `Buffer`s, which are read and handed over without copying. The `Async` variants run on
+//! the libuv thread pool and return promises, leaving the event loop free while large files
+//! are converted.
+use napi::bindgen_prelude::{AsyncTask, Buffer};
+use napi::{Env, Error, Result, Task};
+use napi_derive::napi;
+
+use crate::codec;
+use crate::decode::Decoder;
+use crate::encode::Encoder;
+use crate::validate::validate;
+
+/// Encodes the UTF-8 GeoJSON in `data` into a Geobuf
+///
+/// `precision` and `dim` default to 6 and 2. Throws an `Error` if `data` isn't valid JSON or
+/// holds GeoJSON that can't be encoded.
+#[napi]
+pub fn encode(data: Buffer, precision: Option<u32>, dim: Option<u32>) -> Result<Buffer> {
+    encode_bytes(&data, precision.unwrap_or(6), dim.unwrap_or(2)).map(Buffer::from)
+}
+
+/// Decodes a Geobuf into the UTF-8 bytes of its GeoJSON, e.g. for `JSON.parse`
+///
+/// Throws an `Error` if `data` isn't a valid Geobuf.
+#[napi]
+pub fn decode(data: Buffer) -> Result<Buffer> {
+    decode_bytes(&data).map(Buffer::from)
+}
+
+/// Same as `encode`, on the libuv thread pool
+#[napi(ts_return_type = "Promise<Buffer>")]
+pub fn encode_async(data: Buffer, precision: Option<u32>, dim: Option<u32>) -> AsyncTask<Encode> {
+    AsyncTask::new(Encode {
+        data,
+        precision: precision.unwrap_or(6),
+        dim: dim.unwrap_or(2),
+    })
+}
+
+/// Same as `decode`, on the libuv thread pool
+#[napi(ts_return_type = "Promise<Buffer>")]
+pub fn decode_async(data: Buffer) -> AsyncTask<Decode> {
+    AsyncTask::new(Decode { data })
+}
+
+pub struct Encode {
+    data: Buffer,
+    precision: u32,
+    dim: u32,
+}
+
+#[napi]
+impl Task for Encode {
+    type Output = Vec<u8>;
+    type JsValue = Buffer;
+
+    fn compute(&mut self) -> Result<Self::Output> {
+        encode_bytes(&self.data, self.precision, self.dim)
+    }
+
+    fn resolve(&mut self, _env: Env, output: Self::Output) -> Result<Self::JsValue> {
+        Ok(output.into())
+    }
+}
+
+pub struct Decode {
+    data: Buffer,
+}
+
+#[napi]
+impl Task for Decode {
+    type Output = Vec<u8>;
+    type JsValue = Buffer;
+
+    fn compute(&mut self) -> Result<Self::Output> {
+        decode_bytes(&self.data)
+    }
+
+    fn resolve(&mut self, _env: Env, output: Self::Output) -> Result<Self::JsValue> {
+        Ok(output.into())
+    }
+}
+
+fn encode_bytes(data: &[u8], precision: u32, dim: u32) -> Result<Vec<u8>> {
+    let geojson = serde_json::from_slice(data)
+        .map_err(|err| Error::from_reason(format!("Could not parse geojson: {}", err)))?;
+    // Encoding panics on malformed geometries, which would abort the process
+    Encoder::check(&geojson, precision, dim).map_err(Error::from_reason)?;
+    let data = Encoder::encode(&geojson, precision, dim).map_err(Error::from_reason)?;
+    codec::serialize(&data).map_err(Error::from_reason)
+}
+
+fn decode_bytes(data: &[u8]) -> Result<Vec<u8>> {
+    let geobuf = codec::parse(data).map_err(Error::from_reason)?;
+    // Decoding panics on out of range indexes and lengths
+    if let Some(problem) = validate(&geobuf).first() {
+        return Err(Error::from_reason(format!("Invalid geobuf: {}", problem)));
+    }
+    let geojson = Decoder::decode(&geobuf).map_err(Error::from_reason)?;
+    serde_json::to_vec(&geojson).map_err(|err| Error::from_reason(err.to_string()))
+}
@@ -0,0 +1,192 @@
+//! Conversion between Geobufs and GeoParquet files
+//!
+//! Features go through the record batches of `geobuf::arrow`, so properties become typed
+//! columns and geometries a WKB `geometry` column. Written files get the GeoParquet `geo`
+//! metadata with the geometry types and bounding box of the features. Reading takes the
+//! geometry column from that metadata, leaves out other geometry columns and bounding box
+//! covering columns, and only supports WKB encoded geometries.
+use std::collections::BTreeSet;
+use std::io::Write;
+use std::sync::Arc;
+
+use arrow_array::RecordBatch;
+use arrow_schema::Schema;
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use parquet::arrow::{ArrowWriter, ProjectionMask};
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
+use parquet::file::reader::ChunkReader;
+use parquet::format::KeyValue;
+use serde_json::Value as JSONValue;
+
+use crate::arrow::{from_record_batches, to_record_batch, EXTENSION_NAME, WKB_EXTENSION};
+use crate::bbox::bbox;
+use crate::geobuf_pb::data::geometry::Type;
+use crate::geobuf_pb::data::Data_type;
+use crate::geobuf_pb::Data;
+
+/// Version of the GeoParquet specification the written metadata follows
+pub const VERSION: &str = "1.1.0";
+
+/// Writes the features of a Geobuf holding a FeatureCollection or Feature as a GeoParquet
+/// file
+///
+/// Geometry types are left out, meaning any type, for Geobufs with 4 dimensions, which
+/// GeoParquet has no names for.
+///
+/// # Example
+///
+/// ```
+/// use std::fs::File;
+///
+/// use geobuf::decode::Decoder;
+/// use geobuf::encode::Encoder;
+///
+/// let geojson = serde_json::from_str(r#"{"type": "FeatureCollection", "features": [
+///     {"type": "Feature", "properties": {"name": "a"}, "geometry": {"type": "Point", "coordinates": [1.0, 2.0]}},
+///     {"type": "Feature", "properties": {"name": "b"}, "geometry": {"type": "Point", "coordinates": [3.0, 4.0]}}
+/// ]}"#).unwrap();
+/// let data = Encoder::encode(&geojson, 6, 2).unwrap();
+///
+/// let path = std::env::temp_dir().join("geobuf-parquet-example.parquet");
+/// geobuf::parquet::write(&data, File::create(&path).unwrap()).unwrap();
+/// let round_trip = geobuf::parquet::read(File::open(&path).unwrap(), 6, 2).unwrap();
+/// assert_eq!(Decoder::decode(&round_trip).unwrap(), geojson);
+/// ```
+pub fn write<W: Write + Send>(data: &Data, writer: W) -> Result<(), String> {
+    let batch = to_record_batch(data)?;
+    let mut column = serde_json::json!({
+        "encoding": "WKB",
+        "geometry_types": geometry_types(data),
+    });
+    if let Some(bbox) = bbox(data) {
+        column["bbox"] = JSONValue::from(bbox.to_vec());
+    }
+    let metadata = serde_json::json!({
+        "version": VERSION,
+        "primary_column": "geometry",
+        "columns": {"geometry": column},
+    });
+
+    let properties = WriterProperties::builder()
+        .set_compression(Compression::SNAPPY)
+        .set_key_value_metadata(Some(vec![KeyValue::new(
+            String::from("geo"),
+            metadata.to_string(),
+        )]))
+        .build();
+    let mut writer = ArrowWriter::try_new(writer, batch.schema(), Some(properties))
+        .map_err(|err| err.to_string())?;
+    writer.write(&batch).map_err(|err| err.to_string())?;
+    writer.close().map_err(|err| err.to_string())?;
+    Ok(())
+}
+
+/// Reads a GeoParquet file into a Geobuf FeatureCollection with `precision` and `dim`
+/// dimensions
+///
+/// Columns are converted as `geobuf::arrow::from_record_batches` does, so an `id` column
+/// gives the feature ids and every other column a property.
+pub fn read<R: ChunkReader + 'static>(reader: R, precision: u32, dim: u32) -> Result<Data, String> {
+    let builder =
+        ParquetRecordBatchReaderBuilder::try_new(reader).map_err(|err| err.to_string())?;
+    let metadata = builder
+        .metadata()
+        .file_metadata()
+        .key_value_metadata()
+        .and_then(|metadata| metadata.iter().find(|key_value| key_value.key == "geo"))
+        .and_then(|key_value| key_value.value.as_deref())
+        .ok_or("Parquet file without GeoParquet metadata.")?;
+    let metadata: JSONValue = serde_json::from_str(metadata)
+        .map_err(|err| format!("Invalid GeoParquet metadata: {}", err))?;
+    let primary = metadata["primary_column"]
+        .as_str()
+        .ok_or("GeoParquet metadata without a primary column.")?;
+    let encoding = &metadata["columns"][primary]["encoding"];
+    if encoding != "WKB" {
+        return Err(format!("Unsupported geometry encoding {}.", encoding));
+    }
+
+    // Other geometry columns and the bounding box columns of geometries aren't properties
+    let mut skipped = BTreeSet::new();
+    if let Some(columns) = metadata["columns"].as_object() {
+        for (name, column) in columns {
+            if name != primary {
+                skipped.insert(name.as_str());
+            }
+            if let Some(covering) = column["covering"]["bbox"]["xmin"][0].as_str() {
+                skipped.insert(covering);
+            }
+        }
+    }
+    let roots = builder
+        .schema()
+        .fields()
+        .iter()
+        .enumerate()
+        .filter(|(_, field)| !skipped.contains(field.name().as_str()))
+        .map(|(index, _)| index)
+        .collect::<Vec<_>>();
+    let projection = ProjectionMask::roots(builder.parquet_schema(), roots);
+
+    let batches = builder
+        .with_projection(projection)
+        .build()
+        .map_err(|err| err.to_string())?
+        .map(|batch| tag_geometry(batch.map_err(|err| err.to_string())?, primary))
+        .collect::<Result<Vec<_>, _>>()?;
+    from_record_batches(&batches, precision, dim)
+}
+
+/// Returns the names of the geometry types of the features of `data`, with a Z suffix for 3
+/// dimensions
+fn geometry_types(data: &Data) -> Vec<String> {
+    let features = match data.data_type.as_ref() {
+        Some(Data_type::FeatureCollection(fc)) => &fc.features[..],
+        Some(Data_type::Feature(feature)) => std::slice::from_ref(feature),
+        _ => &[],
+    };
+    let suffix = match data.dimensions() {
+        0..=2 => "",
+        3 => " Z",
+        _ => return Vec::new(),
+    };
+    let types: BTreeSet<_> = features
+        .iter()
+        .map(|feature| match feature.geometry.type_() {
+            Type::POINT => "Point",
+            Type::MULTIPOINT => "MultiPoint",
+            Type::LINESTRING => "LineString",
+            Type::MULTILINESTRING => "MultiLineString",
+            Type::POLYGON => "Polygon",
+            Type::MULTIPOLYGON => "MultiPolygon",
+            Type::GEOMETRYCOLLECTION => "GeometryCollection",
+        })
+        .collect();
+    types
+        .into_iter()
+        .map(|type_| format!("{}{}", type_, suffix))
+        .collect()
+}
+
+/// Tags the `primary` column of a batch as GeoArrow WKB, since files written by other tools
+/// don't carry Arrow extension types
+fn tag_geometry(batch: RecordBatch, primary: &str) -> Result<RecordBatch, String> {
+    let schema = batch.schema();
+    let fields = schema
+        .fields()
+        .iter()
+        .map(|field| match field.name() == primary {
+            true => {
+                let mut metadata = field.metadata().clone();
+                metadata.insert(String::from(EXTENSION_NAME), String::from(WKB_EXTENSION));
+                Arc::new(field.as_ref().clone().with_metadata(metadata))
+            }
+            false => field.clone(),
+        })
+        .collect::<Vec<_>>();
+    let schema = Schema::new_with_metadata(fields, schema.metadata().clone());
+    batch
+        .with_schema(Arc::new(schema))
+        .map_err(|err| err.to_string())
+}
@@ -0,0 +1,272 @@
+//! Patches turning one FeatureCollection into another
+//!
+//! A patch lists the keys (see `diff::FeatureKey`) of the features to remove and carries the
+//! new version of every added or changed feature in a FeatureCollection, so it is usually
+//! much smaller than the new object itself. Serialized patches are containers (see
+//! `container`) with the `FLAG_PATCH` flag set. Their payload holds a varint count of
+//! removed keys, the keys themselves, a varint count of replaced features and finally the
+//! serialized FeatureCollection. Each key starts with a tag byte: 0 for string ids, followed
+//! by a varint-prefixed UTF-8 string, 1 for integer ids, followed by a zigzag varint, and 2
+//! for content hashes, followed by 8 little endian bytes.
+use std::collections::{HashMap, VecDeque};
+
+use crate::codec;
+use crate::container;
+use crate::diff::{self, FeatureKey};
+use crate::framed::{read_varint, write_varint};
+use crate::geobuf_pb;
+use crate::merge;
+use crate::props;
+use crate::split;
+
+/// Changes turning one FeatureCollection into another, see `make_patch`
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Patch {
+    /// Keys of the features to remove
+    pub removed: Vec<FeatureKey>,
+    /// Number of leading features of `features` replacing the feature with the same id
+    pub replaced: usize,
+    /// FeatureCollection holding the replacing features followed by the added ones
+    pub features: geobuf_pb::Data,
+}
+
+impl Patch {
+    /// Serializes the patch into a patch container
+    pub fn to_bytes(&self) -> Result<Vec<u8>, &'static str> {
+        let mut payload = Vec::new();
+        write_varint(&mut payload, self.removed.len() as u64);
+        for key in &self.removed {
+            match key {
+                FeatureKey::Id(id) => {
+                    payload.push(0);
+                    write_varint(&mut payload, id.len() as u64);
+                    payload.extend_from_slice(id.as_bytes());
+                }
+                FeatureKey::IntId(id) => {
+                    payload.push(1);
+                    write_varint(&mut payload, ((id << 1) ^ (id >> 63)) as u64);
+                }
+                FeatureKey::Hash(hash) => {
+                    payload.push(2);
+                    payload.extend_from_slice(&hash.to_le_bytes());
+                }
+            }
+        }
+        write_varint(&mut payload, self.replaced as u64);
+        match codec::serialize(&self.features) {
+            Ok(msg) => payload.extend_from_slice(&msg),
+            Err(_) => return Err("Could not serialize patch."),
+        }
+        Ok(container::write_container_with_flags(
+            &payload,
+            container::FLAG_PATCH,
+        ))
+    }
+
+    /// Parses a patch container
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, &'static str> {
+        let (flags, mut payload) = container::read_container(bytes)?;
+        if flags & container::FLAG_PATCH == 0 {
+            return Err("Container does not hold a patch.");
+        }
+
+        let mut patch = Patch::default();
+        let count = read_length(&mut payload)?;
+        for _ in 0..count {
+            let (tag, rest) = match payload.split_first() {
+                Some(split) => split,
+                None => return Err("Truncated patch."),
+            };
+            payload = rest;
+            let key = match tag {
+                0 => {
+                    let len = read_length(&mut payload)?;
+                    if payload.len() < len {
+                        return Err("Truncated patch.");
+                    }
+                    let (id, rest) = payload.split_at(len);
+                    payload = rest;
+                    match std::str::from_utf8(id) {
+                        Ok(id) => FeatureKey::Id(String::from(id)),
+                        Err(_) => return Err("Invalid feature id."),
+                    }
+                }
+                1 => {
+                    let id = read_length(&mut payload)? as u64;
+                    FeatureKey::IntId(((id >> 1) as i64) ^ -((id & 1) as i64))
+                }
+                2 => {
+                    if payload.len() < 8 {
+                        return Err("Truncated patch.");
+                    }
+                    let mut hash = [0u8; 8];
+                    hash.copy_from_slice(&payload[..8]);
+                    payload = &payload[8..];
+                    FeatureKey::Hash(u64::from_le_bytes(hash))
+                }
+                _ => return Err("Invalid feature key."),
+            };
+            patch.removed.push(key);
+        }
+        patch.replaced = read_length(&mut payload)?;
+        patch.features = match codec::parse(payload) {
+            Ok(features) => features,
+            Err(_) => return Err("Could not parse patch."),
+        };
+        Ok(patch)
+    }
+
+    /// Returns true if applying the patch changes nothing
+    pub fn is_empty(&self) -> bool {
+        self.removed.is_empty()
+            && diff::features(&self.features)
+                .map(|features| features.is_empty())
+                .unwrap_or(true)
+    }
+}
+
+/// Returns the patch turning `old` into `new`, see `diff`
+///
+/// Changed features are carried whole, with their new geometry and all of their properties.
+///
+/// # Example
+///
+/// ```
+/// use geobuf::encode::Encoder;
+/// use geobuf::patch::Patch;
+///
+/// let old = serde_json::from_str(r#"{"type": "FeatureCollection", "features": [
+///     {"type": "Feature", "id": 1, "properties": {"name": "a"}, "geometry": {"type": "Point", "coordinates": [1.0, 2.0]}},
+///     {"type": "Feature", "id": 2, "properties": {"name": "b"}, "geometry": {"type": "Point", "coordinates": [3.0, 4.0]}}
+/// ]}"#).unwrap();
+/// let new = serde_json::from_str(r#"{"type": "FeatureCollection", "features": [
+///     {"type": "Feature", "id": 2, "properties": {"name": "c"}, "geometry": {"type": "Point", "coordinates": [3.0, 4.0]}},
+///     {"type": "Feature", "id": 3, "properties": {"name": "d"}, "geometry": {"type": "Point", "coordinates": [5.0, 6.0]}}
+/// ]}"#).unwrap();
+/// let mut data = Encoder::encode(&old, 6, 2).unwrap();
+/// let new = Encoder::encode(&new, 6, 2).unwrap();
+///
+/// let bytes = geobuf::make_patch(&data, &new).unwrap().to_bytes().unwrap();
+/// geobuf::apply_patch(&mut data, &Patch::from_bytes(&bytes).unwrap()).unwrap();
+/// assert!(geobuf::diff(&data, &new).unwrap().is_empty());
+/// ```
+pub fn make_patch(old: &geobuf_pb::Data, new: &geobuf_pb::Data) -> Result<Patch, &'static str> {
+    let diff = diff::diff(old, new)?;
+    let old_features = diff::features(old)?;
+    let new_features = diff::features(new)?;
+
+    let removed = diff
+        .removed
+        .iter()
+        .map(|o| FeatureKey::of(old_features[*o], &old.keys, &old.strings))
+        .collect();
+
+    let mut collection = geobuf_pb::data::FeatureCollection::new();
+    for change in &diff.changed {
+        collection.features.push(new_features[change.new].clone());
+    }
+    for n in &diff.added {
+        collection.features.push(new_features[*n].clone());
+    }
+
+    Ok(Patch {
+        removed,
+        replaced: diff.changed.len(),
+        features: split::standalone(new, collection)?,
+    })
+}
+
+/// Applies `patch` to the FeatureCollection `data`
+///
+/// Replacing features keep the position of the feature they replace and added features are
+/// appended. The patch features are rescaled if `data` has a higher precision. Nothing is
+/// changed if the patch doesn't fit `data`, e.g. if a feature to remove or replace is missing
+/// or the patch has a higher precision.
+pub fn apply_patch(data: &mut geobuf_pb::Data, patch: &Patch) -> Result<(), &'static str> {
+    let patch_features = diff::features(&patch.features)?;
+    if patch.replaced > patch_features.len() {
+        return Err("Invalid patch.");
+    }
+    if !patch_features.is_empty() {
+        if patch.features.dimensions() != data.dimensions() {
+            return Err("Patch has different dimensions.");
+        }
+        if patch.features.transform != data.transform {
+            return Err("Patch has a different transform.");
+        }
+        if patch.features.precision() > data.precision() {
+            return Err("Patch has a higher precision.");
+        }
+    }
+    let scale = 10i64.pow(data.precision().saturating_sub(patch.features.precision()));
+
+    let feature_collection = match data.data_type.as_ref() {
+        Some(geobuf_pb::data::Data_type::FeatureCollection(fc)) => fc,
+        _ => return Err("Patches only apply to FeatureCollections."),
+    };
+    let mut unmatched: HashMap<FeatureKey, VecDeque<usize>> = HashMap::new();
+    for (n, feature) in feature_collection.features.iter().enumerate() {
+        unmatched
+            .entry(FeatureKey::of(feature, &data.keys, &data.strings))
+            .or_default()
+            .push_back(n);
+    }
+
+    let mut removed = vec![false; feature_collection.features.len()];
+    for key in &patch.removed {
+        match unmatched.get_mut(key).and_then(VecDeque::pop_front) {
+            Some(n) => removed[n] = true,
+            None => return Err("Feature to remove not found."),
+        }
+    }
+    let mut replaced = Vec::with_capacity(patch.replaced);
+    for feature in &patch_features[..patch.replaced] {
+        let key = FeatureKey::of(feature, &patch.features.keys, &patch.features.strings);
+        if matches!(key, FeatureKey::Hash(_)) {
+            return Err("Replacing feature has no id.");
+        }
+        match unmatched.get_mut(&key).and_then(VecDeque::pop_front) {
+            Some(n) => replaced.push(n),
+            None => return Err("Feature to replace not found."),
+        }
+    }
+
+    // The tables of `data` are only extended once every patch feature fits them
+    let mut keys = data.keys.clone();
+    let mut strings = data.strings.clone();
+    let mapping = props::key_mapping(&patch.features.keys, &mut keys);
+    let string_mapping = props::key_mapping(&patch.features.strings, &mut strings);
+    let mut features: Vec<geobuf_pb::data::Feature> = patch_features.into_iter().cloned().collect();
+    for feature in features.iter_mut() {
+        if merge::remap_feature(feature, &mapping, &string_mapping).is_err() {
+            return Err("Invalid patch.");
+        }
+        if scale != 1 {
+            if let Some(geometry) = feature.geometry.as_mut() {
+                merge::rescale_geometry(geometry, scale);
+            }
+        }
+    }
+    data.keys = keys;
+    data.strings = strings;
+
+    let feature_collection = data.mut_feature_collection();
+    let mut added = features.split_off(patch.replaced);
+    for (n, feature) in replaced.into_iter().zip(features) {
+        feature_collection.features[n] = feature;
+    }
+    let mut n = 0;
+    feature_collection.features.retain(|_| {
+        n += 1;
+        !removed[n - 1]
+    });
+    feature_collection.features.append(&mut added);
+    Ok(())
+}
+
+fn read_length(payload: &mut &[u8]) -> Result<usize, &'static str> {
+    match read_varint(payload)? {
+        Some(len) => Ok(len as usize),
+        None => Err("Truncated patch."),
+    }
+}
@@ -0,0 +1,59 @@
+//! Walking the geometries and positions of GeoJSON objects
+use serde_json::Value as JSONValue;
+
+/// Calls `f` with the coordinates of every geometry of a GeoJSON object, going through the
+/// features of collections and the members of geometry collections
+///
+/// `bbox` members are removed on the way as changed coordinates would make them stale.
+pub fn for_each_geometry_mut<F>(geojson: &mut JSONValue, f: &mut F) -> Result<(), &'static str>
+where
+    F: FnMut(&str, &mut JSONValue) -> Result<(), &'static str>,
+{
+    if let Some(object) = geojson.as_object_mut() {
+        object.remove("bbox");
+    }
+    match geojson["type"].as_str() {
+        Some("FeatureCollection") => match geojson["features"].as_array_mut() {
+            Some(features) => features
+                .iter_mut()
+                .try_for_each(|feature| for_each_geometry_mut(feature, f)),
+            None => Err("FeatureCollection without features."),
+        },
+        Some("Feature") if geojson["geometry"].is_null() => Ok(()),
+        Some("Feature") => for_each_geometry_mut(&mut geojson["geometry"], f),
+        Some("GeometryCollection") => match geojson["geometries"].as_array_mut() {
+            Some(geometries) => geometries
+                .iter_mut()
+                .try_for_each(|geometry| for_each_geometry_mut(geometry, f)),
+            None => Err("GeometryCollection without geometries."),
+        },
+        Some(geometry_type) => {
+            let geometry_type = String::from(geometry_type);
+            f(&geometry_type, &mut geojson["coordinates"])
+        }
+        None => Err("Missing GeoJSON type."),
+    }
+}
+
+/// Calls `f` with every position of a GeoJSON object, see `for_each_geometry_mut`
+pub fn for_each_position_mut<F>(geojson: &mut JSONValue, f: &mut F) -> Result<(), &'static str>
+where
+    F: FnMut(&mut Vec<JSONValue>) -> Result<(), &'static str>,
+{
+    for_each_geometry_mut(geojson, &mut |_, coordinates| {
+        for_each_nested_position(coordinates, f)
+    })
+}
+
+fn for_each_nested_position<F>(coordinates: &mut JSONValue, f: &mut F) -> Result<(), &'static str>
+where
+    F: FnMut(&mut Vec<JSONValue>) -> Result<(), &'static str>,
+{
+    match coordinates.as_array_mut() {
+        Some(position) if matches!(position.first(), Some(JSONValue::Number(_))) => f(position),
+        Some(items) => items
+            .iter_mut()
+            .try_for_each(|item| for_each_nested_position(item, f)),
+        None => Err("Invalid coordinates."),
+    }
+}
@@ -0,0 +1,217 @@
+//! Helpers for reading and writing key/value property tables
+use serde_json::Value as JSONValue;
+
+use crate::geobuf_pb;
+
+/// Returns the index of `key` in `keys`, appending it if missing
+pub(crate) fn key_index(keys: &mut Vec<String>, key: &str) -> u32 {
+    match keys.iter().position(|k| k == key) {
+        Some(index) => index as u32,
+        None => {
+            keys.push(String::from(key));
+            keys.len() as u32 - 1
+        }
+    }
+}
+
+/// Data keys, values and custom property table of the root object, and the other property
+/// table indexing into the same values
+pub(crate) type RootTables<'a> = (
+    &'a mut Vec<String>,
+    &'a mut Vec<geobuf_pb::data::Value>,
+    &'a mut Vec<u32>,
+    &'a mut [u32],
+);
+
+/// Returns the tables of the root object
+pub(crate) fn root_tables_mut(data: &mut geobuf_pb::Data) -> Option<RootTables<'_>> {
+    let keys = &mut data.keys;
+    match data.data_type.as_mut()? {
+        geobuf_pb::data::Data_type::FeatureCollection(fc) => {
+            Some((keys, &mut fc.values, &mut fc.custom_properties, &mut []))
+        }
+        geobuf_pb::data::Data_type::Feature(f) => Some((
+            keys,
+            &mut f.values,
+            &mut f.custom_properties,
+            &mut f.properties,
+        )),
+        geobuf_pb::data::Data_type::Geometry(g) => Some((
+            keys,
+            &mut g.values,
+            &mut g.custom_properties,
+            &mut g.properties,
+        )),
+        geobuf_pb::data::Data_type::Topology(t) => {
+            Some((keys, &mut t.values, &mut t.custom_properties, &mut []))
+        }
+    }
+}
+
+/// Returns the custom property table of the root object
+pub(crate) fn root_tables(data: &geobuf_pb::Data) -> Option<(&[geobuf_pb::data::Value], &[u32])> {
+    match data.data_type.as_ref()? {
+        geobuf_pb::data::Data_type::FeatureCollection(fc) => {
+            Some((&fc.values, &fc.custom_properties))
+        }
+        geobuf_pb::data::Data_type::Feature(f) => Some((&f.values, &f.custom_properties)),
+        geobuf_pb::data::Data_type::Geometry(g) => Some((&g.values, &g.custom_properties)),
+        geobuf_pb::data::Data_type::Topology(t) => Some((&t.values, &t.custom_properties)),
+    }
+}
+
+/// Returns the value stored for `key` in a property table
+pub(crate) fn get<'a>(
+    keys: &[String],
+    values: &'a [geobuf_pb::data::Value],
+    properties: &[u32],
+    key: &str,
+) -> Option<&'a geobuf_pb::data::Value> {
+    properties
+        .chunks(2)
+        .find(|pair| pair.len() == 2 && keys.get(pair[0] as usize).map(|k| k == key) == Some(true))
+        .and_then(|pair| values.get(pair[1] as usize))
+}
+
+/// Sets `key` to `value` in a property table, replacing any existing value
+///
+/// `other` is the other property table indexing into `values`, see `remove`.
+pub(crate) fn set(
+    keys: &mut Vec<String>,
+    values: &mut Vec<geobuf_pb::data::Value>,
+    properties: &mut Vec<u32>,
+    other: &mut [u32],
+    key: &str,
+    value: geobuf_pb::data::Value,
+) {
+    remove(keys, values, properties, other, key);
+    let key_index = key_index(keys, key);
+    values.push(value);
+    properties.push(key_index);
+    properties.push(values.len() as u32 - 1);
+}
+
+/// Removes `key` from a property table, returning true if it was present
+///
+/// Features and geometries keep their properties and custom properties in two tables
+/// indexing into the same values, so `other` is the table that isn't edited. Values that
+/// neither table refers to any more are dropped and the value indexes of both are shifted
+/// accordingly.
+pub(crate) fn remove(
+    keys: &[String],
+    values: &mut Vec<geobuf_pb::data::Value>,
+    properties: &mut Vec<u32>,
+    other: &mut [u32],
+    key: &str,
+) -> bool {
+    let key_index = match keys.iter().position(|k| k == key) {
+        Some(index) => index as u32,
+        None => return false,
+    };
+    let pair = match properties.chunks(2).position(|pair| pair[0] == key_index) {
+        Some(pair) => pair,
+        None => return false,
+    };
+    let value_index = properties[pair * 2 + 1];
+    properties.drain(pair * 2..pair * 2 + 2);
+
+    let refers = |table: &[u32]| {
+        table
+            .chunks(2)
+            .any(|pair| pair.len() == 2 && pair[1] == value_index)
+    };
+    if !refers(properties) && !refers(other) && (value_index as usize) < values.len() {
+        values.remove(value_index as usize);
+        for table in [&mut properties[..], other] {
+            for i in (1..table.len()).step_by(2) {
+                if table[i] > value_index {
+                    table[i] -= 1;
+                }
+            }
+        }
+    }
+    true
+}
+
+/// Converts a protobuf value into its JSON equivalent, resolving shared string references
+/// against `strings`
+pub(crate) fn value_to_json(value: &geobuf_pb::data::Value, strings: &[String]) -> JSONValue {
+    match value.value_type.as_ref() {
+        Some(geobuf_pb::data::value::Value_type::StringValue(v)) => serde_json::json!(v),
+        Some(geobuf_pb::data::value::Value_type::DoubleValue(v)) => serde_json::json!(v),
+        Some(geobuf_pb::data::value::Value_type::PosIntValue(v)) => serde_json::json!(v),
+        Some(geobuf_pb::data::value::Value_type::NegIntValue(v)) => {
+            serde_json::json!(-(*v as i64))
+        }
+        Some(geobuf_pb::data::value::Value_type::BoolValue(v)) => serde_json::json!(v),
+        Some(geobuf_pb::data::value::Value_type::JsonValue(v)) => {
+            serde_json::from_str(v).unwrap_or(JSONValue::Null)
+        }
+        Some(geobuf_pb::data::value::Value_type::StringRef(v)) => match strings.get(*v as usize) {
+            Some(v) => serde_json::json!(v),
+            None => JSONValue::Null,
+        },
+        None => JSONValue::Null,
+    }
+}
+
+/// Converts a JSON value into a protobuf value, returning `None` for `null`
+pub(crate) fn json_to_value(json: &JSONValue) -> Option<geobuf_pb::data::Value> {
+    let mut value = geobuf_pb::data::Value::new();
+    match json {
+        JSONValue::String(v) => value.set_string_value(v.clone()),
+        JSONValue::Bool(v) => value.set_bool_value(*v),
+        JSONValue::Number(v) => {
+            if let Some(v) = v.as_u64() {
+                value.set_pos_int_value(v);
+            } else if let Some(v) = v.as_i64() {
+                value.set_neg_int_value(v.unsigned_abs());
+            } else if let Some(v) = v.as_f64() {
+                value.set_double_value(v);
+            }
+        }
+        JSONValue::Object(_) | JSONValue::Array(_) => value.set_json_value(json.to_string()),
+        JSONValue::Null => return None,
+    }
+    Some(value)
+}
+
+/// Returns a table mapping each index of `src_keys` to its index in `dst_keys`, appending
+/// missing keys to `dst_keys`
+pub(crate) fn key_mapping(src_keys: &[String], dst_keys: &mut Vec<String>) -> Vec<u32> {
+    src_keys
+        .iter()
+        .map(|key| key_index(dst_keys, key))
+        .collect()
+}
+
+/// Rewrites the key indexes of a feature, including its geometry, using `map_key`
+pub(crate) fn remap_feature<F: FnMut(u32) -> u32>(
+    feature: &mut geobuf_pb::data::Feature,
+    map_key: &mut F,
+) {
+    remap_pairs(&mut feature.properties, map_key);
+    remap_pairs(&mut feature.custom_properties, map_key);
+    if let Some(geometry) = feature.geometry.as_mut() {
+        remap_geometry(geometry, map_key);
+    }
+}
+
+/// Rewrites the key indexes of a geometry and its child geometries using `map_key`
+pub(crate) fn remap_geometry<F: FnMut(u32) -> u32>(
+    geometry: &mut geobuf_pb::data::Geometry,
+    map_key: &mut F,
+) {
+    remap_pairs(&mut geometry.properties, map_key);
+    remap_pairs(&mut geometry.custom_properties, map_key);
+    for child in geometry.geometries.iter_mut() {
+        remap_geometry(child, map_key);
+    }
+}
+
+/// Rewrites the key indexes of a property table using `map_key`
+pub(crate) fn remap_pairs<F: FnMut(u32) -> u32>(properties: &mut [u32], map_key: &mut F) {
+    for i in (0..properties.len()).step_by(2) {
+        properties[i] = map_key(properties[i]);
+    }
+}
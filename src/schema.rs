@@ -0,0 +1,48 @@
+//! The protobuf schema this crate is built from
+//!
+//! `SCHEMA` holds the exact `.proto` source the types in `geobuf_pb` were generated from, so
+//! code for other languages can be generated from the same definitions. The generated message
+//! types are re-exported here under shorter names.
+pub use crate::geobuf_pb::data::geometry::Type as GeometryType;
+pub use crate::geobuf_pb::data::{
+    Feature, FeatureCollection, Geometry, Topology, Transform, Value,
+};
+pub use crate::geobuf_pb::Data;
+
+/// Source of `protos/geobuf.proto`
+///
+/// # Example
+///
+/// ```
+/// use geobuf::schema::{self, Data, FeatureCollection};
+///
+/// assert!(schema::SCHEMA.contains("message Data {"));
+///
+/// let mut data = Data::new();
+/// data.set_feature_collection(FeatureCollection::new());
+/// let bytes = schema::serialize(&data).unwrap();
+/// assert_eq!(schema::parse(&bytes).unwrap(), data);
+/// ```
+pub const SCHEMA: &str = include_str!("../protos/geobuf.proto");
+
+/// Version of the Geobuf format `SCHEMA` implements
+///
+/// This is version 3 of the format, whose readers skip the shared strings and transforms
+/// this crate adds to it.
+pub const VERSION: u32 = 3;
+
+/// Parses a serialized `Data` message
+pub fn parse(bytes: &[u8]) -> Result<Data, &'static str> {
+    match crate::codec::parse(bytes) {
+        Ok(data) => Ok(data),
+        Err(_) => Err("Could not parse geobuf data."),
+    }
+}
+
+/// Serializes a `Data` message
+pub fn serialize(data: &Data) -> Result<Vec<u8>, &'static str> {
+    match crate::codec::serialize(data) {
+        Ok(bytes) => Ok(bytes),
+        Err(_) => Err("Could not serialize geobuf data."),
+    }
+}
@@ -0,0 +1,122 @@
+//! Douglas–Peucker simplification of GeoJSON geometries
+use serde_json::Value as JSONValue;
+
+use crate::positions;
+
+/// Simplifies every line and ring of `geojson` with the Douglas–Peucker algorithm, dropping
+/// positions closer than `tolerance` to the simplified line in x and y
+///
+/// Rings that would be left with fewer than 4 positions are kept as they are. Returns the
+/// number of positions before and after.
+///
+/// # Example
+///
+/// ```
+/// use geobuf::simplify::simplify;
+///
+/// let mut geojson = serde_json::json!({"type": "LineString", "coordinates": [[0.0, 0.0], [1.0, 0.1], [2.0, 0.0]]});
+/// assert_eq!(simplify(&mut geojson, 0.5), Ok((3, 2)));
+/// assert_eq!(geojson["coordinates"], serde_json::json!([[0.0, 0.0], [2.0, 0.0]]));
+/// ```
+pub fn simplify(geojson: &mut JSONValue, tolerance: f64) -> Result<(usize, usize), &'static str> {
+    let mut before = 0;
+    let mut after = 0;
+    positions::for_each_geometry_mut(geojson, &mut |geometry_type, coordinates| {
+        let (lines, min_len) = match geometry_type {
+            "Point" => {
+                before += 1;
+                after += 1;
+                return Ok(());
+            }
+            "MultiPoint" => {
+                let len = coordinates.as_array().map_or(0, Vec::len);
+                before += len;
+                after += len;
+                return Ok(());
+            }
+            "LineString" => (vec![coordinates], 2),
+            "MultiLineString" => (nested_mut(coordinates)?, 2),
+            "Polygon" => (nested_mut(coordinates)?, 4),
+            "MultiPolygon" => {
+                let mut rings = Vec::new();
+                for polygon in nested_mut(coordinates)? {
+                    rings.extend(nested_mut(polygon)?);
+                }
+                (rings, 4)
+            }
+            _ => return Err("Unknown geometry type."),
+        };
+        for line in lines {
+            let positions = line.as_array_mut().ok_or("Invalid coordinates.")?;
+            before += positions.len();
+            let keep = douglas_peucker(positions, tolerance)?;
+            if keep.iter().filter(|keep| **keep).count() >= min_len {
+                let mut keep = keep.into_iter();
+                positions.retain(|_| keep.next().unwrap());
+            }
+            after += positions.len();
+        }
+        Ok(())
+    })?;
+    Ok((before, after))
+}
+
+fn nested_mut(coordinates: &mut JSONValue) -> Result<Vec<&mut JSONValue>, &'static str> {
+    match coordinates.as_array_mut() {
+        Some(items) => Ok(items.iter_mut().collect()),
+        None => Err("Invalid coordinates."),
+    }
+}
+
+/// Returns which positions of a line the Douglas–Peucker algorithm keeps, always keeping the
+/// first and last ones
+fn douglas_peucker(positions: &[JSONValue], tolerance: f64) -> Result<Vec<bool>, &'static str> {
+    let points = positions
+        .iter()
+        .map(
+            |position| match (position[0].as_f64(), position[1].as_f64()) {
+                (Some(x), Some(y)) => Ok([x, y]),
+                _ => Err("Invalid position."),
+            },
+        )
+        .collect::<Result<Vec<[f64; 2]>, &'static str>>()?;
+    if points.len() < 3 {
+        return Ok(vec![true; points.len()]);
+    }
+    let mut keep = vec![false; points.len()];
+    keep[0] = true;
+    keep[points.len() - 1] = true;
+    // Ranges of points still to simplify, avoiding recursion on long lines
+    let mut ranges = vec![(0, points.len() - 1)];
+    while let Some((first, last)) = ranges.pop() {
+        let mut farthest = first;
+        let mut max_distance = 0.0;
+        for n in first + 1..last {
+            let distance = segment_distance(points[n], points[first], points[last]);
+            if distance > max_distance {
+                farthest = n;
+                max_distance = distance;
+            }
+        }
+        if max_distance > tolerance {
+            keep[farthest] = true;
+            ranges.push((first, farthest));
+            ranges.push((farthest, last));
+        }
+    }
+    Ok(keep)
+}
+
+/// Returns the distance from `point` to the segment from `a` to `b`
+fn segment_distance(point: [f64; 2], a: [f64; 2], b: [f64; 2]) -> f64 {
+    let delta = [b[0] - a[0], b[1] - a[1]];
+    let len_squared = delta[0] * delta[0] + delta[1] * delta[1];
+    let t = if len_squared == 0.0 {
+        0.0
+    } else {
+        (((point[0] - a[0]) * delta[0] + (point[1] - a[1]) * delta[1]) / len_squared)
+            .clamp(0.0, 1.0)
+    };
+    let closest = [a[0] + t * delta[0], a[1] + t * delta[1]];
+    ((point[0] - closest[0]).powi(2) + (point[1] - closest[1]).powi(2)).sqrt()
+}
@@ -0,0 +1,240 @@
+//! Splitting a FeatureCollection into self-contained chunks
+
+use crate::codec;
+use crate::diff;
+use crate::geobuf_pb;
+use crate::merge;
+use crate::props;
+use crate::strings;
+
+/// Returns FeatureCollections of at most `chunk_size` features each
+///
+/// Every chunk is a standalone Geobuf with the same precision, dimensions and collection
+/// custom properties as `data`, and key and shared string tables trimmed down to the entries
+/// its features use.
+/// Anything other than a FeatureCollection is returned as a single chunk. Fails if a key
+/// index or string reference of `data` is out of range.
+///
+/// # Panics
+///
+/// Panics if `chunk_size` is 0.
+///
+/// # Example
+///
+/// ```
+/// use geobuf::encode::Encoder;
+///
+/// let geojson = serde_json::from_str(r#"{"type": "FeatureCollection", "features": [
+///     {"type": "Feature", "properties": {"a": 1}, "geometry": {"type": "Point", "coordinates": [1.0, 2.0]}},
+///     {"type": "Feature", "properties": {"b": 2}, "geometry": {"type": "Point", "coordinates": [3.0, 4.0]}},
+///     {"type": "Feature", "properties": {"b": 3}, "geometry": {"type": "Point", "coordinates": [5.0, 6.0]}}
+/// ]}"#).unwrap();
+/// let data = Encoder::encode(&geojson, 6, 2).unwrap();
+///
+/// let chunks = geobuf::split(&data, 2).unwrap();
+/// assert_eq!(chunks.len(), 2);
+/// assert_eq!(chunks[0].keys, vec!["a", "b"]);
+/// assert_eq!(chunks[1].keys, vec!["b"]);
+/// assert_eq!(chunks[1].feature_collection().features.len(), 1);
+/// ```
+pub fn split(
+    data: &geobuf_pb::Data,
+    chunk_size: usize,
+) -> Result<Vec<geobuf_pb::Data>, &'static str> {
+    assert!(chunk_size != 0, "chunk size must be non-zero");
+    let feature_collection = match data.data_type.as_ref() {
+        Some(geobuf_pb::data::Data_type::FeatureCollection(fc)) => fc,
+        _ => return Ok(vec![data.clone()]),
+    };
+
+    feature_collection
+        .features
+        .chunks(chunk_size)
+        .map(|features| chunk(data, feature_collection, features))
+        .collect()
+}
+
+/// Returns FeatureCollections whose serialized size is at most `max_bytes` each
+///
+/// Chunks are built like in `split`, holding as many consecutive features as fit. A feature
+/// too large to fit on its own still gets a chunk of its own, which then exceeds
+/// `max_bytes`. Anything other than a FeatureCollection is returned as a single chunk, and
+/// out of range indexes fail like in `split`.
+///
+/// # Example
+///
+/// ```
+/// use geobuf::encode::Encoder;
+///
+/// let file = std::fs::File::open("fixtures/us-states.json").unwrap();
+/// let geojson = serde_json::from_reader(file).unwrap();
+/// let data = Encoder::encode(&geojson, 6, 2).unwrap();
+///
+/// let chunks = geobuf::split_by_size(&data, 4096).unwrap();
+/// assert!(chunks.len() > 6);
+/// ```
+pub fn split_by_size(
+    data: &geobuf_pb::Data,
+    max_bytes: usize,
+) -> Result<Vec<geobuf_pb::Data>, &'static str> {
+    let feature_collection = match data.data_type.as_ref() {
+        Some(geobuf_pb::data::Data_type::FeatureCollection(fc)) => fc,
+        _ => return Ok(vec![data.clone()]),
+    };
+    let features = &feature_collection.features;
+    let fits = |start: usize, end: usize| -> Result<bool, &'static str> {
+        let chunk = chunk(data, feature_collection, &features[start..end])?;
+        Ok(codec::size(&chunk) as usize <= max_bytes)
+    };
+
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    while start < features.len() {
+        // Gallop to a chunk that doesn't fit, then bisect for the largest one that does
+        let mut fitting = start + 1;
+        let mut step = 1;
+        let mut too_large = loop {
+            let end = (fitting + step).min(features.len());
+            if end == fitting {
+                break features.len() + 1;
+            }
+            if !fits(start, end)? {
+                break end;
+            }
+            fitting = end;
+            step *= 2;
+        };
+        while too_large - fitting > 1 {
+            let middle = fitting + (too_large - fitting) / 2;
+            if fits(start, middle)? {
+                fitting = middle;
+            } else {
+                too_large = middle;
+            }
+        }
+        chunks.push(chunk(data, feature_collection, &features[start..fitting])?);
+        start = fitting;
+    }
+    Ok(chunks)
+}
+
+/// Returns a FeatureCollection of the features at `indexes`, in that order
+///
+/// The result is a standalone Geobuf like the chunks of `split`. Indexes past the last
+/// feature are skipped, and a Feature counts as a collection of one. Out of range indexes in
+/// the selected features fail like in `split`.
+///
+/// # Example
+///
+/// ```
+/// use geobuf::encode::Encoder;
+///
+/// let geojson = serde_json::from_str(r#"{"type": "FeatureCollection", "features": [
+///     {"type": "Feature", "properties": {"a": 1}, "geometry": {"type": "Point", "coordinates": [1.0, 2.0]}},
+///     {"type": "Feature", "properties": {"b": 2}, "geometry": {"type": "Point", "coordinates": [3.0, 4.0]}},
+///     {"type": "Feature", "properties": {"b": 3}, "geometry": {"type": "Point", "coordinates": [5.0, 6.0]}}
+/// ]}"#).unwrap();
+/// let data = Encoder::encode(&geojson, 6, 2).unwrap();
+///
+/// let selected = geobuf::select(&data, &[2, 1, 7]).unwrap();
+/// assert_eq!(selected.keys, vec!["b"]);
+/// assert_eq!(selected.feature_collection().features.len(), 2);
+/// assert_eq!(selected.get_property(0, "b"), Some(serde_json::json!(3)));
+/// ```
+pub fn select(data: &geobuf_pb::Data, indexes: &[usize]) -> Result<geobuf_pb::Data, &'static str> {
+    let features = diff::features(data)?;
+    let mut collection = geobuf_pb::data::FeatureCollection::new();
+    if let Some(geobuf_pb::data::Data_type::FeatureCollection(fc)) = data.data_type.as_ref() {
+        collection.values = fc.values.clone();
+        collection.custom_properties = fc.custom_properties.clone();
+    }
+    collection.features = indexes
+        .iter()
+        .filter_map(|n| features.get(*n))
+        .map(|feature| (*feature).clone())
+        .collect();
+    standalone(data, collection)
+}
+
+fn chunk(
+    data: &geobuf_pb::Data,
+    feature_collection: &geobuf_pb::data::FeatureCollection,
+    features: &[geobuf_pb::data::Feature],
+) -> Result<geobuf_pb::Data, &'static str> {
+    let mut chunk_collection = geobuf_pb::data::FeatureCollection::new();
+    chunk_collection.values = feature_collection.values.clone();
+    chunk_collection.custom_properties = feature_collection.custom_properties.clone();
+    chunk_collection.features = features.to_vec();
+    standalone(data, chunk_collection)
+}
+
+/// Returns a Data holding `collection`, whose key indexes and string references point into
+/// the tables of `data`, with the header of `data` and the tables trimmed down to the used
+/// entries
+///
+/// Fails if a key index or string reference is out of range, or a property table of a
+/// feature has an odd length.
+pub(crate) fn standalone(
+    data: &geobuf_pb::Data,
+    mut collection: geobuf_pb::data::FeatureCollection,
+) -> Result<geobuf_pb::Data, &'static str> {
+    if !collection.custom_properties.len().is_multiple_of(2)
+        || !collection.features.iter().all(merge::paired_feature)
+    {
+        return Err("Odd-length property table.");
+    }
+
+    let mut chunk = geobuf_pb::Data::new();
+    chunk.set_precision(data.precision());
+    chunk.set_dimensions(data.dimensions());
+    chunk.transform = data.transform.clone();
+
+    let mut mapping: Vec<Option<u32>> = vec![None; data.keys.len()];
+    let mut invalid_key = false;
+    let mut map_key = |key: u32| match mapping.get_mut(key as usize) {
+        Some(Some(new_key)) => *new_key,
+        Some(mapped) => {
+            chunk.keys.push(data.keys[key as usize].clone());
+            let new_key = chunk.keys.len() as u32 - 1;
+            *mapped = Some(new_key);
+            new_key
+        }
+        None => {
+            invalid_key = true;
+            key
+        }
+    };
+    props::remap_pairs(&mut collection.custom_properties, &mut map_key);
+    for feature in collection.features.iter_mut() {
+        props::remap_feature(feature, &mut map_key);
+    }
+    if invalid_key {
+        return Err("Invalid key index.");
+    }
+
+    let mut string_mapping: Vec<Option<u32>> = vec![None; data.strings.len()];
+    let mut invalid_ref = false;
+    let mut map_ref = |index: u32| match string_mapping.get_mut(index as usize) {
+        Some(Some(new_index)) => *new_index,
+        Some(mapped) => {
+            chunk.strings.push(data.strings[index as usize].clone());
+            let new_index = chunk.strings.len() as u32 - 1;
+            *mapped = Some(new_index);
+            new_index
+        }
+        None => {
+            invalid_ref = true;
+            index
+        }
+    };
+    strings::remap_refs(&mut collection.values, &mut map_ref);
+    for feature in collection.features.iter_mut() {
+        strings::remap_feature_refs(feature, &mut map_ref);
+    }
+    if invalid_ref {
+        return Err("Invalid string index.");
+    }
+
+    chunk.set_feature_collection(collection);
+    Ok(chunk)
+}
@@ -0,0 +1,300 @@
+//! Incremental decoding of Geobuf messages and framed streams as their bytes arrive
+//!
+//! Features are decoded as soon as their bytes are complete, e.g. while a file is being
+//! downloaded. Shared strings and transforms are stored after the features of a
+//! FeatureCollection, so features referring to shared strings are held back until the end
+//! of the message, and a transform coming after decoded features is an error. Frames of a
+//! framed stream (see `framed`) are self-contained, so their features are decoded as soon
+//! as each frame is complete.
+use serde_json::Value as JSONValue;
+
+use crate::codec;
+use crate::decode::Decoder;
+use crate::geobuf_pb;
+use crate::validate::validate;
+
+/// Wire type of length-delimited fields: messages, strings and packed arrays
+const LEN: u8 = 2;
+
+/// Decodes the features of a Geobuf message pushed in chunks
+///
+/// # Example
+///
+/// ```
+/// use geobuf::codec;
+/// use geobuf::encode::Encoder;
+/// use geobuf::stream::StreamDecoder;
+///
+/// let geojson = serde_json::from_str(r#"{"type": "FeatureCollection", "features": [
+///     {"type": "Feature", "properties": {"a": 1}, "geometry": {"type": "Point", "coordinates": [1.0, 2.0]}},
+///     {"type": "Feature", "properties": {"a": 2}, "geometry": {"type": "Point", "coordinates": [3.0, 4.0]}}
+/// ]}"#).unwrap();
+/// let bytes = codec::write(&Encoder::encode(&geojson, 6, 2).unwrap());
+///
+/// let mut decoder = StreamDecoder::new();
+/// let mut features = Vec::new();
+/// for chunk in bytes.chunks(8) {
+///     features.extend(decoder.push(chunk).unwrap());
+/// }
+/// features.extend(decoder.finish().unwrap());
+/// assert_eq!(features, geojson["features"].as_array().unwrap().clone());
+/// ```
+#[derive(Default)]
+pub struct StreamDecoder {
+    /// Bytes received but not read yet
+    buffer: Vec<u8>,
+    /// Fields of the Data message other than the FeatureCollection
+    header: Vec<u8>,
+    /// Number of bytes of the FeatureCollection left to read, once it has started
+    collection: Option<usize>,
+    /// Features referring to shared strings
+    held: Vec<geobuf_pb::data::Feature>,
+    /// Whether features were decoded before any transform was read
+    untransformed: bool,
+}
+
+/// Key and length of a field
+struct Field {
+    number: u64,
+    wire_type: u8,
+    /// Offset of the value from the start of the field
+    start: usize,
+    len: usize,
+}
+
+impl StreamDecoder {
+    pub fn new() -> Self {
+        StreamDecoder::default()
+    }
+
+    /// Adds the next bytes of the message and returns the features completed by them
+    pub fn push(&mut self, bytes: &[u8]) -> Result<Vec<JSONValue>, &'static str> {
+        self.buffer.extend_from_slice(bytes);
+        let mut features = Vec::new();
+        let mut offset = 0;
+        loop {
+            if self.collection == Some(0) {
+                self.collection = None;
+            }
+            let rest = &self.buffer[offset..];
+            let field = match read_field(rest)? {
+                Some(field) => field,
+                None => break,
+            };
+            if self.collection.is_none() && (field.number, field.wire_type) == (4, LEN) {
+                // Features are read one at a time from the FeatureCollection
+                self.collection = Some(field.len);
+                offset += field.start;
+                continue;
+            }
+            let end = field.start + field.len;
+            if rest.len() < end {
+                break;
+            }
+            match self.collection.as_mut() {
+                Some(remaining) => {
+                    if end > *remaining {
+                        return Err("Invalid feature collection.");
+                    }
+                    *remaining -= end;
+                    // Properties of the collection itself are left out
+                    if (field.number, field.wire_type) == (1, LEN) {
+                        let feature = codec::parse_feature(&rest[field.start..end])
+                            .map_err(|_| "Could not parse feature.")?;
+                        if uses_strings(&feature) {
+                            self.held.push(feature);
+                        } else {
+                            features.push(feature);
+                        }
+                    }
+                }
+                None => self.header.extend_from_slice(&rest[..end]),
+            }
+            offset += end;
+        }
+        self.buffer.drain(..offset);
+        if features.is_empty() {
+            return Ok(Vec::new());
+        }
+        let data = self.header()?;
+        self.untransformed |= data.transform.is_none();
+        decode(data, features)
+    }
+
+    /// Ends the message and returns the features left: the features referring to shared
+    /// strings, or the Feature, Geometry or Topology of messages without FeatureCollection
+    pub fn finish(self) -> Result<Vec<JSONValue>, &'static str> {
+        if !self.buffer.is_empty() || matches!(self.collection, Some(remaining) if remaining > 0) {
+            return Err("Truncated geobuf.");
+        }
+        let data = self.header()?;
+        if data.transform.is_some() && self.untransformed {
+            return Err("Transform found after decoded features.");
+        }
+        if data.data_type.is_some() {
+            if !validate(&data).is_empty() {
+                return Err("Invalid geobuf.");
+            }
+            return Ok(vec![Decoder::decode(&data)?]);
+        }
+        decode(data, self.held)
+    }
+
+    fn header(&self) -> Result<geobuf_pb::Data, &'static str> {
+        codec::parse(&self.header)
+    }
+}
+
+/// Decodes the features of a framed stream pushed in chunks
+///
+/// FeatureCollection frames contribute all of their features, and other frames their
+/// decoded Feature, Geometry or Topology.
+///
+/// # Example
+///
+/// ```
+/// use geobuf::framed::FramedWriter;
+/// use geobuf::stream::FramedStreamDecoder;
+///
+/// let geojson = serde_json::from_str(r#"{"type": "FeatureCollection", "features": [
+///     {"type": "Feature", "properties": {"a": 1}, "geometry": {"type": "Point", "coordinates": [1.0, 2.0]}},
+///     {"type": "Feature", "properties": {"b": 2}, "geometry": {"type": "Point", "coordinates": [3.0, 4.0]}}
+/// ]}"#).unwrap();
+/// let mut writer = FramedWriter::new(Vec::new());
+/// writer.write_geojson(&geojson, 6, 2).unwrap();
+/// let bytes = writer.into_inner();
+///
+/// let mut decoder = FramedStreamDecoder::new();
+/// let mut features = Vec::new();
+/// for chunk in bytes.chunks(8) {
+///     features.extend(decoder.push(chunk).unwrap());
+/// }
+/// decoder.finish().unwrap();
+/// assert_eq!(features, geojson["features"].as_array().unwrap().clone());
+/// ```
+#[derive(Default)]
+pub struct FramedStreamDecoder {
+    /// Bytes of the frame being received
+    buffer: Vec<u8>,
+}
+
+impl FramedStreamDecoder {
+    pub fn new() -> Self {
+        FramedStreamDecoder::default()
+    }
+
+    /// Adds the next bytes of the stream and returns the features of the frames completed by
+    /// them
+    pub fn push(&mut self, bytes: &[u8]) -> Result<Vec<JSONValue>, &'static str> {
+        self.buffer.extend_from_slice(bytes);
+        let mut features = Vec::new();
+        let mut offset = 0;
+        while let Some((len, len_len)) = read_varint(&self.buffer[offset..])? {
+            let start = offset + len_len;
+            let end = start.saturating_add(len as usize);
+            if self.buffer.len() < end {
+                break;
+            }
+            let data =
+                codec::parse(&self.buffer[start..end]).map_err(|_| "Could not parse frame.")?;
+            if !validate(&data).is_empty() {
+                return Err("Invalid frame.");
+            }
+            match Decoder::decode(&data)? {
+                JSONValue::Object(mut object) if object["type"] == "FeatureCollection" => {
+                    if let Some(JSONValue::Array(frame)) = object.remove("features") {
+                        features.extend(frame);
+                    }
+                }
+                decoded => features.push(decoded),
+            }
+            offset = end;
+        }
+        self.buffer.drain(..offset);
+        Ok(features)
+    }
+
+    /// Ends the stream
+    ///
+    /// Returns an error if the last frame is incomplete.
+    pub fn finish(self) -> Result<(), &'static str> {
+        if !self.buffer.is_empty() {
+            return Err("Truncated frame.");
+        }
+        Ok(())
+    }
+}
+
+/// Decodes `features` with the keys, precision, dimensions, shared strings and transform of
+/// `data`
+fn decode(
+    mut data: geobuf_pb::Data,
+    features: Vec<geobuf_pb::data::Feature>,
+) -> Result<Vec<JSONValue>, &'static str> {
+    if features.is_empty() {
+        return Ok(Vec::new());
+    }
+    let mut collection = geobuf_pb::data::FeatureCollection::new();
+    collection.features = features;
+    data.set_feature_collection(collection);
+    // Decoding panics on out of range indexes and lengths
+    if !validate(&data).is_empty() {
+        return Err("Invalid feature.");
+    }
+    Ok(Decoder::features(&data)?.collect())
+}
+
+/// Returns true if the properties of `feature` or of its geometries refer to shared strings
+fn uses_strings(feature: &geobuf_pb::data::Feature) -> bool {
+    fn geometry_uses_strings(geometry: &geobuf_pb::data::Geometry) -> bool {
+        geometry.values.iter().any(|value| value.has_string_ref())
+            || geometry.geometries.iter().any(geometry_uses_strings)
+    }
+    feature.values.iter().any(|value| value.has_string_ref())
+        || feature.geometry.as_ref().is_some_and(geometry_uses_strings)
+}
+
+/// Reads the key and length of the field at the start of `bytes`, or returns `None` if they
+/// aren't complete
+fn read_field(bytes: &[u8]) -> Result<Option<Field>, &'static str> {
+    let (key, key_len) = match read_varint(bytes)? {
+        Some(key) => key,
+        None => return Ok(None),
+    };
+    let wire_type = (key & 7) as u8;
+    let (start, len) = match wire_type {
+        0 => match read_varint(&bytes[key_len..])? {
+            Some((_, len)) => (key_len, len),
+            None => return Ok(None),
+        },
+        1 => (key_len, 8),
+        LEN => match read_varint(&bytes[key_len..])? {
+            Some((len, len_len)) => (key_len + len_len, len as usize),
+            None => return Ok(None),
+        },
+        5 => (key_len, 4),
+        _ => return Err("Unsupported wire type."),
+    };
+    Ok(Some(Field {
+        number: key >> 3,
+        wire_type,
+        start,
+        len,
+    }))
+}
+
+/// Reads the varint at the start of `bytes` and returns it with its length, or `None` if it
+/// isn't complete
+fn read_varint(bytes: &[u8]) -> Result<Option<(u64, usize)>, &'static str> {
+    let mut value: u64 = 0;
+    for (i, byte) in bytes.iter().take(10).enumerate() {
+        value |= ((byte & 0x7f) as u64) << (7 * i);
+        if byte & 0x80 == 0 {
+            return Ok(Some((value, i + 1)));
+        }
+    }
+    if bytes.len() >= 10 {
+        return Err("Invalid varint.");
+    }
+    Ok(None)
+}
@@ -0,0 +1,406 @@
+//! Spec conformance and consistency checks for `geobuf_pb::Data`
+use std::fmt;
+
+use crate::geobuf_pb;
+use crate::geobuf_pb::data::geometry::Type;
+
+/// A problem found by `validate`
+#[derive(Clone, Debug, PartialEq)]
+pub struct Problem {
+    /// Location of the problem, e.g. `features[3].geometry.lengths`
+    pub path: String,
+    pub message: String,
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+/// Checks `data` for problems that would make decoding fail or produce invalid GeoJSON
+///
+/// The checks cover the header fields, key, value and shared string indexes, `lengths`
+/// arrays against coordinate counts, ring sizes, and TopoJSON arc references.
+/// An empty result means the data is valid.
+///
+/// # Example
+///
+/// ```
+/// use geobuf::encode::Encoder;
+///
+/// let geojson = serde_json::from_str(r#"{"type": "Feature", "properties": {"a": 1},
+///     "geometry": {"type": "LineString", "coordinates": [[0.0, 0.0], [1.0, 1.0]]}}"#).unwrap();
+/// let mut data = Encoder::encode(&geojson, 6, 2).unwrap();
+/// assert!(geobuf::validate::validate(&data).is_empty());
+///
+/// data.mut_feature().properties[1] = 5;
+/// let problems = geobuf::validate::validate(&data);
+/// assert_eq!(problems[0].path, "feature.properties");
+/// assert_eq!(problems[0].message, "value index 5 out of range (1 values)");
+/// ```
+pub fn validate(data: &geobuf_pb::Data) -> Vec<Problem> {
+    let mut validator = Validator {
+        data,
+        dim: data.dimensions() as usize,
+        problems: Vec::new(),
+    };
+    validator.validate();
+    validator.problems
+}
+
+struct Validator<'a> {
+    data: &'a geobuf_pb::Data,
+    dim: usize,
+    problems: Vec<Problem>,
+}
+
+impl<'a> Validator<'a> {
+    fn report(&mut self, path: &str, message: String) {
+        self.problems.push(Problem {
+            path: String::from(path),
+            message,
+        });
+    }
+
+    fn validate(&mut self) {
+        let data = self.data;
+        if self.dim < 2 {
+            self.report(
+                "dimensions",
+                format!("must be at least 2, got {}", self.dim),
+            );
+            return;
+        }
+        if data.precision() > 15 {
+            self.report(
+                "precision",
+                format!("must be at most 15, got {}", data.precision()),
+            );
+        }
+        if data.transform.is_some()
+            && (data.transform.scale_x() == 0.0 || data.transform.scale_y() == 0.0)
+        {
+            self.report("transform", String::from("scale must not be 0"));
+        }
+
+        match data.data_type.as_ref() {
+            Some(geobuf_pb::data::Data_type::FeatureCollection(fc)) => {
+                self.check_properties("custom_properties", &fc.custom_properties, &fc.values);
+                self.check_values("values", &fc.values);
+                for (n, feature) in fc.features.iter().enumerate() {
+                    self.check_feature(&format!("features[{}]", n), feature);
+                }
+            }
+            Some(geobuf_pb::data::Data_type::Feature(feature)) => {
+                self.check_feature("feature", feature)
+            }
+            Some(geobuf_pb::data::Data_type::Geometry(geometry)) => {
+                self.check_geometry("geometry", geometry)
+            }
+            Some(geobuf_pb::data::Data_type::Topology(topology)) => self.check_topology(topology),
+            _ => self.report("data_type", String::from("missing")),
+        }
+    }
+
+    fn check_feature(&mut self, path: &str, feature: &geobuf_pb::data::Feature) {
+        self.check_properties(
+            &format!("{}.properties", path),
+            &feature.properties,
+            &feature.values,
+        );
+        self.check_properties(
+            &format!("{}.custom_properties", path),
+            &feature.custom_properties,
+            &feature.values,
+        );
+        self.check_values(&format!("{}.values", path), &feature.values);
+        match feature.geometry.as_ref() {
+            Some(geometry) => self.check_geometry(&format!("{}.geometry", path), geometry),
+            None => self.report(path, String::from("missing geometry")),
+        }
+    }
+
+    fn check_properties(
+        &mut self,
+        path: &str,
+        properties: &[u32],
+        values: &[geobuf_pb::data::Value],
+    ) {
+        if !properties.chunks_exact(2).remainder().is_empty() {
+            self.report(
+                path,
+                format!("odd number of indexes ({})", properties.len()),
+            );
+        }
+        for pair in properties.chunks_exact(2) {
+            if pair[0] as usize >= self.data.keys.len() {
+                self.report(
+                    path,
+                    format!(
+                        "key index {} out of range ({} keys)",
+                        pair[0],
+                        self.data.keys.len()
+                    ),
+                );
+            }
+            if pair[1] as usize >= values.len() {
+                self.report(
+                    path,
+                    format!(
+                        "value index {} out of range ({} values)",
+                        pair[1],
+                        values.len()
+                    ),
+                );
+            }
+        }
+    }
+
+    fn check_values(&mut self, path: &str, values: &[geobuf_pb::data::Value]) {
+        for (n, value) in values.iter().enumerate() {
+            match value.value_type.as_ref() {
+                Some(geobuf_pb::data::value::Value_type::StringRef(index))
+                    if *index as usize >= self.data.strings.len() =>
+                {
+                    self.report(
+                        &format!("{}[{}]", path, n),
+                        format!(
+                            "string reference {} out of range ({} strings)",
+                            index,
+                            self.data.strings.len()
+                        ),
+                    )
+                }
+                Some(geobuf_pb::data::value::Value_type::JsonValue(json))
+                    if serde_json::from_str::<serde_json::Value>(json).is_err() =>
+                {
+                    self.report(
+                        &format!("{}[{}]", path, n),
+                        String::from("invalid JSON value"),
+                    )
+                }
+                Some(geobuf_pb::data::value::Value_type::DoubleValue(v)) if !v.is_finite() => self
+                    .report(
+                        &format!("{}[{}]", path, n),
+                        String::from("non-finite number"),
+                    ),
+                None => self.report(&format!("{}[{}]", path, n), String::from("missing value")),
+                _ => {}
+            }
+        }
+    }
+
+    fn check_geometry(&mut self, path: &str, geometry: &geobuf_pb::data::Geometry) {
+        self.check_properties(
+            &format!("{}.custom_properties", path),
+            &geometry.custom_properties,
+            &geometry.values,
+        );
+        self.check_values(&format!("{}.values", path), &geometry.values);
+        if !geometry.arcs.is_empty() {
+            self.report(path, String::from("arcs outside of a topology"));
+        }
+        if geometry.type_() != Type::GEOMETRYCOLLECTION && !geometry.geometries.is_empty() {
+            self.report(
+                path,
+                String::from("child geometries outside of a GeometryCollection"),
+            );
+        }
+
+        let coords = &geometry.coords;
+        if !coords.chunks_exact(self.dim).remainder().is_empty() {
+            self.report(
+                &format!("{}.coords", path),
+                format!(
+                    "{} coordinates is not a multiple of {} dimensions",
+                    coords.len(),
+                    self.dim
+                ),
+            );
+            return;
+        }
+        let points = coords.len() / self.dim;
+        let lengths_path = format!("{}.lengths", path);
+        match geometry.type_() {
+            Type::POINT => {
+                if points != 1 {
+                    self.report(path, format!("Point with {} positions", points));
+                }
+            }
+            Type::MULTIPOINT | Type::LINESTRING => {
+                if !geometry.lengths.is_empty() {
+                    self.report(&lengths_path, String::from("unexpected lengths"));
+                }
+                if geometry.type_() == Type::LINESTRING && points < 2 {
+                    self.report(path, format!("LineString with {} positions", points));
+                }
+            }
+            Type::MULTILINESTRING => {
+                self.check_lines(&lengths_path, &geometry.lengths, points);
+            }
+            Type::POLYGON => {
+                if self.check_lines(&lengths_path, &geometry.lengths, points) {
+                    self.check_rings(path, &geometry.lengths, points);
+                }
+            }
+            Type::MULTIPOLYGON => {
+                if geometry.lengths.is_empty() {
+                    self.check_rings(path, &[], points);
+                    return;
+                }
+                match ring_lengths(&geometry.lengths) {
+                    Some(rings) => {
+                        if self.check_lines(&lengths_path, &rings, points) {
+                            self.check_rings(path, &rings, points);
+                        }
+                    }
+                    None => self.report(
+                        &lengths_path,
+                        String::from("polygon and ring counts don't match the array"),
+                    ),
+                }
+            }
+            Type::GEOMETRYCOLLECTION => {
+                if points != 0 || !geometry.lengths.is_empty() {
+                    self.report(path, String::from("GeometryCollection with coordinates"));
+                }
+                for (n, child) in geometry.geometries.iter().enumerate() {
+                    self.check_geometry(&format!("{}.geometries[{}]", path, n), child);
+                }
+            }
+        }
+    }
+
+    /// Checks that line lengths add up to the number of points, returning true if they do
+    fn check_lines(&mut self, path: &str, lengths: &[u32], points: usize) -> bool {
+        if lengths.is_empty() {
+            return true;
+        }
+        let total: u64 = lengths.iter().map(|l| *l as u64).sum();
+        if total != points as u64 {
+            self.report(
+                path,
+                format!("lengths add up to {} positions, found {}", total, points),
+            );
+            return false;
+        }
+        true
+    }
+
+    /// Checks that every ring can be closed: the closing position is implicit, so a ring
+    /// needs at least 3 stored positions to make up the 4 positions GeoJSON requires
+    fn check_rings(&mut self, path: &str, lengths: &[u32], points: usize) {
+        let single = [points as u32];
+        let lengths = if lengths.is_empty() {
+            &single[..]
+        } else {
+            lengths
+        };
+        for (n, l) in lengths.iter().enumerate() {
+            if *l < 3 {
+                self.report(
+                    path,
+                    format!("ring {} has {} positions, expected at least 3", n, l),
+                );
+            }
+        }
+    }
+
+    fn check_topology(&mut self, topology: &geobuf_pb::data::Topology) {
+        self.check_properties(
+            "topology.custom_properties",
+            &topology.custom_properties,
+            &topology.values,
+        );
+        self.check_values("topology.values", &topology.values);
+        if topology.names.len() != topology.objects.len() {
+            self.report(
+                "topology.names",
+                format!(
+                    "{} names for {} objects",
+                    topology.names.len(),
+                    topology.objects.len()
+                ),
+            );
+        }
+        if !topology
+            .coords
+            .chunks_exact(self.dim)
+            .remainder()
+            .is_empty()
+        {
+            self.report(
+                "topology.coords",
+                format!(
+                    "{} coordinates is not a multiple of {} dimensions",
+                    topology.coords.len(),
+                    self.dim
+                ),
+            );
+        } else {
+            self.check_lines(
+                "topology.lengths",
+                &topology.lengths,
+                topology.coords.len() / self.dim,
+            );
+        }
+        let num_arcs = topology.lengths.len() as i64;
+        for (n, object) in topology.objects.iter().enumerate() {
+            let name = topology.names.get(n).cloned().unwrap_or_default();
+            self.check_topology_geometry(&format!("objects.{}", name), object, num_arcs);
+        }
+    }
+
+    fn check_topology_geometry(
+        &mut self,
+        path: &str,
+        geometry: &geobuf_pb::data::Geometry,
+        num_arcs: i64,
+    ) {
+        self.check_properties(
+            &format!("{}.properties", path),
+            &geometry.properties,
+            &geometry.values,
+        );
+        self.check_properties(
+            &format!("{}.custom_properties", path),
+            &geometry.custom_properties,
+            &geometry.values,
+        );
+        self.check_values(&format!("{}.values", path), &geometry.values);
+        for arc in &geometry.arcs {
+            let index = if *arc < 0 {
+                !(*arc as i64)
+            } else {
+                *arc as i64
+            };
+            if index >= num_arcs {
+                self.report(
+                    &format!("{}.arcs", path),
+                    format!("arc {} out of range ({} arcs)", arc, num_arcs),
+                );
+            }
+        }
+        for (n, child) in geometry.geometries.iter().enumerate() {
+            self.check_topology_geometry(&format!("{}.geometries[{}]", path, n), child, num_arcs);
+        }
+    }
+}
+
+/// Returns the ring lengths of a MultiPolygon `lengths` array, or `None` if the polygon and
+/// ring counts don't match its size
+fn ring_lengths(lengths: &[u32]) -> Option<Vec<u32>> {
+    let mut rings = Vec::new();
+    let mut j = 1;
+    for _ in 0..*lengths.first()? {
+        let num_rings = *lengths.get(j)? as usize;
+        j += 1;
+        rings.extend_from_slice(lengths.get(j..j + num_rings)?);
+        j += num_rings;
+    }
+    if j != lengths.len() {
+        return None;
+    }
+    Some(rings)
+}
@@ -1,11 +1,24 @@
 use cfg_if::cfg_if;
-use protobuf::Message;
-use serde_json;
+use js_sys::futures::{future_to_promise, JsFuture};
+use serde::Serialize;
+use serde_json::Value as JSONValue;
 use wasm_bindgen::prelude::*;
+use web_sys::TransformStreamDefaultController;
 
+use crate::bbox::bbox;
+use crate::codec;
+use crate::container;
 use crate::decode::Decoder;
 use crate::encode::Encoder;
+use crate::filter::{filter, Expr};
+use crate::framed::FramedWriter;
+use crate::geobuf_pb::data::Data_type;
 use crate::geobuf_pb::Data;
+use crate::index::{self, Index};
+use crate::schema;
+use crate::simplify::simplify;
+use crate::stream::{FramedStreamDecoder, StreamDecoder};
+use crate::validate::validate;
 
 cfg_if! {
     // When the `console_error_panic_hook` feature is enabled, we can call the
@@ -19,25 +32,729 @@ cfg_if! {
     }
 }
 
+#[wasm_bindgen(typescript_custom_section)]
+const TYPES: &'static str = r#"
+export type Position = number[];
+
+export type Geometry =
+    | { type: "Point"; coordinates: Position; bbox?: number[] }
+    | { type: "MultiPoint"; coordinates: Position[]; bbox?: number[] }
+    | { type: "LineString"; coordinates: Position[]; bbox?: number[] }
+    | { type: "MultiLineString"; coordinates: Position[][]; bbox?: number[] }
+    | { type: "Polygon"; coordinates: Position[][]; bbox?: number[] }
+    | { type: "MultiPolygon"; coordinates: Position[][][]; bbox?: number[] }
+    | { type: "GeometryCollection"; geometries: Geometry[]; bbox?: number[] };
+
+export interface Feature {
+    type: "Feature";
+    id?: string | number;
+    geometry: Geometry | null;
+    properties: { [key: string]: any } | null;
+    [key: string]: any;
+}
+
+export interface FeatureCollection {
+    type: "FeatureCollection";
+    features: Feature[];
+    [key: string]: any;
+}
+
+export interface Topology {
+    type: "Topology";
+    objects: { [name: string]: any };
+    arcs: Position[][];
+    [key: string]: any;
+}
+
+export type GeoJSON = Geometry | Feature | FeatureCollection | Topology;
+
+export interface EncodeOptions {
+    precision?: number;
+    dim?: number;
+    auto?: boolean;
+    keepProps?: string[];
+}
+
+export interface GeobufInfo {
+    precision: number;
+    dimensions: number;
+    type: "FeatureCollection" | "Feature" | "Geometry" | "Topology";
+    featureCount: number;
+    keys: string[];
+    bbox: [number, number, number, number] | null;
+}
+
+export interface BuildInfo {
+    version: string;
+    schemaVersion: number;
+    containerVersion: number;
+    indexVersion: number;
+    features: string[];
+}
+"#;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(typescript_type = "GeoJSON")]
+    pub type GeoJson;
+
+    #[wasm_bindgen(typescript_type = "string | GeoJSON")]
+    pub type GeoJsonInput;
+
+    #[wasm_bindgen(typescript_type = "Feature")]
+    pub type Feature;
+
+    #[wasm_bindgen(typescript_type = "FeatureCollection")]
+    pub type FeatureCollection;
+
+    #[wasm_bindgen(typescript_type = "EncodeOptions")]
+    pub type JsEncodeOptions;
+
+    #[wasm_bindgen(typescript_type = "GeobufInfo")]
+    pub type GeobufInfo;
+
+    #[wasm_bindgen(typescript_type = "BuildInfo")]
+    pub type BuildInfo;
+
+    #[wasm_bindgen(typescript_type = "IteratorResult<Feature, null>")]
+    pub type FeatureIteratorResult;
+
+    #[wasm_bindgen(typescript_type = "(feature: GeoJSON) => void")]
+    pub type FeatureCallback;
+
+    /// The global `fetch`, available in windows and workers
+    #[wasm_bindgen(js_name = fetch)]
+    fn fetch_request(request: &web_sys::Request) -> js_sys::Promise;
+}
+
+/// Frames of a framed stream less than this many bytes apart are fetched with a single range
+/// request by `fetch_within`
+const MAX_RANGE_GAP: u64 = 64 * 1024;
+
+/// Cargo features reported by `version`, with whether the package was built with them
+const FEATURES: [(&str, bool); 2] = [
+    ("light-codec", cfg!(feature = "light-codec")),
+    ("wasm-gzip", cfg!(feature = "wasm-gzip")),
+];
+
 /// Enables logging of errors
 #[wasm_bindgen]
 pub fn debug() {
     set_panic_hook();
 }
 
+/// Returns `{version, schemaVersion, containerVersion, indexVersion, features}` describing the
+/// build of the package
+///
+/// `version` is the crate version, `schemaVersion` the version of the Geobuf format, the next
+/// two the versions of the container and index formats, and `features` the optional Cargo
+/// features the package was built with, e.g. to log which converter produced a payload.
+#[wasm_bindgen]
+pub fn version() -> Result<BuildInfo, JsError> {
+    let features: Vec<&str> = FEATURES
+        .iter()
+        .filter(|(_, enabled)| *enabled)
+        .map(|(name, _)| *name)
+        .collect();
+    let info = to_js(&serde_json::json!({
+        "version": env!("CARGO_PKG_VERSION"),
+        "schemaVersion": schema::VERSION,
+        "containerVersion": container::VERSION,
+        "indexVersion": index::VERSION,
+        "features": features,
+    }))?;
+    Ok(info.unchecked_into())
+}
+
+/// Decodes a Geobuf into a GeoJSON object
+///
+/// With a `tolerance`, lines and rings are simplified with the Douglas–Peucker algorithm
+/// before being passed to JS, dropping positions closer than `tolerance` to the simplified
+/// line, e.g. for low zoom levels. Throws an `Error` if `data` isn't a valid Geobuf.
+#[wasm_bindgen]
+pub fn decode(data: &[u8], tolerance: Option<f64>) -> Result<GeoJson, JsError> {
+    let mut geojson = Decoder::decode(&parse(data)?).map_err(JsError::new)?;
+    if let Some(tolerance) = tolerance {
+        simplify(&mut geojson, tolerance).map_err(JsError::new)?;
+    }
+    Ok(to_js(&geojson)?.unchecked_into())
+}
+
+/// Decodes a gzip compressed Geobuf into a GeoJSON object
+///
+/// Uncompressed Geobufs are decoded as they are, e.g. when the browser already decompressed
+/// a response served with `Content-Encoding: gzip`. Throws an `Error` if `data` isn't a
+/// valid Geobuf once decompressed.
+#[cfg(feature = "wasm-gzip")]
+#[wasm_bindgen]
+pub fn decode_gzip(data: &[u8]) -> Result<GeoJson, JsError> {
+    use std::io::Read;
+
+    if !data.starts_with(&[0x1f, 0x8b]) {
+        return decode(data, None);
+    }
+    let mut bytes = Vec::new();
+    flate2::read::MultiGzDecoder::new(data)
+        .read_to_end(&mut bytes)
+        .map_err(|err| JsError::new(&format!("Could not decompress geobuf: {}", err)))?;
+    decode(&bytes, None)
+}
+
+/// Decodes a Geobuf into a GeoJSON string
+///
+/// Serializing in Rust and parsing the string with `JSON.parse` is faster than building the
+/// object of `decode` for large inputs. Throws an `Error` if `data` isn't a valid Geobuf.
+#[wasm_bindgen]
+pub fn decode_to_string(data: &[u8]) -> Result<String, JsError> {
+    let geojson = Decoder::decode(&parse(data)?).map_err(JsError::new)?;
+    Ok(geojson.to_string())
+}
+
+/// Decodes a Geobuf in an `ArrayBuffer` into the UTF-8 bytes of a GeoJSON string, in a new
+/// `ArrayBuffer`
+///
+/// Meant for web workers: both buffers can be transferred with `postMessage` instead of
+/// being copied, and the main thread only runs `JSON.parse(new TextDecoder().decode(buffer))`.
+/// Throws an `Error` if `buffer` isn't a valid Geobuf.
+#[wasm_bindgen]
+pub fn decode_buffer(buffer: &js_sys::ArrayBuffer) -> Result<js_sys::ArrayBuffer, JsError> {
+    let data = js_sys::Uint8Array::new(buffer).to_vec();
+    let geojson = Decoder::decode(&parse(&data)?).map_err(JsError::new)?;
+    Ok(to_buffer(geojson.to_string().as_bytes()))
+}
+
+/// Decodes the features of a Geobuf whose bounding box intersects
+/// `min_x, min_y, max_x, max_y`, e.g. the extent of a map view, into a FeatureCollection
+///
+/// Features are selected before decoding, so the others are never converted. Throws an
+/// `Error` if `data` isn't a valid Geobuf with features.
+#[wasm_bindgen]
+pub fn decode_within(
+    data: &[u8],
+    min_x: f64,
+    min_y: f64,
+    max_x: f64,
+    max_y: f64,
+) -> Result<FeatureCollection, JsError> {
+    let extent = Expr::Intersects([min_x, min_y, max_x, max_y]);
+    let data = filter(&parse(data)?, &extent).map_err(JsError::new)?;
+    let geojson = Decoder::decode(&data).map_err(JsError::new)?;
+    Ok(to_js(&geojson)?.unchecked_into())
+}
+
+/// Decodes the features of a Geobuf at `indices`, in that order, into a FeatureCollection
+///
+/// Only the selected features are decoded, and indices past the last feature are skipped.
+/// Throws an `Error` if `data` isn't a valid Geobuf with features.
+#[wasm_bindgen]
+pub fn decode_features(data: &[u8], indices: &[u32]) -> Result<FeatureCollection, JsError> {
+    let indices: Vec<usize> = indices.iter().map(|n| *n as usize).collect();
+    let data = crate::select(&parse(data)?, &indices).map_err(JsError::new)?;
+    let geojson = Decoder::decode(&data).map_err(JsError::new)?;
+    Ok(to_js(&geojson)?.unchecked_into())
+}
+
+/// Fetches the features of a framed stream whose bounding box intersects
+/// `min_x, min_y, max_x, max_y` into a FeatureCollection, reading only their bytes
+///
+/// The `.gbi` index of the stream is fetched from `index_url`, by default the URL of the
+/// stream with a `.gbi` extension, then the frames of the matching features with HTTP
+/// `Range` requests, one for frames less than 64 KiB apart. Rejects with an `Error` if a
+/// request fails, if the index has no bounding boxes (version 1) or if a frame is invalid.
+#[wasm_bindgen(unchecked_return_type = "Promise<FeatureCollection>")]
+pub fn fetch_within(
+    url: String,
+    min_x: f64,
+    min_y: f64,
+    max_x: f64,
+    max_y: f64,
+    index_url: Option<String>,
+) -> js_sys::Promise {
+    future_to_promise(async move {
+        let index_url = index_url.unwrap_or_else(|| index::sidecar_path(&url));
+        let index = Index::from_bytes(&fetch_bytes(&index_url, None).await?)
+            .map_err(|err| JsError::new(&format!("Invalid index {}: {}", index_url, err)))?;
+        if !index.is_empty() && index.entries.iter().all(|entry| entry.bbox.is_none()) {
+            return Err(JsError::new("Index without bounding boxes, rebuild it.").into());
+        }
+
+        let positions = index.intersecting(&[min_x, min_y, max_x, max_y]);
+        let ranges = index.ranges(&positions, MAX_RANGE_GAP);
+        // Every request is sent before waiting for the first response
+        let responses = ranges
+            .iter()
+            .map(|range| fetch(&url, Some((range.start, range.end))))
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut features = Vec::new();
+        for (range, response) in ranges.iter().zip(responses) {
+            let bytes = read_body(response, &url, Some((range.start, range.end))).await?;
+            for n in &range.entries {
+                let entry = &index.entries[*n];
+                let start = (entry.offset - range.start) as usize;
+                let frame = bytes
+                    .get(start..start + entry.len as usize)
+                    .ok_or_else(|| JsError::new("Truncated frame."))?;
+                match Decoder::decode(&parse(frame)?).map_err(JsError::new)? {
+                    JSONValue::Object(mut object) if object["type"] == "FeatureCollection" => {
+                        if let Some(JSONValue::Array(frame)) = object.remove("features") {
+                            features.extend(frame);
+                        }
+                    }
+                    feature => features.push(feature),
+                }
+            }
+        }
+        Ok(to_js(
+            &serde_json::json!({"type": "FeatureCollection", "features": features}),
+        )?)
+    })
+}
+
+/// Starts a GET request for `url`, limited to the bytes in `start..end` if given
+fn fetch(url: &str, range: Option<(u64, u64)>) -> Result<js_sys::Promise, JsValue> {
+    let init = web_sys::RequestInit::new();
+    init.set_method("GET");
+    if let Some((start, end)) = range {
+        let headers = web_sys::Headers::new()?;
+        headers.set("Range", &format!("bytes={}-{}", start, end - 1))?;
+        init.set_headers(&headers);
+    }
+    let request = web_sys::Request::new_with_str_and_init(url, &init)?;
+    Ok(fetch_request(&request))
+}
+
+async fn fetch_bytes(url: &str, range: Option<(u64, u64)>) -> Result<Vec<u8>, JsValue> {
+    read_body(fetch(url, range)?, url, range).await
+}
+
+/// Waits for the response to a request and returns its body
+///
+/// Servers ignoring the range answer with the whole file, which the range is cut out of.
+async fn read_body(
+    response: js_sys::Promise,
+    url: &str,
+    range: Option<(u64, u64)>,
+) -> Result<Vec<u8>, JsValue> {
+    let response: web_sys::Response = JsFuture::from(response).await?.unchecked_into();
+    if !response.ok() {
+        let message = format!("Could not fetch {}: HTTP {}", url, response.status());
+        return Err(JsError::new(&message).into());
+    }
+    let body = JsFuture::from(response.array_buffer()?).await?;
+    let bytes = js_sys::Uint8Array::new(&body).to_vec();
+    match range {
+        Some((start, end)) if response.status() == 200 => bytes
+            .get(start as usize..end as usize)
+            .map(<[u8]>::to_vec)
+            .ok_or_else(|| JsError::new(&format!("Truncated response from {}.", url)).into()),
+        _ => Ok(bytes),
+    }
+}
+
+/// Returns `{precision, dimensions, type, featureCount, keys, bbox}` for a Geobuf, without
+/// decoding its geometries or properties
+///
+/// `type` is FeatureCollection, Feature, Geometry or Topology, and `bbox` is
+/// `[minX, minY, maxX, maxY]`, or null without coordinates. Throws an `Error` if `data` isn't
+/// a valid Geobuf.
+#[wasm_bindgen]
+pub fn info(data: &[u8]) -> Result<GeobufInfo, JsError> {
+    let data = parse(data)?;
+    let root_type = match data.data_type.as_ref() {
+        Some(Data_type::FeatureCollection(_)) => "FeatureCollection",
+        Some(Data_type::Feature(_)) => "Feature",
+        Some(Data_type::Geometry(_)) => "Geometry",
+        Some(Data_type::Topology(_)) => "Topology",
+        None => return Err(JsError::new("Geobuf holds no data.")),
+    };
+    let info = to_js(&serde_json::json!({
+        "precision": data.precision(),
+        "dimensions": data.dimensions(),
+        "type": root_type,
+        "featureCount": data.feature_count(),
+        "keys": data.keys,
+        "bbox": bbox(&data),
+    }))?;
+    Ok(info.unchecked_into())
+}
+
+/// Iterates over the features of a Geobuf with the JS iterator protocol, decoding a single
+/// feature on each `next()` call
+#[wasm_bindgen]
+pub struct FeatureIterator {
+    data: Data,
+    index: usize,
+}
+
+#[wasm_bindgen]
+impl FeatureIterator {
+    /// Throws an `Error` if `data` isn't a valid Geobuf holding a Feature or FeatureCollection
+    #[wasm_bindgen(constructor)]
+    pub fn new(data: &[u8]) -> Result<FeatureIterator, JsError> {
+        let data = parse(data)?;
+        Decoder::features(&data).map_err(JsError::new)?;
+        Ok(FeatureIterator { data, index: 0 })
+    }
+
+    /// Returns `{value, done}`, with the next feature as `value` until `done`
+    #[wasm_bindgen(js_name = next)]
+    pub fn next_feature(&mut self) -> Result<FeatureIteratorResult, JsError> {
+        let feature = Decoder::features(&self.data)
+            .map_err(JsError::new)?
+            .nth(self.index);
+        let done = feature.is_none();
+        if !done {
+            self.index += 1;
+        }
+        let result = to_js(&serde_json::json!({"value": feature, "done": done}))?;
+        Ok(result.unchecked_into())
+    }
+
+    /// Number of features of the Geobuf
+    #[wasm_bindgen(getter)]
+    pub fn length(&self) -> usize {
+        self.data.feature_count()
+    }
+}
+
+/// Decodes a Geobuf as its bytes arrive, e.g. from the `ReadableStream` of a `fetch`
+/// response, passing each feature to a callback as soon as it is complete
+///
+/// Features referring to shared strings are passed on `finish`, as are the Feature, Geometry
+/// or Topology of Geobufs without FeatureCollection.
 #[wasm_bindgen]
-pub fn decode(data: &[u8]) -> JsValue {
-    let mut geobuf = Data::new();
-    geobuf.merge_from_bytes(&data).unwrap();
-    let geojson = Decoder::decode(&geobuf).unwrap();
-    JsValue::from_serde(&geojson).unwrap()
+pub struct GeobufStreamDecoder {
+    decoder: StreamDecoder,
+    callback: js_sys::Function,
+}
+
+#[wasm_bindgen]
+impl GeobufStreamDecoder {
+    #[wasm_bindgen(constructor)]
+    pub fn new(callback: FeatureCallback) -> Self {
+        GeobufStreamDecoder {
+            decoder: StreamDecoder::new(),
+            callback: callback.unchecked_into(),
+        }
+    }
+
+    /// Adds the next chunk of the Geobuf, calling back with the features it completes
+    pub fn push(&mut self, chunk: &[u8]) -> Result<(), JsValue> {
+        let features = self.decoder.push(chunk).map_err(JsError::new)?;
+        emit(&self.callback, features)
+    }
+
+    /// Ends the Geobuf, calling back with the features left
+    ///
+    /// Throws an `Error` if the Geobuf is truncated.
+    pub fn finish(self) -> Result<(), JsValue> {
+        let features = self.decoder.finish().map_err(JsError::new)?;
+        emit(&self.callback, features)
+    }
+}
+
+/// Transformer of a `TransformStream` turning the bytes of a Geobuf into GeoJSON feature
+/// strings, e.g. `response.body.pipeThrough(new TransformStream(new GeobufDecodeTransformer()))`
+///
+/// With `framed`, the bytes are a framed stream, like the output of
+/// `GeobufEncodeTransformer`, instead of a single Geobuf. Features referring to shared
+/// strings, and the Feature, Geometry or Topology of Geobufs without FeatureCollection, are
+/// enqueued on `flush`.
+#[wasm_bindgen]
+pub struct GeobufDecodeTransformer {
+    decoder: TransformDecoder,
+}
+
+enum TransformDecoder {
+    Geobuf(StreamDecoder),
+    Framed(FramedStreamDecoder),
 }
 
 #[wasm_bindgen]
-pub fn encode(geojson_str: &str, precision: u32, dim: u32) -> Vec<u8> {
-    let geojson = serde_json::from_str(geojson_str).unwrap();
-    Encoder::encode(&geojson, precision, dim)
-        .unwrap()
-        .write_to_bytes()
-        .unwrap()
+impl GeobufDecodeTransformer {
+    #[wasm_bindgen(constructor)]
+    pub fn new(framed: Option<bool>) -> Self {
+        let decoder = if framed.unwrap_or(false) {
+            TransformDecoder::Framed(FramedStreamDecoder::new())
+        } else {
+            TransformDecoder::Geobuf(StreamDecoder::new())
+        };
+        GeobufDecodeTransformer { decoder }
+    }
+
+    /// Enqueues the features completed by the next chunk of bytes
+    pub fn transform(
+        &mut self,
+        chunk: &[u8],
+        controller: &TransformStreamDefaultController,
+    ) -> Result<(), JsValue> {
+        let features = match &mut self.decoder {
+            TransformDecoder::Geobuf(decoder) => decoder.push(chunk),
+            TransformDecoder::Framed(decoder) => decoder.push(chunk),
+        };
+        enqueue(controller, features.map_err(JsError::new)?)
+    }
+
+    /// Enqueues the features left at the end of the bytes
+    ///
+    /// Errors the stream if the bytes are truncated.
+    pub fn flush(&mut self, controller: &TransformStreamDefaultController) -> Result<(), JsValue> {
+        let features = match &mut self.decoder {
+            TransformDecoder::Geobuf(decoder) => std::mem::take(decoder).finish(),
+            TransformDecoder::Framed(decoder) => {
+                std::mem::take(decoder).finish().map(|_| Vec::new())
+            }
+        };
+        enqueue(controller, features.map_err(JsError::new)?)
+    }
+}
+
+/// Transformer of a `TransformStream` turning GeoJSON strings into the bytes of a framed
+/// stream, with one frame per feature
+///
+/// Each chunk holds a whole GeoJSON object, e.g. a feature string from
+/// `GeobufDecodeTransformer`. A single Geobuf can't be written before its last feature is
+/// known, so the bytes form a framed stream instead, which `GeobufDecodeTransformer` reads
+/// back with `framed`.
+#[wasm_bindgen]
+pub struct GeobufEncodeTransformer {
+    precision: u32,
+    dim: u32,
+}
+
+#[wasm_bindgen]
+impl GeobufEncodeTransformer {
+    #[wasm_bindgen(constructor)]
+    pub fn new(precision: u32, dim: u32) -> Self {
+        GeobufEncodeTransformer { precision, dim }
+    }
+
+    /// Enqueues the frames of the next GeoJSON string
+    ///
+    /// Errors the stream if the chunk isn't valid JSON or holds GeoJSON that can't be
+    /// encoded.
+    pub fn transform(
+        &self,
+        chunk: &str,
+        controller: &TransformStreamDefaultController,
+    ) -> Result<(), JsValue> {
+        let geojson = serde_json::from_str(chunk)
+            .map_err(|err| JsError::new(&format!("Could not parse geojson: {}", err)))?;
+        Encoder::check(&geojson, self.precision, self.dim).map_err(|err| JsError::new(&err))?;
+        let mut writer = FramedWriter::new(Vec::new());
+        writer
+            .write_geojson(&geojson, self.precision, self.dim)
+            .map_err(JsError::new)?;
+        controller.enqueue_with_chunk(&js_sys::Uint8Array::from(&writer.into_inner()[..]))
+    }
+}
+
+/// Enqueues features as GeoJSON strings
+fn enqueue(
+    controller: &TransformStreamDefaultController,
+    features: Vec<JSONValue>,
+) -> Result<(), JsValue> {
+    for feature in features {
+        controller.enqueue_with_chunk(&JsValue::from_str(&feature.to_string()))?;
+    }
+    Ok(())
+}
+
+fn emit(callback: &js_sys::Function, features: Vec<JSONValue>) -> Result<(), JsValue> {
+    for feature in features {
+        callback.call1(&JsValue::NULL, &to_js(&feature)?)?;
+    }
+    Ok(())
+}
+
+fn to_js(geojson: &JSONValue) -> Result<JsValue, JsError> {
+    geojson
+        .serialize(&serde_wasm_bindgen::Serializer::json_compatible())
+        .map_err(|err| JsError::new(&format!("Could not convert geojson: {}", err)))
+}
+
+/// Copies bytes into an `ArrayBuffer` owned by JS, which can be transferred unlike views of
+/// the wasm memory
+fn to_buffer(bytes: &[u8]) -> js_sys::ArrayBuffer {
+    js_sys::Uint8Array::from(bytes).buffer()
+}
+
+/// Parses and validates a Geobuf
+fn parse(data: &[u8]) -> Result<Data, JsError> {
+    let geobuf = codec::parse(data).map_err(JsError::new)?;
+    // Decoding panics on out of range indexes and lengths
+    if let Some(problem) = validate(&geobuf).first() {
+        return Err(JsError::new(&format!("Invalid geobuf: {}", problem)));
+    }
+    Ok(geobuf)
+}
+
+/// Encodes a GeoJSON string into a Geobuf
+///
+/// Throws an `Error` if `geojson_str` isn't valid JSON or holds GeoJSON that can't be encoded.
+#[wasm_bindgen]
+pub fn encode(geojson_str: &str, precision: u32, dim: u32) -> Result<Vec<u8>, JsError> {
+    let geojson = serde_json::from_str(geojson_str)
+        .map_err(|err| JsError::new(&format!("Could not parse geojson: {}", err)))?;
+    encode_json(&geojson, precision, dim)
+}
+
+/// Encodes a GeoJSON object into a Geobuf, without stringifying it first
+///
+/// Throws an `Error` if `geojson` holds GeoJSON that can't be encoded.
+#[wasm_bindgen]
+pub fn encode_value(geojson: GeoJson, precision: u32, dim: u32) -> Result<Vec<u8>, JsError> {
+    let geojson = serde_wasm_bindgen::from_value(geojson.into())
+        .map_err(|err| JsError::new(&format!("Could not convert geojson: {}", err)))?;
+    encode_json(&geojson, precision, dim)
+}
+
+/// Encodes the UTF-8 bytes of a GeoJSON string in an `ArrayBuffer`, e.g. the output of
+/// `TextEncoder`, into a Geobuf in a new `ArrayBuffer`
+///
+/// The counterpart of `decode_buffer` for web workers. Throws an `Error` if `buffer` isn't
+/// valid JSON or holds GeoJSON that can't be encoded.
+#[wasm_bindgen]
+pub fn encode_buffer(
+    buffer: &js_sys::ArrayBuffer,
+    precision: u32,
+    dim: u32,
+) -> Result<js_sys::ArrayBuffer, JsError> {
+    let geojson = serde_json::from_slice(&js_sys::Uint8Array::new(buffer).to_vec())
+        .map_err(|err| JsError::new(&format!("Could not parse geojson: {}", err)))?;
+    Ok(to_buffer(&encode_json(&geojson, precision, dim)?))
+}
+
+/// Encodes a GeoJSON string into a gzip compressed Geobuf
+///
+/// Throws an `Error` if `geojson_str` isn't valid JSON or holds GeoJSON that can't be encoded.
+#[cfg(feature = "wasm-gzip")]
+#[wasm_bindgen]
+pub fn encode_gzip(geojson_str: &str, precision: u32, dim: u32) -> Result<Vec<u8>, JsError> {
+    use std::io::Write;
+
+    let bytes = encode(geojson_str, precision, dim)?;
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder
+        .write_all(&bytes)
+        .and_then(|_| encoder.finish())
+        .map_err(|err| JsError::new(&format!("Could not compress geobuf: {}", err)))
+}
+
+/// Encodes GeoJSON, as a string or an object, into a Geobuf with an options object:
+/// `{precision?: number, dim?: number, auto?: boolean, keepProps?: string[]}`
+///
+/// `precision` and `dim` default to 6 and 2, or with `auto` to the fewest digits after the
+/// decimal point that keep every coordinate and the largest number of dimensions found.
+/// `keepProps` lists the properties to keep, all of them by default. Throws an `Error` if an
+/// option is unknown or invalid, or if the GeoJSON can't be encoded.
+#[wasm_bindgen]
+pub fn encode_with_options(
+    geojson: GeoJsonInput,
+    options: Option<JsEncodeOptions>,
+) -> Result<Vec<u8>, JsError> {
+    let geojson = match geojson.as_string() {
+        Some(geojson_str) => serde_json::from_str(&geojson_str)
+            .map_err(|err| JsError::new(&format!("Could not parse geojson: {}", err)))?,
+        None => serde_wasm_bindgen::from_value(geojson.into())
+            .map_err(|err| JsError::new(&format!("Could not convert geojson: {}", err)))?,
+    };
+    let options = match options {
+        Some(options) => {
+            let options = serde_wasm_bindgen::from_value(options.into())
+                .map_err(|err| JsError::new(&format!("Could not convert options: {}", err)))?;
+            EncodeOptions::from_json(&options).map_err(|err| JsError::new(&err))?
+        }
+        None => EncodeOptions::default(),
+    };
+
+    let (auto_precision, auto_dim) =
+        if options.auto && (options.precision.is_none() || options.dim.is_none()) {
+            Encoder::analyze(&geojson)
+        } else {
+            (6, 2)
+        };
+    let precision = options.precision.unwrap_or(auto_precision);
+    let dim = options.dim.unwrap_or(auto_dim);
+    Encoder::check(&geojson, precision, dim).map_err(|err| JsError::new(&err))?;
+    let mut data = Encoder::encode(&geojson, precision, dim).map_err(JsError::new)?;
+    if let Some(keep_props) = &options.keep_props {
+        let dropped: Vec<String> = data
+            .keys
+            .iter()
+            .filter(|key| !keep_props.contains(key))
+            .cloned()
+            .collect();
+        for n in 0..data.feature_count() {
+            for key in &dropped {
+                data.delete_property(n, key).map_err(JsError::new)?;
+            }
+        }
+        data.prune_keys();
+    }
+    codec::serialize(&data).map_err(JsError::new)
+}
+
+/// Options of `encode_with_options`
+#[derive(Default)]
+struct EncodeOptions {
+    precision: Option<u32>,
+    dim: Option<u32>,
+    auto: bool,
+    keep_props: Option<Vec<String>>,
+}
+
+impl EncodeOptions {
+    fn from_json(options: &JSONValue) -> Result<Self, String> {
+        let options = options
+            .as_object()
+            .ok_or_else(|| String::from("Options must be an object."))?;
+        let mut parsed = EncodeOptions::default();
+        for (key, value) in options {
+            let invalid = || format!("Invalid option {}.", key);
+            match key.as_str() {
+                "precision" | "dim" => {
+                    let value = value
+                        .as_u64()
+                        .and_then(|value| u32::try_from(value).ok())
+                        .ok_or_else(invalid)?;
+                    if key == "precision" {
+                        parsed.precision = Some(value);
+                    } else {
+                        parsed.dim = Some(value);
+                    }
+                }
+                "auto" => parsed.auto = value.as_bool().ok_or_else(invalid)?,
+                "keepProps" => {
+                    let keep_props = value
+                        .as_array()
+                        .and_then(|keys| {
+                            keys.iter()
+                                .map(|key| key.as_str().map(String::from))
+                                .collect::<Option<Vec<String>>>()
+                        })
+                        .ok_or_else(invalid)?;
+                    parsed.keep_props = Some(keep_props);
+                }
+                _ => return Err(format!("Unknown option {}.", key)),
+            }
+        }
+        Ok(parsed)
+    }
+}
+
+fn encode_json(geojson: &JSONValue, precision: u32, dim: u32) -> Result<Vec<u8>, JsError> {
+    encode_checked(geojson, precision, dim).map_err(|err| JsError::new(&err))
+}
+
+/// Checks and encodes GeoJSON for the encode exports, with errors that don't need a JS
+/// runtime
+pub(crate) fn encode_checked(
+    geojson: &JSONValue,
+    precision: u32,
+    dim: u32,
+) -> Result<Vec<u8>, String> {
+    Encoder::check(geojson, precision, dim)?;
+    let data = Encoder::encode(geojson, precision, dim)?;
+    Ok(codec::serialize(&data)?)
 }
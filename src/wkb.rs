@@ -0,0 +1,494 @@
+//! Conversion of Geobuf geometries to and from Well-Known Binary, without a GeoJSON step
+//!
+//! Geometries are written as little-endian ISO WKB, with Z coordinates for 3 dimensions and
+//! ZM for 4. Both byte orders, ISO and PostGIS extended WKB are read, the SRID of the latter
+//! being skipped. Curves, surfaces and other types without a GeoJSON equivalent are errors.
+use crate::geobuf_pb::data::geometry::Type;
+use crate::geobuf_pb::data::Geometry;
+
+const POINT: u32 = 1;
+const LINESTRING: u32 = 2;
+const POLYGON: u32 = 3;
+const MULTIPOINT: u32 = 4;
+const MULTILINESTRING: u32 = 5;
+const MULTIPOLYGON: u32 = 6;
+const GEOMETRYCOLLECTION: u32 = 7;
+
+/// Flags of the extended WKB geometry types
+const EWKB_Z: u32 = 0x8000_0000;
+const EWKB_M: u32 = 0x4000_0000;
+const EWKB_SRID: u32 = 0x2000_0000;
+
+/// Returns the WKB of a geometry of a Geobuf with `dim` dimensions and `precision`
+///
+/// Coordinates are written as stored, so geometries of Geobufs with a transform come out in
+/// transformed units. Points without coordinates are written as empty points, with NaN
+/// coordinates.
+///
+/// # Example
+///
+/// ```
+/// use geobuf::encode::Encoder;
+/// use geobuf::wkb::{geometry_to_wkb, wkb_to_geometry};
+///
+/// let geojson = serde_json::from_str(r#"{"type": "LineString", "coordinates": [[1.0, 2.0], [3.0, 4.0]]}"#).unwrap();
+/// let data = Encoder::encode(&geojson, 6, 2).unwrap();
+///
+/// let wkb = geometry_to_wkb(data.geometry(), 2, 6).unwrap();
+/// assert_eq!(&wkb[..9], &[1, 2, 0, 0, 0, 2, 0, 0, 0]);
+/// assert_eq!(&wkb[9..17], &1.0f64.to_le_bytes());
+/// assert_eq!(wkb_to_geometry(&wkb, 2, 6).unwrap(), *data.geometry());
+/// ```
+pub fn geometry_to_wkb(
+    geometry: &Geometry,
+    dim: u32,
+    precision: u32,
+) -> Result<Vec<u8>, &'static str> {
+    let writer = Writer {
+        dim: dim as usize,
+        e: 10f64.powi(precision as i32),
+        flags: match dim {
+            2 => 0,
+            3 => 1000,
+            4 => 3000,
+            _ => return Err("WKB positions have 2 to 4 coordinates."),
+        },
+    };
+    let mut wkb = Vec::new();
+    writer.write_geometry(&mut wkb, geometry)?;
+    Ok(wkb)
+}
+
+/// Returns the geometry encoded with `dim` dimensions and `precision` from its WKB
+///
+/// Positions need at least `dim` coordinates, which are taken in the order of the WKB, and
+/// coordinates beyond `dim` are dropped. Empty points become points without coordinates.
+pub fn wkb_to_geometry(wkb: &[u8], dim: u32, precision: u32) -> Result<Geometry, &'static str> {
+    let mut reader = Reader {
+        wkb,
+        offset: 0,
+        little_endian: true,
+        dim: dim as usize,
+        e: 10f64.powi(precision as i32),
+    };
+    let geometry = reader.read_geometry()?;
+    if reader.offset != wkb.len() {
+        return Err("Trailing bytes after the WKB geometry.");
+    }
+    Ok(geometry)
+}
+
+struct Writer {
+    dim: usize,
+    e: f64,
+    /// Added to the geometry types for Z and ZM positions
+    flags: u32,
+}
+
+impl Writer {
+    fn write_geometry(&self, wkb: &mut Vec<u8>, geometry: &Geometry) -> Result<(), &'static str> {
+        let coords = &geometry.coords;
+        let lengths = &geometry.lengths;
+        match geometry.type_() {
+            Type::POINT => {
+                self.write_header(wkb, POINT);
+                if coords.is_empty() {
+                    self.write_position(wkb, &vec![f64::NAN; self.dim]);
+                } else if coords.len() == self.dim {
+                    let position: Vec<f64> = coords.iter().map(|&c| c as f64 / self.e).collect();
+                    self.write_position(wkb, &position);
+                } else {
+                    return Err("Point with a number of coordinates other than the dimensions.");
+                }
+            }
+            Type::MULTIPOINT => {
+                let positions = self.positions(coords)?;
+                self.write_header(wkb, MULTIPOINT);
+                write_u32(wkb, positions.len());
+                for position in positions {
+                    self.write_header(wkb, POINT);
+                    self.write_position(wkb, &position);
+                }
+            }
+            Type::LINESTRING => {
+                self.write_header(wkb, LINESTRING);
+                self.write_line(wkb, coords, false)?;
+            }
+            Type::MULTILINESTRING => {
+                let lines = self.split(coords, lengths)?;
+                self.write_header(wkb, MULTILINESTRING);
+                write_u32(wkb, lines.len());
+                for line in lines {
+                    self.write_header(wkb, LINESTRING);
+                    self.write_line(wkb, line, false)?;
+                }
+            }
+            Type::POLYGON => {
+                self.write_header(wkb, POLYGON);
+                self.write_rings(wkb, &self.split(coords, lengths)?)?;
+            }
+            Type::MULTIPOLYGON => {
+                let polygons = self.split_polygons(coords, lengths)?;
+                self.write_header(wkb, MULTIPOLYGON);
+                write_u32(wkb, polygons.len());
+                for rings in polygons {
+                    self.write_header(wkb, POLYGON);
+                    self.write_rings(wkb, &rings)?;
+                }
+            }
+            Type::GEOMETRYCOLLECTION => {
+                self.write_header(wkb, GEOMETRYCOLLECTION);
+                write_u32(wkb, geometry.geometries.len());
+                for geometry in &geometry.geometries {
+                    self.write_geometry(wkb, geometry)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn write_header(&self, wkb: &mut Vec<u8>, geometry_type: u32) {
+        wkb.push(1);
+        wkb.extend_from_slice(&(geometry_type + self.flags).to_le_bytes());
+    }
+
+    fn write_position(&self, wkb: &mut Vec<u8>, position: &[f64]) {
+        for coord in position {
+            wkb.extend_from_slice(&coord.to_le_bytes());
+        }
+    }
+
+    /// Writes the positions of a line, repeating the first one at the end of rings
+    fn write_line(
+        &self,
+        wkb: &mut Vec<u8>,
+        coords: &[i64],
+        is_closed: bool,
+    ) -> Result<(), &'static str> {
+        let mut positions = self.positions(coords)?;
+        if is_closed {
+            match positions.first() {
+                Some(first) => positions.push(first.clone()),
+                None => return Err("Empty ring."),
+            }
+        }
+        write_u32(wkb, positions.len());
+        for position in positions {
+            self.write_position(wkb, &position);
+        }
+        Ok(())
+    }
+
+    fn write_rings(&self, wkb: &mut Vec<u8>, rings: &[&[i64]]) -> Result<(), &'static str> {
+        write_u32(wkb, rings.len());
+        rings
+            .iter()
+            .try_for_each(|ring| self.write_line(wkb, ring, true))
+    }
+
+    /// Returns the positions of delta-encoded coordinates
+    fn positions(&self, coords: &[i64]) -> Result<Vec<Vec<f64>>, &'static str> {
+        let deltas = coords.chunks_exact(self.dim);
+        if !deltas.remainder().is_empty() {
+            return Err("Number of coordinates not a multiple of the dimensions.");
+        }
+        let mut p0 = vec![0i64; self.dim];
+        Ok(deltas
+            .map(|delta| {
+                p0.iter_mut()
+                    .zip(delta)
+                    .map(|(p, d)| {
+                        *p += d;
+                        *p as f64 / self.e
+                    })
+                    .collect()
+            })
+            .collect())
+    }
+
+    /// Splits the coordinates of a MultiLineString or Polygon into lines
+    fn split<'a>(
+        &self,
+        coords: &'a [i64],
+        lengths: &[u32],
+    ) -> Result<Vec<&'a [i64]>, &'static str> {
+        if lengths.is_empty() {
+            return Ok(vec![coords]);
+        }
+        let mut lines = Vec::with_capacity(lengths.len());
+        let mut i = 0;
+        for l in lengths {
+            let end = i + (*l as usize) * self.dim;
+            lines.push(coords.get(i..end).ok_or("Invalid geometry lengths.")?);
+            i = end;
+        }
+        Ok(lines)
+    }
+
+    /// Splits the coordinates of a MultiPolygon into the rings of each polygon
+    fn split_polygons<'a>(
+        &self,
+        coords: &'a [i64],
+        lengths: &[u32],
+    ) -> Result<Vec<Vec<&'a [i64]>>, &'static str> {
+        let (num_polygons, mut lengths) = match lengths.split_first() {
+            Some((num_polygons, lengths)) => (*num_polygons, lengths),
+            None => return Ok(vec![vec![coords]]),
+        };
+        let mut polygons = Vec::new();
+        let mut i = 0;
+        for _ in 0..num_polygons {
+            let (num_rings, rest) = lengths.split_first().ok_or("Invalid geometry lengths.")?;
+            let ring_lengths = rest
+                .get(..*num_rings as usize)
+                .ok_or("Invalid geometry lengths.")?;
+            let rings = self.split(&coords[i..], ring_lengths)?;
+            i += rings.iter().map(|ring| ring.len()).sum::<usize>();
+            lengths = &rest[ring_lengths.len()..];
+            polygons.push(rings);
+        }
+        Ok(polygons)
+    }
+}
+
+fn write_u32(wkb: &mut Vec<u8>, n: usize) {
+    wkb.extend_from_slice(&(n as u32).to_le_bytes());
+}
+
+struct Reader<'a> {
+    wkb: &'a [u8],
+    offset: usize,
+    /// Byte order of the geometry being read
+    little_endian: bool,
+    dim: usize,
+    e: f64,
+}
+
+impl Reader<'_> {
+    fn read_geometry(&mut self) -> Result<Geometry, &'static str> {
+        let (geometry_type, wkb_dim) = self.read_header()?;
+        let mut geometry = Geometry::new();
+        match geometry_type {
+            POINT => {
+                geometry.set_type(Type::POINT);
+                let position = self.read_position(wkb_dim)?;
+                // Empty points have NaN coordinates
+                if !position.iter().all(|coord| coord.is_nan()) {
+                    geometry.coords = self.quantize(&position)?;
+                }
+            }
+            MULTIPOINT => {
+                geometry.set_type(Type::MULTIPOINT);
+                let mut positions = Vec::new();
+                for _ in 0..self.read_count()? {
+                    match self.read_header()? {
+                        (POINT, wkb_dim) => positions.push(self.read_position(wkb_dim)?),
+                        _ => return Err("MultiPoint with a member other than a Point."),
+                    }
+                }
+                self.add_line(&mut geometry.coords, &positions, false)?;
+            }
+            LINESTRING => {
+                geometry.set_type(Type::LINESTRING);
+                let positions = self.read_positions(wkb_dim)?;
+                self.add_line(&mut geometry.coords, &positions, false)?;
+            }
+            MULTILINESTRING => {
+                geometry.set_type(Type::MULTILINESTRING);
+                let mut lines = Vec::new();
+                for _ in 0..self.read_count()? {
+                    match self.read_header()? {
+                        (LINESTRING, wkb_dim) => lines.push(self.read_positions(wkb_dim)?),
+                        _ => return Err("MultiLineString with a member other than a LineString."),
+                    }
+                }
+                self.add_multi_line(&mut geometry, &lines, false)?;
+            }
+            POLYGON => {
+                geometry.set_type(Type::POLYGON);
+                let rings = self.read_rings(wkb_dim)?;
+                self.add_multi_line(&mut geometry, &rings, true)?;
+            }
+            MULTIPOLYGON => {
+                geometry.set_type(Type::MULTIPOLYGON);
+                let mut polygons = Vec::new();
+                for _ in 0..self.read_count()? {
+                    match self.read_header()? {
+                        (POLYGON, wkb_dim) => polygons.push(self.read_rings(wkb_dim)?),
+                        _ => return Err("MultiPolygon with a member other than a Polygon."),
+                    }
+                }
+                self.add_multi_polygon(&mut geometry, &polygons)?;
+            }
+            GEOMETRYCOLLECTION => {
+                geometry.set_type(Type::GEOMETRYCOLLECTION);
+                for _ in 0..self.read_count()? {
+                    geometry.geometries.push(self.read_geometry()?);
+                }
+            }
+            _ => return Err("Unsupported WKB geometry type."),
+        }
+        Ok(geometry)
+    }
+
+    /// Reads the byte order and type of a geometry, returning its base type and the number of
+    /// coordinates of its positions
+    fn read_header(&mut self) -> Result<(u32, usize), &'static str> {
+        self.little_endian = match self.read_bytes::<1>()? {
+            [0] => false,
+            [1] => true,
+            _ => return Err("Invalid WKB byte order."),
+        };
+        let wkb_type = self.read_u32()?;
+        if wkb_type & EWKB_SRID != 0 {
+            self.read_u32()?;
+        }
+        let z = wkb_type & EWKB_Z != 0;
+        let m = wkb_type & EWKB_M != 0;
+        let wkb_type = wkb_type & !(EWKB_Z | EWKB_M | EWKB_SRID);
+        let (z, m) = match wkb_type / 1000 {
+            0 => (z, m),
+            1 => (true, m),
+            2 => (z, true),
+            3 => (true, true),
+            _ => return Err("Unsupported WKB geometry type."),
+        };
+        let wkb_dim = 2 + z as usize + m as usize;
+        if wkb_dim < self.dim {
+            return Err("WKB positions with fewer coordinates than the dimensions.");
+        }
+        Ok((wkb_type % 1000, wkb_dim))
+    }
+
+    fn read_bytes<const N: usize>(&mut self) -> Result<[u8; N], &'static str> {
+        let bytes = self
+            .wkb
+            .get(self.offset..self.offset + N)
+            .ok_or("Truncated WKB.")?;
+        self.offset += N;
+        Ok(bytes.try_into().unwrap())
+    }
+
+    fn read_u32(&mut self) -> Result<u32, &'static str> {
+        let bytes = self.read_bytes()?;
+        Ok(match self.little_endian {
+            true => u32::from_le_bytes(bytes),
+            false => u32::from_be_bytes(bytes),
+        })
+    }
+
+    fn read_f64(&mut self) -> Result<f64, &'static str> {
+        let bytes = self.read_bytes()?;
+        Ok(match self.little_endian {
+            true => f64::from_le_bytes(bytes),
+            false => f64::from_be_bytes(bytes),
+        })
+    }
+
+    /// Reads a number of items, checking that enough bytes are left for them
+    fn read_count(&mut self) -> Result<usize, &'static str> {
+        let count = self.read_u32()? as usize;
+        // Every item takes at least 4 bytes
+        if count > (self.wkb.len() - self.offset) / 4 {
+            return Err("Truncated WKB.");
+        }
+        Ok(count)
+    }
+
+    /// Reads a position, keeping its first `dim` coordinates
+    fn read_position(&mut self, wkb_dim: usize) -> Result<Vec<f64>, &'static str> {
+        let mut position = Vec::with_capacity(self.dim);
+        for j in 0..wkb_dim {
+            let coord = self.read_f64()?;
+            if j < self.dim {
+                position.push(coord);
+            }
+        }
+        Ok(position)
+    }
+
+    fn read_positions(&mut self, wkb_dim: usize) -> Result<Vec<Vec<f64>>, &'static str> {
+        (0..self.read_count()?)
+            .map(|_| self.read_position(wkb_dim))
+            .collect()
+    }
+
+    fn read_rings(&mut self, wkb_dim: usize) -> Result<Vec<Vec<Vec<f64>>>, &'static str> {
+        (0..self.read_count()?)
+            .map(|_| self.read_positions(wkb_dim))
+            .collect()
+    }
+
+    fn quantize(&self, position: &[f64]) -> Result<Vec<i64>, &'static str> {
+        // Deltas between quantized coordinates must fit in an i64 too
+        let max = (i64::MAX / 2) as f64;
+        position
+            .iter()
+            .map(|coord| match coord * self.e {
+                coord if coord.abs() < max => Ok(coord.round() as i64),
+                coord if coord.is_nan() => Err("Invalid coordinates."),
+                _ => Err("Coordinate too large to quantize."),
+            })
+            .collect()
+    }
+
+    /// Adds the delta-encoded positions of a line, leaving out the last one of rings
+    fn add_line(
+        &self,
+        coords: &mut Vec<i64>,
+        positions: &[Vec<f64>],
+        is_closed: bool,
+    ) -> Result<(), &'static str> {
+        if is_closed && positions.is_empty() {
+            return Err("Empty ring.");
+        }
+        let mut sum = vec![0; self.dim];
+        for position in positions.iter().take(positions.len() - is_closed as usize) {
+            for (j, coord) in self.quantize(position)?.into_iter().enumerate() {
+                coords.push(coord - sum[j]);
+                sum[j] = coord;
+            }
+        }
+        Ok(())
+    }
+
+    fn add_multi_line(
+        &self,
+        geometry: &mut Geometry,
+        lines: &[Vec<Vec<f64>>],
+        is_closed: bool,
+    ) -> Result<(), &'static str> {
+        for line in lines {
+            self.add_line(&mut geometry.coords, line, is_closed)?;
+            if lines.len() != 1 {
+                geometry
+                    .lengths
+                    .push((line.len() - is_closed as usize) as u32);
+            }
+        }
+        Ok(())
+    }
+
+    fn add_multi_polygon(
+        &self,
+        geometry: &mut Geometry,
+        polygons: &[Vec<Vec<Vec<f64>>>],
+    ) -> Result<(), &'static str> {
+        let lengths = polygons.len() != 1 || polygons[0].len() != 1;
+        if lengths {
+            geometry.lengths.push(polygons.len() as u32);
+        }
+        for rings in polygons {
+            if lengths {
+                geometry.lengths.push(rings.len() as u32);
+            }
+            for ring in rings {
+                self.add_line(&mut geometry.coords, ring, true)?;
+                if lengths {
+                    geometry.lengths.push(ring.len() as u32 - 1);
+                }
+            }
+        }
+        Ok(())
+    }
+}
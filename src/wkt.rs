@@ -0,0 +1,240 @@
+//! Conversion of GeoJSON geometries and features to and from Well-Known Text
+//!
+//! Positions with 3 coordinates are written with a Z tag and positions with 4 with a ZM tag,
+//! the number of coordinates of a geometry being given by its first position. M values are
+//! read after Z values, so that `POINT M (1 2 3)` becomes `[1.0, 2.0, 3.0]`.
+use serde_json::{Map, Value as JSONValue};
+use wkt::types::{
+    Coord, Dimension, GeometryCollection, LineString, MultiLineString, MultiPoint, MultiPolygon,
+    Point, Polygon,
+};
+use wkt::Wkt;
+
+/// Returns the WKT of a GeoJSON geometry, or of the geometry of a GeoJSON feature
+///
+/// Features without geometry are written as `GEOMETRYCOLLECTION EMPTY`.
+///
+/// # Example
+///
+/// ```
+/// use geobuf::wkt::to_wkt;
+///
+/// let geojson = serde_json::json!({"type": "LineString", "coordinates": [[1.0, 2.0, 3.0], [4.0, 5.0, 6.5]]});
+/// assert_eq!(to_wkt(&geojson).unwrap(), "LINESTRING Z(1 2 3,4 5 6.5)");
+///
+/// let feature = serde_json::json!({"type": "Feature", "properties": {}, "geometry": null});
+/// assert_eq!(to_wkt(&feature).unwrap(), "GEOMETRYCOLLECTION EMPTY");
+/// ```
+pub fn to_wkt(geojson: &JSONValue) -> Result<String, &'static str> {
+    let geometry = match geojson["type"].as_str() {
+        Some("Feature") => &geojson["geometry"],
+        _ => geojson,
+    };
+    if geometry.is_null() {
+        return Ok(String::from("GEOMETRYCOLLECTION EMPTY"));
+    }
+    Ok(geometry_to_wkt(geometry)?.to_string())
+}
+
+/// Returns the GeoJSON geometry of a WKT string
+///
+/// # Example
+///
+/// ```
+/// use geobuf::wkt::from_wkt;
+///
+/// let geometry = from_wkt("POLYGON ((0 0, 1 0, 1 1, 0 0))").unwrap();
+/// assert_eq!(geometry, serde_json::json!({
+///     "type": "Polygon",
+///     "coordinates": [[[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 0.0]]]
+/// }));
+/// ```
+pub fn from_wkt(wkt: &str) -> Result<JSONValue, &'static str> {
+    Ok(wkt_to_geometry(&wkt.parse::<Wkt<f64>>()?))
+}
+
+/// Returns a GeoJSON feature with the geometry of a WKT string and `properties`
+pub fn feature_from_wkt(
+    wkt: &str,
+    properties: Map<String, JSONValue>,
+) -> Result<JSONValue, &'static str> {
+    Ok(serde_json::json!({
+        "type": "Feature",
+        "properties": properties,
+        "geometry": from_wkt(wkt)?,
+    }))
+}
+
+fn geometry_to_wkt(geometry: &JSONValue) -> Result<Wkt<f64>, &'static str> {
+    let coordinates = &geometry["coordinates"];
+    let wkt = match geometry["type"].as_str() {
+        Some("GeometryCollection") => {
+            let geometries = geometry["geometries"]
+                .as_array()
+                .ok_or("GeometryCollection without geometries.")?
+                .iter()
+                .map(geometry_to_wkt)
+                .collect::<Result<Vec<_>, _>>()?;
+            let dim = geometries.first().map_or(Dimension::XY, dimension);
+            Wkt::GeometryCollection(GeometryCollection::new(geometries, dim))
+        }
+        Some("Point") => {
+            let dim = first_dimension(coordinates, 0);
+            let coord = match array(coordinates)?.is_empty() {
+                true => None,
+                false => Some(coord(coordinates, dim)?),
+            };
+            Wkt::Point(Point::new(coord, dim))
+        }
+        Some("MultiPoint") => {
+            let dim = first_dimension(coordinates, 1);
+            let points = array(coordinates)?
+                .iter()
+                .map(|position| Ok(Point::new(Some(coord(position, dim)?), dim)))
+                .collect::<Result<_, _>>()?;
+            Wkt::MultiPoint(MultiPoint::new(points, dim))
+        }
+        Some("LineString") => {
+            let dim = first_dimension(coordinates, 1);
+            Wkt::LineString(line_string(coordinates, dim)?)
+        }
+        Some("MultiLineString") => {
+            let dim = first_dimension(coordinates, 2);
+            Wkt::MultiLineString(MultiLineString::new(line_strings(coordinates, dim)?, dim))
+        }
+        Some("Polygon") => {
+            let dim = first_dimension(coordinates, 2);
+            Wkt::Polygon(Polygon::new(line_strings(coordinates, dim)?, dim))
+        }
+        Some("MultiPolygon") => {
+            let dim = first_dimension(coordinates, 3);
+            let polygons = array(coordinates)?
+                .iter()
+                .map(|rings| Ok(Polygon::new(line_strings(rings, dim)?, dim)))
+                .collect::<Result<_, _>>()?;
+            Wkt::MultiPolygon(MultiPolygon::new(polygons, dim))
+        }
+        _ => return Err("Unknown geometry type."),
+    };
+    Ok(wkt)
+}
+
+fn array(value: &JSONValue) -> Result<&Vec<JSONValue>, &'static str> {
+    value.as_array().ok_or("Invalid coordinates.")
+}
+
+fn line_string(positions: &JSONValue, dim: Dimension) -> Result<LineString<f64>, &'static str> {
+    let coords = array(positions)?
+        .iter()
+        .map(|position| coord(position, dim))
+        .collect::<Result<_, _>>()?;
+    Ok(LineString::new(coords, dim))
+}
+
+fn line_strings(lines: &JSONValue, dim: Dimension) -> Result<Vec<LineString<f64>>, &'static str> {
+    array(lines)?
+        .iter()
+        .map(|positions| line_string(positions, dim))
+        .collect()
+}
+
+/// Returns the dimension of the first position, nested `depth` arrays deep
+fn first_dimension(coordinates: &JSONValue, depth: usize) -> Dimension {
+    let mut position = coordinates;
+    for _ in 0..depth {
+        position = &position[0];
+    }
+    match position.as_array().map(Vec::len) {
+        Some(3) => Dimension::XYZ,
+        Some(len) if len > 3 => Dimension::XYZM,
+        _ => Dimension::XY,
+    }
+}
+
+fn dimension(wkt: &Wkt<f64>) -> Dimension {
+    match wkt {
+        Wkt::Point(point) => point.dimension(),
+        Wkt::LineString(line_string) => line_string.dimension(),
+        Wkt::Polygon(polygon) => polygon.dimension(),
+        Wkt::MultiPoint(multi_point) => multi_point.dimension(),
+        Wkt::MultiLineString(multi_line_string) => multi_line_string.dimension(),
+        Wkt::MultiPolygon(multi_polygon) => multi_polygon.dimension(),
+        Wkt::GeometryCollection(collection) => collection.dimension(),
+    }
+}
+
+/// Returns the coordinate of a position with at least as many values as `dim`
+fn coord(position: &JSONValue, dim: Dimension) -> Result<Coord<f64>, &'static str> {
+    let values = array(position)?;
+    let value = |n: usize| match values.get(n).map(JSONValue::as_f64) {
+        Some(Some(value)) => Ok(value),
+        _ => Err("Invalid position."),
+    };
+    let (z, m) = match dim {
+        Dimension::XY => (None, None),
+        Dimension::XYZ => (Some(value(2)?), None),
+        Dimension::XYM => (None, Some(value(2)?)),
+        Dimension::XYZM => (Some(value(2)?), Some(value(3)?)),
+    };
+    Ok(Coord {
+        x: value(0)?,
+        y: value(1)?,
+        z,
+        m,
+    })
+}
+
+fn wkt_to_geometry(wkt: &Wkt<f64>) -> JSONValue {
+    let (type_, coordinates) = match wkt {
+        Wkt::Point(point) => (
+            "Point",
+            point.coord().map_or(JSONValue::Array(Vec::new()), position),
+        ),
+        Wkt::MultiPoint(multi_point) => (
+            "MultiPoint",
+            multi_point
+                .points()
+                .iter()
+                .filter_map(|point| point.coord().map(position))
+                .collect(),
+        ),
+        Wkt::LineString(line_string) => ("LineString", positions(line_string)),
+        Wkt::MultiLineString(multi_line_string) => (
+            "MultiLineString",
+            multi_line_string
+                .line_strings()
+                .iter()
+                .map(positions)
+                .collect(),
+        ),
+        Wkt::Polygon(polygon) => ("Polygon", rings(polygon)),
+        Wkt::MultiPolygon(multi_polygon) => (
+            "MultiPolygon",
+            multi_polygon.polygons().iter().map(rings).collect(),
+        ),
+        Wkt::GeometryCollection(collection) => {
+            let geometries: Vec<JSONValue> = collection
+                .geometries()
+                .iter()
+                .map(wkt_to_geometry)
+                .collect();
+            return serde_json::json!({"type": "GeometryCollection", "geometries": geometries});
+        }
+    };
+    serde_json::json!({"type": type_, "coordinates": coordinates})
+}
+
+fn position(coord: &Coord<f64>) -> JSONValue {
+    let mut position = vec![coord.x, coord.y];
+    position.extend(coord.z);
+    position.extend(coord.m);
+    JSONValue::from(position)
+}
+
+fn positions(line_string: &LineString<f64>) -> JSONValue {
+    line_string.coords().iter().map(position).collect()
+}
+
+fn rings(polygon: &Polygon<f64>) -> JSONValue {
+    polygon.rings().iter().map(positions).collect()
+}
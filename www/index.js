@@ -29,8 +29,13 @@ const getLayer = (url) => {
             xhr.onerror = onError;
             xhr.onload = () => {
                 if (xhr.status === 200) {
-                    const geojson = geobufWasm.decode(new Uint8Array(xhr.response));
-                    source.addFeatures(geoJSONFormat.readFeatures(geojson));
+                    try {
+                        const geojson = geobufWasm.decode(new Uint8Array(xhr.response));
+                        source.addFeatures(geoJSONFormat.readFeatures(geojson));
+                    } catch (error) {
+                        console.error(error.message);
+                        onError();
+                    }
                 } else {
                     onError();
                 }